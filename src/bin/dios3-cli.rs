@@ -0,0 +1,704 @@
+//! Command line tool running the common s3 operations from scripts, with the accounts
+//! configured in the DioS3 app.
+//!
+//! Exit codes: 0 on success, 1 when an operation failed, 2 for invalid arguments or
+//! a missing account.
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{Duration, UNIX_EPOCH};
+use aws_sdk_s3::primitives::DateTimeFormat;
+use clap::{Args, Parser, Subcommand};
+use color_eyre::{eyre, Report};
+use serde::Serialize;
+use tokio::sync::mpsc::unbounded_channel;
+use dios3::model::account::Account;
+use dios3::model::bucket::{validate_bucket_name, BucketOptions};
+use dios3::model::local_selected_item::LocalSelectedItem;
+use dios3::model::s3_data_item::S3DataItem;
+use dios3::model::s3_uri::{join_key, local_path, plan_sync, relative_key, Location, SyncDirection, SyncEntry};
+use dios3::model::transfer_options::TransferOptions;
+use dios3::repositories::account_repo::{fetch_accounts, get_default_account};
+use dios3::repositories::database::Database;
+use dios3::services::s3_data_fetcher::S3DataFetcher;
+use dios3::settings::file_credentials::FileCredential;
+use dios3::utils::default_db_path;
+
+/// Presigned URLs can't be valid for longer than a week
+const MAX_PRESIGN_EXPIRY: u64 = 7 * 24 * 3600;
+
+#[derive(Parser)]
+#[command(name = "dios3-cli", version, about = "Scriptable s3 access with the accounts of DioS3")]
+struct Cli {
+    /// Name or id of the account, the default account when not given
+    #[arg(short, long, global = true)]
+    account: Option<String>,
+    /// Prints one JSON object per line instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Options of the objects written by cp, mv and sync
+#[derive(Args)]
+struct TransferFlags {
+    /// Storage class of the uploaded or copied objects, e.g. STANDARD_IA or GLACIER
+    #[arg(long)]
+    storage_class: Option<String>,
+    /// Server-side encryption of the written objects instead of the account's, AES256 or aws:kms
+    #[arg(long)]
+    sse: Option<String>,
+    /// KMS key encrypting the written objects, implies --sse aws:kms
+    #[arg(long)]
+    sse_kms_key_id: Option<String>,
+    /// Base64 encoded 256-bit customer key (SSE-C) writing and reading the objects
+    #[arg(long)]
+    sse_c_key: Option<String>,
+}
+
+impl TransferFlags {
+    fn options(&self) -> Result<TransferOptions, CliError> {
+        TransferOptions::from_flags(
+            self.storage_class.as_deref(),
+            self.sse.as_deref(),
+            self.sse_kms_key_id.as_deref(),
+            self.sse_c_key.as_deref(),
+        )
+        .map_err(CliError::Usage)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists the buckets, or the objects under s3://bucket/prefix
+    Ls {
+        path: Option<String>,
+        /// Lists everything under the prefix instead of a single level
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Copies between a local path and s3, or within s3
+    Cp {
+        source: String,
+        destination: String,
+        /// Copies everything under the directory or prefix
+        #[arg(short, long)]
+        recursive: bool,
+        #[command(flatten)]
+        options: TransferFlags,
+    },
+    /// Copies and removes the source once copied
+    Mv {
+        source: String,
+        destination: String,
+        /// Moves everything under the directory or prefix
+        #[arg(short, long)]
+        recursive: bool,
+        #[command(flatten)]
+        options: TransferFlags,
+    },
+    /// Removes the object
+    Rm {
+        path: String,
+        /// Removes everything under the prefix
+        #[arg(short, long)]
+        recursive: bool,
+    },
+    /// Copies the new and changed files between a local directory and a prefix
+    Sync {
+        source: String,
+        destination: String,
+        /// Removes what is missing in the source from the destination
+        #[arg(long)]
+        delete: bool,
+        #[command(flatten)]
+        options: TransferFlags,
+    },
+    /// Prints a URL downloading the object without credentials
+    Presign {
+        path: String,
+        /// Seconds the URL is valid for, a week at most
+        #[arg(long, default_value_t = 3600)]
+        expires_in: u64,
+    },
+    /// Creates a bucket
+    Mb {
+        path: String,
+        /// Region of the bucket, the account's default region when not given
+        #[arg(long)]
+        region: Option<String>,
+    },
+    /// Removes an empty bucket
+    Rb { path: String },
+    /// Lists the accounts configured in the app
+    Accounts,
+}
+
+enum CliError {
+    /// Invalid arguments or configuration, nothing was done
+    Usage(String),
+    /// An operation failed
+    Failed(String),
+}
+
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            CliError::Usage(_) => ExitCode::from(2),
+            CliError::Failed(_) => ExitCode::FAILURE,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            CliError::Usage(msg) | CliError::Failed(msg) => msg,
+        }
+    }
+}
+
+impl From<Report> for CliError {
+    fn from(e: Report) -> Self {
+        CliError::Failed(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        CliError::Failed(e.to_string())
+    }
+}
+
+/// One line of the output
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Record {
+    Bucket { name: String, region: Option<String> },
+    Object {
+        uri: String,
+        size: Option<u64>,
+        last_modified: Option<String>,
+        storage_class: Option<String>,
+        is_directory: bool,
+    },
+    Transfer { action: &'static str, source: String, destination: String },
+    Done { action: &'static str, uri: String },
+    Url { uri: String, url: String, expires_in: u64 },
+    Account { id: i64, name: String, region: String, is_default: bool },
+    Failure { uri: String, error: String },
+}
+
+impl Record {
+    fn text(&self) -> String {
+        match self {
+            Record::Bucket { name, region } => format!("{}\t{}", name, region.as_deref().unwrap_or("")),
+            Record::Object { uri, size, last_modified, is_directory, .. } => {
+                let size = if *is_directory { "DIR".to_string() } else { size.unwrap_or(0).to_string() };
+                format!("{:<20}\t{:>12}\t{}", last_modified.as_deref().unwrap_or(""), size, uri)
+            }
+            Record::Transfer { action, source, destination } => format!("{}: {} -> {}", action, source, destination),
+            Record::Done { action, uri } => format!("{}: {}", action, uri),
+            Record::Url { url, .. } => url.clone(),
+            Record::Account { id, name, region, is_default } => {
+                format!("{}\t{}\t{}{}", id, name, region, if *is_default { "\t(default)" } else { "" })
+            }
+            Record::Failure { uri, error } => format!("failed: {}: {}", uri, error),
+        }
+    }
+}
+
+/// Prints the records as they come and counts the failed operations
+struct Output {
+    json: bool,
+    failures: usize,
+}
+
+impl Output {
+    fn emit(&mut self, record: Record) {
+        if matches!(record, Record::Failure { .. }) {
+            self.failures += 1;
+        }
+        match (self.json, &record) {
+            (true, _) => println!("{}", serde_json::to_string(&record).unwrap_or_default()),
+            (false, Record::Failure { .. }) => eprintln!("{}", record.text()),
+            (false, _) => println!("{}", record.text()),
+        }
+    }
+
+    fn error(&self, error: &CliError) {
+        if self.json {
+            eprintln!("{}", serde_json::json!({ "error": error.message() }));
+        } else {
+            eprintln!("dios3-cli: {}", error.message());
+        }
+    }
+
+    fn finish(&self) -> Result<(), CliError> {
+        match self.failures {
+            0 => Ok(()),
+            n => Err(CliError::Failed(format!("{} operation(s) failed", n))),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let mut out = Output { json: cli.json, failures: 0 };
+    let db = match Database::open(&default_db_path()) {
+        Ok(db) => db,
+        Err(e) => {
+            let error = CliError::Usage(format!("Cannot open the accounts database: {}", e));
+            out.error(&error);
+            return error.exit_code();
+        }
+    };
+    let result = match run(cli, &db, &mut out).await {
+        Ok(()) => out.finish(),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            out.error(&error);
+            error.exit_code()
+        }
+    }
+}
+
+async fn run(cli: Cli, db: &Database, out: &mut Output) -> Result<(), CliError> {
+    let selector = cli.account.as_deref();
+    let connect = |selector| select_account(db, selector).map(|account| S3DataFetcher::new(account, db.clone()));
+    match cli.command {
+        Command::Accounts => {
+            for account in fetch_accounts(db) {
+                out.emit(Record::Account {
+                    id: account.id,
+                    name: account.name,
+                    region: account.default_region,
+                    is_default: account.is_default,
+                });
+            }
+            Ok(())
+        }
+        Command::Ls { path, recursive } => {
+            let location = path.as_deref().map(parse).transpose()?;
+            ls(&connect(selector)?, location, recursive, out).await
+        }
+        Command::Cp { source, destination, recursive, options } => {
+            let options = options.options()?;
+            transfer(&connect(selector)?, parse(&source)?, parse(&destination)?, recursive, false, &options, out).await
+        }
+        Command::Mv { source, destination, recursive, options } => {
+            let options = options.options()?;
+            transfer(&connect(selector)?, parse(&source)?, parse(&destination)?, recursive, true, &options, out).await
+        }
+        Command::Rm { path, recursive } => rm(&connect(selector)?, parse(&path)?, recursive, out).await,
+        Command::Sync { source, destination, delete, options } => {
+            let options = options.options()?;
+            sync(&connect(selector)?, parse(&source)?, parse(&destination)?, delete, &options, out).await
+        }
+        Command::Presign { path, expires_in } => {
+            if expires_in == 0 || expires_in > MAX_PRESIGN_EXPIRY {
+                return Err(CliError::Usage(format!("--expires-in must be between 1 and {} seconds", MAX_PRESIGN_EXPIRY)));
+            }
+            let location = parse(&path)?;
+            let Location::S3 { bucket, key } = &location else {
+                return Err(CliError::Usage("Only s3:// objects can be presigned".to_string()));
+            };
+            if location.is_prefix() {
+                return Err(CliError::Usage(format!("{} is not an object", location)));
+            }
+            let url = connect(selector)?
+                .presign_get_object(bucket, key, Duration::from_secs(expires_in))
+                .await?;
+            out.emit(Record::Url { uri: location.to_string(), url, expires_in });
+            Ok(())
+        }
+        Command::Mb { path, region } => {
+            let bucket = bucket_of(&path)?;
+            validate_bucket_name(&bucket).map_err(CliError::Usage)?;
+            let account = select_account(db, selector)?;
+            let region = region.unwrap_or(account.default_region.clone());
+            match S3DataFetcher::new(account, db.clone()).create_bucket(bucket.clone(), region, &BucketOptions::default()).await? {
+                None => {
+                    out.emit(Record::Done { action: "make_bucket", uri: path });
+                    Ok(())
+                }
+                Some(err) => Err(CliError::Failed(err)),
+            }
+        }
+        Command::Rb { path } => {
+            let bucket = bucket_of(&path)?;
+            match connect(selector)?.delete_data(true, None, bucket, false).await? {
+                None => {
+                    out.emit(Record::Done { action: "remove_bucket", uri: path });
+                    Ok(())
+                }
+                Some(err) => Err(CliError::Failed(err)),
+            }
+        }
+    }
+}
+
+fn parse(value: &str) -> Result<Location, CliError> {
+    Location::parse(value).map_err(CliError::Usage)
+}
+
+/// Name of the bucket of an `s3://bucket` location
+fn bucket_of(value: &str) -> Result<String, CliError> {
+    match parse(value)? {
+        Location::S3 { bucket, key } if key.is_empty() => Ok(bucket),
+        _ => Err(CliError::Usage(format!("Expected s3://bucket, got '{}'", value))),
+    }
+}
+
+/// The account given by name or id, or the default one
+fn select_account(db: &Database, selector: Option<&str>) -> Result<Account, CliError> {
+    match selector {
+        Some(selector) => fetch_accounts(db)
+            .into_iter()
+            .find(|acc| acc.name == selector || acc.id.to_string() == selector)
+            .ok_or_else(|| CliError::Usage(format!("No account '{}', see `dios3-cli accounts`", selector))),
+        None => get_default_account(db).ok_or_else(|| {
+            CliError::Usage("No default account configured. Set one up in DioS3 or pass --account.".to_string())
+        }),
+    }
+}
+
+/// Prefix listing everything "inside" the key, `dir` must not match `dir2/...`
+fn dir_prefix(key: &str) -> String {
+    if key.is_empty() || key.ends_with('/') {
+        key.to_string()
+    } else {
+        format!("{}/", key)
+    }
+}
+
+fn last_segment(key: &str) -> &str {
+    key.rsplit('/').next().unwrap_or(key)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Objects under the prefix, without the zero-sized "directory" markers
+async fn objects_under(fetcher: &S3DataFetcher, bucket: &str, prefix: &str) -> eyre::Result<Vec<S3DataItem>> {
+    let prefix = (!prefix.is_empty()).then(|| prefix.to_string());
+    let objects = fetcher.list_all_objects(bucket, prefix).await?;
+    Ok(objects.into_iter().filter(|item| !item.path.ends_with('/')).collect())
+}
+
+/// Files under the directory with their paths relative to it, nothing when it doesn't exist.
+/// Links to files are compared with the file they point to, linked directories are skipped
+/// as they can link back into the tree.
+fn local_files(root: &Path) -> std::io::Result<Vec<SyncEntry>> {
+    let mut files = Vec::new();
+    if !root.exists() {
+        return Ok(files);
+    }
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = if entry.file_type()?.is_symlink() {
+                match fs::metadata(&path) {
+                    Ok(target) if target.is_file() => target,
+                    _ => continue,
+                }
+            } else {
+                entry.metadata()?
+            };
+            if metadata.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |since| since.as_secs() as i64);
+            files.push(SyncEntry {
+                path: relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                size: metadata.len(),
+                modified,
+                plaintext_size: None,
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Objects under the prefix with their paths relative to it. The ones with a file in `files`
+/// get the size of their content when they're client-side encrypted, to compare it with the file.
+async fn remote_files(
+    fetcher: &S3DataFetcher,
+    bucket: &str,
+    prefix: &str,
+    files: &[SyncEntry],
+) -> eyre::Result<Vec<SyncEntry>> {
+    let objects = objects_under(fetcher, bucket, prefix).await?;
+    let compared: HashSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    let keys = objects
+        .iter()
+        .filter(|item| compared.contains(relative_key(prefix, &item.path)))
+        .map(|item| item.path.clone())
+        .collect();
+    let plaintext_sizes = fetcher.plaintext_sizes(bucket, keys).await?;
+    Ok(objects
+        .into_iter()
+        .map(|item| SyncEntry {
+            path: relative_key(prefix, &item.path).to_string(),
+            size: item.size.unwrap_or(0),
+            modified: item.last_modified.map_or(0, |time| time.secs()),
+            plaintext_size: plaintext_sizes.get(&item.path).copied(),
+        })
+        .collect())
+}
+
+async fn ls(fetcher: &S3DataFetcher, location: Option<Location>, recursive: bool, out: &mut Output) -> Result<(), CliError> {
+    match location {
+        None => {
+            for bucket in fetcher.list_all_buckets().await? {
+                out.emit(Record::Bucket { name: bucket.name, region: bucket.region });
+            }
+        }
+        Some(Location::S3 { bucket, key }) => {
+            let prefix = (!key.is_empty()).then_some(key);
+            let items = if recursive {
+                fetcher.list_all_objects(&bucket, prefix).await?
+            } else {
+                fetcher.list_objects(&bucket, prefix).await?
+            };
+            for item in items {
+                out.emit(Record::Object {
+                    uri: Location::S3 { bucket: bucket.clone(), key: item.path.clone() }.to_string(),
+                    size: item.size,
+                    last_modified: item.last_modified.and_then(|time| time.fmt(DateTimeFormat::DateTime).ok()),
+                    storage_class: item.storage_class,
+                    is_directory: item.is_directory,
+                });
+            }
+        }
+        Some(Location::Local(_)) => return Err(CliError::Usage("Only s3:// locations can be listed".to_string())),
+    }
+    Ok(())
+}
+
+/// Pairs of the files or objects to copy for `cp` and `mv`
+async fn transfer_pairs(
+    fetcher: &S3DataFetcher,
+    source: &Location,
+    destination: &Location,
+    recursive: bool,
+) -> Result<Vec<(Location, Location)>, CliError> {
+    let pairs = match (source, destination) {
+        (Location::Local(_), Location::Local(_)) => {
+            return Err(CliError::Usage("One of the locations must be s3://".to_string()));
+        }
+        (Location::Local(path), Location::S3 { bucket, key }) if path.is_dir() => {
+            if !recursive {
+                return Err(CliError::Usage(format!("{} is a directory, use --recursive", source)));
+            }
+            local_files(path)?
+                .into_iter()
+                .map(|file| {
+                    let to = Location::S3 { bucket: bucket.clone(), key: join_key(key, &file.path) };
+                    (Location::Local(path.join(&file.path)), to)
+                })
+                .collect()
+        }
+        (Location::Local(path), Location::S3 { bucket, key }) => {
+            let key = if destination.is_prefix() { join_key(key, &file_name(path)) } else { key.clone() };
+            vec![(source.clone(), Location::S3 { bucket: bucket.clone(), key })]
+        }
+        (Location::S3 { bucket, key }, _) if recursive => {
+            let prefix = dir_prefix(key);
+            objects_under(fetcher, bucket, &prefix)
+                .await?
+                .into_iter()
+                .map(|item| {
+                    let relative = relative_key(&prefix, &item.path);
+                    let to = match destination {
+                        Location::Local(path) => Location::Local(local_path(path, relative).map_err(CliError::Failed)?),
+                        Location::S3 { bucket, key } => Location::S3 { bucket: bucket.clone(), key: join_key(key, relative) },
+                    };
+                    Ok((Location::S3 { bucket: bucket.clone(), key: item.path.clone() }, to))
+                })
+                .collect::<Result<_, CliError>>()?
+        }
+        (Location::S3 { key, .. }, _) => {
+            if source.is_prefix() {
+                return Err(CliError::Usage(format!("{} is not an object, use --recursive", source)));
+            }
+            let name = last_segment(key);
+            let to = match destination {
+                Location::Local(path) if path.is_dir() || path.to_string_lossy().ends_with(std::path::MAIN_SEPARATOR) => {
+                    Location::Local(local_path(path, name).map_err(CliError::Failed)?)
+                }
+                Location::S3 { bucket, key } if destination.is_prefix() => {
+                    Location::S3 { bucket: bucket.clone(), key: join_key(key, name) }
+                }
+                other => other.clone(),
+            };
+            vec![(source.clone(), to)]
+        }
+    };
+    Ok(pairs)
+}
+
+async fn copy_one(
+    fetcher: &S3DataFetcher,
+    source: &Location,
+    destination: &Location,
+    options: &TransferOptions,
+) -> eyre::Result<&'static str> {
+    match (source, destination) {
+        (Location::Local(path), Location::S3 { bucket, key }) => {
+            let (upload_tx, _progress) = unbounded_channel();
+            let item = options.apply_to(LocalSelectedItem::new(
+                file_name(path),
+                path.to_string_lossy().to_string(),
+                false,
+                bucket.clone(),
+                key.clone(),
+                FileCredential::default(),
+                None,
+            ));
+            fetcher.upload_item(item, upload_tx).await?;
+            Ok("upload")
+        }
+        (Location::S3 { bucket, key }, Location::Local(path)) => {
+            let (download_tx, _progress) = unbounded_channel();
+            fetcher.download_to_file(bucket, key, path, options.encryption.clone(), download_tx).await?;
+            Ok("download")
+        }
+        (Location::S3 { bucket: source_bucket, key: source_key }, Location::S3 { bucket, key }) => {
+            fetcher.copy_object(source_bucket, source_key, bucket, key, options).await?;
+            Ok("copy")
+        }
+        (Location::Local(_), Location::Local(_)) => Err(Report::msg("One of the locations must be s3://")),
+    }
+}
+
+async fn remove_one(fetcher: &S3DataFetcher, location: &Location) -> eyre::Result<()> {
+    match location {
+        Location::Local(path) => Ok(fs::remove_file(path)?),
+        Location::S3 { bucket, key } => match fetcher.delete_data(false, Some(bucket.clone()), key.clone(), false).await? {
+            None => Ok(()),
+            Some(err) => Err(Report::msg(err)),
+        },
+    }
+}
+
+/// Copies the pairs one by one, a failed copy doesn't stop the others
+async fn copy_all(
+    fetcher: &S3DataFetcher,
+    pairs: Vec<(Location, Location)>,
+    remove_source: bool,
+    options: &TransferOptions,
+    out: &mut Output,
+) {
+    for (source, destination) in pairs {
+        let result = match copy_one(fetcher, &source, &destination, options).await {
+            Ok(_) if remove_source => remove_one(fetcher, &source).await.map(|_| "move"),
+            other => other,
+        };
+        match result {
+            Ok(action) => out.emit(Record::Transfer { action, source: source.to_string(), destination: destination.to_string() }),
+            Err(e) => out.emit(Record::Failure { uri: source.to_string(), error: e.to_string() }),
+        }
+    }
+}
+
+async fn transfer(
+    fetcher: &S3DataFetcher,
+    source: Location,
+    destination: Location,
+    recursive: bool,
+    remove_source: bool,
+    options: &TransferOptions,
+    out: &mut Output,
+) -> Result<(), CliError> {
+    let pairs = transfer_pairs(fetcher, &source, &destination, recursive).await?;
+    copy_all(fetcher, pairs, remove_source, options, out).await;
+    Ok(())
+}
+
+async fn rm(fetcher: &S3DataFetcher, location: Location, recursive: bool, out: &mut Output) -> Result<(), CliError> {
+    let Location::S3 { bucket, key } = &location else {
+        return Err(CliError::Usage("Only s3:// objects can be removed".to_string()));
+    };
+    let keys = if recursive {
+        // the "directory" markers are removed too
+        let prefix = (!key.is_empty()).then(|| dir_prefix(key));
+        fetcher.list_all_objects(bucket, prefix).await?.into_iter().map(|item| item.path).collect()
+    } else if location.is_prefix() {
+        return Err(CliError::Usage(format!("{} is not an object, use --recursive", location)));
+    } else {
+        vec![key.clone()]
+    };
+    for key in keys {
+        let object = Location::S3 { bucket: bucket.clone(), key };
+        match remove_one(fetcher, &object).await {
+            Ok(()) => out.emit(Record::Done { action: "delete", uri: object.to_string() }),
+            Err(e) => out.emit(Record::Failure { uri: object.to_string(), error: e.to_string() }),
+        }
+    }
+    Ok(())
+}
+
+async fn sync(
+    fetcher: &S3DataFetcher,
+    source: Location,
+    destination: Location,
+    delete: bool,
+    options: &TransferOptions,
+    out: &mut Output,
+) -> Result<(), CliError> {
+    let (pairs, removed) = match (&source, &destination) {
+        (Location::Local(dir), Location::S3 { bucket, key }) => {
+            if !dir.is_dir() {
+                return Err(CliError::Usage(format!("{} is not a directory", source)));
+            }
+            let prefix = dir_prefix(key);
+            let files = local_files(dir)?;
+            let objects = remote_files(fetcher, bucket, &prefix, &files).await?;
+            let plan = plan_sync(&files, &objects, delete, SyncDirection::Upload);
+            let object = |path: &String| Location::S3 { bucket: bucket.clone(), key: join_key(&prefix, path) };
+            let pairs = plan.transfer.iter().map(|path| (Location::Local(dir.join(path)), object(path))).collect();
+            (pairs, plan.delete.iter().map(object).collect::<Vec<_>>())
+        }
+        (Location::S3 { bucket, key }, Location::Local(dir)) => {
+            let prefix = dir_prefix(key);
+            let files = local_files(dir)?;
+            let objects = remote_files(fetcher, bucket, &prefix, &files).await?;
+            let plan = plan_sync(&objects, &files, delete, SyncDirection::Download);
+            let file = |path: &String| local_path(dir, path).map(Location::Local).map_err(CliError::Failed);
+            let pairs = plan
+                .transfer
+                .iter()
+                .map(|path| Ok((Location::S3 { bucket: bucket.clone(), key: join_key(&prefix, path) }, file(path)?)))
+                .collect::<Result<_, CliError>>()?;
+            (pairs, plan.delete.iter().map(file).collect::<Result<Vec<_>, _>>()?)
+        }
+        _ => return Err(CliError::Usage("sync works between a local directory and an s3:// prefix".to_string())),
+    };
+    copy_all(fetcher, pairs, false, options, out).await;
+    for location in removed {
+        match remove_one(fetcher, &location).await {
+            Ok(()) => out.emit(Record::Done { action: "delete", uri: location.to_string() }),
+            Err(e) => out.emit(Record::Failure { uri: location.to_string(), error: e.to_string() }),
+        }
+    }
+    Ok(())
+}
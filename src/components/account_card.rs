@@ -1,8 +1,14 @@
-use std::time::Duration;
 use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
 
+fn format_size(size: u64) -> String {
+    size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()
+}
+
+/// Bytes stored in the analyzed buckets, `None` until they are analyzed
 #[component]
-pub fn AccountCard() -> Element {
+pub fn AccountCard(total_size: Option<u64>) -> Element {
+    let value = total_size.map(format_size).unwrap_or("-".to_string());
 
     rsx!(
             div {
@@ -23,11 +29,11 @@ pub fn AccountCard() -> Element {
         div {
             p {
                 class: "mb-2 text-sm font-medium text-gray-600 dark:text-gray-400",
-                "Account balance"
+                "Stored data"
             }
             p {
                 class: "text-lg font-semibold text-gray-700 dark:text-gray-200",
-                "$ 46,760.89"
+                "{value}"
             }
         }
     }
@@ -1,10 +1,15 @@
 use dioxus::prelude::*;
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
-use crate::utils::DB;
 use tokio::task::spawn_blocking;
 use crate::model::account::Account;
+use crate::model::bucket::REGIONS;
+use crate::model::client_encryption::ClientKey;
+use crate::model::encryption::EncryptionSettings;
 use crate::repositories::account_repo::save_account_to_db;
+use crate::repositories::client_key_repo::{fetch_client_keys, save_client_key};
+use crate::services::s3_data_fetcher::S3DataFetcher;
+use crate::state::{db, AccountHealth, ACCOUNT_HEALTH};
 
 #[derive(Props, Clone, PartialEq)]
 pub struct AccountModalProps {
@@ -22,6 +27,65 @@ pub fn AccountModal(mut props: AccountModalProps) -> Element {
     let mut secret_key = use_signal(|| account.as_ref().map(|a| a.secret_key.clone()).unwrap_or_default());
     let mut is_default = use_signal(|| account.as_ref().map(|a| a.is_default.clone()).unwrap_or_default());
     let mut default_region = use_signal(|| account.as_ref().map(|a| a.default_region.clone()).unwrap_or_default());
+    let mut endpoint_url = use_signal(|| account.as_ref().and_then(|a| a.endpoint_url.clone()).unwrap_or_default());
+    let encryption = account.as_ref().map(|a| a.encryption.clone()).unwrap_or_default();
+    let mut sse_mode = use_signal(|| encryption.mode().to_string());
+    let mut kms_key_id = use_signal(|| encryption.kms_key_id().unwrap_or_default());
+    let mut customer_key = use_signal(|| encryption.sse_customer_key().unwrap_or_default());
+    let mut kms_keys = use_signal(Vec::<(String, String)>::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut client_key_id = use_signal(|| account.as_ref().and_then(|a| a.client_key_id));
+    let client_keys = use_signal(|| fetch_client_keys(db()));
+    // key generated with "New key", stored only when the account is saved. Its id is 0 until then.
+    let mut new_client_key = use_signal(|| None as Option<ClientKey>);
+    // credentials of the last connection test together with its outcome
+    let mut connection = use_signal(|| None as Option<(Account, Result<String, String>)>);
+    let mut testing = use_signal(|| false);
+    let account_id = account.as_ref().map(|a| a.id);
+
+    // the account as it's being edited, connections are tested with its credentials
+    let edited_account = move || Account {
+        access_key: access_key.read().clone(),
+        secret_key: secret_key.read().clone(),
+        default_region: default_region.read().clone(),
+        endpoint_url: Some(endpoint_url.read().trim().to_string()).filter(|url| !url.is_empty()),
+        ..props.selected_account.read().clone().unwrap_or_default()
+    };
+    // outcome of the test, unless the credentials were changed since
+    let tested_connection = connection
+        .read()
+        .as_ref()
+        .filter(|(tested, _)| *tested == edited_account())
+        .map(|(_, outcome)| outcome.clone());
+
+    let save = move |health: AccountHealth| {
+        let name = account_name.read().clone();
+        let description = short_description.read().clone();
+        let account = edited_account();
+        let is_default = *is_default.read();
+        let encryption = EncryptionSettings::from_parts(
+            &sse_mode.read(),
+            Some(kms_key_id.read().trim().to_string()),
+            Some(customer_key.read().trim().to_string()),
+        );
+        let client_key_id = *client_key_id.read();
+        let new_key = new_client_key.read().clone().filter(|_| client_key_id == Some(0));
+
+        spawn_blocking(move || {
+            let client_key_id = match new_key {
+                Some(key) => save_client_key(db(), &key.name, &key.key),
+                None => client_key_id,
+            };
+            save_account_to_db(db(), account_id, &name, &description, &account.access_key, &account.secret_key, is_default, &account.default_region, &encryption, client_key_id, account.endpoint_url.as_deref());
+        });
+        // new accounts get tested when they're listed
+        if let Some(account_id) = account_id {
+            ACCOUNT_HEALTH.write().insert(account_id, health);
+        }
+        let (mut refresh_accounts, mut show_modal) = (props.refresh_accounts, props.show_modal);
+        refresh_accounts.set(true);
+        show_modal.set(false);
+    };
 
     rsx! {
         div {
@@ -33,26 +97,50 @@ pub fn AccountModal(mut props: AccountModalProps) -> Element {
 
                 h2 { class: "text-xl font-bold mb-4 text-gray-900 dark:text-gray-100", if account.is_some() { "Edit Account" } else { "New Account" } }
 
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                        "{error}"
+                    }
+                }
+
                 form {
                     class: "space-y-4",
                     onsubmit: move |evt| {
                         evt.prevent_default();
 
-                        let name = account_name.read().clone();
-                        let description = short_description.read().clone();
-                        let access_key = access_key.read().clone();
-                        let secret_key = secret_key.read().clone();
-                        let is_default = is_default.read().clone();
-                        let default_region = default_region.read().clone();
-                        let account_id = account.as_ref().map(|a| a.id);
-
-                        println!("SEtting account default value to: {:?}", is_default);
+                        let encryption = EncryptionSettings::from_parts(
+                            &sse_mode.read(),
+                            Some(kms_key_id.read().trim().to_string()),
+                            Some(customer_key.read().trim().to_string()),
+                        );
+                        if let Err(err) = encryption.validate() {
+                            error_message.set(Some(err));
+                            return;
+                        }
 
-                        spawn_blocking(move || {
-                            save_account_to_db(account_id, &name, &description, &access_key, &secret_key, is_default, &default_region);
-                        });
-                        props.refresh_accounts.set(true);
-                        props.show_modal.set(false);
+                        let tested = connection
+                            .read()
+                            .as_ref()
+                            .filter(|(tested, _)| *tested == edited_account())
+                            .map(|(_, outcome)| outcome.clone());
+                        match tested {
+                            // a failed test was shown already, saving anyway is the user's call
+                            Some(Ok(identity)) => save(AccountHealth::Healthy(identity)),
+                            Some(Err(error)) => save(AccountHealth::Failed(error)),
+                            None => {
+                                let tested = edited_account();
+                                let fetcher = S3DataFetcher::new(tested.clone(), db().clone());
+                                testing.set(true);
+                                spawn(async move {
+                                    match fetcher.test_connection().await {
+                                        Ok(identity) => save(AccountHealth::Healthy(identity)),
+                                        Err(e) => connection.set(Some((tested, Err(e.to_string())))),
+                                    }
+                                    testing.set(false);
+                                });
+                            }
+                        }
                     },
                     div {
                         label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Account Name" }
@@ -110,24 +198,156 @@ pub fn AccountModal(mut props: AccountModalProps) -> Element {
                             value: "{default_region}",
                             oninput: move |e| default_region.set(e.value().clone()),
                             option { value: "", "Select a region" }
-                            option { value: "us-east-1", "US East (N. Virginia)" }
-                            option { value: "us-west-1", "US West (N. California)" }
-                            option { value: "us-west-2", "US West (Oregon)" }
-                            option { value: "eu-west-1", "EU (Ireland)" }
-                            option { value: "eu-central-1", "EU (Frankfurt)" }
-                            option { value: "ap-southeast-1", "Asia Pacific (Singapore)" }
-                            option { value: "ap-northeast-1", "Asia Pacific (Tokyo)" }
-                            option { value: "ap-southeast-2", "Asia Pacific (Sydney)" }
-                            option { value: "ap-northeast-2", "Asia Pacific (Seoul)" }
-                            option { value: "sa-east-1", "South America (São Paulo)" }
-                            option { value: "ca-central-1", "Canada (Central)" }
+                            {REGIONS.iter().map(|(code, label)| rsx!(
+                                option { value: "{code}", "{label} ({code})" }
+                            ))}
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Endpoint" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "Empty for AWS, eg. http://localhost:9000 for MinIO",
+                            value: "{endpoint_url}",
+                            oninput: move |e| endpoint_url.set(e.value().clone()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Encryption" }
+                        select {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{sse_mode}",
+                            oninput: move |e| sse_mode.set(e.value().clone()),
+                            option { value: "none", "Bucket default" }
+                            option { value: "sse-s3", "SSE-S3 (S3 managed keys)" }
+                            option { value: "sse-kms", "SSE-KMS (KMS managed keys)" }
+                            option { value: "sse-c", "SSE-C (customer provided key)" }
+                        }
+                    }
+                    if *sse_mode.read() == "sse-kms" {
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "KMS Key" }
+                            div { class: "flex space-x-2",
+                                input {
+                                    class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                    r#type: "text",
+                                    list: "kms-keys",
+                                    placeholder: "Key id, ARN or alias (empty for aws/s3)",
+                                    value: "{kms_key_id}",
+                                    oninput: move |e| kms_key_id.set(e.value().clone()),
+                                }
+                                button {
+                                    class: "px-3 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                                    r#type: "button",
+                                    onclick: move |_| {
+                                        // the keys are listed with the credentials being edited, not the selected account
+                                        let fetcher = S3DataFetcher::new(edited_account(), db().clone());
+                                        spawn(async move {
+                                            match fetcher.list_kms_keys().await {
+                                                Ok(keys) => kms_keys.set(keys),
+                                                Err(e) => error_message.set(Some(format!("Cannot list KMS keys: {}", e))),
+                                            }
+                                        });
+                                    },
+                                    "Load keys"
+                                }
+                            }
+                            datalist { id: "kms-keys",
+                                {kms_keys.read().iter().map(|(alias, key_id)| rsx!(
+                                    option { value: "{key_id}", "{alias}" }
+                                ))}
+                            }
+                        }
+                    }
+                    if *sse_mode.read() == "sse-c" {
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Customer Key (base64, 256-bit)" }
+                            div { class: "flex space-x-2",
+                                input {
+                                    class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                    r#type: "password",
+                                    placeholder: "SSE-C key",
+                                    value: "{customer_key}",
+                                    oninput: move |e| customer_key.set(e.value().clone()),
+                                }
+                                button {
+                                    class: "px-3 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                                    r#type: "button",
+                                    onclick: move |_| customer_key.set(EncryptionSettings::generate_customer_key()),
+                                    "Generate"
+                                }
+                            }
+                            p { class: "mt-1 text-xs text-gray-600 dark:text-gray-400",
+                                "The key is stored only in the local database. Objects can't be read without it."
+                            }
                         }
                     }
                     div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Client-side encryption" }
+                        div { class: "flex space-x-2",
+                            select {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                value: client_key_id().map(|id| id.to_string()).unwrap_or_default(),
+                                oninput: move |e| client_key_id.set(e.value().parse().ok()),
+                                option { value: "", "Disabled" }
+                                {client_keys.read().iter().chain(new_client_key.read().as_ref()).map(|key| rsx!(
+                                    option { value: "{key.id}", "{key.name}" }
+                                ))}
+                            }
+                            button {
+                                class: "px-3 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                                r#type: "button",
+                                disabled: new_client_key.read().is_some(),
+                                onclick: move |_| {
+                                    let name = format!("{} key {}", account_name.read(), client_keys.read().len() + 1);
+                                    new_client_key.set(Some(ClientKey { id: 0, name: name.trim().to_string(), key: ClientKey::generate() }));
+                                    client_key_id.set(Some(0));
+                                },
+                                "New key"
+                            }
+                        }
+                        p { class: "mt-1 text-xs text-gray-600 dark:text-gray-400",
+                            "Uploads are encrypted before leaving this machine. Keys are kept only in the local database, back it up to keep access to the data."
+                        }
+                    }
+                    match &tested_connection {
+                        Some(Ok(identity)) => rsx! {
+                            div {
+                                class: "p-3 bg-green-100 border border-green-400 text-green-700 rounded break-words",
+                                "Connected as {identity}"
+                            }
+                        },
+                        Some(Err(error)) => rsx! {
+                            div {
+                                class: "p-3 bg-red-100 border border-red-400 text-red-700 rounded break-words",
+                                "Connection failed: {error}"
+                            }
+                        },
+                        None => rsx! {},
+                    }
+                    div { class: "flex space-x-2",
+                        button {
+                            class: "px-4 py-2 text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50",
+                            r#type: "button",
+                            disabled: *testing.read(),
+                            onclick: move |_| {
+                                let tested = edited_account();
+                                let fetcher = S3DataFetcher::new(tested.clone(), db().clone());
+                                testing.set(true);
+                                spawn(async move {
+                                    let outcome = fetcher.test_connection().await.map_err(|e| e.to_string());
+                                    connection.set(Some((tested, outcome)));
+                                    testing.set(false);
+                                });
+                            },
+                            if *testing.read() { "Testing..." } else { "Test connection" }
+                        }
                         button {
-                            class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700",
+                            class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50",
                             r#type: "submit",
-                            "Save"
+                            disabled: *testing.read(),
+                            if matches!(tested_connection, Some(Err(_))) { "Save anyway" } else { "Save" }
                         }
                     }
                 }
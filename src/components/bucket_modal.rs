@@ -1,20 +1,23 @@
 use dioxus::prelude::*;
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
-use crate::utils::DB;
 use tokio::task::spawn_blocking;
-use crate::model::bucket::Bucket;
-use crate::services::s3_data_fetcher::S3DataFetcher;
+use crate::components::key_value_editor::{entries_to_map, KeyValueEditor};
+use crate::model::bucket::{validate_bucket_name, BucketOptions, REGIONS};
+use crate::model::encryption::EncryptionSettings;
+use crate::repositories::account_repo::fetch_accounts;
+use crate::state::{account_fetcher, db};
 
 #[derive(Props, Clone, PartialEq)]
 pub struct BucketModalProps {
+    account: i64,
     show_modal: Signal<bool>,
     refresh_buckets: Signal<bool>,
 }
 
-async fn save_bucket(name: &str, region: &str) -> Result<(), String> {
-    if let Some(fetcher) = S3DataFetcher::from_db_account() {
-        match fetcher.create_bucket(name.to_string(), region.to_string()).await {
+async fn save_bucket(account: i64, name: &str, region: &str, options: &BucketOptions) -> Result<(), String> {
+    if let Some(fetcher) = account_fetcher(account) {
+        match fetcher.create_bucket(name.to_string(), region.to_string(), options).await {
             Ok(None) => {
                 println!("Bucket '{}' created successfully in region '{}'", name, region);
                 Ok(())
@@ -30,7 +33,7 @@ async fn save_bucket(name: &str, region: &str) -> Result<(), String> {
             }
         }
     } else {
-        let error_msg = "No default account configured. Please set up an AWS account first.".to_string();
+        let error_msg = "The account doesn't exist anymore".to_string();
         println!("{}", error_msg);
         Err(error_msg)
     }
@@ -38,17 +41,34 @@ async fn save_bucket(name: &str, region: &str) -> Result<(), String> {
 
 #[component]
 pub fn BucketModal(mut props: BucketModalProps) -> Element {
-    let mut bucket_name = use_signal(|| String::new());
-    let mut region = use_signal(|| String::new());
+    // buckets are created in the region of the account unless another one is picked
+    let account_region = fetch_accounts(db())
+        .into_iter()
+        .find(|account| account.id == props.account)
+        .map(|account| account.default_region)
+        .unwrap_or_default();
+    let mut bucket_name = use_signal(String::new);
+    let mut region = use_signal(|| account_region.clone());
+    let mut versioning = use_signal(|| false);
+    let mut object_lock = use_signal(|| false);
+    let mut sse_mode = use_signal(|| "none".to_string());
+    let mut kms_key_id = use_signal(String::new);
+    let tags = use_signal(Vec::<(String, String)>::new);
+    let mut block_public_access = use_signal(|| true);
     let mut error_message = use_signal(|| None as Option<String>);
     let mut is_saving = use_signal(|| false);
+    let name_error = Some(bucket_name.read().trim().to_string())
+        .filter(|name| !name.is_empty())
+        .and_then(|name| validate_bucket_name(&name).err());
+    // s3 compatible services have their own region names
+    let custom_region = !account_region.is_empty() && !REGIONS.iter().any(|(code, _)| *code == account_region);
     
     rsx! {
         div {
             class: "fixed inset-0 z-50 w-screen h-screen flex items-center justify-center bg-black bg-opacity-50",
             onclick: move |_| props.show_modal.set(false),
             div {
-                class: "bg-white dark:bg-gray-800 rounded-lg p-6 shadow-xl w-full max-w-md",
+                class: "bg-white dark:bg-gray-800 rounded-lg p-6 shadow-xl w-full max-w-md max-h-screen overflow-y-auto",
                 onclick: move |e| e.stop_propagation(), // prevent click from closing the modal
 
                 h2 { class: "text-xl font-bold mb-4 text-gray-900 dark:text-gray-100", "New Bucket" }
@@ -65,29 +85,47 @@ pub fn BucketModal(mut props: BucketModalProps) -> Element {
                     onsubmit: move |evt| {
                         evt.prevent_default();
                         
-                        let name = bucket_name.read().clone();
+                        let name = bucket_name.read().trim().to_string();
                         let region = region.read().clone();
                         
-                        if name.trim().is_empty() {
+                        if name.is_empty() {
                             error_message.set(Some("Bucket name is required".to_string()));
                             return;
                         }
+                        if let Err(err) = validate_bucket_name(&name) {
+                            error_message.set(Some(err));
+                            return;
+                        }
                         
                         if region.trim().is_empty() {
                             error_message.set(Some("Region is required".to_string()));
                             return;
                         }
 
+                        let options = BucketOptions {
+                            object_lock: *object_lock.read(),
+                            versioning: *versioning.read() || *object_lock.read(),
+                            encryption: EncryptionSettings::from_parts(&sse_mode.read(), Some(kms_key_id.read().trim().to_string()), None),
+                            tags: entries_to_map(&tags.read()),
+                            block_public_access: *block_public_access.read(),
+                        };
+                        if let Err(err) = options.validate() {
+                            error_message.set(Some(err));
+                            return;
+                        }
+
                         error_message.set(None);
                         is_saving.set(true);
                         
                         spawn(async move {
-                            match save_bucket(&name, &region).await {
+                            match save_bucket(props.account, &name, &region, &options).await {
                                 Ok(()) => {
                                     props.refresh_buckets.set(true);
                                     props.show_modal.set(false);
                                 }
                                 Err(err) => {
+                                    // the bucket may exist already, with only some of the options applied
+                                    props.refresh_buckets.set(true);
                                     error_message.set(Some(err));
                                     is_saving.set(false);
                                 }
@@ -103,6 +141,9 @@ pub fn BucketModal(mut props: BucketModalProps) -> Element {
                             value: "{bucket_name}",
                             oninput: move |e| bucket_name.set(e.value().clone()),
                         }
+                        if let Some(error) = name_error {
+                            p { class: "mt-1 text-xs text-red-600", "{error}" }
+                        }
                     }
                     div {
                         label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Region" }
@@ -111,19 +152,73 @@ pub fn BucketModal(mut props: BucketModalProps) -> Element {
                             value: "{region}",
                             oninput: move |e| region.set(e.value().clone()),
                             option { value: "", "Select a region" }
-                            option { value: "us-east-1", "US East (N. Virginia)" }
-                            option { value: "us-west-1", "US West (N. California)" }
-                            option { value: "us-west-2", "US West (Oregon)" }
-                            option { value: "eu-west-1", "EU (Ireland)" }
-                            option { value: "eu-central-1", "EU (Frankfurt)" }
-                            option { value: "ap-southeast-1", "Asia Pacific (Singapore)" }
-                            option { value: "ap-northeast-1", "Asia Pacific (Tokyo)" }
-                            option { value: "ap-southeast-2", "Asia Pacific (Sydney)" }
-                            option { value: "ap-northeast-2", "Asia Pacific (Seoul)" }
-                            option { value: "sa-east-1", "South America (São Paulo)" }
-                            option { value: "ca-central-1", "Canada (Central)" }
+                            if custom_region {
+                                option { value: "{account_region}", "{account_region}" }
+                            }
+                            {REGIONS.iter().map(|(code, label)| rsx!(
+                                option { value: "{code}", "{label} ({code})" }
+                            ))}
+                        }
+                    }
+                    div { class: "space-y-2",
+                        label { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300",
+                            input {
+                                class: "h-4 w-4 text-purple-600 rounded",
+                                r#type: "checkbox",
+                                checked: *versioning.read() || *object_lock.read(),
+                                disabled: *object_lock.read(),
+                                onchange: move |e| versioning.set(e.checked()),
+                            }
+                            span { "Versioning" }
+                        }
+                        label { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300",
+                            input {
+                                class: "h-4 w-4 text-purple-600 rounded",
+                                r#type: "checkbox",
+                                checked: *object_lock.read(),
+                                onchange: move |e| object_lock.set(e.checked()),
+                            }
+                            span { "Object lock" }
+                        }
+                        if *object_lock.read() {
+                            p { class: "text-xs text-gray-600 dark:text-gray-400",
+                                "Object lock can't be disabled later and keeps versioning enabled."
+                            }
+                        }
+                        label { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300",
+                            input {
+                                class: "h-4 w-4 text-purple-600 rounded",
+                                r#type: "checkbox",
+                                checked: *block_public_access.read(),
+                                onchange: move |e| block_public_access.set(e.checked()),
+                            }
+                            span { "Block all public access" }
                         }
                     }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Default encryption" }
+                        select {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{sse_mode}",
+                            oninput: move |e| sse_mode.set(e.value().clone()),
+                            option { value: "none", "Service default" }
+                            option { value: "sse-s3", "SSE-S3 (S3 managed keys)" }
+                            option { value: "sse-kms", "SSE-KMS (KMS managed keys)" }
+                        }
+                        if *sse_mode.read() == "sse-kms" {
+                            input {
+                                class: "mt-2 w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "text",
+                                placeholder: "Key id, ARN or alias (empty for aws/s3)",
+                                value: "{kms_key_id}",
+                                oninput: move |e| kms_key_id.set(e.value().clone()),
+                            }
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Tags" }
+                        KeyValueEditor { entries: tags }
+                    }
                     div {
                         button {
                             class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
@@ -136,4 +231,4 @@ pub fn BucketModal(mut props: BucketModalProps) -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
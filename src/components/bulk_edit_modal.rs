@@ -0,0 +1,165 @@
+use dioxus::prelude::*;
+use crate::components::key_value_editor::{entries_to_map, KeyValueEditor};
+use crate::model::object_details::{ObjectMetadataUpdate, ObjectTagsUpdate};
+use crate::model::storage_class::STORAGE_CLASSES;
+use crate::state::account_fetcher;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct BulkEditModalProps {
+    account: i64,
+    bucket: String,
+    keys: Vec<String>,
+    show_modal: Signal<bool>,
+    refresh_objects: Signal<bool>,
+}
+
+fn split_keys(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// Applies the same metadata and tag changes to all the selected objects
+#[component]
+pub fn BulkEditModal(mut props: BulkEditModalProps) -> Element {
+    let mut content_type = use_signal(String::new);
+    let mut cache_control = use_signal(String::new);
+    let mut storage_class = use_signal(String::new);
+    let metadata = use_signal(Vec::<(String, String)>::new);
+    let mut remove_metadata = use_signal(String::new);
+    let tags = use_signal(Vec::<(String, String)>::new);
+    let mut remove_tags = use_signal(String::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut is_saving = use_signal(|| false);
+    let keys_count = props.keys.len();
+
+    rsx! {
+        div {
+            class: "fixed inset-0 z-50 w-screen h-screen flex items-center justify-center bg-black bg-opacity-50",
+            onclick: move |_| props.show_modal.set(false),
+            div {
+                class: "bg-white dark:bg-gray-800 rounded-lg p-6 shadow-xl w-full max-w-lg max-h-screen overflow-y-auto",
+                onclick: move |e| e.stop_propagation(), // prevent click from closing the modal
+
+                h2 { class: "text-xl font-bold mb-4 text-gray-900 dark:text-gray-100", "Edit {keys_count} objects" }
+                p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400", "Leave a field empty to keep the current value of every object." }
+
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line",
+                        "{error}"
+                    }
+                }
+
+                form {
+                    class: "space-y-4",
+                    onsubmit: move |evt| {
+                        evt.prevent_default();
+                        let bucket = props.bucket.clone();
+                        let keys = props.keys.clone();
+                        let metadata_update = ObjectMetadataUpdate {
+                            content_type: Some(content_type.read().trim().to_string()).filter(|v| !v.is_empty()),
+                            cache_control: Some(cache_control.read().trim().to_string()).filter(|v| !v.is_empty()),
+                            upsert_metadata: entries_to_map(&metadata.read()),
+                            remove_metadata: split_keys(&remove_metadata.read()),
+                            storage_class: Some(storage_class.read().clone()).filter(|v| !v.is_empty()),
+                        };
+                        let tags_update = ObjectTagsUpdate {
+                            upsert: entries_to_map(&tags.read()),
+                            remove: split_keys(&remove_tags.read()),
+                        };
+                        if metadata_update.is_empty() && tags_update.is_empty() {
+                            error_message.set(Some("Nothing to update".to_string()));
+                            return;
+                        }
+                        error_message.set(None);
+                        is_saving.set(true);
+                        spawn(async move {
+                            let errors = match account_fetcher(props.account) {
+                                Some(fetcher) => fetcher.update_objects(&bucket, keys, Some(metadata_update), Some(tags_update)).await,
+                                None => vec!["The account doesn't exist anymore".to_string()],
+                            };
+                            is_saving.set(false);
+                            props.refresh_objects.set(true);
+                            if errors.is_empty() {
+                                props.show_modal.set(false);
+                            } else {
+                                error_message.set(Some(errors.join("\n")));
+                            }
+                        });
+                    },
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Content-Type" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. text/html",
+                            value: "{content_type}",
+                            oninput: move |e| content_type.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Cache-Control" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. max-age=3600",
+                            value: "{cache_control}",
+                            oninput: move |e| cache_control.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Storage class" }
+                        select {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{storage_class}",
+                            oninput: move |e| storage_class.set(e.value()),
+                            option { value: "", "Keep current" }
+                            {STORAGE_CLASSES.iter().map(|(value, label)| rsx!(
+                                option { value: "{value}", "{label}" }
+                            ))}
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Set metadata" }
+                        KeyValueEditor { entries: metadata }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Remove metadata keys" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "comma separated keys",
+                            value: "{remove_metadata}",
+                            oninput: move |e| remove_metadata.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Set tags" }
+                        KeyValueEditor { entries: tags }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Remove tag keys" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "comma separated keys",
+                            value: "{remove_tags}",
+                            oninput: move |e| remove_tags.set(e.value()),
+                        }
+                    }
+                    div {
+                        button {
+                            class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                            r#type: "submit",
+                            disabled: *is_saving.read(),
+                            if *is_saving.read() { "Applying..." } else { "Apply to {keys_count} objects" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
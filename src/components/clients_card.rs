@@ -1,9 +1,9 @@
 use std::time::Duration;
 use dioxus::prelude::*;
-use crate::services::s3_data_fetcher::S3DataFetcher;
+use crate::state::account_fetcher;
 
 #[component]
-pub fn ClientsCard() -> Element {
+pub fn ClientsCard(account: i64) -> Element {
     let clients = use_signal(|| None as Option<u32>);
     let loading = use_signal(|| true);
 
@@ -11,7 +11,7 @@ pub fn ClientsCard() -> Element {
         let mut clients = clients.clone();
         let mut loading = loading.clone();
         move |_: UnboundedReceiver<u32>| async move {
-            let buckets_count = if let Some(fetcher) = S3DataFetcher::from_db_account() {
+            let buckets_count = if let Some(fetcher) = account_fetcher(account) {
                 match fetcher.list_current_location(None, None).await {
                     Ok(buckets) => {
                         buckets.len() as u32
@@ -22,7 +22,7 @@ pub fn ClientsCard() -> Element {
                     }
                 }
             } else {
-                println!("S3DataFetcher::from_db_account() returned None - no default account?");
+                println!("Account {} doesn't exist anymore", account);
                 0
             };
             clients.set(Some(buckets_count));
@@ -1,8 +1,14 @@
-use std::time::Duration;
 use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
 
+fn format_size(size: u64) -> String {
+    size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()
+}
+
+/// Bytes taken by the parts of incomplete multipart uploads, `None` until the buckets are analyzed
 #[component]
-pub fn ContactsCard() -> Element {
+pub fn ContactsCard(incomplete_size: Option<u64>) -> Element {
+    let value = incomplete_size.map(format_size).unwrap_or("-".to_string());
     rsx!(
            div {
         class: "flex items-center p-4 bg-white rounded-lg shadow-xs dark:bg-gray-800",
@@ -22,11 +28,11 @@ pub fn ContactsCard() -> Element {
         div {
             p {
                 class: "mb-2 text-sm font-medium text-gray-600 dark:text-gray-400",
-                "Pending contacts"
+                "Incomplete uploads"
             }
             p {
                 class: "text-lg font-semibold text-gray-700 dark:text-gray-200",
-                "35"
+                "{value}"
             }
         }
     }
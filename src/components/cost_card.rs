@@ -0,0 +1,40 @@
+use dioxus::prelude::*;
+use crate::model::pricing::format_cost;
+
+/// Estimated monthly storage cost of the analyzed buckets, `None` until they are analyzed
+#[component]
+pub fn CostCard(monthly_cost: Option<f64>) -> Element {
+    let value = monthly_cost.map(format_cost).unwrap_or("-".to_string());
+
+    rsx!(
+        div {
+            class: "flex items-center p-4 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            div {
+                class: "p-3 mr-4 text-teal-500 bg-teal-100 rounded-full dark:text-teal-100 dark:bg-teal-500",
+                svg {
+                    class: "w-5 h-5",
+                    fill: "currentColor",
+                    view_box: "0 0 20 20",
+                    path {
+                        d: "M8.433 7.418c.155-.103.346-.196.567-.267v1.698a2.305 2.305 0 01-.567-.267C8.07 8.34 8 8.114 8 8c0-.114.07-.34.433-.582zM11 12.849v-1.698c.22.071.412.164.567.267.364.243.433.468.433.582 0 .114-.07.34-.433.582a2.305 2.305 0 01-.567.267z"
+                    }
+                    path {
+                        fill_rule: "evenodd",
+                        clip_rule: "evenodd",
+                        d: "M10 18a8 8 0 100-16 8 8 0 000 16zm1-13a1 1 0 10-2 0v.092a4.535 4.535 0 00-1.676.662C6.602 6.234 6 7.009 6 8c0 .99.602 1.765 1.324 2.246.48.32 1.054.545 1.676.662v1.941c-.391-.127-.68-.317-.843-.504a1 1 0 10-1.51 1.31c.562.649 1.413 1.076 2.353 1.253V15a1 1 0 102 0v-.092a4.535 4.535 0 001.676-.662C13.398 13.766 14 12.991 14 12c0-.99-.602-1.765-1.324-2.246A4.535 4.535 0 0011 9.092V7.151c.391.127.68.317.843.504a1 1 0 101.511-1.31c-.563-.649-1.413-1.076-2.354-1.253V5z"
+                    }
+                }
+            }
+            div {
+                p {
+                    class: "mb-2 text-sm font-medium text-gray-600 dark:text-gray-400",
+                    "Estimated monthly cost"
+                }
+                p {
+                    class: "text-lg font-semibold text-gray-700 dark:text-gray-200",
+                    "{value}"
+                }
+            }
+        }
+    )
+}
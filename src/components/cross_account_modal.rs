@@ -0,0 +1,145 @@
+use dioxus::prelude::*;
+use crate::model::cross_account::CrossAccountTransfer;
+use crate::model::s3_uri::{join_key, relative_key};
+use crate::repositories::account_repo::fetch_accounts;
+use crate::state::{account_fetcher, db, queue_cross_account_transfers};
+
+#[derive(Props, Clone, PartialEq)]
+pub struct CrossAccountModalProps {
+    /// Account of the bucket the objects are copied from
+    account: i64,
+    bucket: String,
+    /// Prefix the objects are listed under, the copies keep their keys relative to it
+    prefix: String,
+    keys: Vec<String>,
+    show_modal: Signal<bool>,
+}
+
+/// Copies the selected objects into a bucket of another account, streaming them through the app
+#[component]
+pub fn CrossAccountModal(mut props: CrossAccountModalProps) -> Element {
+    let source_account_id = props.account;
+    let accounts: Vec<_> = fetch_accounts(db())
+        .into_iter()
+        .filter(|account| account.id != source_account_id)
+        .collect();
+    let mut destination_account_id = use_signal(|| accounts.first().map(|account| account.id));
+    let mut destination_buckets = use_signal(Vec::<String>::new);
+    let mut destination_bucket = use_signal(String::new);
+    let mut destination_prefix = use_signal(|| props.prefix.clone());
+    let mut error_message = use_signal(|| None as Option<String>);
+    let keys_count = props.keys.len();
+
+    // the buckets of the chosen account are offered as suggestions
+    use_effect(move || {
+        let Some(fetcher) = destination_account_id.read().and_then(account_fetcher) else {
+            return;
+        };
+        destination_buckets.write().clear();
+        spawn(async move {
+            match fetcher.list_all_buckets().await {
+                Ok(buckets) => destination_buckets.set(buckets.into_iter().map(|bucket| bucket.name).collect()),
+                Err(e) => error_message.set(Some(format!("Failed to list the buckets of the account: {}", e))),
+            }
+        });
+    });
+
+    rsx! {
+        div {
+            class: "fixed inset-0 z-50 w-screen h-screen flex items-center justify-center bg-black bg-opacity-50",
+            onclick: move |_| props.show_modal.set(false),
+            div {
+                class: "bg-white dark:bg-gray-800 rounded-lg p-6 shadow-xl w-full max-w-lg max-h-screen overflow-y-auto",
+                onclick: move |e| e.stop_propagation(), // prevent click from closing the modal
+
+                h2 { class: "text-xl font-bold mb-4 text-gray-900 dark:text-gray-100", "Copy {keys_count} objects to another account" }
+                p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                    "The objects are streamed through the app without being stored locally. Interrupted copies can be resumed from the transfers list."
+                }
+
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line",
+                        "{error}"
+                    }
+                }
+
+                if accounts.is_empty() {
+                    p { class: "text-sm text-gray-700 dark:text-gray-400", "Add another account first." }
+                } else {
+                    form {
+                        class: "space-y-4",
+                        onsubmit: move |evt| {
+                            evt.prevent_default();
+                            let Some(destination_account_id) = *destination_account_id.read() else {
+                                return;
+                            };
+                            let bucket = destination_bucket.read().trim().to_string();
+                            if bucket.is_empty() {
+                                error_message.set(Some("Choose the destination bucket".to_string()));
+                                return;
+                            }
+                            let prefix = destination_prefix.read().trim().to_string();
+                            let transfers = props
+                                .keys
+                                .iter()
+                                .map(|key| {
+                                    let destination_key = join_key(&prefix, relative_key(&props.prefix, key));
+                                    CrossAccountTransfer::new(source_account_id, &props.bucket, key, destination_account_id, &bucket, &destination_key)
+                                })
+                                .collect();
+                            queue_cross_account_transfers(transfers);
+                            props.show_modal.set(false);
+                        },
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Destination account" }
+                            select {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                onchange: move |e| destination_account_id.set(e.value().parse().ok()),
+                                for account in accounts.iter() {
+                                    option {
+                                        value: "{account.id}",
+                                        selected: Some(account.id) == *destination_account_id.read(),
+                                        "{account.name}"
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Destination bucket" }
+                            input {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "text",
+                                list: "destination-buckets",
+                                value: "{destination_bucket}",
+                                oninput: move |e| destination_bucket.set(e.value()),
+                            }
+                            datalist { id: "destination-buckets",
+                                for bucket in destination_buckets.read().iter() {
+                                    option { value: "{bucket}" }
+                                }
+                            }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Destination prefix" }
+                            input {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "text",
+                                placeholder: "eg. backups/",
+                                value: "{destination_prefix}",
+                                oninput: move |e| destination_prefix.set(e.value()),
+                            }
+                        }
+                        div {
+                            button {
+                                class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700",
+                                r#type: "submit",
+                                "Copy {keys_count} objects"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use dioxus::prelude::*;
+use std::collections::BTreeMap;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct KeyValueEditorProps {
+    entries: Signal<Vec<(String, String)>>,
+    #[props(default = "Key".to_string())]
+    key_placeholder: String,
+    #[props(default = "Value".to_string())]
+    value_placeholder: String,
+}
+
+/// Turns the edited rows into a map, skipping rows without a key
+pub fn entries_to_map(entries: &[(String, String)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .filter(|(k, _)| !k.trim().is_empty())
+        .map(|(k, v)| (k.trim().to_string(), v.clone()))
+        .collect()
+}
+
+/// Editable list of key/value pairs used for object metadata and tags
+#[component]
+pub fn KeyValueEditor(mut props: KeyValueEditorProps) -> Element {
+    let rows = props.entries.read().clone();
+    rsx! {
+        div { class: "space-y-2",
+            {rows.into_iter().enumerate().map(|(idx, (key, value))| {
+                rsx!(
+                    div { class: "flex items-center space-x-2",
+                        key: "{idx}",
+                        input {
+                            class: "w-1/2 px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "{props.key_placeholder}",
+                            value: "{key}",
+                            oninput: move |e| props.entries.write()[idx].0 = e.value(),
+                        }
+                        input {
+                            class: "w-1/2 px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "{props.value_placeholder}",
+                            value: "{value}",
+                            oninput: move |e| props.entries.write()[idx].1 = e.value(),
+                        }
+                        button {
+                            class: "px-2 py-1 text-sm text-white bg-red-500 rounded hover:bg-red-600 focus:outline-none",
+                            r#type: "button",
+                            onclick: move |_| {
+                                props.entries.write().remove(idx);
+                            },
+                            "✕"
+                        }
+                    }
+                )
+            })}
+            button {
+                class: "px-2 py-1 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 focus:outline-none",
+                r#type: "button",
+                onclick: move |_| props.entries.write().push((String::new(), String::new())),
+                "Add"
+            }
+        }
+    }
+}
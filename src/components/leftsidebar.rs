@@ -1,9 +1,17 @@
 use dioxus::prelude::*;
+use crate::model::account::Account;
+use crate::repositories::account_repo::fetch_accounts;
+use crate::state::{account_fetcher, close_account, db, open_account, OPEN_ACCOUNTS};
 use crate::Route;
 
 #[component]
 pub fn LeftSidebar() -> Element {
     let route = use_route::<Route>();
+    // the saved accounts which can be opened next to the open ones
+    let closed: Vec<Account> = fetch_accounts(db())
+        .into_iter()
+        .filter(|account| !OPEN_ACCOUNTS.read().iter().any(|open| open.id == account.id))
+        .collect();
     
     rsx!(
     ul { class: "mt-6",
@@ -39,15 +47,50 @@ pub fn LeftSidebar() -> Element {
     },
         ul {
         li { class: "relative px-6 py-3",
-            if matches!(route, Route::Buckets {}) {
+            span { class: "inline-flex items-center w-full text-sm font-semibold",
+                svg {
+                    class: "w-5 h-5", fill: "none",
+                    stroke_linecap: "round", stroke_linejoin: "round", stroke_width: "2",
+                    view_box: "0 0 24 24", stroke: "currentColor",
+                    path { d: "M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-3 7h3m-3 4h3m-6-4h.01M9 16h.01" }
+                }
+                span { class: "ml-4", "Buckets" }
+            }
+            ul { class: "mt-2 ml-9 space-y-1 text-sm",
+                for account in OPEN_ACCOUNTS.read().iter() {
+                    AccountRoot { key: "{account.id}", account: account.clone() }
+                }
+            }
+            if !closed.is_empty() {
+                select {
+                    class: "w-full mt-2 ml-9 px-2 py-1 text-xs border rounded-md max-w-[10rem] dark:bg-gray-700 dark:text-white",
+                    value: "",
+                    onchange: move |e| {
+                        let Ok(id) = e.value().parse::<i64>() else {
+                            return;
+                        };
+                        if let Some(account) = fetch_accounts(db()).into_iter().find(|account| account.id == id) {
+                            open_account(account);
+                            navigator().push(Route::Buckets { account: id });
+                        }
+                    },
+                    option { value: "", "Open account..." }
+                    for account in closed {
+                        option { value: "{account.id}", "{account.name}" }
+                    }
+                }
+            }
+        }
+        li { class: "relative px-6 py-3",
+            if matches!(route, Route::Commander {}) {
                 span {
                     class: "absolute inset-y-0 left-0 w-1 bg-purple-600 rounded-tr-lg rounded-br-lg",
                     aria_hidden: "true"
                 }
             }
-            Link { 
-                to: Route::Buckets {}, 
-                class: if matches!(route, Route::Buckets {}) {
+            Link {
+                to: Route::Commander {},
+                class: if matches!(route, Route::Commander {}) {
                     "inline-flex items-center w-full text-sm font-semibold text-gray-800 transition-colors duration-150 hover:text-gray-800 dark:hover:text-gray-200 dark:text-gray-100"
                 } else {
                     "inline-flex items-center w-full text-sm font-semibold transition-colors duration-150 hover:text-gray-800 dark:hover:text-gray-200"
@@ -56,9 +99,9 @@ pub fn LeftSidebar() -> Element {
                     class: "w-5 h-5", fill: "none",
                     stroke_linecap: "round", stroke_linejoin: "round", stroke_width: "2",
                     view_box: "0 0 24 24", stroke: "currentColor",
-                    path { d: "M9 5H7a2 2 0 00-2 2v12a2 2 0 002 2h10a2 2 0 002-2V7a2 2 0 00-2-2h-2M9 5a2 2 0 002 2h2a2 2 0 002-2M9 5a2 2 0 012-2h2a2 2 0 012 2m-3 7h3m-3 4h3m-6-4h.01M9 16h.01" }
+                    path { d: "M8 7h12m0 0l-4-4m4 4l-4 4m0 6H4m0 0l4 4m-4-4l4-4" }
                 }
-                span { class: "ml-4", "Buckets" }
+                span { class: "ml-4", "Commander" }
             }
         }
         li { class: "relative px-6 py-3",
@@ -133,3 +176,103 @@ pub fn LeftSidebar() -> Element {
 )
 }
 
+/// Open account in the sidebar, a tree root with the buckets of the account below it
+#[component]
+fn AccountRoot(account: Account) -> Element {
+    let route = use_route::<Route>();
+    let mut expanded = use_signal(|| false);
+    let mut buckets = use_signal(|| None as Option<Result<Vec<String>, String>>);
+    let account_id = account.id;
+    let is_current = route.account() == Some(account_id);
+
+    // the buckets are listed the first time the account is expanded
+    use_effect(move || {
+        if !*expanded.read() || buckets.peek().is_some() {
+            return;
+        }
+        spawn(async move {
+            let Some(fetcher) = account_fetcher(account_id) else {
+                buckets.set(Some(Err("The account doesn't exist anymore".to_string())));
+                return;
+            };
+            let listed = fetcher
+                .list_all_buckets()
+                .await
+                .map(|items| items.into_iter().map(|item| item.name).collect())
+                .map_err(|e| e.to_string());
+            buckets.set(Some(listed));
+        });
+    });
+
+    let current_bucket = match &route {
+        Route::Objects { account, bucket, .. }
+        | Route::BucketIndex { account, bucket }
+        | Route::MultipartUploads { account, bucket }
+        | Route::BucketSettings { account, bucket }
+            if *account == account_id =>
+        {
+            Some(bucket.clone())
+        }
+        _ => None,
+    };
+
+    rsx!(
+        li {
+            div { class: "flex items-center",
+                button {
+                    class: "w-4 mr-1 text-xs",
+                    title: if *expanded.read() { "Collapse" } else { "Expand" },
+                    onclick: move |_| {
+                        let open = !*expanded.peek();
+                        expanded.set(open);
+                    },
+                    if *expanded.read() { "▾" } else { "▸" }
+                }
+                Link {
+                    class: if is_current {
+                        "flex-1 truncate font-semibold text-gray-800 dark:text-gray-100"
+                    } else {
+                        "flex-1 truncate hover:text-gray-800 dark:hover:text-gray-200"
+                    },
+                    to: Route::Buckets { account: account_id },
+                    title: account.endpoint_url.clone().unwrap_or(account.default_region.clone()),
+                    "{account.name}"
+                }
+                button {
+                    class: "ml-1 text-xs hover:text-red-600",
+                    title: "Close the account",
+                    onclick: move |_| {
+                        close_account(account_id);
+                        if is_current {
+                            navigator().push(Route::Dashboard {});
+                        }
+                    },
+                    "×"
+                }
+            }
+            if *expanded.read() {
+                ul { class: "ml-5 text-xs",
+                    match &*buckets.read() {
+                        None => rsx!( li { class: "py-1", "Loading..." } ),
+                        Some(Err(error)) => rsx!( li { class: "py-1 text-red-600 break-all", "{error}" } ),
+                        Some(Ok(names)) => rsx!(
+                            for name in names.iter() {
+                                li { key: "{name}", class: "py-1 truncate",
+                                    Link {
+                                        class: if current_bucket.as_deref() == Some(name.as_str()) {
+                                            "font-semibold text-gray-800 dark:text-gray-100"
+                                        } else {
+                                            "hover:text-gray-800 dark:hover:text-gray-200"
+                                        },
+                                        to: Route::Objects { account: account_id, bucket: name.clone(), prefix: String::new(), selected: String::new() },
+                                        "{name}"
+                                    }
+                                }
+                            }
+                        ),
+                    }
+                }
+            }
+        }
+    )
+}
@@ -9,6 +9,17 @@ pub mod leftsidebar;
 pub mod github_star_action;
 
 pub mod bucket_modal;
+pub mod key_value_editor;
+pub mod object_details_panel;
+pub mod bulk_edit_modal;
+pub mod search_bar;
+pub mod preview_pane;
+pub mod text_editor_pane;
+pub mod cost_card;
+pub mod price_table_modal;
+pub mod transfer_queue;
+pub mod cross_account_modal;
+pub mod transfer_settings;
 
 pub use crate::components::clients_card::ClientsCard;
 pub use crate::components::account_card::AccountCard;
@@ -19,4 +30,14 @@ pub use crate::components::account_modal::AccountModal;
 pub use crate::components::topbar::TopBar;
 pub use crate::components::leftsidebar::LeftSidebar;
 pub use crate::components::github_star_action::GithubStarAction;
-pub use crate::components::bucket_modal::BucketModal;
\ No newline at end of file
+pub use crate::components::bucket_modal::BucketModal;
+pub use crate::components::object_details_panel::ObjectDetailsPanel;
+pub use crate::components::bulk_edit_modal::BulkEditModal;
+pub use crate::components::search_bar::SearchBar;
+pub use crate::components::preview_pane::PreviewPane;
+pub use crate::components::text_editor_pane::TextEditorPane;
+pub use crate::components::cost_card::CostCard;
+pub use crate::components::price_table_modal::PriceTableModal;
+pub use crate::components::transfer_queue::TransferQueue;
+pub use crate::components::cross_account_modal::CrossAccountModal;
+pub use crate::components::transfer_settings::TransferSettings;
\ No newline at end of file
@@ -0,0 +1,437 @@
+use std::time::SystemTime;
+use aws_sdk_s3::primitives::DateTime;
+use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
+use crate::components::key_value_editor::{entries_to_map, KeyValueEditor};
+use crate::model::client_encryption::{is_envelope_key, META_ALGORITHM};
+use crate::model::object_details::{ObjectDetails, ObjectMetadataUpdate, ObjectTagsUpdate};
+use crate::model::object_lock::{format_retain_until, parse_retain_until, RETENTION_MODES};
+use crate::model::storage_class::{requires_restore, restore_tiers, RestoreStatus, STORAGE_CLASSES};
+use crate::state::account_fetcher;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct ObjectDetailsPanelProps {
+    account: i64,
+    bucket: String,
+    selected_key: Signal<Option<String>>,
+    refresh_objects: Signal<bool>,
+}
+
+async fn load_details(account: i64, bucket: String, key: String) -> Result<ObjectDetails, String> {
+    match account_fetcher(account) {
+        Some(fetcher) => fetcher
+            .get_object_details(&bucket, &key)
+            .await
+            .map_err(|e| format!("Cannot load object details: {}", e)),
+        None => Err("The account doesn't exist anymore".to_string()),
+    }
+}
+
+/// Side panel showing the properties of a single object with metadata and tags editing
+#[component]
+pub fn ObjectDetailsPanel(mut props: ObjectDetailsPanelProps) -> Element {
+    let mut details = use_signal(|| None as Option<ObjectDetails>);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut is_saving = use_signal(|| false);
+    let mut content_type = use_signal(String::new);
+    let mut cache_control = use_signal(String::new);
+    let mut storage_class = use_signal(String::new);
+    let mut restore_tier = use_signal(|| "Standard".to_string());
+    let mut restore_days = use_signal(|| "7".to_string());
+    let mut metadata = use_signal(Vec::<(String, String)>::new);
+    let mut tags = use_signal(Vec::<(String, String)>::new);
+    let mut retention_mode = use_signal(|| "GOVERNANCE".to_string());
+    let mut retain_until = use_signal(String::new);
+    let mut bypass_governance = use_signal(|| false);
+    let mut lock_enabled = use_signal(|| false);
+    // kept apart from `error_message`, which is cleared whenever another object is selected
+    let mut lock_error = use_signal(|| None as Option<String>);
+    let mut reload = use_signal(|| 0u32);
+    let account = props.account;
+
+    // the retention of objects can only be edited in buckets with object lock
+    let lock_bucket = props.bucket.clone();
+    use_effect(move || {
+        let bucket = lock_bucket.clone();
+        spawn(async move {
+            if let Some(fetcher) = account_fetcher(account) {
+                match fetcher.get_object_lock(&bucket).await {
+                    Ok(lock) => lock_enabled.set(lock.enabled),
+                    Err(e) => lock_error.set(Some(format!("Cannot read the object lock of bucket '{}': {}", bucket, e))),
+                }
+            }
+        });
+    });
+
+    let bucket = props.bucket.clone();
+    use_effect(move || {
+        let _ = reload.read();
+        let Some(key) = props.selected_key.read().clone() else {
+            return;
+        };
+        let bucket = bucket.clone();
+        details.set(None);
+        error_message.set(None);
+        spawn(async move {
+            match load_details(account, bucket, key).await {
+                Ok(d) => {
+                    content_type.set(d.content_type.clone().unwrap_or_default());
+                    cache_control.set(d.cache_control.clone().unwrap_or_default());
+                    // s3 doesn't report the class of objects in the standard class
+                    storage_class.set(d.storage_class.clone().unwrap_or("STANDARD".to_string()));
+                    // the envelope of client-side encryption isn't editable
+                    metadata.set(d.metadata.clone().into_iter().filter(|(key, _)| !is_envelope_key(key)).collect());
+                    tags.set(d.tags.clone().into_iter().collect());
+                    retention_mode.set(d.retention_mode.clone().unwrap_or("GOVERNANCE".to_string()));
+                    retain_until.set(d.retain_until.as_ref().map(format_retain_until).unwrap_or_default());
+                    details.set(Some(d));
+                }
+                Err(err) => error_message.set(Some(err)),
+            }
+        });
+    });
+
+    let save_metadata = move |_| {
+        let Some(current) = details.read().clone() else {
+            return;
+        };
+        let edited = entries_to_map(&metadata.read());
+        let current_class = current.storage_class.clone().unwrap_or("STANDARD".to_string());
+        let update = ObjectMetadataUpdate {
+            content_type: Some(content_type.read().trim().to_string()),
+            cache_control: Some(cache_control.read().trim().to_string()),
+            remove_metadata: current
+                .metadata
+                .keys()
+                .filter(|k| !edited.contains_key(*k) && !is_envelope_key(k))
+                .cloned()
+                .collect(),
+            upsert_metadata: edited,
+            storage_class: Some(storage_class.read().clone()).filter(|sc| *sc != current_class),
+        };
+        is_saving.set(true);
+        spawn(async move {
+            let errors = match account_fetcher(account) {
+                Some(fetcher) => fetcher.update_objects(&current.bucket, vec![current.key.clone()], Some(update), None).await,
+                None => vec!["The account doesn't exist anymore".to_string()],
+            };
+            is_saving.set(false);
+            if errors.is_empty() {
+                props.refresh_objects.set(true);
+                *reload.write() += 1;
+            } else {
+                error_message.set(Some(errors.join("\n")));
+            }
+        });
+    };
+
+    let save_tags = move |_| {
+        let Some(current) = details.read().clone() else {
+            return;
+        };
+        let update = ObjectTagsUpdate::from_edit(&current.tags, &entries_to_map(&tags.read()));
+        is_saving.set(true);
+        spawn(async move {
+            let errors = match account_fetcher(account) {
+                Some(fetcher) => fetcher.update_objects(&current.bucket, vec![current.key.clone()], None, Some(update)).await,
+                None => vec!["The account doesn't exist anymore".to_string()],
+            };
+            is_saving.set(false);
+            if errors.is_empty() {
+                *reload.write() += 1;
+            } else {
+                error_message.set(Some(errors.join("\n")));
+            }
+        });
+    };
+
+    let restore = move |_| {
+        let Some(current) = details.read().clone() else {
+            return;
+        };
+        let Ok(days) = restore_days.read().trim().parse::<i32>() else {
+            error_message.set(Some("Number of days must be a number".to_string()));
+            return;
+        };
+        let tier = restore_tier.read().clone();
+        is_saving.set(true);
+        spawn(async move {
+            let result = match account_fetcher(account) {
+                Some(fetcher) => fetcher.restore_object(&current.bucket, &current.key, days, &tier).await,
+                None => Ok(Some("The account doesn't exist anymore".to_string())),
+            };
+            is_saving.set(false);
+            match result {
+                Ok(None) => {
+                    props.refresh_objects.set(true);
+                    *reload.write() += 1;
+                }
+                Ok(Some(err)) => error_message.set(Some(err)),
+                Err(e) => error_message.set(Some(e.to_string())),
+            }
+        });
+    };
+
+    let save_retention = move |_| {
+        let Some(current) = details.read().clone() else {
+            return;
+        };
+        let until = match parse_retain_until(&retain_until.read(), DateTime::from(SystemTime::now())) {
+            Ok(until) => until,
+            Err(err) => {
+                error_message.set(Some(err));
+                return;
+            }
+        };
+        let mode = retention_mode.read().clone();
+        let bypass = *bypass_governance.read();
+        is_saving.set(true);
+        spawn(async move {
+            let result = match account_fetcher(account) {
+                Some(fetcher) => fetcher
+                    .set_object_retention(&current.bucket, &current.key, current.version_id.clone(), &mode, until, bypass)
+                    .await
+                    .map_err(|e| format!("Failed to save the retention: {}", e)),
+                None => Err("The account doesn't exist anymore".to_string()),
+            };
+            is_saving.set(false);
+            match result {
+                Ok(()) => *reload.write() += 1,
+                Err(err) => error_message.set(Some(err)),
+            }
+        });
+    };
+
+    let toggle_legal_hold = move |_| {
+        let Some(current) = details.read().clone() else {
+            return;
+        };
+        is_saving.set(true);
+        spawn(async move {
+            let result = match account_fetcher(account) {
+                Some(fetcher) => fetcher
+                    .set_legal_hold(&current.bucket, &current.key, current.version_id.clone(), !current.legal_hold)
+                    .await
+                    .map_err(|e| format!("Failed to change the legal hold: {}", e)),
+                None => Err("The account doesn't exist anymore".to_string()),
+            };
+            is_saving.set(false);
+            match result {
+                Ok(()) => *reload.write() += 1,
+                Err(err) => error_message.set(Some(err)),
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 w-full max-w-md p-6 overflow-y-auto bg-white shadow-xl dark:bg-gray-800",
+            div { class: "flex items-center justify-between mb-4",
+                h2 { class: "text-xl font-bold text-gray-900 dark:text-gray-100 break-all",
+                    {props.selected_key.read().clone().unwrap_or_default()}
+                }
+                button {
+                    class: "px-2 py-1 text-sm bg-gray-300 rounded hover:bg-gray-400",
+                    onclick: move |_| props.selected_key.set(None),
+                    "Close"
+                }
+            }
+
+            if let Some(error) = error_message.read().as_ref() {
+                div {
+                    class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line",
+                    "{error}"
+                }
+            }
+            if let Some(error) = lock_error.read().as_ref() {
+                div {
+                    class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                    "{error}"
+                }
+            }
+
+            if let Some(d) = details.read().as_ref() {
+                table { class: "w-full mb-6 text-sm text-gray-700 dark:text-gray-400",
+                    tbody {
+                        DetailsRow { label: "Size", value: d.size.map(|s| s.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()) }
+                        DetailsRow { label: "Last modified", value: d.last_modified.map(|lm| lm.to_string()) }
+                        DetailsRow { label: "ETag", value: d.e_tag.clone() }
+                        DetailsRow { label: "Storage class", value: d.storage_class.clone() }
+                        if requires_restore(d.storage_class.as_deref()) {
+                            DetailsRow { label: "Restore", value: RestoreStatus::from_header(d.restore.as_deref()).label() }
+                        }
+                        DetailsRow { label: "Encryption", value: d.server_side_encryption.clone().or(d.sse_customer_algorithm.clone().map(|a| format!("SSE-C ({})", a))) }
+                        DetailsRow { label: "KMS key", value: d.sse_kms_key_id.clone() }
+                        DetailsRow { label: "Client-side encryption", value: d.metadata.get(META_ALGORITHM).cloned() }
+                        DetailsRow { label: "Version", value: d.version_id.clone() }
+                        DetailsRow { label: "Content encoding", value: d.content_encoding.clone() }
+                    }
+                }
+
+                h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Metadata" }
+                div { class: "mb-6 space-y-3",
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Content-Type" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            value: "{content_type}",
+                            oninput: move |e| content_type.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Cache-Control" }
+                        input {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. max-age=3600",
+                            value: "{cache_control}",
+                            oninput: move |e| cache_control.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Storage class" }
+                        select {
+                            class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{storage_class}",
+                            oninput: move |e| storage_class.set(e.value()),
+                            {STORAGE_CLASSES.iter().map(|(value, label)| rsx!(
+                                option { value: "{value}", "{label}" }
+                            ))}
+                        }
+                    }
+                    label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "User metadata" }
+                    KeyValueEditor { entries: metadata }
+                    button {
+                        class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: *is_saving.read(),
+                        onclick: save_metadata,
+                        "Save metadata"
+                    }
+                }
+
+                if requires_restore(d.storage_class.as_deref()) && RestoreStatus::from_header(d.restore.as_deref()) == RestoreStatus::NotRestored {
+                    h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Restore" }
+                    div { class: "mb-6 space-y-3",
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Retrieval tier" }
+                            select {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                value: "{restore_tier}",
+                                oninput: move |e| restore_tier.set(e.value()),
+                                {restore_tiers(d.storage_class.as_deref()).into_iter().map(|(value, label)| rsx!(
+                                    option { value: "{value}", "{label}" }
+                                ))}
+                            }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Days available" }
+                            input {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "number",
+                                min: "1",
+                                value: "{restore_days}",
+                                oninput: move |e| restore_days.set(e.value()),
+                            }
+                        }
+                        button {
+                            class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: *is_saving.read(),
+                            onclick: restore,
+                            "Restore"
+                        }
+                    }
+                }
+
+                if *lock_enabled.read() || d.retention_mode.is_some() || d.legal_hold {
+                    h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Object lock" }
+                    div { class: "mb-6 space-y-3",
+                        table { class: "w-full text-sm text-gray-700 dark:text-gray-400",
+                            tbody {
+                                DetailsRow {
+                                    label: "Retention",
+                                    value: d.retention_mode.clone().map(|mode| format!("{} until {}", mode, d.retain_until.as_ref().map(format_retain_until).unwrap_or_default())),
+                                }
+                                DetailsRow { label: "Legal hold", value: Some(if d.legal_hold { "On" } else { "Off" }.to_string()) }
+                            }
+                        }
+                        if d.is_locked(&DateTime::from(SystemTime::now())) {
+                            div {
+                                class: "p-3 bg-yellow-100 border border-yellow-400 text-yellow-700 rounded text-sm",
+                                "This version can't be deleted or overwritten while its retention runs or a legal hold is placed on it."
+                            }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Retention mode" }
+                            select {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                value: "{retention_mode}",
+                                oninput: move |e| retention_mode.set(e.value()),
+                                {RETENTION_MODES.iter().map(|(value, label)| rsx!(
+                                    option { value: "{value}", "{label}" }
+                                ))}
+                            }
+                        }
+                        div {
+                            label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Retain until" }
+                            input {
+                                class: "w-full px-3 py-2 border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "date",
+                                value: "{retain_until}",
+                                oninput: move |e| retain_until.set(e.value()),
+                            }
+                        }
+                        if d.retention_mode.as_deref() == Some("GOVERNANCE") {
+                            label { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300",
+                                input {
+                                    class: "h-4 w-4 text-purple-600 rounded",
+                                    r#type: "checkbox",
+                                    checked: *bypass_governance.read(),
+                                    onchange: move |e| bypass_governance.set(e.checked()),
+                                }
+                                span { "Bypass governance retention (to shorten or remove it)" }
+                            }
+                        }
+                        div { class: "flex space-x-2",
+                            button {
+                                class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                                disabled: *is_saving.read(),
+                                onclick: save_retention,
+                                "Save retention"
+                            }
+                            button {
+                                class: "px-4 py-2 text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50 disabled:cursor-not-allowed",
+                                disabled: *is_saving.read(),
+                                onclick: toggle_legal_hold,
+                                if d.legal_hold { "Remove legal hold" } else { "Place legal hold" }
+                            }
+                        }
+                    }
+                }
+
+                h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Tags" }
+                div { class: "space-y-3",
+                    KeyValueEditor { entries: tags }
+                    button {
+                        class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: *is_saving.read(),
+                        onclick: save_tags,
+                        "Save tags"
+                    }
+                }
+            } else if error_message.read().is_none() {
+                p { class: "text-sm text-gray-500", "Loading..." }
+            }
+        }
+    }
+}
+
+#[component]
+fn DetailsRow(label: String, value: Option<String>) -> Element {
+    rsx! {
+        tr {
+            td { class: "py-1 pr-4 font-semibold align-top", "{label}" }
+            td { class: "py-1 break-all", {value.unwrap_or("-".to_string())} }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
+use crate::model::parquet_preview::{footer_range, read_preview, ParquetPreview, SparseFile, FOOTER_FETCH_SIZE, ROW_GROUP_PREVIEW_LIMIT};
+use crate::model::preview::{
+    decode_text, highlight, parse_table, PreviewKind, IMAGE_PREVIEW_LIMIT, PREVIEW_CHUNK_SIZE, PREVIEW_ROWS,
+};
+use crate::state::account_fetcher;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct PreviewPaneProps {
+    account: i64,
+    bucket: String,
+    selected_key: Signal<Option<String>>,
+}
+
+async fn fetch_range(account: i64, bucket: &str, key: &str, start: u64, end: u64) -> Result<Vec<u8>, String> {
+    match account_fetcher(account) {
+        Some(fetcher) => fetcher
+            .get_object_range(bucket, key, start..=end - 1)
+            .await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| format!("Cannot load the preview: {}", e)),
+        None => Err("The account doesn't exist anymore".to_string()),
+    }
+}
+
+/// Fetches the footer of the Parquet file and the first row group when it's small enough
+/// (or `with_rows` is set). The parts fetched before are reused.
+async fn load_parquet(
+    account: i64,
+    bucket: &str,
+    key: &str,
+    file: Option<SparseFile>,
+    size: u64,
+    with_rows: bool,
+) -> Result<(SparseFile, ParquetPreview), String> {
+    let mut file = match file {
+        Some(file) => file,
+        None => {
+            let mut file = SparseFile::new(size);
+            let tail_start = size.saturating_sub(FOOTER_FETCH_SIZE);
+            file.add(tail_start, fetch_range(account, bucket, key, tail_start, size).await?.into());
+            file
+        }
+    };
+    let footer = footer_range(&file)?;
+    if !file.contains(&footer) {
+        file.add(footer.start, fetch_range(account, bucket, key, footer.start, footer.end).await?.into());
+    }
+    let mut preview = read_preview(file.clone(), PREVIEW_ROWS)?;
+    if let Some(row_group) = preview.first_row_group.clone() {
+        let small_enough = row_group.end - row_group.start <= ROW_GROUP_PREVIEW_LIMIT;
+        if !row_group.is_empty() && !file.contains(&row_group) && (with_rows || small_enough) {
+            file.add(row_group.start, fetch_range(account, bucket, key, row_group.start, row_group.end).await?.into());
+            preview = read_preview(file.clone(), PREVIEW_ROWS)?;
+        }
+    }
+    Ok((file, preview))
+}
+
+/// Side panel rendering the beginning of the object, more is fetched on demand
+#[component]
+pub fn PreviewPane(mut props: PreviewPaneProps) -> Element {
+    let mut kind = use_signal(|| PreviewKind::Unsupported);
+    let mut size = use_signal(|| 0u64);
+    let mut content = use_signal(Vec::<u8>::new);
+    let mut parquet = use_signal(|| None as Option<(SparseFile, ParquetPreview)>);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut is_loading = use_signal(|| false);
+    let account = props.account;
+
+    let bucket = props.bucket.clone();
+    use_effect(move || {
+        let Some(key) = props.selected_key.read().clone() else {
+            return;
+        };
+        let bucket = bucket.clone();
+        content.set(Vec::new());
+        parquet.set(None);
+        error_message.set(None);
+        is_loading.set(true);
+        spawn(async move {
+            let details = match account_fetcher(account) {
+                Some(fetcher) => fetcher.get_object_details(&bucket, &key).await.map_err(|e| format!("Cannot load object details: {}", e)),
+                None => Err("The account doesn't exist anymore".to_string()),
+            };
+            let details = match details {
+                Ok(details) => details,
+                Err(err) => {
+                    error_message.set(Some(err));
+                    is_loading.set(false);
+                    return;
+                }
+            };
+            let object_size = details.size.unwrap_or(0);
+            let detected = PreviewKind::detect(&key, details.content_type.as_deref());
+            size.set(object_size);
+            kind.set(detected.clone());
+            let result = match detected {
+                _ if object_size == 0 => Ok(()),
+                PreviewKind::Unsupported => Ok(()),
+                PreviewKind::Image(_) if object_size > IMAGE_PREVIEW_LIMIT => Ok(()),
+                PreviewKind::Image(_) => fetch_range(account, &bucket, &key, 0, object_size).await.map(|bytes| content.set(bytes)),
+                PreviewKind::Parquet => load_parquet(account, &bucket, &key, None, object_size, false).await.map(|p| parquet.set(Some(p))),
+                _ => fetch_range(account, &bucket, &key, 0, object_size.min(PREVIEW_CHUNK_SIZE)).await.map(|bytes| content.set(bytes)),
+            };
+            if let Err(err) = result {
+                error_message.set(Some(err));
+            }
+            is_loading.set(false);
+        });
+    });
+
+    let bucket = props.bucket.clone();
+    let load_more = move |_| {
+        let Some(key) = props.selected_key.read().clone() else {
+            return;
+        };
+        let bucket = bucket.clone();
+        let object_size = *size.read();
+        let current_kind = kind.read().clone();
+        is_loading.set(true);
+        spawn(async move {
+            let result = match current_kind {
+                PreviewKind::Image(_) => fetch_range(account, &bucket, &key, 0, object_size).await.map(|bytes| content.set(bytes)),
+                PreviewKind::Parquet => {
+                    let file = parquet.read().as_ref().map(|(file, _)| file.clone());
+                    load_parquet(account, &bucket, &key, file, object_size, true).await.map(|p| parquet.set(Some(p)))
+                }
+                _ => {
+                    let start = content.read().len() as u64;
+                    let end = object_size.min(start + PREVIEW_CHUNK_SIZE);
+                    fetch_range(account, &bucket, &key, start, end).await.map(|bytes| content.write().extend(bytes))
+                }
+            };
+            if let Err(err) = result {
+                error_message.set(Some(err));
+            }
+            is_loading.set(false);
+        });
+    };
+
+    let object_size = *size.read();
+    let loaded = content.read().len() as u64;
+    let complete = loaded >= object_size;
+    let format_size = |bytes: u64| bytes.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 flex flex-col w-full max-w-3xl p-6 bg-white shadow-xl dark:bg-gray-800",
+            div { class: "flex items-center justify-between mb-4",
+                h2 { class: "text-xl font-bold text-gray-900 dark:text-gray-100 break-all",
+                    {props.selected_key.read().clone().unwrap_or_default()}
+                }
+                button {
+                    class: "px-2 py-1 text-sm bg-gray-300 rounded hover:bg-gray-400",
+                    onclick: move |_| props.selected_key.set(None),
+                    "Close"
+                }
+            }
+
+            if let Some(error) = error_message.read().as_ref() {
+                div {
+                    class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line",
+                    "{error}"
+                }
+            }
+
+            div { class: "flex-1 overflow-auto text-sm text-gray-700 dark:text-gray-300",
+                match kind.read().clone() {
+                    PreviewKind::Unsupported => rsx!(p { "No preview available for this type of object." }),
+                    PreviewKind::Image(mime) => rsx!(
+                        if !content.read().is_empty() {
+                            img {
+                                class: "max-w-full",
+                                src: format!("data:{};base64,{}", mime, STANDARD.encode(&*content.read())),
+                            }
+                        } else if object_size > IMAGE_PREVIEW_LIMIT {
+                            p { "The image has {format_size(object_size)}, it isn't loaded automatically." }
+                            button {
+                                class: "px-2 py-1 mt-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700",
+                                disabled: *is_loading.read(),
+                                onclick: load_more.clone(),
+                                "Load image"
+                            }
+                        }
+                    ),
+                    PreviewKind::Parquet => rsx!(
+                        if let Some((_, preview)) = parquet.read().as_ref() {
+                            p { class: "mb-2", "{preview.num_rows} rows in {preview.num_row_groups} row groups" }
+                            pre { class: "p-3 mb-4 text-xs bg-gray-100 rounded dark:bg-gray-900", "{preview.schema}" }
+                            if !preview.rows.is_empty() {
+                                PreviewTable { headers: preview.columns.clone(), types: Vec::new(), rows: preview.rows.clone() }
+                            } else if let Some(row_group) = preview.first_row_group.clone().filter(|r| !r.is_empty()) {
+                                button {
+                                    class: "px-2 py-1 text-sm text-white bg-purple-600 rounded hover:bg-purple-700",
+                                    disabled: *is_loading.read(),
+                                    onclick: load_more.clone(),
+                                    "Load rows ({format_size(row_group.end - row_group.start)})"
+                                }
+                            }
+                        }
+                    ),
+                    PreviewKind::Table(delimiter) => {
+                        let table = parse_table(&decode_text(&content.read(), complete), delimiter, complete, PREVIEW_ROWS);
+                        let types = table.column_types.iter().map(|t| t.label().to_string()).collect::<Vec<_>>();
+                        rsx!(PreviewTable { headers: table.headers, types, rows: table.rows })
+                    }
+                    text_kind => {
+                        let text = decode_text(&content.read(), complete);
+                        rsx!(
+                            pre { class: "p-3 text-xs whitespace-pre-wrap break-all bg-gray-100 rounded dark:bg-gray-900",
+                                for (token, part) in highlight(&text_kind, &text) {
+                                    span { class: token.class(), "{part}" }
+                                }
+                            }
+                        )
+                    }
+                }
+            }
+
+            div { class: "flex items-center justify-between pt-3 mt-3 text-xs text-gray-600 border-t dark:text-gray-400 dark:border-gray-700",
+                if *is_loading.read() {
+                    span { "Loading..." }
+                } else if matches!(*kind.read(), PreviewKind::Text | PreviewKind::Json | PreviewKind::Yaml | PreviewKind::Table(_)) {
+                    span { "Showing {format_size(loaded)} of {format_size(object_size)}" }
+                } else {
+                    span { "{format_size(object_size)}" }
+                }
+                if !complete && matches!(*kind.read(), PreviewKind::Text | PreviewKind::Json | PreviewKind::Yaml | PreviewKind::Table(_)) {
+                    button {
+                        class: "px-2 py-1 text-sm text-white bg-purple-600 rounded hover:bg-purple-700 disabled:opacity-50",
+                        disabled: *is_loading.read(),
+                        onclick: load_more,
+                        "Load more"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rows of a table preview, the detected column types are shown under the headers
+#[component]
+fn PreviewTable(headers: Vec<String>, types: Vec<String>, rows: Vec<Vec<String>>) -> Element {
+    rsx! {
+        table { class: "w-full text-xs whitespace-no-wrap",
+            thead {
+                tr { class: "font-semibold text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                    for (idx, header) in headers.iter().enumerate() {
+                        th { class: "px-2 py-2",
+                            "{header}"
+                            if let Some(column_type) = types.get(idx) {
+                                div { class: "font-normal normal-case text-gray-400", "{column_type}" }
+                            }
+                        }
+                    }
+                }
+            }
+            tbody { class: "divide-y dark:divide-gray-700",
+                for row in rows {
+                    tr {
+                        for cell in row {
+                            td { class: "px-2 py-1 align-top", "{cell}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use dioxus::prelude::*;
+use crate::model::pricing::{PriceTable, StoragePrice, ANY_REGION};
+use crate::model::storage_class::STORAGE_CLASSES;
+use crate::repositories::pricing_repo::{reset_price_table, save_price_table};
+use crate::state::db;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct PriceTableModalProps {
+    show_modal: Signal<bool>,
+    prices: Signal<PriceTable>,
+}
+
+/// Row being edited, the price is parsed on save
+#[derive(Debug, Clone, PartialEq)]
+struct PriceRow {
+    region: String,
+    storage_class: String,
+    price: String,
+}
+
+fn to_rows(table: &PriceTable) -> Vec<PriceRow> {
+    table
+        .prices
+        .iter()
+        .map(|price| PriceRow {
+            region: price.region.clone(),
+            storage_class: price.storage_class.clone(),
+            price: price.per_gb_month.to_string(),
+        })
+        .collect()
+}
+
+fn to_table(rows: &[PriceRow]) -> Result<PriceTable, String> {
+    let prices = rows
+        .iter()
+        .map(|row| {
+            let region = row.region.trim();
+            if region.is_empty() {
+                return Err(format!("The region of the {} price is missing, use {} for any region", row.storage_class, ANY_REGION));
+            }
+            match row.price.trim().parse::<f64>() {
+                Ok(per_gb_month) if per_gb_month >= 0.0 => Ok(StoragePrice {
+                    region: region.to_string(),
+                    storage_class: row.storage_class.clone(),
+                    per_gb_month,
+                }),
+                _ => Err(format!("'{}' isn't a valid price of {} in {}", row.price, row.storage_class, region)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(PriceTable { prices })
+}
+
+/// Editor of the local price table the costs are estimated with
+#[component]
+pub fn PriceTableModal(mut props: PriceTableModalProps) -> Element {
+    let mut rows = use_signal(|| to_rows(&props.prices.read()));
+    let mut error_message = use_signal(|| None as Option<String>);
+
+    rsx! {
+        div {
+            class: "fixed inset-0 z-50 w-screen h-screen flex items-center justify-center bg-black bg-opacity-50",
+            onclick: move |_| props.show_modal.set(false),
+            div {
+                class: "bg-white dark:bg-gray-800 rounded-lg p-6 shadow-xl w-full max-w-2xl max-h-screen overflow-y-auto",
+                onclick: move |e| e.stop_propagation(), // prevent click from closing the modal
+
+                h2 { class: "text-xl font-bold mb-2 text-gray-900 dark:text-gray-100", "Storage prices" }
+                p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                    "USD per GB-month. Prices of a region override the ones of region {ANY_REGION}."
+                }
+
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                        "{error}"
+                    }
+                }
+
+                table { class: "w-full mb-4 text-sm text-gray-700 dark:text-gray-400",
+                    thead {
+                        tr { class: "text-xs font-semibold text-left text-gray-500 uppercase dark:text-gray-400",
+                            th { class: "pb-2 pr-2", "Region" }
+                            th { class: "pb-2 pr-2", "Storage class" }
+                            th { class: "pb-2 pr-2", "Price" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        for (idx, row) in rows.read().clone().into_iter().enumerate() {
+                            tr {
+                                td { class: "py-1 pr-2",
+                                    input {
+                                        class: "w-full px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        value: "{row.region}",
+                                        oninput: move |e| rows.write()[idx].region = e.value(),
+                                    }
+                                }
+                                td { class: "py-1 pr-2",
+                                    select {
+                                        class: "w-full px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                                        value: "{row.storage_class}",
+                                        oninput: move |e| rows.write()[idx].storage_class = e.value(),
+                                        for (value, label) in STORAGE_CLASSES.iter() {
+                                            option { value: *value, selected: *value == row.storage_class, "{label}" }
+                                        }
+                                    }
+                                }
+                                td { class: "py-1 pr-2",
+                                    input {
+                                        class: "w-28 px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        value: "{row.price}",
+                                        oninput: move |e| rows.write()[idx].price = e.value(),
+                                    }
+                                }
+                                td { class: "py-1",
+                                    button {
+                                        class: "px-2 text-red-600 hover:text-red-800",
+                                        onclick: move |_| {
+                                            rows.write().remove(idx);
+                                        },
+                                        "✕"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div { class: "flex justify-between",
+                    div { class: "space-x-2",
+                        button {
+                            class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                            onclick: move |_| rows.write().push(PriceRow {
+                                region: String::new(),
+                                storage_class: "STANDARD".to_string(),
+                                price: String::new(),
+                            }),
+                            "Add price"
+                        }
+                        button {
+                            class: "px-4 py-2 text-sm text-gray-600 border border-gray-400 rounded hover:bg-gray-100 dark:text-gray-300 dark:hover:bg-gray-700",
+                            onclick: move |_| {
+                                reset_price_table(db());
+                                props.prices.set(PriceTable::default());
+                                rows.set(to_rows(&PriceTable::default()));
+                                error_message.set(None);
+                            },
+                            "Reset to defaults"
+                        }
+                    }
+                    button {
+                        class: "bg-purple-600 text-white px-4 py-2 rounded hover:bg-purple-700",
+                        onclick: move |_| match to_table(&rows.read()) {
+                            Ok(table) => {
+                                save_price_table(db(), &table);
+                                props.prices.set(table);
+                                props.show_modal.set(false);
+                            }
+                            Err(error) => error_message.set(Some(error)),
+                        },
+                        "Save"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
-use std::time::Duration;
 use dioxus::prelude::*;
 
+/// Objects in the analyzed buckets, `None` until they are analyzed
 #[component]
-pub fn SalesCard() -> Element {
+pub fn SalesCard(object_count: Option<u64>) -> Element {
+    let value = object_count.map(|count| count.to_string()).unwrap_or("-".to_string());
 
     rsx!(
             div {
@@ -21,11 +22,11 @@ pub fn SalesCard() -> Element {
         div {
             p {
                 class: "mb-2 text-sm font-medium text-gray-600 dark:text-gray-400",
-                "New sales"
+                "Objects"
             }
             p {
                 class: "text-lg font-semibold text-gray-700 dark:text-gray-200",
-                "376"
+                "{value}"
             }
         }
     }
@@ -0,0 +1,242 @@
+use dioxus::prelude::*;
+use crate::model::s3_data_item::S3DataItem;
+use crate::model::search::SearchPattern;
+use crate::repositories::index_repo::{get_index_info, search_index};
+use crate::Route;
+use crate::state::{account_fetcher, db, first_open_account_id};
+
+/// Stop listing once there's this many results, nobody scrolls through more
+const MAX_RESULTS: usize = 500;
+
+/// Parent prefix of the key, the location the object is shown at in the browser
+fn parent_prefix(key: &str) -> String {
+    key.rfind('/').map(|idx| key[..=idx].to_string()).unwrap_or_default()
+}
+
+async fn search(
+    account_id: i64,
+    query: String,
+    bucket: Option<String>,
+    mut results: Signal<Vec<S3DataItem>>,
+    mut error_message: Signal<Option<String>>,
+) {
+    let pattern = match SearchPattern::parse(&query) {
+        Ok(pattern) => pattern,
+        Err(err) => {
+            error_message.set(Some(err));
+            return;
+        }
+    };
+    let Some(fetcher) = account_fetcher(account_id) else {
+        error_message.set(Some("The account doesn't exist anymore".to_string()));
+        return;
+    };
+    let buckets = match bucket {
+        Some(bucket) => vec![bucket],
+        None => match fetcher.list_all_buckets().await {
+            Ok(buckets) => {
+                let names: Vec<String> = buckets.iter().map(|b| b.name.clone()).collect();
+                results.write().extend(buckets.into_iter().filter(|b| pattern.matches_bucket(&b.name)));
+                names
+            }
+            Err(e) => {
+                error_message.set(Some(format!("Cannot list buckets: {}", e)));
+                return;
+            }
+        },
+    };
+
+    let mut errors = Vec::new();
+    for bucket in buckets {
+        // indexed buckets are searched locally, without listing them again
+        if get_index_info(db(), account_id, &bucket).is_some_and(|info| info.last_indexed.is_some()) {
+            let remaining = MAX_RESULTS.saturating_sub(results.peek().len());
+            let prefix = pattern.list_prefix();
+            let found = search_index(db(), account_id, &bucket, prefix.as_deref(), |key| pattern.matches(key), remaining);
+            results.write().extend(found.iter().map(|object| object.to_s3_data_item(&bucket)));
+            if results.peek().len() >= MAX_RESULTS {
+                break;
+            }
+            continue;
+        }
+        let (results_tx, mut results_rx) = tokio::sync::mpsc::unbounded_channel::<S3DataItem>();
+        let listing = fetcher.search_objects(&bucket, &pattern, results_tx);
+        // the receiver is moved in, so it's dropped once enough is found and the listing stops
+        let receiving = async move {
+            while let Some(item) = results_rx.recv().await {
+                results.write().push(item);
+                if results.peek().len() >= MAX_RESULTS {
+                    break;
+                }
+            }
+        };
+        let (listed, _) = tokio::join!(listing, receiving);
+        if let Err(e) = listed {
+            errors.push(format!("{}: {}", bucket, e));
+        }
+        if results.peek().len() >= MAX_RESULTS {
+            break;
+        }
+    }
+    if !errors.is_empty() {
+        error_message.set(Some(errors.join("\n")));
+    }
+}
+
+/// Searches bucket names and object keys of the account of the page, the account opened
+/// first on the pages without one. Within a bucket only the bucket is searched, unless
+/// "All buckets" is chosen.
+#[component]
+pub fn SearchBar() -> Element {
+    let mut query = use_signal(String::new);
+    let mut all_buckets = use_signal(|| false);
+    let mut results = use_signal(Vec::<S3DataItem>::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut search_task = use_signal(|| None as Option<Task>);
+    let mut is_open = use_signal(|| false);
+    // the results link to the account they were found in, even when the page changes
+    let mut searched_account = use_signal(|| None as Option<i64>);
+    let navigator = use_navigator();
+
+    let route = use_route::<Route>();
+    let current_account = route.account().or_else(first_open_account_id);
+    let current_bucket = match route {
+        Route::Objects { bucket, .. } => Some(bucket),
+        _ => None,
+    };
+    let scope_bucket = current_bucket.clone().filter(|_| !*all_buckets.read());
+
+    let mut cancel = move || {
+        if let Some(task) = search_task.write().take() {
+            task.cancel();
+        }
+    };
+
+    let mut start_search = move |bucket: Option<String>| {
+        cancel();
+        results.write().clear();
+        error_message.set(None);
+        is_open.set(true);
+        let Some(account) = current_account else {
+            error_message.set(Some("Open an account in the sidebar first".to_string()));
+            return;
+        };
+        searched_account.set(Some(account));
+        let query = query.read().clone();
+        let task = spawn(async move {
+            search(account, query, bucket, results, error_message).await;
+            search_task.set(None);
+        });
+        search_task.set(Some(task));
+    };
+
+    let is_searching = search_task.read().is_some();
+    let results_count = results.read().len();
+
+    rsx! {
+        div {
+            class: "relative w-full max-w-xl mr-6 focus-within:text-purple-500",
+            div {
+                class: "absolute inset-y-0 flex items-center pl-2",
+                svg {
+                    class: "w-4 h-4",
+                    fill: "currentColor",
+                    view_box: "0 0 20 20",
+                    path {
+                        fill_rule: "evenodd",
+                        clip_rule: "evenodd",
+                        d: "M8 4a4 4 0 100 8 4 4 0 000-8zM2 8a6 6 0 1110.89 3.476l4.817 4.817a1 1 0 01-1.414 1.414l-4.816-4.816A6 6 0 012 8z"
+                    }
+                }
+            }
+            input {
+                class: "w-full pl-8 pr-2 text-sm text-gray-700 placeholder-gray-600 bg-gray-100 border-0 rounded-md dark:placeholder-gray-500 dark:focus:shadow-outline-gray dark:focus:placeholder-gray-600 dark:bg-gray-700 dark:text-gray-200 focus:placeholder-gray-500 focus:bg-white focus:border-purple-300 focus:outline-none focus:shadow-outline-purple form-input",
+                r#type: "text",
+                placeholder: "Search buckets and keys (prefix, *.glob or /regex/)",
+                aria_label: "Search",
+                value: "{query}",
+                oninput: move |e| query.set(e.value()),
+                onkeydown: move |e| {
+                    match e.key() {
+                        Key::Enter => start_search(scope_bucket.clone()),
+                        Key::Escape => {
+                            cancel();
+                            is_open.set(false);
+                        }
+                        _ => {}
+                    }
+                },
+            }
+            if *is_open.read() {
+                div {
+                    class: "absolute z-30 w-full mt-1 overflow-y-auto bg-white rounded-md shadow-lg max-h-96 dark:bg-gray-800",
+                    div {
+                        class: "flex items-center justify-between px-3 py-2 text-xs text-gray-600 border-b dark:text-gray-400 dark:border-gray-700",
+                        span {
+                            if is_searching { "Searching... {results_count} found" } else { "{results_count} found" }
+                        }
+                        div { class: "space-x-2",
+                            if let Some(bucket) = current_bucket.clone() {
+                                label {
+                                    input {
+                                        r#type: "checkbox",
+                                        class: "mr-1",
+                                        checked: *all_buckets.read(),
+                                        onchange: move |e| all_buckets.set(e.checked()),
+                                    }
+                                    "All buckets (not only {bucket})"
+                                }
+                            }
+                            if is_searching {
+                                button {
+                                    class: "px-2 py-1 text-white bg-red-500 rounded hover:bg-red-600",
+                                    onclick: move |_| cancel(),
+                                    "Cancel"
+                                }
+                            }
+                            button {
+                                class: "px-2 py-1 bg-gray-300 rounded hover:bg-gray-400",
+                                onclick: move |_| {
+                                    cancel();
+                                    is_open.set(false);
+                                },
+                                "Close"
+                            }
+                        }
+                    }
+                    if let Some(error) = error_message.read().as_ref() {
+                        div { class: "px-3 py-2 text-sm text-red-700 bg-red-100 whitespace-pre-line", "{error}" }
+                    }
+                    ul {
+                        {results.read().clone().into_iter().map(|item| {
+                            let account = searched_account.read().unwrap_or_default();
+                            let (label, target) = if item.is_bucket {
+                                (
+                                    format!("🪣 {}", item.name),
+                                    Route::Objects { account, bucket: item.name.clone(), prefix: String::new(), selected: String::new() },
+                                )
+                            } else {
+                                let bucket = item.bucket.clone().unwrap_or_default();
+                                (
+                                    format!("{}/{}", bucket, item.path),
+                                    Route::Objects { account, bucket, prefix: parent_prefix(&item.path), selected: item.path.clone() },
+                                )
+                            };
+                            rsx!(
+                                li {
+                                    class: "px-3 py-2 text-sm text-gray-700 cursor-pointer break-all hover:bg-purple-100 dark:text-gray-300 dark:hover:bg-gray-700",
+                                    onclick: move |_| {
+                                        is_open.set(false);
+                                        navigator.push(target.clone());
+                                    },
+                                    "{label}"
+                                }
+                            )
+                        })}
+                    }
+                }
+            }
+        }
+    }
+}
+
@@ -0,0 +1,196 @@
+use dioxus::prelude::*;
+use crate::model::text_edit::{diff_lines, DiffKind, SaveOutcome};
+use crate::state::account_fetcher;
+
+#[derive(Props, Clone, PartialEq)]
+pub struct TextEditorPaneProps {
+    account: i64,
+    bucket: String,
+    selected_key: Signal<Option<String>>,
+    refresh_objects: Signal<bool>,
+}
+
+/// Content and ETag of the object as it was read from s3
+#[derive(Debug, Clone, PartialEq)]
+struct RemoteVersion {
+    content: String,
+    e_tag: Option<String>,
+}
+
+async fn save(account: i64, bucket: String, key: String, content: String, e_tag: Option<String>) -> Result<SaveOutcome, String> {
+    match account_fetcher(account) {
+        Some(fetcher) => fetcher
+            .save_text_object(&bucket, &key, content, e_tag)
+            .await
+            .map_err(|e| format!("Cannot save the object: {}", e)),
+        None => Err("The account doesn't exist anymore".to_string()),
+    }
+}
+
+/// Side panel editing a small text object. Saving is refused when the object was changed
+/// by somebody else since it was opened, the changes are shown as a diff instead.
+#[component]
+pub fn TextEditorPane(mut props: TextEditorPaneProps) -> Element {
+    let mut original = use_signal(|| None as Option<RemoteVersion>);
+    let mut text = use_signal(String::new);
+    let mut conflict = use_signal(|| None as Option<RemoteVersion>);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut status = use_signal(|| None as Option<String>);
+    let mut is_saving = use_signal(|| false);
+    let account = props.account;
+
+    let bucket = props.bucket.clone();
+    use_effect(move || {
+        let Some(key) = props.selected_key.read().clone() else {
+            return;
+        };
+        let bucket = bucket.clone();
+        original.set(None);
+        conflict.set(None);
+        error_message.set(None);
+        status.set(None);
+        spawn(async move {
+            let loaded = match account_fetcher(account) {
+                Some(fetcher) => fetcher.get_text_object(&bucket, &key).await.map_err(|e| format!("Cannot open the object: {}", e)),
+                None => Err("The account doesn't exist anymore".to_string()),
+            };
+            match loaded {
+                Ok((content, e_tag)) => {
+                    text.set(content.clone());
+                    original.set(Some(RemoteVersion { content, e_tag }));
+                }
+                Err(err) => error_message.set(Some(err)),
+            }
+        });
+    });
+
+    // `e_tag` is the version the edit is based on, the remote one when overwriting after a conflict
+    let bucket = props.bucket.clone();
+    let save_with = use_callback(move |e_tag: Option<String>| {
+        let Some(key) = props.selected_key.read().clone() else {
+            return;
+        };
+        let bucket = bucket.clone();
+        let content = text.read().clone();
+        is_saving.set(true);
+        error_message.set(None);
+        status.set(None);
+        spawn(async move {
+            match save(account, bucket, key, content.clone(), e_tag).await {
+                Ok(SaveOutcome::Saved { e_tag }) => {
+                    original.set(Some(RemoteVersion { content, e_tag }));
+                    conflict.set(None);
+                    status.set(Some("Saved".to_string()));
+                    props.refresh_objects.set(true);
+                }
+                Ok(SaveOutcome::Conflict { remote_content, remote_e_tag }) => {
+                    conflict.set(Some(RemoteVersion { content: remote_content, e_tag: remote_e_tag }));
+                }
+                Err(err) => error_message.set(Some(err)),
+            }
+            is_saving.set(false);
+        });
+    });
+
+    let is_modified = original.read().as_ref().is_some_and(|o| o.content != *text.read());
+
+    rsx! {
+        div {
+            class: "fixed inset-y-0 right-0 z-40 flex flex-col w-full max-w-3xl p-6 bg-white shadow-xl dark:bg-gray-800",
+            div { class: "flex items-center justify-between mb-4",
+                h2 { class: "text-xl font-bold text-gray-900 dark:text-gray-100 break-all",
+                    {props.selected_key.read().clone().unwrap_or_default()}
+                    if is_modified { " *" }
+                }
+                button {
+                    class: "px-2 py-1 text-sm bg-gray-300 rounded hover:bg-gray-400",
+                    onclick: move |_| props.selected_key.set(None),
+                    "Close"
+                }
+            }
+
+            if let Some(error) = error_message.read().as_ref() {
+                div {
+                    class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line",
+                    "{error}"
+                }
+            }
+
+            if let Some(remote) = conflict.read().clone() {
+                div { class: "mb-4 p-3 bg-yellow-100 border border-yellow-400 text-yellow-800 rounded",
+                    p { class: "mb-2 font-semibold", "The object was changed by somebody else since you opened it." }
+                    p { class: "mb-2 text-sm", "Lines marked - are only in the current object, + only in your version." }
+                    pre { class: "p-2 mb-2 overflow-auto text-xs bg-white rounded max-h-64 dark:bg-gray-900",
+                        for (kind, line) in diff_lines(&remote.content, &text.read()) {
+                            div {
+                                class: match kind {
+                                    DiffKind::Same => "text-gray-600",
+                                    DiffKind::Removed => "text-red-700 bg-red-50",
+                                    DiffKind::Added => "text-green-700 bg-green-50",
+                                },
+                                "{kind.prefix()}{line}"
+                            }
+                        }
+                    }
+                    div { class: "space-x-2",
+                        button {
+                            class: "px-2 py-1 text-sm text-white bg-red-600 rounded hover:bg-red-700 disabled:opacity-50",
+                            disabled: *is_saving.read(),
+                            onclick: {
+                                let e_tag = remote.e_tag.clone();
+                                move |_| save_with.call(e_tag.clone())
+                            },
+                            "Overwrite with my version"
+                        }
+                        button {
+                            class: "px-2 py-1 text-sm bg-gray-300 rounded hover:bg-gray-400",
+                            onclick: {
+                                let remote = remote.clone();
+                                move |_| {
+                                    text.set(remote.content.clone());
+                                    original.set(Some(remote.clone()));
+                                    conflict.set(None);
+                                }
+                            },
+                            "Discard mine and load theirs"
+                        }
+                    }
+                }
+            }
+
+            if original.read().is_some() {
+                textarea {
+                    class: "flex-1 w-full p-3 font-mono text-xs border rounded-md dark:bg-gray-700 dark:text-white",
+                    spellcheck: false,
+                    value: "{text}",
+                    oninput: move |e| text.set(e.value()),
+                }
+                div { class: "flex items-center justify-end pt-3 space-x-2",
+                    if let Some(status) = status.read().as_ref() {
+                        span { class: "text-sm text-green-700", "{status}" }
+                    }
+                    button {
+                        class: "px-4 py-2 text-sm bg-gray-300 rounded hover:bg-gray-400 disabled:opacity-50",
+                        disabled: !is_modified,
+                        onclick: move |_| {
+                            let content = original.read().as_ref().map(|o| o.content.clone()).unwrap_or_default();
+                            text.set(content);
+                        },
+                        "Revert"
+                    }
+                    button {
+                        class: "px-4 py-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700 disabled:opacity-50",
+                        disabled: !is_modified || *is_saving.read() || conflict.read().is_some(),
+                        onclick: move |_| {
+                            let e_tag = original.read().as_ref().and_then(|o| o.e_tag.clone());
+                            save_with.call(e_tag);
+                        },
+                        if *is_saving.read() { "Saving..." } else { "Save" }
+                    }
+                }
+            } else if error_message.read().is_none() {
+                p { class: "text-sm text-gray-500", "Loading..." }
+            }
+        }
+    }
+}
@@ -1,12 +1,8 @@
 use dioxus::prelude::*;
-use crate::components::SettingsModal;
-use crate::model::account::Account;
-use crate::repositories::account_repo::fetch_accounts;
-use crate::utils::CURRENT_ACCOUNT;
+use crate::components::{SearchBar, SettingsModal};
 
 #[component]
 pub fn TopBar() -> Element {
-    let accounts = fetch_accounts();
     rsx! {
     header {
         class: "z-10 py-4 bg-white shadow-md dark:bg-gray-800",
@@ -32,28 +28,7 @@ pub fn TopBar() -> Element {
             // Search input
             div {
                 class: "flex justify-center flex-1 lg:mr-32",
-                div {
-                    class: "relative w-full max-w-xl mr-6 focus-within:text-purple-500",
-                    div {
-                        class: "absolute inset-y-0 flex items-center pl-2",
-                        svg {
-                            class: "w-4 h-4",
-                            fill: "currentColor",
-                            view_box: "0 0 20 20",
-                            path {
-                                fill_rule: "evenodd",
-                                clip_rule: "evenodd",
-                                d: "M8 4a4 4 0 100 8 4 4 0 000-8zM2 8a6 6 0 1110.89 3.476l4.817 4.817a1 1 0 01-1.414 1.414l-4.816-4.816A6 6 0 012 8z"
-                            }
-                        }
-                    }
-                    input {
-                        class: "w-full pl-8 pr-2 text-sm text-gray-700 placeholder-gray-600 bg-gray-100 border-0 rounded-md dark:placeholder-gray-500 dark:focus:shadow-outline-gray dark:focus:placeholder-gray-600 dark:bg-gray-700 dark:text-gray-200 focus:placeholder-gray-500 focus:bg-white focus:border-purple-300 focus:outline-none focus:shadow-outline-purple form-input",
-                        r#type: "text",
-                        placeholder: "Search for projects",
-                        aria_label: "Search"
-                    }
-                }
+                SearchBar {}
             }
 
             // Right-side icons
@@ -100,27 +75,6 @@ pub fn TopBar() -> Element {
                         }
                     }
                 }
-                li {
-                    class: "relative",
-                    select {
-                        class: "px-2 py-1 text-sm bg-white border border-gray-300 rounded-md dark:bg-gray-700 dark:text-white dark:border-gray-600",
-                        onchange: move |e| {
-                            let selected_name = e.value().clone();
-                            if let Some(account) = accounts.iter().find(|a| a.name == selected_name) {
-                                CURRENT_ACCOUNT.write().insert(account.clone());
-                            }
-                        },
-                        {accounts.iter().map(|account| {
-                            rsx!(
-                                option {
-                                    value: "{account.name}",
-                                    selected: "{account.is_default}",
-                                    "{account.name}"
-                                }
-                            )
-                        }).collect::<Vec<_>>().into_iter()}
-                    }
-                }
             }
         }
     }
@@ -0,0 +1,141 @@
+use std::path::PathBuf;
+use dioxus::html::HasFileData;
+use dioxus::prelude::*;
+use crate::repositories::cross_account_repo::fetch_transfers;
+use crate::state::{
+    clear_finished_transfers, db, discard_cross_account_transfer, queue_cross_account_transfers, TransferDirection,
+    TransferState, TRANSFERS,
+};
+
+/// Native paths of the files and directories dropped from the OS file manager
+pub fn dropped_paths(evt: &DragEvent) -> Vec<PathBuf> {
+    evt.files()
+        .map(|engine| engine.files().into_iter().map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// Cross-account transfers in progress, the saved ones which aren't are interrupted
+fn is_in_progress(account_id: i64, bucket: &str, key: &str) -> bool {
+    TRANSFERS.read().iter().any(|transfer| {
+        transfer.direction == TransferDirection::CrossAccount
+            && transfer.account_id == account_id
+            && transfer.bucket == bucket
+            && transfer.key == key
+            && matches!(transfer.state, TransferState::Queued | TransferState::Running(_))
+    })
+}
+
+/// Uploads, downloads and copies queued in this session, only the ones of `account` and
+/// `bucket` when they're set. The cross-account transfers interrupted before can be resumed
+/// or discarded.
+#[component]
+pub fn TransferQueue(account: Option<i64>, bucket: Option<String>) -> Element {
+    let mut reload_saved = use_signal(|| 0u32);
+    // the saved transfers change when a copy finishes
+    let finished_copies = use_memo(|| {
+        TRANSFERS
+            .read()
+            .iter()
+            .filter(|transfer| {
+                transfer.direction == TransferDirection::CrossAccount
+                    && matches!(transfer.state, TransferState::Done | TransferState::Failed(_))
+            })
+            .count()
+    });
+    let saved = use_memo(move || {
+        let _ = (finished_copies.read(), reload_saved.read());
+        fetch_transfers(db())
+    });
+    let interrupted: Vec<_> = saved
+        .read()
+        .iter()
+        .filter(|transfer| account.is_none_or(|account| account == transfer.source_account_id))
+        .filter(|transfer| bucket.as_ref().is_none_or(|bucket| *bucket == transfer.source_bucket))
+        .filter(|transfer| !is_in_progress(transfer.source_account_id, &transfer.source_bucket, &transfer.source_key))
+        .cloned()
+        .collect();
+
+    let transfers: Vec<_> = TRANSFERS
+        .read()
+        .iter()
+        .filter(|transfer| account.is_none_or(|account| account == transfer.account_id))
+        .filter(|transfer| bucket.as_ref().is_none_or(|bucket| *bucket == transfer.bucket))
+        .cloned()
+        .collect();
+    if transfers.is_empty() && interrupted.is_empty() {
+        return rsx!();
+    }
+    let pending = transfers
+        .iter()
+        .filter(|transfer| matches!(transfer.state, TransferState::Queued | TransferState::Running(_)))
+        .count();
+
+    rsx!(
+        div { class: "p-4 mb-4 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            div { class: "flex items-center justify-between mb-2",
+                h4 { class: "font-semibold text-gray-800 dark:text-gray-300",
+                    "Transfers ({pending} of {transfers.len()} remaining)"
+                }
+                button {
+                    class: "text-sm text-purple-600 hover:underline",
+                    onclick: move |_| clear_finished_transfers(),
+                    "Clear finished"
+                }
+            }
+            ul { class: "max-h-40 overflow-y-auto text-sm text-gray-700 dark:text-gray-400",
+                for transfer in transfers {
+                    li { key: "{transfer.id}", class: "flex justify-between py-1",
+                        span { class: "break-all",
+                            match transfer.direction {
+                                TransferDirection::Upload => rsx!("↑ {transfer.other_side} → s3://{transfer.bucket}/{transfer.key}"),
+                                TransferDirection::Download => rsx!("↓ s3://{transfer.bucket}/{transfer.key} → {transfer.other_side}"),
+                                TransferDirection::CrossAccount => rsx!("⇄ s3://{transfer.bucket}/{transfer.key} → {transfer.other_side}"),
+                            }
+                        }
+                        span { class: "ml-4 whitespace-nowrap",
+                            match &transfer.state {
+                                TransferState::Queued => rsx!("Queued"),
+                                TransferState::Running(progress) => rsx!("{progress:.0}%"),
+                                TransferState::Done => rsx!("Done"),
+                                TransferState::Failed(error) => rsx!( span { class: "text-red-600", "Failed: {error}" } ),
+                            }
+                        }
+                    }
+                }
+            }
+            if !interrupted.is_empty() {
+                h4 { class: "mt-4 mb-2 font-semibold text-gray-800 dark:text-gray-300", "Interrupted copies to other accounts" }
+                ul { class: "text-sm text-gray-700 dark:text-gray-400",
+                    for transfer in interrupted {
+                        li { key: "{transfer.id}", class: "flex items-center justify-between py-1",
+                            span { class: "break-all",
+                                "⇄ s3://{transfer.source_bucket}/{transfer.source_key} → s3://{transfer.destination_bucket}/{transfer.destination_key}"
+                            }
+                            span { class: "ml-4 space-x-2 whitespace-nowrap",
+                                button {
+                                    class: "text-purple-600 hover:underline",
+                                    onclick: {
+                                        let transfer = transfer.clone();
+                                        move |_| queue_cross_account_transfers(vec![transfer.clone()])
+                                    },
+                                    "Resume"
+                                }
+                                button {
+                                    class: "text-red-600 hover:underline",
+                                    onclick: {
+                                        let transfer = transfer.clone();
+                                        move |_| {
+                                            discard_cross_account_transfer(transfer.clone());
+                                            *reload_saved.write() += 1;
+                                        }
+                                    },
+                                    "Discard"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
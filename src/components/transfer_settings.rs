@@ -0,0 +1,77 @@
+use dioxus::prelude::*;
+use crate::model::encryption::EncryptionSettings;
+use crate::model::storage_class::STORAGE_CLASSES;
+use crate::state::TRANSFER_OPTIONS;
+
+/// Options of the files dropped or copied into a bucket, they're kept for the session.
+/// The SSE-C key chosen here is also used to download the objects encrypted with it.
+#[component]
+pub fn TransferSettings() -> Element {
+    let options = TRANSFER_OPTIONS.read().clone();
+    let storage_class = options.storage_class.clone().unwrap_or_default();
+    let sse_mode = options.encryption.as_ref().map(|e| e.mode()).unwrap_or_default();
+    let kms_key_id = options.encryption.as_ref().and_then(|e| e.kms_key_id()).unwrap_or_default();
+    let customer_key = options.encryption.as_ref().and_then(|e| e.sse_customer_key()).unwrap_or_default();
+    let invalid = options.encryption.as_ref().and_then(|e| e.validate().err());
+    let set_encryption = move |mode: &str, kms_key_id: Option<String>, customer_key: Option<String>| {
+        TRANSFER_OPTIONS.write().encryption =
+            (!mode.is_empty()).then(|| EncryptionSettings::from_parts(mode, kms_key_id, customer_key));
+    };
+
+    rsx!(
+        div { class: "flex flex-wrap items-center gap-2 mb-2 text-xs text-gray-600 dark:text-gray-400",
+            span { "Uploads:" }
+            select {
+                class: "px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                title: "Storage class of the uploaded objects",
+                value: "{storage_class}",
+                oninput: move |e| TRANSFER_OPTIONS.write().storage_class = Some(e.value()).filter(|class| !class.is_empty()),
+                option { value: "", "Bucket default class" }
+                {STORAGE_CLASSES.iter().map(|(value, label)| rsx!(
+                    option { value: "{value}", "{label}" }
+                ))}
+            }
+            select {
+                class: "px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                title: "Encryption of the uploaded objects instead of the account's",
+                value: "{sse_mode}",
+                oninput: {
+                    let kms_key_id = kms_key_id.clone();
+                    let customer_key = customer_key.clone();
+                    move |e: FormEvent| set_encryption(&e.value(), Some(kms_key_id.clone()), Some(customer_key.clone()))
+                },
+                option { value: "", "Account encryption" }
+                option { value: "none", "Bucket default encryption" }
+                option { value: "sse-s3", "SSE-S3" }
+                option { value: "sse-kms", "SSE-KMS" }
+                option { value: "sse-c", "SSE-C" }
+            }
+            if sse_mode == "sse-kms" {
+                input {
+                    class: "px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                    r#type: "text",
+                    placeholder: "KMS key (empty for aws/s3)",
+                    value: "{kms_key_id}",
+                    oninput: move |e| set_encryption("sse-kms", Some(e.value()), None),
+                }
+            }
+            if sse_mode == "sse-c" {
+                input {
+                    class: "px-2 py-1 border rounded-md dark:bg-gray-700 dark:text-white",
+                    r#type: "password",
+                    placeholder: "SSE-C key (base64, 256-bit)",
+                    value: "{customer_key}",
+                    oninput: move |e| set_encryption("sse-c", None, Some(e.value())),
+                }
+                button {
+                    class: "text-purple-600 hover:underline",
+                    onclick: move |_| set_encryption("sse-c", None, Some(EncryptionSettings::generate_customer_key())),
+                    "Generate"
+                }
+            }
+            if let Some(error) = invalid {
+                span { class: "text-red-600", "{error}" }
+            }
+        }
+    )
+}
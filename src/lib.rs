@@ -0,0 +1,8 @@
+//! Core of DioS3: the s3 access, the accounts store and the models, shared by the
+//! desktop app and the `dios3-cli` command line tool
+
+pub mod model;
+pub mod repositories;
+pub mod services;
+pub mod settings;
+pub mod utils;
@@ -1,21 +1,22 @@
 mod pages;
 mod components;
-mod services;
-mod model;
+mod state;
 
-mod settings;
-mod utils;
-
-mod repositories;
+use dios3::{model, repositories, services, utils};
 
 use dioxus::prelude::*;
 use pages::Buckets;
 use pages::Dashboard;
 use pages::Accounts;
+use pages::Objects;
+use pages::BucketIndex;
+use pages::MultipartUploads;
+use pages::BucketSettings;
+use pages::Commander;
 use components::SettingsModal;
 use components::TopBar;
 use components::LeftSidebar;
-use crate::utils::{init_db, init_state};
+use crate::state::{init_db, init_state};
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -23,14 +24,38 @@ enum Route {
     #[layout(MainContent)]
     #[route("/")]
     Dashboard {},
-    #[route("/buckets")]
-    Buckets {},
+    #[route("/accounts/:account/buckets")]
+    Buckets { account: i64 },
+    #[route("/accounts/:account/buckets/:bucket?:prefix&:selected")]
+    Objects { account: i64, bucket: String, prefix: String, selected: String },
+    #[route("/accounts/:account/buckets/:bucket/index")]
+    BucketIndex { account: i64, bucket: String },
+    #[route("/accounts/:account/buckets/:bucket/uploads")]
+    MultipartUploads { account: i64, bucket: String },
+    #[route("/accounts/:account/buckets/:bucket/settings")]
+    BucketSettings { account: i64, bucket: String },
+    #[route("/commander")]
+    Commander {},
     #[route("/accounts")]
     Accounts {},
     // #[route("/blog/:id")]
     // Blog { id: i32 },
 }
 
+impl Route {
+    /// Account the page browses, the pages of the app itself have none
+    fn account(&self) -> Option<i64> {
+        match self {
+            Route::Buckets { account }
+            | Route::Objects { account, .. }
+            | Route::BucketIndex { account, .. }
+            | Route::MultipartUploads { account, .. }
+            | Route::BucketSettings { account, .. } => Some(*account),
+            Route::Dashboard {} | Route::Commander {} | Route::Accounts {} => None,
+        }
+    }
+}
+
 const FAVICON: Asset = asset!("/assets/favicon.ico");
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 const HEADER_SVG: Asset = asset!("/assets/header.svg");
@@ -1,4 +1,6 @@
-#[derive(Clone, Debug, PartialEq)]
+use crate::model::encryption::EncryptionSettings;
+
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Account {
     pub id: i64,
     pub name: String,
@@ -6,7 +8,12 @@ pub struct Account {
     pub access_key: String,
     pub secret_key: String,
     pub is_default: bool,
-    pub default_region: String
+    pub default_region: String,
+    pub encryption: EncryptionSettings,
+    /// Master key used for client-side encryption of uploads, `None` disables it
+    pub client_key_id: Option<i64>,
+    /// Endpoint of an s3 compatible service (eg. MinIO), `None` for AWS
+    pub endpoint_url: Option<String>,
 }
 impl Account {
     pub fn masked_secret_key(&self) -> String {
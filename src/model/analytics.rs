@@ -0,0 +1,436 @@
+//! This module provides the storage analytics of a bucket computed from its listing:
+//! sizes by storage class, top-level prefix and extension, the largest objects, the age
+//! of the data and the space taken by incomplete multipart uploads
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::model::s3_data_item::S3DataItem;
+
+/// Number of the largest objects kept
+pub const LARGEST_OBJECTS: usize = 10;
+
+/// Prefixes and extensions beyond these are summed up as "other"
+pub const TOP_GROUPS: usize = 10;
+
+const DAY: i64 = 24 * 3600;
+
+/// Upper bounds (in days) of the age histogram bins, the last bin has no bound
+const AGE_BINS: [(i64, &str); 5] = [
+    (30, "< 30 days"),
+    (90, "30-90 days"),
+    (180, "90-180 days"),
+    (365, "180 days - 1 year"),
+    (730, "1-2 years"),
+];
+const OLDEST_BIN: &str = "> 2 years";
+
+/// Age (in days) where the bins of the age histogram start, after the first one
+pub fn age_thresholds() -> Vec<i64> {
+    AGE_BINS.iter().map(|(max_days, _)| *max_days).collect()
+}
+
+/// Objects and bytes of a group of objects
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Breakdown {
+    pub label: String,
+    pub object_count: u64,
+    pub total_size: u64,
+}
+
+/// Objects sharing the top-level prefix, the storage class and the age bin.
+/// The costs and their projections are computed from these.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageSlice {
+    pub prefix: String,
+    pub storage_class: String,
+    /// Where the age bin of the objects starts, in days
+    pub min_age_days: i64,
+    pub object_count: u64,
+    pub total_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LargeObject {
+    pub size: u64,
+    pub key: String,
+    pub storage_class: Option<String>,
+    /// Seconds since the epoch
+    pub last_modified: Option<i64>,
+}
+
+/// Multipart upload which was neither completed nor aborted, its parts are billed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncompleteUpload {
+    pub key: String,
+    pub upload_id: String,
+    /// Seconds since the epoch
+    pub initiated: Option<i64>,
+    /// Bytes of the uploaded parts
+    pub size: u64,
+    #[serde(default)]
+    pub part_count: usize,
+}
+
+impl IncompleteUpload {
+    /// Whole days since the upload was started, `now` is in seconds since the epoch
+    pub fn age_days(&self, now: i64) -> Option<i64> {
+        self.initiated.map(|initiated| (now - initiated).max(0) / DAY)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BucketAnalytics {
+    pub bucket: String,
+    pub region: Option<String>,
+    pub object_count: u64,
+    pub total_size: u64,
+    pub by_storage_class: Vec<Breakdown>,
+    pub by_prefix: Vec<Breakdown>,
+    pub by_extension: Vec<Breakdown>,
+    /// Biggest first
+    pub largest: Vec<LargeObject>,
+    /// From the newest to the oldest objects
+    pub by_age: Vec<Breakdown>,
+    pub incomplete_uploads: Vec<IncompleteUpload>,
+    /// Why the incomplete uploads couldn't be listed, they're missing from the analytics then
+    #[serde(default)]
+    pub incomplete_uploads_error: Option<String>,
+    /// Sizes by prefix, storage class and age, the prefixes beyond the top ones are "other".
+    /// Missing in the analytics cached before the costs were estimated.
+    #[serde(default)]
+    pub slices: Vec<StorageSlice>,
+    /// When the listing was analyzed, seconds since the epoch
+    pub computed_at: i64,
+}
+
+impl BucketAnalytics {
+    pub fn incomplete_upload_size(&self) -> u64 {
+        self.incomplete_uploads.iter().map(|upload| upload.size).sum()
+    }
+}
+
+/// Collects the analytics object by object, so the listing doesn't have to be kept in memory
+#[derive(Debug, Clone)]
+pub struct AnalyticsBuilder {
+    bucket: String,
+    region: Option<String>,
+    now: i64,
+    object_count: u64,
+    total_size: u64,
+    by_storage_class: HashMap<String, Breakdown>,
+    by_prefix: HashMap<String, Breakdown>,
+    by_extension: HashMap<String, Breakdown>,
+    by_age: Vec<Breakdown>,
+    /// By prefix, storage class and the start of the age bin
+    slices: HashMap<(String, String, i64), StorageSlice>,
+    /// Min-heap of the largest objects seen so far
+    largest: BinaryHeap<Reverse<LargeObject>>,
+    incomplete_uploads: Vec<IncompleteUpload>,
+    incomplete_uploads_error: Option<String>,
+}
+
+fn add_to(groups: &mut HashMap<String, Breakdown>, label: &str, size: u64) {
+    let group = groups.entry(label.to_string()).or_insert_with(|| Breakdown {
+        label: label.to_string(),
+        ..Breakdown::default()
+    });
+    group.object_count += 1;
+    group.total_size += size;
+}
+
+/// Biggest groups first, beyond `limit` they're summed up as "other"
+fn top_groups(groups: HashMap<String, Breakdown>, limit: Option<usize>) -> Vec<Breakdown> {
+    let mut groups: Vec<Breakdown> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.label.cmp(&b.label)));
+    if let Some(limit) = limit.filter(|limit| groups.len() > *limit) {
+        let rest = groups.split_off(limit);
+        groups.push(Breakdown {
+            label: "other".to_string(),
+            object_count: rest.iter().map(|g| g.object_count).sum(),
+            total_size: rest.iter().map(|g| g.total_size).sum(),
+        });
+    }
+    groups
+}
+
+/// Moves the slices of the prefixes which didn't make it to the top ones to "other"
+fn merge_other_prefixes(slices: HashMap<(String, String, i64), StorageSlice>, top: &[Breakdown]) -> Vec<StorageSlice> {
+    let top: HashSet<&str> = top.iter().map(|group| group.label.as_str()).collect();
+    let mut merged: HashMap<(String, String, i64), StorageSlice> = HashMap::new();
+    for (_, mut slice) in slices {
+        if !top.contains(slice.prefix.as_str()) {
+            slice.prefix = "other".to_string();
+        }
+        let key = (slice.prefix.clone(), slice.storage_class.clone(), slice.min_age_days);
+        match merged.get_mut(&key) {
+            Some(existing) => {
+                existing.object_count += slice.object_count;
+                existing.total_size += slice.total_size;
+            }
+            None => {
+                merged.insert(key, slice);
+            }
+        }
+    }
+    let mut merged: Vec<StorageSlice> = merged.into_values().collect();
+    merged.sort_by(|a, b| {
+        (&a.prefix, &a.storage_class, a.min_age_days).cmp(&(&b.prefix, &b.storage_class, b.min_age_days))
+    });
+    merged
+}
+
+/// Top-level "directory" of the key, `/` for the objects in the root of the bucket
+pub fn top_level_prefix(key: &str) -> &str {
+    match key.find('/') {
+        Some(idx) => &key[..=idx],
+        None => "/",
+    }
+}
+
+/// Lowercase extension of the key's file name, `(none)` when there isn't any
+pub fn extension(key: &str) -> String {
+    let name = key.rsplit('/').next().unwrap_or(key);
+    match name.rfind('.') {
+        Some(idx) if idx > 0 && idx + 1 < name.len() => name[idx + 1..].to_lowercase(),
+        _ => "(none)".to_string(),
+    }
+}
+
+impl AnalyticsBuilder {
+    /// `now` (seconds since the epoch) is what the age of the objects is measured against
+    pub fn new(bucket: &str, region: Option<String>, now: i64) -> AnalyticsBuilder {
+        let by_age = AGE_BINS
+            .iter()
+            .map(|(_, label)| *label)
+            .chain([OLDEST_BIN])
+            .map(|label| Breakdown { label: label.to_string(), ..Breakdown::default() })
+            .collect();
+        AnalyticsBuilder {
+            bucket: bucket.to_string(),
+            region,
+            now,
+            object_count: 0,
+            total_size: 0,
+            by_storage_class: HashMap::new(),
+            by_prefix: HashMap::new(),
+            by_extension: HashMap::new(),
+            by_age,
+            slices: HashMap::new(),
+            largest: BinaryHeap::new(),
+            incomplete_uploads: Vec::new(),
+            incomplete_uploads_error: None,
+        }
+    }
+
+    /// Adds a listed object, "directories" are skipped
+    pub fn add(&mut self, item: &S3DataItem) {
+        if item.is_directory || item.is_bucket {
+            return;
+        }
+        let key = item.path.as_str();
+        let size = item.size.unwrap_or(0);
+        let last_modified = item.last_modified.map(|time| time.secs());
+        self.object_count += 1;
+        self.total_size += size;
+        let storage_class = item.storage_class.as_deref().unwrap_or("STANDARD");
+        add_to(&mut self.by_storage_class, storage_class, size);
+        add_to(&mut self.by_prefix, top_level_prefix(key), size);
+        add_to(&mut self.by_extension, &extension(key), size);
+
+        let age_days = last_modified.map_or(0, |modified| (self.now - modified).max(0) / DAY);
+        let bin = AGE_BINS
+            .iter()
+            .position(|(max_days, _)| age_days < *max_days)
+            .unwrap_or(AGE_BINS.len());
+        self.by_age[bin].object_count += 1;
+        self.by_age[bin].total_size += size;
+        let min_age_days = if bin == 0 { 0 } else { AGE_BINS[bin - 1].0 };
+        let slice = self
+            .slices
+            .entry((top_level_prefix(key).to_string(), storage_class.to_string(), min_age_days))
+            .or_insert_with(|| StorageSlice {
+                prefix: top_level_prefix(key).to_string(),
+                storage_class: storage_class.to_string(),
+                min_age_days,
+                object_count: 0,
+                total_size: 0,
+            });
+        slice.object_count += 1;
+        slice.total_size += size;
+
+        let object = LargeObject {
+            size,
+            key: key.to_string(),
+            storage_class: item.storage_class.clone(),
+            last_modified,
+        };
+        if self.largest.len() < LARGEST_OBJECTS {
+            self.largest.push(Reverse(object));
+        } else if self.largest.peek().is_some_and(|Reverse(smallest)| smallest.size < size) {
+            self.largest.pop();
+            self.largest.push(Reverse(object));
+        }
+    }
+
+    pub fn add_incomplete_upload(&mut self, upload: IncompleteUpload) {
+        self.incomplete_uploads.push(upload);
+    }
+
+    /// The incomplete uploads couldn't be listed, the analytics don't include them
+    pub fn incomplete_uploads_failed(&mut self, error: String) {
+        self.incomplete_uploads_error = Some(error);
+    }
+
+    pub fn finish(self) -> BucketAnalytics {
+        let mut largest: Vec<LargeObject> = self.largest.into_iter().map(|Reverse(object)| object).collect();
+        largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.key.cmp(&b.key)));
+        let by_prefix = top_groups(self.by_prefix, Some(TOP_GROUPS));
+        let slices = merge_other_prefixes(self.slices, &by_prefix);
+        BucketAnalytics {
+            bucket: self.bucket,
+            region: self.region,
+            object_count: self.object_count,
+            total_size: self.total_size,
+            by_storage_class: top_groups(self.by_storage_class, None),
+            by_prefix,
+            by_extension: top_groups(self.by_extension, Some(TOP_GROUPS)),
+            largest,
+            by_age: self.by_age,
+            incomplete_uploads: self.incomplete_uploads,
+            incomplete_uploads_error: self.incomplete_uploads_error,
+            slices,
+            computed_at: self.now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::primitives::DateTime;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn object(key: &str, size: u64, age_days: i64, storage_class: Option<&str>) -> S3DataItem {
+        S3DataItem {
+            bucket: Some("bucket".to_string()),
+            name: key.rsplit('/').next().unwrap_or(key).to_string(),
+            size: Some(size),
+            file_type: String::new(),
+            path: key.to_string(),
+            is_directory: false,
+            is_bucket: false,
+            region: None,
+            last_modified: Some(DateTime::from_secs(NOW - age_days * DAY)),
+            e_tag: None,
+            storage_class: storage_class.map(String::from),
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn groups_by_class_prefix_extension_and_age() {
+        let mut builder = AnalyticsBuilder::new("bucket", None, NOW);
+        builder.add(&object("logs/a.LOG", 100, 1, None));
+        builder.add(&object("logs/b.log", 50, 100, Some("GLACIER")));
+        builder.add(&object("readme", 10, 1000, Some("STANDARD")));
+        builder.add(&S3DataItem { is_directory: true, ..object("logs/", 0, 0, None) });
+        let analytics = builder.finish();
+
+        assert_eq!(analytics.object_count, 3);
+        assert_eq!(analytics.total_size, 160);
+        let groups = |breakdown: &[Breakdown]| {
+            breakdown.iter().map(|g| (g.label.clone(), g.object_count, g.total_size)).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            groups(&analytics.by_storage_class),
+            vec![("STANDARD".to_string(), 2, 110), ("GLACIER".to_string(), 1, 50)]
+        );
+        assert_eq!(groups(&analytics.by_prefix), vec![("logs/".to_string(), 2, 150), ("/".to_string(), 1, 10)]);
+        assert_eq!(groups(&analytics.by_extension), vec![("log".to_string(), 2, 150), ("(none)".to_string(), 1, 10)]);
+        let ages: Vec<u64> = analytics.by_age.iter().map(|bin| bin.object_count).collect();
+        assert_eq!(ages, vec![1, 0, 1, 0, 0, 1]);
+        let slices: Vec<_> = analytics
+            .slices
+            .iter()
+            .map(|s| (s.prefix.as_str(), s.storage_class.as_str(), s.min_age_days, s.total_size))
+            .collect();
+        assert_eq!(
+            slices,
+            vec![("/", "STANDARD", 730, 10), ("logs/", "GLACIER", 90, 50), ("logs/", "STANDARD", 0, 100)]
+        );
+    }
+
+    #[test]
+    fn keeps_the_largest_objects() {
+        let mut builder = AnalyticsBuilder::new("bucket", None, NOW);
+        for size in 0..50 {
+            builder.add(&object(&format!("file-{}", size), size, 0, None));
+        }
+        let largest: Vec<u64> = builder.finish().largest.iter().map(|o| o.size).collect();
+        assert_eq!(largest, (40..50).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn sums_up_small_groups_as_other() {
+        let mut builder = AnalyticsBuilder::new("bucket", None, NOW);
+        for idx in 0..TOP_GROUPS + 5 {
+            builder.add(&object(&format!("dir{}/file", idx), 1000 - idx as u64, 0, None));
+        }
+        let by_prefix = builder.finish().by_prefix;
+        assert_eq!(by_prefix.len(), TOP_GROUPS + 1);
+        let other = by_prefix.last().unwrap();
+        assert_eq!((other.label.as_str(), other.object_count), ("other", 5));
+    }
+
+    #[test]
+    fn slices_of_small_prefixes_are_merged() {
+        let mut builder = AnalyticsBuilder::new("bucket", None, NOW);
+        for idx in 0..TOP_GROUPS + 5 {
+            builder.add(&object(&format!("dir{}/file", idx), 1000 - idx as u64, 0, None));
+        }
+        let analytics = builder.finish();
+        assert_eq!(analytics.slices.len(), TOP_GROUPS + 1);
+        let other = analytics.slices.iter().find(|slice| slice.prefix == "other").unwrap();
+        assert_eq!(other.object_count, 5);
+        assert_eq!(analytics.slices.iter().map(|s| s.total_size).sum::<u64>(), analytics.total_size);
+    }
+
+    #[test]
+    fn age_of_incomplete_uploads() {
+        let upload = IncompleteUpload {
+            key: "big.iso".to_string(),
+            upload_id: "id".to_string(),
+            initiated: Some(NOW - 3 * DAY - 60),
+            size: 0,
+            part_count: 0,
+        };
+        assert_eq!(upload.age_days(NOW), Some(3));
+        assert_eq!(IncompleteUpload { initiated: None, ..upload }.age_days(NOW), None);
+    }
+
+    #[test]
+    fn failed_upload_listings_are_kept_with_the_analytics() {
+        let mut builder = AnalyticsBuilder::new("bucket", None, NOW);
+        builder.incomplete_uploads_failed("AccessDenied".to_string());
+        let analytics = builder.finish();
+        assert_eq!(analytics.incomplete_uploads_error.as_deref(), Some("AccessDenied"));
+
+        // analytics cached before the listing failures were kept don't have them
+        let json = serde_json::to_value(&analytics).unwrap();
+        let mut cached = json.as_object().unwrap().clone();
+        cached.remove("incomplete_uploads_error");
+        let cached: BucketAnalytics = serde_json::from_value(cached.into()).unwrap();
+        assert_eq!(cached.incomplete_uploads_error, None);
+    }
+
+    #[test]
+    fn extensions_of_keys() {
+        assert_eq!(extension("photos/IMG.JPG"), "jpg");
+        assert_eq!(extension("archive.tar.gz"), "gz");
+        assert_eq!(extension("dir.v2/Makefile"), "(none)");
+        assert_eq!(extension(".hidden"), "(none)");
+        assert_eq!(top_level_prefix("a/b/c.txt"), "a/");
+        assert_eq!(top_level_prefix("c.txt"), "/");
+    }
+}
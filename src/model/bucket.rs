@@ -1,5 +1,134 @@
+//! This module provides the buckets listed for an account and the options new buckets are created with
+use std::collections::BTreeMap;
+use std::net::Ipv4Addr;
+use crate::model::encryption::EncryptionSettings;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Bucket {
     pub name: String,
     pub region: Option<String>
-}
\ No newline at end of file
+}
+
+/// Regions offered when creating buckets and accounts, as (code, label)
+pub const REGIONS: &[(&str, &str)] = &[
+    ("us-east-1", "US East (N. Virginia)"),
+    ("us-east-2", "US East (Ohio)"),
+    ("us-west-1", "US West (N. California)"),
+    ("us-west-2", "US West (Oregon)"),
+    ("ca-central-1", "Canada (Central)"),
+    ("sa-east-1", "South America (São Paulo)"),
+    ("eu-west-1", "EU (Ireland)"),
+    ("eu-west-2", "EU (London)"),
+    ("eu-west-3", "EU (Paris)"),
+    ("eu-central-1", "EU (Frankfurt)"),
+    ("eu-central-2", "EU (Zurich)"),
+    ("eu-north-1", "EU (Stockholm)"),
+    ("eu-south-1", "EU (Milan)"),
+    ("eu-south-2", "EU (Spain)"),
+    ("me-central-1", "Middle East (UAE)"),
+    ("af-south-1", "Africa (Cape Town)"),
+    ("ap-south-1", "Asia Pacific (Mumbai)"),
+    ("ap-southeast-1", "Asia Pacific (Singapore)"),
+    ("ap-southeast-2", "Asia Pacific (Sydney)"),
+    ("ap-northeast-1", "Asia Pacific (Tokyo)"),
+    ("ap-northeast-2", "Asia Pacific (Seoul)"),
+    ("ap-northeast-3", "Asia Pacific (Osaka)"),
+    ("ap-east-1", "Asia Pacific (Hong Kong)"),
+];
+
+/// Region of the buckets created without a location constraint, s3 rejects it as one
+pub const DEFAULT_BUCKET_REGION: &str = "us-east-1";
+
+/// Settings applied to a bucket as part of its creation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BucketOptions {
+    /// Can only be enabled when the bucket is created, versioning gets enabled with it
+    pub object_lock: bool,
+    pub versioning: bool,
+    /// Default encryption of the objects, `None` keeps the SSE-S3 default of s3
+    pub encryption: EncryptionSettings,
+    pub tags: BTreeMap<String, String>,
+    pub block_public_access: bool,
+}
+
+impl BucketOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.encryption, EncryptionSettings::SseC { .. }) {
+            return Err("SSE-C can't be the default encryption of a bucket".to_string());
+        }
+        if self.tags.len() > 50 {
+            return Err("A bucket can have at most 50 tags".to_string());
+        }
+        match self.tags.iter().find(|(key, value)| key.is_empty() || key.len() > 128 || value.len() > 256) {
+            Some((key, _)) => Err(format!("Tag '{}' must have a key of 1-128 characters and a value of at most 256", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks the name against the naming rules of s3 general purpose buckets
+pub fn validate_bucket_name(name: &str) -> Result<(), String> {
+    if !(3..=63).contains(&name.len()) {
+        return Err("Bucket names must be between 3 and 63 characters long".to_string());
+    }
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-') {
+        return Err("Bucket names can contain only lowercase letters, numbers, dots and hyphens".to_string());
+    }
+    let alphanumeric = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    if !alphanumeric(name.chars().next()) || !alphanumeric(name.chars().last()) {
+        return Err("Bucket names must begin and end with a letter or number".to_string());
+    }
+    if name.contains("..") {
+        return Err("Bucket names can't contain two adjacent dots".to_string());
+    }
+    if name.parse::<Ipv4Addr>().is_ok() {
+        return Err("Bucket names can't be formatted as an IP address".to_string());
+    }
+    if let Some(prefix) = ["xn--", "sthree-", "amzn-s3-demo-"].iter().find(|prefix| name.starts_with(*prefix)) {
+        return Err(format!("Bucket names can't start with '{}'", prefix));
+    }
+    if let Some(suffix) = ["-s3alias", "--ol-s3", ".mrap", "--x-s3", "--table-s3"].iter().find(|suffix| name.ends_with(*suffix)) {
+        return Err(format!("Bucket names can't end with '{}'", suffix));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_bucket_names() {
+        for name in ["abc", "my-bucket.logs", "2024-backups", &"a".repeat(63)] {
+            assert_eq!(validate_bucket_name(name), Ok(()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_names_breaking_the_naming_rules() {
+        for name in [
+            "ab",
+            &"a".repeat(64),
+            "My-Bucket",
+            "my_bucket",
+            "-bucket",
+            "bucket.",
+            "my..bucket",
+            "192.168.5.4",
+            "xn--bucket",
+            "bucket-s3alias",
+        ] {
+            assert!(validate_bucket_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn customer_keys_and_long_tags_are_rejected() {
+        let options = BucketOptions { encryption: EncryptionSettings::SseC { key: String::new() }, ..Default::default() };
+        assert!(options.validate().is_err());
+        let options = BucketOptions { tags: BTreeMap::from([("team".to_string(), "x".repeat(257))]), ..Default::default() };
+        assert!(options.validate().is_err());
+        let options = BucketOptions { tags: BTreeMap::from([("team".to_string(), "data".to_string())]), ..Default::default() };
+        assert_eq!(options.validate(), Ok(()));
+    }
+}
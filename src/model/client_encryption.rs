@@ -0,0 +1,389 @@
+//! This module provides the client-side envelope encryption applied to transfers.
+//!
+//! Every object is encrypted with its own random data key using AES-256-GCM in the STREAM
+//! construction (the content is split into chunks, each authenticated separately, so files
+//! of any size can be processed without keeping them in memory). The data key is wrapped with
+//! one of the master keys kept in the local database and stored, together with everything
+//! else needed for decryption, in the object's user metadata.
+use aes_gcm::aead::stream::{DecryptorBE32, EncryptorBE32};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use color_eyre::{eyre, Report};
+use rand::RngCore;
+use std::collections::HashMap;
+
+pub const CLIENT_ENCRYPTION_ALGORITHM: &str = "AES-256-GCM-STREAM";
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+/// Largest chunk accepted from the metadata of an object, it's kept in memory while decrypting
+const MAX_CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+/// Prefix of the metadata keys holding the envelope, the object can't be decrypted without them
+const META_PREFIX: &str = "dios3-cse-";
+
+pub const META_ALGORITHM: &str = "dios3-cse-alg";
+const META_KEY_ID: &str = "dios3-cse-key-id";
+const META_WRAPPED_KEY: &str = "dios3-cse-key";
+const META_NONCE: &str = "dios3-cse-nonce";
+const META_CHUNK_SIZE: &str = "dios3-cse-chunk";
+const META_PLAINTEXT_SIZE: &str = "dios3-cse-size";
+
+const KEY_SIZE: usize = 32;
+const WRAP_NONCE_SIZE: usize = 12;
+const STREAM_NONCE_SIZE: usize = 7;
+const TAG_SIZE: usize = 16;
+
+/// Master key kept in the local database, used for wrapping the per-object data keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientKey {
+    pub id: i64,
+    pub name: String,
+    /// Base64 encoded 256-bit key
+    pub key: String,
+}
+
+impl ClientKey {
+    /// Generates a new random base64 encoded master key
+    pub fn generate() -> String {
+        STANDARD.encode(random_bytes::<KEY_SIZE>())
+    }
+
+    fn raw_key(&self) -> eyre::Result<[u8; KEY_SIZE]> {
+        STANDARD
+            .decode(&self.key)
+            .ok()
+            .and_then(|raw| raw.try_into().ok())
+            .ok_or_else(|| Report::msg(format!("Client key '{}' is not a valid 256-bit key", self.name)))
+    }
+}
+
+/// Everything needed to decrypt an object, stored in its user metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopeHeader {
+    pub key_id: i64,
+    /// Base64 encoded (nonce, encrypted data key)
+    pub wrapped_key: String,
+    /// Base64 encoded nonce prefix of the stream
+    pub nonce: String,
+    pub chunk_size: usize,
+    pub plaintext_size: u64,
+}
+
+impl EnvelopeHeader {
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (META_ALGORITHM.to_string(), CLIENT_ENCRYPTION_ALGORITHM.to_string()),
+            (META_KEY_ID.to_string(), self.key_id.to_string()),
+            (META_WRAPPED_KEY.to_string(), self.wrapped_key.clone()),
+            (META_NONCE.to_string(), self.nonce.clone()),
+            (META_CHUNK_SIZE.to_string(), self.chunk_size.to_string()),
+            (META_PLAINTEXT_SIZE.to_string(), self.plaintext_size.to_string()),
+        ])
+    }
+
+    /// Reads the header from the object metadata, `None` if the object isn't client-side encrypted
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<EnvelopeHeader> {
+        if metadata.get(META_ALGORITHM).map(String::as_str) != Some(CLIENT_ENCRYPTION_ALGORITHM) {
+            return None;
+        }
+        Some(EnvelopeHeader {
+            key_id: metadata.get(META_KEY_ID)?.parse().ok()?,
+            wrapped_key: metadata.get(META_WRAPPED_KEY)?.clone(),
+            nonce: metadata.get(META_NONCE)?.clone(),
+            chunk_size: metadata
+                .get(META_CHUNK_SIZE)?
+                .parse()
+                .ok()
+                .filter(|size| (1..=MAX_CHUNK_SIZE).contains(size))?,
+            plaintext_size: metadata.get(META_PLAINTEXT_SIZE)?.parse().ok()?,
+        })
+    }
+
+    /// Size of the encrypted content, every chunk carries an additional authentication tag
+    pub fn ciphertext_size(&self) -> u64 {
+        self.plaintext_size + chunk_count(self.plaintext_size, self.chunk_size) * TAG_SIZE as u64
+    }
+}
+
+/// Whether the metadata marks the object as client-side encrypted
+pub fn is_client_encrypted(metadata: &HashMap<String, String>) -> bool {
+    metadata.contains_key(META_ALGORITHM)
+}
+
+/// Whether the metadata key is part of the envelope of client-side encrypted objects
+pub fn is_envelope_key(key: &str) -> bool {
+    key.starts_with(META_PREFIX)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Number of chunks the content is split into, empty content is still a single (empty) chunk
+fn chunk_count(plaintext_size: u64, chunk_size: usize) -> u64 {
+    std::cmp::max(1, plaintext_size.div_ceil(chunk_size as u64))
+}
+
+fn wrap_key(master: &ClientKey, data_key: &[u8]) -> eyre::Result<String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&master.raw_key()?));
+    let nonce = random_bytes::<WRAP_NONCE_SIZE>();
+    let wrapped = cipher
+        .encrypt(Nonce::from_slice(&nonce), data_key)
+        .map_err(|_| Report::msg("Cannot wrap the data key"))?;
+    Ok(STANDARD.encode([nonce.as_slice(), wrapped.as_slice()].concat()))
+}
+
+fn unwrap_key(master: &ClientKey, wrapped_key: &str) -> eyre::Result<[u8; KEY_SIZE]> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&master.raw_key()?));
+    let wrapped = STANDARD.decode(wrapped_key)?;
+    if wrapped.len() <= WRAP_NONCE_SIZE {
+        return Err(Report::msg("Wrapped data key is too short"));
+    }
+    let (nonce, encrypted) = wrapped.split_at(WRAP_NONCE_SIZE);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), encrypted)
+        .ok()
+        .and_then(|raw| raw.try_into().ok())
+        .ok_or_else(|| Report::msg(format!("Cannot unwrap the data key with client key '{}'", master.name)))
+}
+
+/// Encrypts the content chunk by chunk as it's read, so it can be encrypted straight into
+/// the body of the upload
+pub struct ChunkEncryptor {
+    encryptor: Option<EncryptorBE32<Aes256Gcm>>,
+    chunk_size: usize,
+    remaining_chunks: u64,
+    remaining_size: u64,
+}
+
+impl ChunkEncryptor {
+    /// Starts the encryption of content of the given size, returning the header to be stored
+    /// in the object metadata
+    pub fn new(master: &ClientKey, plaintext_size: u64, chunk_size: usize) -> eyre::Result<(ChunkEncryptor, EnvelopeHeader)> {
+        let data_key = random_bytes::<KEY_SIZE>();
+        let nonce = random_bytes::<STREAM_NONCE_SIZE>();
+        let header = EnvelopeHeader {
+            key_id: master.id,
+            wrapped_key: wrap_key(master, &data_key)?,
+            nonce: STANDARD.encode(nonce),
+            chunk_size,
+            plaintext_size,
+        };
+        let encryptor = ChunkEncryptor {
+            encryptor: Some(EncryptorBE32::<Aes256Gcm>::new(
+                Key::<Aes256Gcm>::from_slice(&data_key),
+                nonce.as_slice().into(),
+            )),
+            chunk_size,
+            remaining_chunks: chunk_count(plaintext_size, chunk_size),
+            remaining_size: plaintext_size,
+        };
+        Ok((encryptor, header))
+    }
+
+    /// Encrypts the next part of the content. Parts have to be made of whole chunks, only the
+    /// one ending the content can be shorter.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> eyre::Result<Vec<u8>> {
+        let mut ciphertext = Vec::with_capacity(plaintext.len() + (plaintext.len() / self.chunk_size + 1) * TAG_SIZE);
+        let mut offset = 0;
+        while self.remaining_chunks > 1 && offset < plaintext.len() {
+            let chunk = &plaintext[offset..plaintext.len().min(offset + self.chunk_size)];
+            if chunk.len() < self.chunk_size {
+                return Err(Report::msg("The content doesn't match the announced size"));
+            }
+            let encryptor = self
+                .encryptor
+                .as_mut()
+                .ok_or_else(|| Report::msg("Content already encrypted"))?;
+            let encrypted = encryptor
+                .encrypt_next(chunk)
+                .map_err(|_| Report::msg("Cannot encrypt the content"))?;
+            ciphertext.extend_from_slice(&encrypted);
+            self.remaining_chunks -= 1;
+            self.remaining_size -= self.chunk_size as u64;
+            offset += self.chunk_size;
+        }
+        // the last chunk is marked, so truncated content is detected on decryption
+        if self.remaining_chunks == 1 && (plaintext.len() - offset) as u64 == self.remaining_size {
+            let encryptor = self
+                .encryptor
+                .take()
+                .ok_or_else(|| Report::msg("Content already encrypted"))?;
+            let encrypted = encryptor
+                .encrypt_last(&plaintext[offset..])
+                .map_err(|_| Report::msg("Cannot encrypt the content"))?;
+            ciphertext.extend_from_slice(&encrypted);
+            self.remaining_chunks = 0;
+            self.remaining_size = 0;
+            offset = plaintext.len();
+        }
+        if offset < plaintext.len() {
+            return Err(Report::msg("The content doesn't match the announced size"));
+        }
+        Ok(ciphertext)
+    }
+}
+
+/// Decrypts the content as it arrives, the bytes can be pushed in pieces of any size
+pub struct ChunkDecryptor {
+    decryptor: Option<DecryptorBE32<Aes256Gcm>>,
+    buffer: Vec<u8>,
+    encrypted_chunk_size: usize,
+    remaining_chunks: u64,
+}
+
+impl ChunkDecryptor {
+    pub fn new(master: &ClientKey, header: &EnvelopeHeader) -> eyre::Result<ChunkDecryptor> {
+        let data_key = unwrap_key(master, &header.wrapped_key)?;
+        let nonce = STANDARD.decode(&header.nonce)?;
+        if nonce.len() != STREAM_NONCE_SIZE {
+            return Err(Report::msg("Invalid client-side encryption nonce"));
+        }
+        Ok(ChunkDecryptor {
+            decryptor: Some(DecryptorBE32::<Aes256Gcm>::new(
+                Key::<Aes256Gcm>::from_slice(&data_key),
+                nonce.as_slice().into(),
+            )),
+            buffer: Vec::new(),
+            encrypted_chunk_size: header.chunk_size + TAG_SIZE,
+            remaining_chunks: chunk_count(header.plaintext_size, header.chunk_size),
+        })
+    }
+
+    /// Returns the plaintext of all the complete chunks received so far
+    pub fn push(&mut self, bytes: &[u8]) -> eyre::Result<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut plaintext = Vec::new();
+        // the last chunk is left in the buffer until `finish`
+        while self.remaining_chunks > 1 && self.buffer.len() >= self.encrypted_chunk_size {
+            let chunk: Vec<u8> = self.buffer.drain(..self.encrypted_chunk_size).collect();
+            let decryptor = self
+                .decryptor
+                .as_mut()
+                .ok_or_else(|| Report::msg("Content already decrypted"))?;
+            let decrypted = decryptor
+                .decrypt_next(chunk.as_slice())
+                .map_err(|_| Report::msg("Cannot decrypt the content, it was modified or the key is wrong"))?;
+            plaintext.extend_from_slice(&decrypted);
+            self.remaining_chunks -= 1;
+        }
+        Ok(plaintext)
+    }
+
+    /// Decrypts the last chunk, failing if the content was truncated
+    pub fn finish(mut self) -> eyre::Result<Vec<u8>> {
+        let decryptor = self
+            .decryptor
+            .take()
+            .ok_or_else(|| Report::msg("Content already decrypted"))?;
+        if self.remaining_chunks != 1 || self.buffer.len() > self.encrypted_chunk_size {
+            return Err(Report::msg("Encrypted content has unexpected size"));
+        }
+        decryptor
+            .decrypt_last(self.buffer.as_slice())
+            .map_err(|_| Report::msg("Cannot decrypt the content, it was modified or truncated"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master_key() -> ClientKey {
+        ClientKey {
+            id: 7,
+            name: "test".into(),
+            key: ClientKey::generate(),
+        }
+    }
+
+    fn encrypt(content: &[u8], chunk_size: usize) -> (EnvelopeHeader, Vec<u8>, ClientKey) {
+        let master = master_key();
+        let (mut encryptor, header) = ChunkEncryptor::new(&master, content.len() as u64, chunk_size).unwrap();
+        (header, encryptor.encrypt(content).unwrap(), master)
+    }
+
+    fn decrypt(master: &ClientKey, header: &EnvelopeHeader, encrypted: &[u8], piece: usize) -> eyre::Result<Vec<u8>> {
+        let mut decryptor = ChunkDecryptor::new(master, header)?;
+        let mut plaintext = Vec::new();
+        for bytes in encrypted.chunks(piece) {
+            plaintext.extend(decryptor.push(bytes)?);
+        }
+        plaintext.extend(decryptor.finish()?);
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn round_trip_over_multiple_chunks() {
+        let content: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let (header, encrypted, master) = encrypt(&content, 64);
+        assert_eq!(encrypted.len() as u64, header.ciphertext_size());
+        assert_ne!(&encrypted[..64], &content[..64]);
+        // pieces not aligned with the chunks, like the parts of a download stream
+        assert_eq!(decrypt(&master, &header, &encrypted, 37).unwrap(), content);
+    }
+
+    #[test]
+    fn round_trip_of_empty_and_aligned_content() {
+        for content in [vec![], vec![1u8; 128]] {
+            let (header, encrypted, master) = encrypt(&content, 64);
+            assert_eq!(decrypt(&master, &header, &encrypted, 1000).unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn header_survives_metadata_round_trip() {
+        let (header, _, _) = encrypt(b"hello", 64);
+        let metadata = header.to_metadata();
+        assert!(is_client_encrypted(&metadata));
+        assert_eq!(EnvelopeHeader::from_metadata(&metadata), Some(header));
+        assert_eq!(EnvelopeHeader::from_metadata(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn content_encrypted_in_parts_matches_the_announced_size() {
+        let content: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let master = master_key();
+        let (mut encryptor, header) = ChunkEncryptor::new(&master, content.len() as u64, 64).unwrap();
+        let mut encrypted = encryptor.encrypt(&content[..640]).unwrap();
+        encrypted.extend(encryptor.encrypt(&content[640..]).unwrap());
+        assert_eq!(encrypted.len() as u64, header.ciphertext_size());
+        assert_eq!(decrypt(&master, &header, &encrypted, 100).unwrap(), content);
+
+        // parts must be made of whole chunks and add up to the announced size
+        let (mut encryptor, _) = ChunkEncryptor::new(&master, content.len() as u64, 64).unwrap();
+        assert!(encryptor.encrypt(&content[..100]).is_err());
+        let (mut encryptor, _) = ChunkEncryptor::new(&master, 10, 64).unwrap();
+        assert!(encryptor.encrypt(&content[..20]).is_err());
+    }
+
+    #[test]
+    fn invalid_chunk_sizes_in_metadata_are_rejected() {
+        let (header, _, _) = encrypt(b"hello", 64);
+        let mut metadata = header.to_metadata();
+        for size in ["0", "-1", "1099511627776"] {
+            metadata.insert(META_CHUNK_SIZE.to_string(), size.to_string());
+            assert_eq!(EnvelopeHeader::from_metadata(&metadata), None, "{}", size);
+        }
+        assert!(is_envelope_key(META_WRAPPED_KEY));
+        assert!(!is_envelope_key("owner"));
+    }
+
+    #[test]
+    fn modified_or_truncated_content_is_rejected() {
+        let content = vec![42u8; 300];
+        let (header, mut encrypted, master) = encrypt(&content, 64);
+        assert!(decrypt(&master, &header, &encrypted[..encrypted.len() - 80], 50).is_err());
+        encrypted[10] ^= 1;
+        assert!(decrypt(&master, &header, &encrypted, 50).is_err());
+    }
+
+    #[test]
+    fn wrong_master_key_is_rejected() {
+        let (header, encrypted, _) = encrypt(b"secret", 64);
+        assert!(decrypt(&master_key(), &header, &encrypted, 64).is_err());
+    }
+}
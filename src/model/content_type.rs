@@ -0,0 +1,144 @@
+//! This module provides the headers objects are uploaded with and the detection of their Content-Type
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Headers the uploaded object is written with, the ones set to `None` are left to s3
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UploadHeaders {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_encoding: Option<String>,
+    /// User-defined metadata, sent as `x-amz-meta-*` headers
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Content types by file extension, as (extension, content type)
+const CONTENT_TYPES: &[(&str, &str)] = &[
+    ("html", "text/html; charset=utf-8"),
+    ("htm", "text/html; charset=utf-8"),
+    ("css", "text/css; charset=utf-8"),
+    ("js", "text/javascript; charset=utf-8"),
+    ("mjs", "text/javascript; charset=utf-8"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("webmanifest", "application/manifest+json"),
+    ("xml", "application/xml"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("svg", "image/svg+xml"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("ico", "image/x-icon"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("wasm", "application/wasm"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("bmp", "image/bmp"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+];
+
+/// Guesses the content type from the extension of the file, `None` for unknown extensions
+pub fn guess_content_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    CONTENT_TYPES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, content_type)| *content_type)
+}
+
+/// Content types recognized by the first bytes of the file, as (offset, signature, content type)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (8, b"WEBP", "image/webp"),
+    (8, b"WAVE", "audio/wav"),
+    (4, b"ftypavif", "image/avif"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"BM", "image/bmp"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\0asm", "application/wasm"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"<?xml", "application/xml"),
+];
+
+/// Number of bytes read from the start of files to recognize their content type
+const SNIFF_LENGTH: usize = 64;
+
+/// Recognizes the content type from the first bytes of the content, `None` when no signature matches
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    let signature = SIGNATURES
+        .iter()
+        .find(|(offset, signature, _)| bytes.get(*offset..*offset + signature.len()) == Some(*signature))
+        .map(|(_, _, content_type)| *content_type);
+    signature.or_else(|| {
+        let start = String::from_utf8_lossy(&bytes[..bytes.len().min(SNIFF_LENGTH)]).trim_start().to_lowercase();
+        (start.starts_with("<!doctype html") || start.starts_with("<html")).then_some("text/html; charset=utf-8")
+    })
+}
+
+/// Content type of the file, by its extension or, for unknown extensions, by its first bytes
+pub fn detect_content_type(path: &Path) -> Option<&'static str> {
+    guess_content_type(path).or_else(|| {
+        let mut start = Vec::with_capacity(SNIFF_LENGTH);
+        File::open(path).ok()?.take(SNIFF_LENGTH as u64).read_to_end(&mut start).ok()?;
+        sniff_content_type(&start)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guesses_content_type_by_extension() {
+        assert_eq!(guess_content_type(Path::new("site/index.HTML")), Some("text/html; charset=utf-8"));
+        assert_eq!(guess_content_type(Path::new("assets/app.js")), Some("text/javascript; charset=utf-8"));
+        assert_eq!(guess_content_type(Path::new("logo.svg")), Some("image/svg+xml"));
+        assert_eq!(guess_content_type(Path::new("data.unknown")), None);
+        assert_eq!(guess_content_type(Path::new("Makefile")), None);
+    }
+
+    #[test]
+    fn recognizes_content_type_by_magic_bytes() {
+        assert_eq!(sniff_content_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(sniff_content_type(b"RIFF\x24\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_content_type(b"\0\0\0\x18ftypavif"), Some("image/avif"));
+        assert_eq!(sniff_content_type(b"  <!DOCTYPE html><html>"), Some("text/html; charset=utf-8"));
+        assert_eq!(sniff_content_type(b"just some text"), None);
+        assert_eq!(sniff_content_type(b""), None);
+    }
+
+    #[test]
+    fn files_without_known_extension_are_sniffed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logo");
+        std::fs::write(&path, b"GIF89a\x01\0\x01\0").unwrap();
+        assert_eq!(detect_content_type(&path), Some("image/gif"));
+        // the extension wins over the content
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, b"%PDF-1.7").unwrap();
+        assert_eq!(detect_content_type(&path), Some("text/plain; charset=utf-8"));
+        assert_eq!(detect_content_type(&dir.path().join("missing")), None);
+    }
+}
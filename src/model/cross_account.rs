@@ -0,0 +1,126 @@
+//! This module provides the state of the transfers between buckets of different accounts.
+//! The objects are streamed through the app, the multipart uploads on the destination are
+//! remembered so an interrupted transfer continues after the parts uploaded already.
+use md5::{Digest, Md5};
+
+/// S3 allows at most this many parts in a multipart upload
+const MAX_PARTS: u64 = 10_000;
+
+/// Object copied from a bucket of one account into a bucket of another
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CrossAccountTransfer {
+    /// Id of the saved transfer, 0 until the multipart upload starts
+    pub id: i64,
+    pub source_account_id: i64,
+    pub source_bucket: String,
+    pub source_key: String,
+    pub destination_account_id: i64,
+    pub destination_bucket: String,
+    pub destination_key: String,
+    /// Size of the source object when the transfer started
+    pub size: u64,
+    /// ETag of the source object when the transfer started, the parts uploaded for
+    /// another version of the object aren't reused
+    pub source_e_tag: Option<String>,
+    /// Multipart upload on the destination, `None` until it's created
+    pub upload_id: Option<String>,
+}
+
+impl CrossAccountTransfer {
+    pub fn new(
+        source_account_id: i64,
+        source_bucket: &str,
+        source_key: &str,
+        destination_account_id: i64,
+        destination_bucket: &str,
+        destination_key: &str,
+    ) -> CrossAccountTransfer {
+        CrossAccountTransfer {
+            source_account_id,
+            source_bucket: source_bucket.to_string(),
+            source_key: source_key.to_string(),
+            destination_account_id,
+            destination_bucket: destination_bucket.to_string(),
+            destination_key: destination_key.to_string(),
+            ..CrossAccountTransfer::default()
+        }
+    }
+}
+
+/// Size of the parts the object is uploaded in, `min_part_size` unless the object needs
+/// bigger parts to fit into the maximum number of parts
+pub fn part_size(size: u64, min_part_size: u64) -> u64 {
+    min_part_size.max(size.div_ceil(MAX_PARTS))
+}
+
+/// Number of the uploaded (part number, size) parts a transfer continues after. Only the
+/// parts from the first one on, without gaps and of the full part size, are reused.
+pub fn reusable_parts(parts: &[(i32, u64)], part_size: u64) -> usize {
+    let mut sorted = parts.to_vec();
+    sorted.sort_by_key(|(number, _)| *number);
+    sorted
+        .iter()
+        .enumerate()
+        .take_while(|(index, (number, size))| *number as usize == index + 1 && *size == part_size)
+        .count()
+}
+
+/// Hex encoded digest, the ETag s3 gives the data unless it's encrypted with KMS or a customer key
+pub fn hex_e_tag(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// MD5 digest of the data of an ETag, `None` when the ETag isn't one
+pub fn e_tag_digest(e_tag: &str) -> Option<[u8; 16]> {
+    let hex = e_tag.trim_matches('"');
+    if hex.len() != 32 || !hex.is_ascii() {
+        return None;
+    }
+    let mut digest = [0u8; 16];
+    for (index, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * index..2 * index + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// ETag of an object uploaded in parts with these MD5 digests: the digest of the concatenated
+/// digests followed by the number of parts
+pub fn multipart_e_tag(part_digests: &[[u8; 16]]) -> String {
+    format!("{}-{}", hex_e_tag(&Md5::digest(part_digests.concat())), part_digests.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_objects_use_bigger_parts() {
+        let min = 16 << 20;
+        assert_eq!(part_size(100 << 20, min), min);
+        let size = 5u64 << 40;
+        let part = part_size(size, min);
+        assert!(part > min);
+        assert!(size.div_ceil(part) <= MAX_PARTS);
+    }
+
+    #[test]
+    fn resumes_after_the_contiguous_full_parts() {
+        assert_eq!(reusable_parts(&[], 10), 0);
+        assert_eq!(reusable_parts(&[(2, 10), (1, 10), (3, 10)], 10), 3);
+        // part 3 is missing, 4 has to be uploaded again too
+        assert_eq!(reusable_parts(&[(1, 10), (2, 10), (4, 10)], 10), 2);
+        // a part of another size was uploaded with different settings
+        assert_eq!(reusable_parts(&[(1, 10), (2, 7)], 10), 1);
+    }
+
+    #[test]
+    fn e_tags_are_computed_from_the_digests_of_the_parts() {
+        let first: [u8; 16] = Md5::digest(b"first part").into();
+        assert_eq!(hex_e_tag(&first), "550cf6b6e60f65a0e3104a26e70fea42");
+        assert_eq!(e_tag_digest("\"550cf6b6e60f65a0e3104a26e70fea42\""), Some(first));
+        assert_eq!(e_tag_digest("550cf6b6e60f65a0e3104a26e70fea42-2"), None);
+
+        let second: [u8; 16] = Md5::digest(b"second").into();
+        assert_eq!(multipart_e_tag(&[first, second]), "93f26da1291790e1134809a03511cd15-2");
+    }
+}
@@ -0,0 +1,199 @@
+//! This module provides the server-side encryption settings applied to transfers
+use aws_sdk_s3::types::ServerSideEncryption;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::{Digest, Md5};
+use rand::RngCore;
+
+pub const SSE_CUSTOMER_ALGORITHM: &str = "AES256";
+
+/// Server-side encryption used when writing objects (and, for SSE-C, reading them back).
+/// Stored per account and optionally overridden for a single transfer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum EncryptionSettings {
+    /// Don't send any encryption headers, the bucket's default encryption applies
+    #[default]
+    None,
+    SseS3,
+    /// `key_id` of `None` means the AWS managed `aws/s3` key
+    SseKms { key_id: Option<String> },
+    /// Base64 encoded 256-bit key kept in the local database, never stored by the provider
+    SseC { key: String },
+}
+
+impl EncryptionSettings {
+    /// Restores the settings from the values kept in the database
+    pub fn from_parts(
+        mode: &str,
+        kms_key_id: Option<String>,
+        customer_key: Option<String>,
+    ) -> EncryptionSettings {
+        match mode {
+            "sse-s3" => EncryptionSettings::SseS3,
+            "sse-kms" => EncryptionSettings::SseKms {
+                key_id: kms_key_id.filter(|k| !k.is_empty()),
+            },
+            "sse-c" => EncryptionSettings::SseC {
+                key: customer_key.unwrap_or_default(),
+            },
+            _ => EncryptionSettings::None,
+        }
+    }
+
+    pub fn mode(&self) -> &'static str {
+        match self {
+            EncryptionSettings::None => "none",
+            EncryptionSettings::SseS3 => "sse-s3",
+            EncryptionSettings::SseKms { .. } => "sse-kms",
+            EncryptionSettings::SseC { .. } => "sse-c",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EncryptionSettings::None => "Bucket default",
+            EncryptionSettings::SseS3 => "SSE-S3",
+            EncryptionSettings::SseKms { .. } => "SSE-KMS",
+            EncryptionSettings::SseC { .. } => "SSE-C",
+        }
+    }
+
+    /// Value of the `x-amz-server-side-encryption` header (not used with SSE-C)
+    pub fn server_side_encryption(&self) -> Option<ServerSideEncryption> {
+        match self {
+            EncryptionSettings::SseS3 => Some(ServerSideEncryption::Aes256),
+            EncryptionSettings::SseKms { .. } => Some(ServerSideEncryption::AwsKms),
+            _ => None,
+        }
+    }
+
+    pub fn kms_key_id(&self) -> Option<String> {
+        match self {
+            EncryptionSettings::SseKms { key_id } => key_id.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn sse_customer_algorithm(&self) -> Option<String> {
+        match self {
+            EncryptionSettings::SseC { .. } => Some(SSE_CUSTOMER_ALGORITHM.to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn sse_customer_key(&self) -> Option<String> {
+        match self {
+            EncryptionSettings::SseC { key } => Some(key.clone()),
+            _ => None,
+        }
+    }
+
+    /// Base64 encoded MD5 digest of the raw customer key, required next to the key itself
+    pub fn sse_customer_key_md5(&self) -> Option<String> {
+        match self {
+            EncryptionSettings::SseC { key } => {
+                let raw = STANDARD.decode(key).ok()?;
+                Some(STANDARD.encode(Md5::digest(raw)))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            EncryptionSettings::SseC { key } => match STANDARD.decode(key) {
+                Ok(raw) if raw.len() == 32 => Ok(()),
+                _ => Err("SSE-C key must be a base64 encoded 256-bit key".to_string()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Generates a new random key suitable for SSE-C
+    pub fn generate_customer_key() -> String {
+        let mut raw = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut raw);
+        STANDARD.encode(raw)
+    }
+}
+
+/// Human readable name of the encryption reported by s3 for an object
+pub fn encryption_label(
+    server_side_encryption: Option<&str>,
+    sse_customer_algorithm: Option<&str>,
+) -> String {
+    if sse_customer_algorithm.is_some() {
+        return "SSE-C".to_string();
+    }
+    match server_side_encryption {
+        Some("AES256") => "SSE-S3".to_string(),
+        Some("aws:kms") => "SSE-KMS".to_string(),
+        Some("aws:kms:dsse") => "DSSE-KMS".to_string(),
+        Some(other) => other.to_string(),
+        None => "None".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_settings_from_db_values() {
+        assert_eq!(
+            EncryptionSettings::from_parts("sse-kms", Some("alias/data".into()), None),
+            EncryptionSettings::SseKms {
+                key_id: Some("alias/data".into())
+            }
+        );
+        assert_eq!(
+            EncryptionSettings::from_parts("sse-kms", Some("".into()), None),
+            EncryptionSettings::SseKms { key_id: None }
+        );
+        assert_eq!(
+            EncryptionSettings::from_parts("unknown", None, None),
+            EncryptionSettings::None
+        );
+        let settings = EncryptionSettings::SseC { key: "abc".into() };
+        assert_eq!(
+            EncryptionSettings::from_parts(settings.mode(), None, settings.sse_customer_key()),
+            settings
+        );
+    }
+
+    #[test]
+    fn generated_customer_key_is_valid() {
+        let settings = EncryptionSettings::SseC {
+            key: EncryptionSettings::generate_customer_key(),
+        };
+        assert!(settings.validate().is_ok());
+        assert_eq!(settings.sse_customer_algorithm(), Some("AES256".into()));
+        assert!(settings.sse_customer_key_md5().is_some());
+        assert_eq!(settings.server_side_encryption(), None);
+    }
+
+    #[test]
+    fn customer_key_md5_is_computed_from_raw_key() {
+        // 32 zero bytes
+        let settings = EncryptionSettings::SseC {
+            key: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into(),
+        };
+        assert_eq!(
+            settings.sse_customer_key_md5(),
+            Some("cLyPS3KoaSFGi/joRB3OUQ==".into())
+        );
+    }
+
+    #[test]
+    fn short_customer_key_is_rejected() {
+        let settings = EncryptionSettings::SseC { key: "c2hvcnQ=".into() };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn encryption_label_prefers_customer_key() {
+        assert_eq!(encryption_label(Some("AES256"), Some("AES256")), "SSE-C");
+        assert_eq!(encryption_label(Some("aws:kms"), None), "SSE-KMS");
+        assert_eq!(encryption_label(None, None), "None");
+    }
+}
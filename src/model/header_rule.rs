@@ -0,0 +1,134 @@
+//! This module provides the user-defined rules setting the headers of uploaded objects by key
+use std::collections::BTreeMap;
+use regex::Regex;
+use crate::model::content_type::UploadHeaders;
+use crate::model::search::glob_to_regex;
+
+/// Headers set on the uploads whose key matches the glob pattern, the fields set to `None`
+/// keep the header detected for the file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderRule {
+    pub id: i64,
+    pub account_id: i64,
+    /// Bucket the rule is limited to, it applies to all the buckets of the account when `None`
+    pub bucket: Option<String>,
+    /// Glob matched against the whole key, eg. `*.css` or `assets/*`
+    pub pattern: String,
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_encoding: Option<String>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl HeaderRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.trim().is_empty() {
+            return Err("The pattern is required, use * to match every key".to_string());
+        }
+        let sets_nothing = self.content_type.is_none()
+            && self.cache_control.is_none()
+            && self.content_encoding.is_none()
+            && self.metadata.is_empty();
+        if sets_nothing {
+            return Err("The rule must set at least one header".to_string());
+        }
+        match self.metadata.keys().find(|key| key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')) {
+            Some(key) => Err(format!("Metadata key '{}' can contain only letters, numbers, dashes and underscores", key)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn matches(&self, bucket: &str, key: &str) -> bool {
+        let in_bucket = self.bucket.as_deref().is_none_or(|rule_bucket| rule_bucket == bucket);
+        in_bucket && Regex::new(&glob_to_regex(self.pattern.trim())).is_ok_and(|regex| regex.is_match(key))
+    }
+
+    /// Scope of the rule, as shown in the rule list
+    pub fn scope_label(&self) -> String {
+        match &self.bucket {
+            Some(bucket) => format!("Bucket {}", bucket),
+            None => "All buckets".to_string(),
+        }
+    }
+}
+
+/// Headers of an upload: the detected content type, overridden by the headers of the item,
+/// overridden by the matching rules. The rules of the whole account apply before the ones
+/// of the bucket, so the more specific rule wins.
+pub fn resolve_headers(
+    headers: &UploadHeaders,
+    detected_content_type: Option<&str>,
+    rules: &[HeaderRule],
+    bucket: &str,
+    key: &str,
+) -> UploadHeaders {
+    let mut resolved = headers.clone();
+    if resolved.content_type.is_none() {
+        resolved.content_type = detected_content_type.map(String::from);
+    }
+    let account_rules = rules.iter().filter(|rule| rule.bucket.is_none());
+    let bucket_rules = rules.iter().filter(|rule| rule.bucket.is_some());
+    for rule in account_rules.chain(bucket_rules).filter(|rule| rule.matches(bucket, key)) {
+        if let Some(content_type) = &rule.content_type {
+            resolved.content_type = Some(content_type.clone());
+        }
+        if let Some(cache_control) = &rule.cache_control {
+            resolved.cache_control = Some(cache_control.clone());
+        }
+        if let Some(content_encoding) = &rule.content_encoding {
+            resolved.content_encoding = Some(content_encoding.clone());
+        }
+        resolved.metadata.extend(rule.metadata.clone());
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(bucket: Option<&str>, pattern: &str) -> HeaderRule {
+        HeaderRule { bucket: bucket.map(String::from), pattern: pattern.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn rules_match_keys_of_their_buckets() {
+        assert!(rule(None, "*.css").matches("site", "assets/app.css"));
+        assert!(!rule(None, "*.css").matches("site", "assets/app.css.map"));
+        assert!(rule(Some("site"), "assets/*").matches("site", "assets/logo.png"));
+        assert!(!rule(Some("site"), "assets/*").matches("other", "assets/logo.png"));
+    }
+
+    #[test]
+    fn bucket_rules_override_account_rules_and_detected_type() {
+        let rules = vec![
+            HeaderRule { content_encoding: Some("gzip".into()), ..rule(Some("site"), "*.js.gz") },
+            HeaderRule {
+                cache_control: Some("max-age=60".into()),
+                metadata: BTreeMap::from([("team".to_string(), "web".to_string())]),
+                ..rule(None, "*")
+            },
+            HeaderRule { content_type: Some("text/javascript".into()), cache_control: Some("max-age=31536000".into()), ..rule(Some("site"), "*.js.gz") },
+        ];
+        let headers = resolve_headers(&UploadHeaders::default(), Some("application/gzip"), &rules, "site", "app.js.gz");
+        assert_eq!(headers.content_type.as_deref(), Some("text/javascript"));
+        assert_eq!(headers.cache_control.as_deref(), Some("max-age=31536000"));
+        assert_eq!(headers.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(headers.metadata.get("team").map(String::as_str), Some("web"));
+
+        let headers = resolve_headers(&UploadHeaders::default(), Some("application/gzip"), &rules, "other", "app.js.gz");
+        assert_eq!(headers.content_type.as_deref(), Some("application/gzip"));
+        assert_eq!(headers.cache_control.as_deref(), Some("max-age=60"));
+        assert_eq!(headers.content_encoding, None);
+    }
+
+    #[test]
+    fn rules_need_a_pattern_and_a_header() {
+        assert!(rule(None, "").validate().is_err());
+        assert!(rule(None, "*.css").validate().is_err());
+        let css = HeaderRule { cache_control: Some("no-cache".into()), ..rule(None, "*.css") };
+        assert_eq!(css.validate(), Ok(()));
+        let bad_metadata = HeaderRule { metadata: BTreeMap::from([("my key".to_string(), "x".to_string())]), ..css };
+        assert!(bad_metadata.validate().is_err());
+    }
+}
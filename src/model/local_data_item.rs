@@ -1,5 +1,10 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use humansize::{file_size_opts, FileSize};
+
 /// Represents an item (file/directory) on your local machine
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LocalDataItem {
     pub name: String,
     pub size: String,
@@ -27,6 +32,28 @@ impl LocalDataItem {
     pub fn to_columns(&self) -> Vec<String> {
         vec![self.name.clone(), self.size.clone(), self.file_type.clone()]
     }
+
+    /// Content of the local directory, the subdirectories first and then the files, by name
+    pub fn list_directory(dir: &Path) -> io::Result<Vec<LocalDataItem>> {
+        let mut items = Vec::new();
+        for entry in fs::read_dir(dir)?.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let item = if path.is_dir() {
+                LocalDataItem::init(name, String::new(), "Dir", &path.to_string_lossy(), true)
+            } else {
+                let size = entry
+                    .metadata()
+                    .map(|metadata| metadata.len().file_size(file_size_opts::CONVENTIONAL).unwrap_or_default())
+                    .unwrap_or_default();
+                let extension = path.extension().map(|ext| ext.to_string_lossy().to_string()).unwrap_or_default();
+                LocalDataItem::init(name, size, &extension, &path.to_string_lossy(), false)
+            };
+            items.push(item);
+        }
+        items.sort_by(|a, b| b.is_directory.cmp(&a.is_directory).then_with(|| a.name.cmp(&b.name)));
+        Ok(items)
+    }
 }
 
 #[cfg(test)]
@@ -66,4 +93,17 @@ mod tests {
         assert_eq!(res[1], item.size);
         assert_eq!(res[2], item.file_type);
     }
+
+    #[test]
+    fn lists_directories_before_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"text").unwrap();
+        fs::create_dir(dir.path().join("z")).unwrap();
+        let items = LocalDataItem::list_directory(dir.path()).unwrap();
+        let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, vec!["z", "a.txt"]);
+        assert_eq!(items[0].file_type, "Dir");
+        assert_eq!(items[1].file_type, "txt");
+        assert_eq!(items[1].size, "4 B");
+    }
 }
@@ -1,4 +1,7 @@
+use crate::model::content_type::UploadHeaders;
+use crate::model::encryption::EncryptionSettings;
 use crate::model::local_data_item::LocalDataItem;
+use crate::model::s3_uri::join_key;
 use crate::settings::file_credentials::FileCredential;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -16,6 +19,14 @@ pub struct LocalSelectedItem {
     pub progress: f64,
     pub children: Option<Vec<LocalSelectedItem>>,
     pub error: Option<String>,
+    /// Overrides the account's encryption settings for this transfer
+    pub encryption: Option<EncryptionSettings>,
+    /// Storage class of the uploaded object, the bucket's default if not set
+    pub storage_class: Option<String>,
+    pub headers: UploadHeaders,
+    /// Encrypts the content with the account's client key, when it has one. Off for the content
+    /// which has to be readable by others, like the pages of a website.
+    pub client_side_encryption: bool,
 }
 
 impl LocalSelectedItem {
@@ -39,6 +50,10 @@ impl LocalSelectedItem {
             progress: 0f64,
             children,
             error: None,
+            encryption: None,
+            storage_class: None,
+            headers: UploadHeaders::default(),
+            client_side_encryption: true,
         }
     }
     /*
@@ -74,6 +89,10 @@ impl LocalSelectedItem {
             progress: 0f64,
             children: None,
             error: None,
+            encryption: None,
+            storage_class: None,
+            headers: UploadHeaders::default(),
+            client_side_encryption: true,
         }
     }
 
@@ -101,6 +120,10 @@ impl LocalSelectedItem {
                             progress: 0.0,
                             children: None,
                             error: None,
+                            encryption: item.encryption.clone(),
+                            storage_class: item.storage_class.clone(),
+                            headers: UploadHeaders::default(),
+                            client_side_encryption: item.client_side_encryption,
                         }));
                     } else {
                         // Process files
@@ -118,6 +141,10 @@ impl LocalSelectedItem {
                             progress: 0.0,
                             children: None,
                             error: None,
+                            encryption: item.encryption.clone(),
+                            storage_class: item.storage_class.clone(),
+                            headers: UploadHeaders::default(),
+                            client_side_encryption: item.client_side_encryption,
                         });
                     }
                 }
@@ -129,6 +156,50 @@ impl LocalSelectedItem {
     }
 }
 
+/// Files dropped from the OS file manager, the directories are replaced with the files inside.
+/// Every file is uploaded under `prefix` keeping its path relative to the dropped directory.
+pub fn items_for_upload(paths: &[PathBuf], bucket: &str, prefix: &str) -> Vec<LocalSelectedItem> {
+    let mut items = Vec::new();
+    for dropped in paths {
+        let Some(root) = dropped.parent() else {
+            continue;
+        };
+        let mut pending = vec![dropped.clone()];
+        while let Some(path) = pending.pop() {
+            if path.is_dir() {
+                if let Ok(entries) = fs::read_dir(&path) {
+                    // linked directories are skipped, a link to a parent would be followed forever
+                    pending.extend(
+                        entries
+                            .filter_map(Result::ok)
+                            .filter(|entry| !(entry.file_type().is_ok_and(|kind| kind.is_symlink()) && entry.path().is_dir()))
+                            .map(|entry| entry.path()),
+                    );
+                }
+                continue;
+            }
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            items.push(LocalSelectedItem::new(
+                path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+                path.to_string_lossy().to_string(),
+                false,
+                bucket.to_string(),
+                join_key(prefix, &relative),
+                FileCredential::default(),
+                None,
+            ));
+        }
+    }
+    items.sort_by(|a, b| a.destination_path.cmp(&b.destination_path));
+    items
+}
+
 impl PartialEq for LocalSelectedItem {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -154,6 +225,10 @@ mod tests {
             s3_creds: Default::default(),
             children: None,
             error: None,
+            encryption: None,
+            storage_class: None,
+            headers: UploadHeaders::default(),
+            client_side_encryption: true,
         };
         let res = LocalSelectedItem::new(
             "file1.txt".into(),
@@ -166,4 +241,53 @@ mod tests {
         );
         assert_eq!(item, res);
     }
+
+    #[test]
+    fn dropped_directories_are_uploaded_with_their_structure() {
+        let dir = tempfile::tempdir().unwrap();
+        let photos = dir.path().join("photos");
+        fs::create_dir_all(photos.join("2024")).unwrap();
+        fs::write(photos.join("2024").join("a.jpg"), b"a").unwrap();
+        fs::write(photos.join("b.jpg"), b"b").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+
+        let items = items_for_upload(&[photos, dir.path().join("notes.txt")], "bucket", "backup/");
+        let keys: Vec<(&str, &str)> = items
+            .iter()
+            .map(|item| (item.destination_bucket.as_str(), item.destination_path.as_str()))
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                ("bucket", "backup/notes.txt"),
+                ("bucket", "backup/photos/2024/a.jpg"),
+                ("bucket", "backup/photos/b.jpg"),
+            ]
+        );
+        assert!(items.iter().all(|item| !item.is_directory));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_directories_in_dropped_trees_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let site = dir.path().join("site");
+        fs::create_dir_all(&site).unwrap();
+        fs::write(site.join("index.html"), b"<html></html>").unwrap();
+        std::os::unix::fs::symlink(&site, site.join("loop")).unwrap();
+
+        let items = items_for_upload(&[site], "bucket", "");
+        let keys: Vec<&str> = items.iter().map(|item| item.destination_path.as_str()).collect();
+        assert_eq!(keys, vec!["site/index.html"]);
+    }
+
+    #[test]
+    fn files_dropped_on_a_bucket_go_to_its_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("report.pdf");
+        fs::write(&file, b"pdf").unwrap();
+        let items = items_for_upload(&[file], "bucket", "");
+        assert_eq!(items[0].destination_path, "report.pdf");
+        assert_eq!(items[0].name, "report.pdf");
+    }
 }
@@ -1,13 +1,33 @@
 //! This module provides common objects used throughout the entire application
 
+pub mod analytics;
+pub mod client_encryption;
+pub mod content_type;
+pub mod cross_account;
 pub mod download_progress_item;
+pub mod encryption;
+pub mod header_rule;
 pub mod local_data_item;
 pub mod local_selected_item;
 pub mod navigation_state;
+pub mod object_index;
+pub mod object_lock;
+pub mod object_details;
+pub mod page_cursor;
+pub mod pane_history;
+pub mod parquet_preview;
+pub mod preview;
+pub mod pricing;
 pub mod s3_data_item;
 pub mod s3_selected_item;
+pub mod s3_uri;
+pub mod search;
+pub mod storage_class;
+pub mod text_edit;
 pub mod transfer_item;
+pub mod transfer_options;
 pub mod upload_progress_item;
+pub mod website;
 
 pub mod account;
 pub mod bucket;
@@ -1,6 +1,6 @@
 /// Keeps the information about currently selected bucket and directory (prefix)
 /// to help with navigation on s3
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct NavigationState {
     pub current_bucket: Option<String>,
     pub current_prefix: Option<String>,
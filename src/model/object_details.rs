@@ -0,0 +1,232 @@
+//! This module provides the representation of a single s3 object's properties
+//! (as returned by `head_object` and `get_object_tagging`) and the edits which can be applied to them
+use aws_sdk_s3::primitives::DateTime;
+use std::collections::BTreeMap;
+use crate::model::client_encryption::is_envelope_key;
+
+/// Keeps all the information about a single object displayed in the object detail panel
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectDetails {
+    pub bucket: String,
+    pub key: String,
+    pub size: Option<u64>,
+    pub e_tag: Option<String>,
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_encoding: Option<String>,
+    pub content_disposition: Option<String>,
+    pub storage_class: Option<String>,
+    /// Raw `x-amz-restore` header of archived objects
+    pub restore: Option<String>,
+    pub server_side_encryption: Option<String>,
+    pub sse_kms_key_id: Option<String>,
+    pub sse_customer_algorithm: Option<String>,
+    pub last_modified: Option<DateTime>,
+    pub version_id: Option<String>,
+    /// Retention of the version in buckets with object lock
+    pub retention_mode: Option<String>,
+    pub retain_until: Option<DateTime>,
+    pub legal_hold: bool,
+    pub metadata: BTreeMap<String, String>,
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Changes to the object's metadata, applied by copying the object onto itself.
+/// Fields set to `None` keep the current value of the object, which allows using
+/// the same update for a whole selection of objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectMetadataUpdate {
+    pub content_type: Option<String>,
+    pub cache_control: Option<String>,
+    pub upsert_metadata: BTreeMap<String, String>,
+    pub remove_metadata: Vec<String>,
+    pub storage_class: Option<String>,
+}
+
+/// Changes to the object's tag set, applied with `put_object_tagging`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectTagsUpdate {
+    pub upsert: BTreeMap<String, String>,
+    pub remove: Vec<String>,
+}
+
+impl ObjectDetails {
+    /// Whether a retention or a legal hold keeps the version from being deleted at the given time
+    pub fn is_locked(&self, now: &DateTime) -> bool {
+        self.legal_hold || self.retain_until.is_some_and(|retain_until| retain_until.secs() > now.secs())
+    }
+}
+
+impl ObjectMetadataUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.content_type.is_none()
+            && self.cache_control.is_none()
+            && self.upsert_metadata.is_empty()
+            && self.remove_metadata.is_empty()
+            && self.storage_class.is_none()
+    }
+
+    /// Returns the details with the update applied, without touching anything else.
+    /// The envelope of client-side encrypted objects is kept, they can't be decrypted without it.
+    pub fn apply(&self, details: &ObjectDetails) -> ObjectDetails {
+        let mut updated = details.clone();
+        if let Some(content_type) = &self.content_type {
+            updated.content_type = Some(content_type.clone()).filter(|ct| !ct.is_empty());
+        }
+        if let Some(cache_control) = &self.cache_control {
+            updated.cache_control = Some(cache_control.clone()).filter(|cc| !cc.is_empty());
+        }
+        if let Some(storage_class) = &self.storage_class {
+            updated.storage_class = Some(storage_class.clone());
+        }
+        let upsert: BTreeMap<String, String> = self
+            .upsert_metadata
+            .iter()
+            .filter(|(key, _)| !is_envelope_key(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let remove: Vec<String> = self.remove_metadata.iter().filter(|key| !is_envelope_key(key)).cloned().collect();
+        apply_map_changes(&mut updated.metadata, &upsert, &remove);
+        updated
+    }
+}
+
+impl ObjectTagsUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.upsert.is_empty() && self.remove.is_empty()
+    }
+
+    /// Builds an update which turns the `current` tag set into the `edited` one
+    pub fn from_edit(
+        current: &BTreeMap<String, String>,
+        edited: &BTreeMap<String, String>,
+    ) -> ObjectTagsUpdate {
+        ObjectTagsUpdate {
+            upsert: edited.clone(),
+            remove: current
+                .keys()
+                .filter(|key| !edited.contains_key(*key))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn apply(&self, tags: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let mut updated = tags.clone();
+        apply_map_changes(&mut updated, &self.upsert, &self.remove);
+        updated
+    }
+}
+
+fn apply_map_changes(
+    map: &mut BTreeMap<String, String>,
+    upsert: &BTreeMap<String, String>,
+    remove: &[String],
+) {
+    for key in remove {
+        map.remove(key);
+    }
+    for (key, value) in upsert {
+        map.insert(key.clone(), value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details() -> ObjectDetails {
+        ObjectDetails {
+            bucket: "bucket".into(),
+            key: "dir/file.txt".into(),
+            content_type: Some("text/plain".into()),
+            cache_control: Some("max-age=60".into()),
+            metadata: BTreeMap::from([
+                ("owner".to_string(), "kris".to_string()),
+                ("team".to_string(), "core".to_string()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn legal_hold_and_running_retention_lock_the_version() {
+        let now = DateTime::from_secs(1_700_000_000);
+        assert!(!details().is_locked(&now));
+        assert!(ObjectDetails { legal_hold: true, ..details() }.is_locked(&now));
+        assert!(ObjectDetails { retain_until: Some(DateTime::from_secs(1_800_000_000)), ..details() }.is_locked(&now));
+        assert!(!ObjectDetails { retain_until: Some(DateTime::from_secs(1_600_000_000)), ..details() }.is_locked(&now));
+    }
+
+    #[test]
+    fn empty_metadata_update_keeps_details() {
+        let update = ObjectMetadataUpdate::default();
+        assert!(update.is_empty());
+        assert_eq!(update.apply(&details()), details());
+    }
+
+    #[test]
+    fn metadata_update_changes_only_given_fields() {
+        let update = ObjectMetadataUpdate {
+            content_type: Some("application/json".into()),
+            cache_control: None,
+            upsert_metadata: BTreeMap::from([("team".to_string(), "infra".to_string())]),
+            remove_metadata: vec!["owner".into()],
+            storage_class: None,
+        };
+        let res = update.apply(&details());
+        assert_eq!(res.content_type, Some("application/json".into()));
+        assert_eq!(res.cache_control, Some("max-age=60".into()));
+        assert_eq!(
+            res.metadata,
+            BTreeMap::from([("team".to_string(), "infra".to_string())])
+        );
+    }
+
+    #[test]
+    fn metadata_update_keeps_the_client_side_encryption_envelope() {
+        let mut encrypted = details();
+        encrypted.metadata.insert("dios3-cse-key".to_string(), "wrapped".to_string());
+        let update = ObjectMetadataUpdate {
+            upsert_metadata: BTreeMap::from([("dios3-cse-key".to_string(), "other".to_string())]),
+            remove_metadata: vec!["dios3-cse-key".into(), "owner".into()],
+            ..Default::default()
+        };
+        let res = update.apply(&encrypted);
+        assert_eq!(res.metadata.get("dios3-cse-key").map(String::as_str), Some("wrapped"));
+        assert!(!res.metadata.contains_key("owner"));
+    }
+
+    #[test]
+    fn metadata_update_with_empty_value_clears_header() {
+        let update = ObjectMetadataUpdate {
+            cache_control: Some("".into()),
+            ..Default::default()
+        };
+        assert_eq!(update.apply(&details()).cache_control, None);
+    }
+
+    #[test]
+    fn storage_class_update_keeps_metadata() {
+        let update = ObjectMetadataUpdate {
+            storage_class: Some("GLACIER".into()),
+            ..Default::default()
+        };
+        assert!(!update.is_empty());
+        let res = update.apply(&details());
+        assert_eq!(res.storage_class, Some("GLACIER".into()));
+        assert_eq!(res.metadata, details().metadata);
+    }
+
+    #[test]
+    fn tags_update_from_edit_removes_missing_keys() {
+        let current = BTreeMap::from([
+            ("env".to_string(), "dev".to_string()),
+            ("project".to_string(), "dios3".to_string()),
+        ]);
+        let edited = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        let update = ObjectTagsUpdate::from_edit(&current, &edited);
+        assert_eq!(update.remove, vec!["project".to_string()]);
+        assert_eq!(update.apply(&current), edited);
+    }
+}
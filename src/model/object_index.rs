@@ -0,0 +1,184 @@
+//! This module provides the records of the local index of bucket contents
+use crate::model::s3_data_item::{BucketInfo, FileInfo, S3DataItem};
+use aws_sdk_s3::primitives::DateTime;
+use std::path::Path;
+
+/// Single object kept in the local index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedObject {
+    pub key: String,
+    pub size: u64,
+    /// Seconds since the unix epoch
+    pub last_modified: Option<i64>,
+    pub e_tag: Option<String>,
+    pub storage_class: Option<String>,
+}
+
+impl IndexedObject {
+    pub fn from_s3_data_item(item: &S3DataItem) -> IndexedObject {
+        IndexedObject {
+            key: item.path.clone(),
+            size: item.size.unwrap_or_default(),
+            last_modified: item.last_modified.map(|lm| lm.secs()),
+            e_tag: item.e_tag.clone(),
+            storage_class: item.storage_class.clone(),
+        }
+    }
+
+    /// Builds the listing item of the indexed object, the same as the one coming from s3
+    pub fn to_s3_data_item(&self, bucket: &str) -> S3DataItem {
+        let path = Path::new(&self.key);
+        S3DataItem::init(
+            BucketInfo {
+                bucket: Some(bucket.to_string()),
+                region: None,
+                is_bucket: false,
+            },
+            FileInfo {
+                file_name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                size: Some(self.size),
+                file_type: path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path: self.key.clone(),
+                is_directory: false,
+                last_modified: self.last_modified.map(DateTime::from_secs),
+                e_tag: self.e_tag.clone(),
+                storage_class: self.storage_class.clone(),
+                owner: None,
+            },
+        )
+    }
+}
+
+/// State of the index of a single bucket
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexInfo {
+    pub bucket: String,
+    /// Seconds since the unix epoch, `None` until the first crawl finishes
+    pub last_indexed: Option<i64>,
+    pub object_count: u64,
+    pub total_size: u64,
+}
+
+/// Number and size of the indexed objects in one storage class
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageClassStats {
+    pub storage_class: String,
+    pub object_count: u64,
+    pub total_size: u64,
+}
+
+/// Columns the indexed objects can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexSortColumn {
+    Key,
+    Size,
+    LastModified,
+    StorageClass,
+}
+
+impl IndexSortColumn {
+    pub fn from_name(name: &str) -> IndexSortColumn {
+        match name {
+            "size" => IndexSortColumn::Size,
+            "last_modified" => IndexSortColumn::LastModified,
+            "storage_class" => IndexSortColumn::StorageClass,
+            _ => IndexSortColumn::Key,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexSortColumn::Key => "key",
+            IndexSortColumn::Size => "size",
+            IndexSortColumn::LastModified => "last_modified",
+            IndexSortColumn::StorageClass => "storage_class",
+        }
+    }
+}
+
+/// Filter applied to the indexed objects
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexQuery {
+    /// Part of the key, matched anywhere in it
+    pub contains: String,
+    pub sort: IndexSortColumn,
+    pub ascending: bool,
+    pub limit: usize,
+}
+
+impl IndexQuery {
+    /// `LIKE` pattern matching keys containing the text, with the wildcards in the text escaped
+    pub fn like_pattern(&self) -> String {
+        let escaped = self
+            .contains
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        format!("%{}%", escaped)
+    }
+
+    pub fn order_by(&self) -> String {
+        format!(
+            "{} {}, key",
+            self.sort.name(),
+            if self.ascending { "ASC" } else { "DESC" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_object_converts_to_listing_item() {
+        let object = IndexedObject {
+            key: "photos/2024/cat.jpg".into(),
+            size: 2048,
+            last_modified: Some(1_700_000_000),
+            e_tag: Some("etag".into()),
+            storage_class: Some("STANDARD_IA".into()),
+        };
+        let item = object.to_s3_data_item("bucket");
+        assert_eq!(item.name, "cat.jpg");
+        assert_eq!(item.file_type, "jpg");
+        assert_eq!(item.bucket, Some("bucket".into()));
+        assert_eq!(IndexedObject::from_s3_data_item(&item), object);
+    }
+
+    #[test]
+    fn like_pattern_escapes_wildcards() {
+        let query = IndexQuery {
+            contains: "50%_off".into(),
+            sort: IndexSortColumn::Key,
+            ascending: true,
+            limit: 10,
+        };
+        assert_eq!(query.like_pattern(), "%50\\%\\_off%");
+    }
+
+    #[test]
+    fn sort_column_round_trip() {
+        for column in [
+            IndexSortColumn::Key,
+            IndexSortColumn::Size,
+            IndexSortColumn::LastModified,
+            IndexSortColumn::StorageClass,
+        ] {
+            assert_eq!(IndexSortColumn::from_name(column.name()), column);
+        }
+        let query = IndexQuery {
+            contains: String::new(),
+            sort: IndexSortColumn::Size,
+            ascending: false,
+            limit: 10,
+        };
+        assert_eq!(query.order_by(), "size DESC, key");
+    }
+}
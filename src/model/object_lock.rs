@@ -0,0 +1,119 @@
+//! This module provides the object lock configuration of buckets and the retention of single objects,
+//! which keep object versions from being deleted or overwritten
+use aws_sdk_s3::primitives::{DateTime, DateTimeFormat};
+
+/// Retention modes, as (value, label)
+pub const RETENTION_MODES: &[(&str, &str)] = &[
+    ("GOVERNANCE", "Governance (users with a special permission can bypass it)"),
+    ("COMPLIANCE", "Compliance (nobody can shorten or remove it)"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetentionPeriod {
+    Days(i32),
+    Years(i32),
+}
+
+impl RetentionPeriod {
+    pub fn label(&self) -> String {
+        match self {
+            RetentionPeriod::Days(1) => "1 day".to_string(),
+            RetentionPeriod::Days(days) => format!("{} days", days),
+            RetentionPeriod::Years(1) => "1 year".to_string(),
+            RetentionPeriod::Years(years) => format!("{} years", years),
+        }
+    }
+}
+
+/// Retention applied to the objects written to a bucket without one of their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultRetention {
+    pub mode: String,
+    pub period: RetentionPeriod,
+}
+
+/// Object lock configuration of a bucket, it can only be enabled when the bucket is created
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BucketObjectLock {
+    pub enabled: bool,
+    pub default_retention: Option<DefaultRetention>,
+}
+
+/// Parses the date picked for the retention of an object (`YYYY-MM-DD`), which has to be in the future
+pub fn parse_retain_until(date: &str, now: DateTime) -> Result<DateTime, String> {
+    let retain_until = DateTime::from_str(&format!("{}T00:00:00Z", date.trim()), DateTimeFormat::DateTime)
+        .map_err(|_| format!("'{}' is not a date", date.trim()))?;
+    if retain_until.secs() <= now.secs() {
+        return Err("The retention date must be in the future".to_string());
+    }
+    Ok(retain_until)
+}
+
+/// Date of the retention, as shown in the date picker
+pub fn format_retain_until(retain_until: &DateTime) -> String {
+    retain_until
+        .fmt(DateTimeFormat::DateTime)
+        .map(|formatted| formatted.chars().take(10).collect())
+        .unwrap_or_default()
+}
+
+/// Whether the object version can't be deleted yet, because of a legal hold or a retention
+/// date in the future
+pub fn is_locked(retain_until: Option<&DateTime>, legal_hold: bool, now: DateTime) -> bool {
+    legal_hold || retain_until.is_some_and(|retain_until| retain_until.secs() > now.secs())
+}
+
+/// Explains the errors of deletes refused because of object lock, the message of s3
+/// alone ("Access Denied") doesn't tell retention apart from missing permissions
+pub fn lock_error_message(key: &str, code: Option<&str>, message: Option<&str>) -> Option<String> {
+    let message = message.unwrap_or_default().to_lowercase();
+    let locked = message.contains("object lock")
+        || message.contains("retention")
+        || message.contains("legal hold")
+        || code == Some("ObjectLocked");
+    locked.then(|| locked_message(key))
+}
+
+/// Why the object can't be deleted while it's locked
+pub fn locked_message(key: &str) -> String {
+    format!(
+        "'{}' is protected by object lock, its retention period or legal hold doesn't allow deleting it",
+        key
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retention_dates_are_parsed_and_formatted() {
+        let now = DateTime::from_secs(1_700_000_000); // 2023-11-14
+        let retain_until = parse_retain_until("2030-01-31", now).unwrap();
+        assert_eq!(format_retain_until(&retain_until), "2030-01-31");
+        assert!(parse_retain_until("2020-01-01", now).is_err());
+        assert!(parse_retain_until("31.01.2030", now).is_err());
+    }
+
+    #[test]
+    fn versions_are_locked_by_legal_hold_or_future_retention() {
+        let now = DateTime::from_secs(1_700_000_000);
+        assert!(is_locked(Some(&DateTime::from_secs(1_800_000_000)), false, now));
+        assert!(!is_locked(Some(&DateTime::from_secs(1_600_000_000)), false, now));
+        assert!(is_locked(None, true, now));
+        assert!(!is_locked(None, false, now));
+    }
+
+    #[test]
+    fn explains_deletes_blocked_by_object_lock() {
+        let message = lock_error_message("report.pdf", Some("AccessDenied"), Some("Access Denied because object protected by object lock."));
+        assert!(message.unwrap().contains("report.pdf"));
+        assert_eq!(lock_error_message("report.pdf", Some("AccessDenied"), Some("Access Denied")), None);
+    }
+
+    #[test]
+    fn retention_periods_are_labeled() {
+        assert_eq!(RetentionPeriod::Days(1).label(), "1 day");
+        assert_eq!(RetentionPeriod::Years(7).label(), "7 years");
+    }
+}
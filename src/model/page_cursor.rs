@@ -0,0 +1,111 @@
+//! This module provides the server-side pagination of bucket and object listings.
+//! S3 only allows going forward with continuation tokens, so the tokens of the visited
+//! pages are remembered to be able to go back.
+use crate::model::s3_data_item::S3DataItem;
+
+/// One page of a listing together with the token of the following page
+#[derive(Debug, Clone, Default)]
+pub struct ListingPage {
+    pub items: Vec<S3DataItem>,
+    /// `None` when this is the last page
+    pub next_token: Option<String>,
+}
+
+/// History of the continuation tokens of the visited pages
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PageCursor {
+    /// Token of every page up to the current one, the first page has no token
+    tokens: Vec<Option<String>>,
+    /// Token of the page after the current one, known once the current page is loaded
+    next_token: Option<String>,
+}
+
+impl PageCursor {
+    /// Token to request the current page with
+    pub fn current_token(&self) -> Option<String> {
+        self.tokens.last().cloned().flatten()
+    }
+
+    /// Zero based number of the current page
+    pub fn page(&self) -> usize {
+        self.tokens.len().saturating_sub(1)
+    }
+
+    pub fn has_previous(&self) -> bool {
+        self.tokens.len() > 1
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.next_token.is_some()
+    }
+
+    /// Remembers the token of the following page after the current page was loaded
+    pub fn set_next_token(&mut self, next_token: Option<String>) {
+        self.next_token = next_token;
+    }
+
+    /// Moves to the following page, returns false when there's none
+    pub fn advance(&mut self) -> bool {
+        match self.next_token.take() {
+            Some(token) => {
+                if self.tokens.is_empty() {
+                    self.tokens.push(None);
+                }
+                self.tokens.push(Some(token));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves back to the previous page, returns false on the first page
+    pub fn previous(&mut self) -> bool {
+        if !self.has_previous() {
+            return false;
+        }
+        self.tokens.pop();
+        self.next_token = None;
+        true
+    }
+
+    /// Starts over from the first page, eg. when the page size or the location changes
+    pub fn reset(&mut self) {
+        *self = PageCursor::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_forward_and_back() {
+        let mut cursor = PageCursor::default();
+        assert_eq!(cursor.page(), 0);
+        assert_eq!(cursor.current_token(), None);
+        assert!(!cursor.advance());
+
+        cursor.set_next_token(Some("a".into()));
+        assert!(cursor.advance());
+        cursor.set_next_token(Some("b".into()));
+        assert!(cursor.advance());
+        assert_eq!(cursor.page(), 2);
+        assert_eq!(cursor.current_token(), Some("b".into()));
+
+        assert!(cursor.previous());
+        assert_eq!(cursor.current_token(), Some("a".into()));
+        assert!(!cursor.has_next());
+        assert!(cursor.previous());
+        assert_eq!(cursor.current_token(), None);
+        assert!(!cursor.previous());
+    }
+
+    #[test]
+    fn last_page_has_no_next() {
+        let mut cursor = PageCursor::default();
+        cursor.set_next_token(None);
+        assert!(!cursor.has_next());
+        assert!(!cursor.advance());
+        assert_eq!(cursor.page(), 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! This module provides the browsing history of the panes of the commander view,
+//! each pane goes back and forward through its own locations like a web browser
+/// Visited locations around the current one
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaneHistory<T> {
+    back: Vec<T>,
+    current: T,
+    forward: Vec<T>,
+}
+
+impl<T: Clone + PartialEq> PaneHistory<T> {
+    pub fn new(start: T) -> PaneHistory<T> {
+        PaneHistory { back: Vec::new(), current: start, forward: Vec::new() }
+    }
+
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    /// Goes to the location, the locations visited after the current one are forgotten
+    pub fn navigate(&mut self, location: T) {
+        if location == self.current {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current, location);
+        self.back.push(previous);
+        self.forward.clear();
+    }
+
+    /// Returns false when there's nothing to go back to
+    pub fn back(&mut self) -> bool {
+        match self.back.pop() {
+            Some(location) => {
+                let next = std::mem::replace(&mut self.current, location);
+                self.forward.push(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns false when there's nothing to go forward to
+    pub fn forward(&mut self) -> bool {
+        match self.forward.pop() {
+            Some(location) => {
+                let previous = std::mem::replace(&mut self.current, location);
+                self.back.push(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goes_back_and_forward_through_visited_locations() {
+        let mut history = PaneHistory::new("/");
+        history.navigate("/a");
+        history.navigate("/a/b");
+        assert!(history.back());
+        assert_eq!(*history.current(), "/a");
+        assert!(history.back());
+        assert_eq!(*history.current(), "/");
+        assert!(!history.back());
+        assert!(history.forward());
+        assert_eq!(*history.current(), "/a");
+        assert!(history.can_go_forward());
+    }
+
+    #[test]
+    fn navigating_drops_the_forward_history() {
+        let mut history = PaneHistory::new("/");
+        history.navigate("/a");
+        history.back();
+        history.navigate("/b");
+        assert!(!history.can_go_forward());
+        // the current location isn't added twice
+        history.navigate("/b");
+        history.back();
+        assert_eq!(*history.current(), "/");
+        assert!(!history.can_go_back());
+    }
+}
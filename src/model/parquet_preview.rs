@@ -0,0 +1,212 @@
+//! This module provides the preview of Parquet files. Only the parts of the file which are
+//! needed are fetched: the footer with the schema and, if small enough, the first row group.
+use std::ops::Range;
+use bytes::{Buf, Bytes};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::ParquetMetaDataReader;
+use parquet::file::reader::{ChunkReader, FileReader, Length};
+use parquet::file::serialized_reader::SerializedFileReader;
+use parquet::file::FOOTER_SIZE;
+use parquet::schema::printer::print_schema;
+
+/// Bytes fetched from the end of the file first, enough for the footer of most files
+pub const FOOTER_FETCH_SIZE: u64 = 64 * 1024;
+
+/// The rows of larger first row groups are loaded only when asked for
+pub const ROW_GROUP_PREVIEW_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Parquet file of which only some byte ranges were fetched
+#[derive(Debug, Clone, Default)]
+pub struct SparseFile {
+    len: u64,
+    chunks: Vec<(u64, Bytes)>,
+}
+
+impl SparseFile {
+    pub fn new(len: u64) -> SparseFile {
+        SparseFile { len, chunks: Vec::new() }
+    }
+
+    /// Adds the bytes fetched from the given offset
+    pub fn add(&mut self, start: u64, bytes: Bytes) {
+        self.chunks.push((start, bytes));
+    }
+
+    /// Whether the whole range was fetched already
+    pub fn contains(&self, range: &Range<u64>) -> bool {
+        self.chunk_at(range.start, range.end - range.start).is_some()
+    }
+
+    fn chunk_at(&self, start: u64, length: u64) -> Option<Bytes> {
+        self.chunks.iter().find_map(|(chunk_start, bytes)| {
+            let offset = start.checked_sub(*chunk_start)?;
+            (offset + length <= bytes.len() as u64)
+                .then(|| bytes.slice(offset as usize..(offset + length) as usize))
+        })
+    }
+}
+
+impl Length for SparseFile {
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl ChunkReader for SparseFile {
+    type T = bytes::buf::Reader<Bytes>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        // the reader gets whatever was fetched from the offset on
+        self.chunks
+            .iter()
+            .filter_map(|(chunk_start, bytes)| {
+                let offset = start.checked_sub(*chunk_start)?;
+                (offset < bytes.len() as u64).then(|| bytes.slice(offset as usize..))
+            })
+            .max_by_key(Bytes::len)
+            .map(Buf::reader)
+            .ok_or_else(|| ParquetError::EOF(format!("Bytes at {} were not fetched", start)))
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        self.chunk_at(start, length as u64)
+            .ok_or_else(|| ParquetError::EOF(format!("Bytes {}..{} were not fetched", start, start + length as u64)))
+    }
+}
+
+/// Range from the end of the file which holds the metadata, known from the last 8 bytes
+pub fn footer_range(file: &SparseFile) -> Result<Range<u64>, String> {
+    if file.len < FOOTER_SIZE as u64 {
+        return Err("File is too small to be a Parquet file".to_string());
+    }
+    let tail = file
+        .get_bytes(file.len - FOOTER_SIZE as u64, FOOTER_SIZE)
+        .map_err(|e| e.to_string())?;
+    let tail: [u8; FOOTER_SIZE] = tail.as_ref().try_into().map_err(|_| "Invalid footer".to_string())?;
+    let metadata_len = ParquetMetaDataReader::decode_footer_tail(&tail)
+        .map_err(|e| format!("Not a Parquet file: {}", e))?
+        .metadata_length() as u64;
+    let footer_len = metadata_len + FOOTER_SIZE as u64;
+    if footer_len > file.len {
+        return Err("Parquet metadata is longer than the file".to_string());
+    }
+    Ok(file.len - footer_len..file.len)
+}
+
+/// Schema, size and the first rows of a Parquet file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetPreview {
+    pub schema: String,
+    pub num_rows: i64,
+    pub num_row_groups: usize,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Bytes of the first row group, still to be fetched when the rows aren't loaded
+    pub first_row_group: Option<Range<u64>>,
+}
+
+/// Reads the preview from the fetched parts, the footer has to be fetched already.
+/// The rows are read only when the first row group is fetched too.
+pub fn read_preview(file: SparseFile, max_rows: usize) -> Result<ParquetPreview, String> {
+    let reader = SerializedFileReader::new(file.clone()).map_err(|e| format!("Cannot read Parquet metadata: {}", e))?;
+    let metadata = reader.metadata();
+    let mut schema = Vec::new();
+    print_schema(&mut schema, metadata.file_metadata().schema());
+    let first_row_group = (metadata.num_row_groups() > 0).then(|| {
+        let ranges = metadata.row_group(0).columns().iter().map(|column| {
+            let (start, length) = column.byte_range();
+            start..start + length
+        });
+        let start = ranges.clone().map(|r| r.start).min().unwrap_or(0);
+        let end = ranges.map(|r| r.end).max().unwrap_or(0);
+        start..end
+    });
+    let columns = metadata
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        .get_fields()
+        .iter()
+        .map(|field| field.name().to_string())
+        .collect();
+
+    let mut rows = Vec::new();
+    if first_row_group.as_ref().is_some_and(|range| file.contains(range)) {
+        let row_group = reader.get_row_group(0).map_err(|e| e.to_string())?;
+        let row_iter = row_group.get_row_iter(None).map_err(|e| e.to_string())?;
+        for row in row_iter.take(max_rows) {
+            let row = row.map_err(|e| format!("Cannot read Parquet rows: {}", e))?;
+            rows.push(row.get_column_iter().map(|(_, field)| field.to_string()).collect());
+        }
+    }
+    Ok(ParquetPreview {
+        schema: String::from_utf8_lossy(&schema).to_string(),
+        num_rows: metadata.file_metadata().num_rows(),
+        num_row_groups: metadata.num_row_groups(),
+        columns,
+        rows,
+        first_row_group,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+
+    fn sample_file() -> Vec<u8> {
+        let schema = Arc::new(
+            parse_message_type("message sample { required int64 id; required binary name (UTF8); }").unwrap(),
+        );
+        let mut buffer = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut buffer, schema, Arc::new(WriterProperties::builder().build())).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        column.typed::<Int64Type>().write_batch(&[1, 2, 3], None, None).unwrap();
+        column.close().unwrap();
+        let mut column = row_group.next_column().unwrap().unwrap();
+        let names: Vec<ByteArray> = ["a", "b", "c"].into_iter().map(ByteArray::from).collect();
+        column.typed::<ByteArrayType>().write_batch(&names, None, None).unwrap();
+        column.close().unwrap();
+        row_group.close().unwrap();
+        writer.close().unwrap();
+        buffer
+    }
+
+    #[test]
+    fn schema_from_footer_only() {
+        let content = Bytes::from(sample_file());
+        let len = content.len() as u64;
+        let mut file = SparseFile::new(len);
+        file.add(len - 8, content.slice(len as usize - 8..));
+        let footer = footer_range(&file).unwrap();
+        file.add(footer.start, content.slice(footer.start as usize..));
+
+        let preview = read_preview(file, 10).unwrap();
+        assert!(preview.schema.contains("INT64 id"));
+        assert_eq!(preview.num_rows, 3);
+        assert_eq!(preview.columns, vec!["id", "name"]);
+        assert!(preview.rows.is_empty());
+        assert!(preview.first_row_group.is_some());
+    }
+
+    #[test]
+    fn rows_once_row_group_is_fetched() {
+        let content = Bytes::from(sample_file());
+        let mut file = SparseFile::new(content.len() as u64);
+        file.add(0, content.clone());
+        let preview = read_preview(file, 2).unwrap();
+        assert_eq!(preview.rows, vec![vec!["1", "\"a\""], vec!["2", "\"b\""]]);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let mut file = SparseFile::new(16);
+        file.add(0, Bytes::from_static(b"not a parquet!!!"));
+        assert!(footer_range(&file).is_err());
+    }
+}
@@ -0,0 +1,491 @@
+//! This module provides what the preview pane needs to render an object from its first bytes:
+//! the kind of the preview, syntax highlighting of text formats and parsing of delimited tables
+
+/// Bytes fetched at once for text previews, "load more" fetches the next chunk
+pub const PREVIEW_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// Larger images aren't fetched until asked for, they can't be shown partially
+pub const IMAGE_PREVIEW_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// Rows shown of delimited tables and Parquet files
+pub const PREVIEW_ROWS: usize = 100;
+
+/// How the object is rendered in the preview pane
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewKind {
+    /// Image with the mime type used for the data url
+    Image(String),
+    Text,
+    Json,
+    Yaml,
+    /// Delimited table, the delimiter is known for `.tsv` and detected otherwise
+    Table(Option<char>),
+    Parquet,
+    Unsupported,
+}
+
+impl PreviewKind {
+    /// Detects the kind by the key extension, the content type is used when the extension is unknown
+    pub fn detect(key: &str, content_type: Option<&str>) -> PreviewKind {
+        let extension = key
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "png" => return PreviewKind::Image("image/png".to_string()),
+            "jpg" | "jpeg" => return PreviewKind::Image("image/jpeg".to_string()),
+            "gif" => return PreviewKind::Image("image/gif".to_string()),
+            "webp" => return PreviewKind::Image("image/webp".to_string()),
+            "svg" => return PreviewKind::Image("image/svg+xml".to_string()),
+            "bmp" => return PreviewKind::Image("image/bmp".to_string()),
+            "json" | "jsonl" | "ndjson" | "geojson" => return PreviewKind::Json,
+            "yaml" | "yml" => return PreviewKind::Yaml,
+            "csv" => return PreviewKind::Table(Some(',')),
+            "tsv" | "tab" => return PreviewKind::Table(Some('\t')),
+            "parquet" => return PreviewKind::Parquet,
+            "txt" | "log" | "md" | "xml" | "html" | "htm" | "css" | "js" | "ts" | "rs" | "py" | "sh"
+            | "toml" | "ini" | "conf" | "cfg" | "sql" | "java" | "go" | "c" | "h" | "cpp" => {
+                return PreviewKind::Text
+            }
+            _ => {}
+        }
+        let content_type = content_type
+            .unwrap_or_default()
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        match content_type.as_str() {
+            "application/json" => PreviewKind::Json,
+            "application/yaml" | "application/x-yaml" | "text/yaml" => PreviewKind::Yaml,
+            "text/csv" => PreviewKind::Table(Some(',')),
+            "text/tab-separated-values" => PreviewKind::Table(Some('\t')),
+            "application/vnd.apache.parquet" => PreviewKind::Parquet,
+            ct if ct.starts_with("image/") => PreviewKind::Image(ct.to_string()),
+            ct if ct.starts_with("text/") => PreviewKind::Text,
+            _ => PreviewKind::Unsupported,
+        }
+    }
+}
+
+/// Decodes the fetched bytes as text. When only the beginning of the object was fetched,
+/// a character cut in half at the end is left out.
+pub fn decode_text(bytes: &[u8], complete: bool) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(e) if !complete && e.error_len().is_none() => {
+            String::from_utf8_lossy(&bytes[..e.valid_up_to()]).to_string()
+        }
+        Err(_) => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+/// Class of a highlighted piece of text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Key,
+    String,
+    Number,
+    Literal,
+    Comment,
+    Punctuation,
+    Plain,
+}
+
+impl TokenKind {
+    /// Tailwind classes the token is rendered with
+    pub fn class(&self) -> &'static str {
+        match self {
+            TokenKind::Key => "text-purple-700 dark:text-purple-300",
+            TokenKind::String => "text-green-700 dark:text-green-400",
+            TokenKind::Number => "text-blue-700 dark:text-blue-400",
+            TokenKind::Literal => "text-orange-600 dark:text-orange-400",
+            TokenKind::Comment => "text-gray-500 italic",
+            TokenKind::Punctuation => "text-gray-500",
+            TokenKind::Plain => "",
+        }
+    }
+}
+
+/// Splits the text into highlighted tokens, text other than JSON and YAML is a single plain token
+pub fn highlight(kind: &PreviewKind, text: &str) -> Vec<(TokenKind, String)> {
+    match kind {
+        PreviewKind::Json => highlight_json(text),
+        PreviewKind::Yaml => highlight_yaml(text),
+        _ => vec![(TokenKind::Plain, text.to_string())],
+    }
+}
+
+fn push_token(tokens: &mut Vec<(TokenKind, String)>, kind: TokenKind, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match tokens.last_mut() {
+        Some((last, content)) if *last == kind => content.push_str(text),
+        _ => tokens.push((kind, text.to_string())),
+    }
+}
+
+fn highlight_json(text: &str) -> Vec<(TokenKind, String)> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = match c {
+            '"' => {
+                let len = string_len(rest);
+                // a string followed by a colon is an object key
+                let is_key = rest[len..].trim_start().starts_with(':');
+                (if is_key { TokenKind::Key } else { TokenKind::String }, len)
+            }
+            '{' | '}' | '[' | ']' | ',' | ':' => (TokenKind::Punctuation, 1),
+            '-' | '0'..='9' => (
+                TokenKind::Number,
+                rest.find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                    .unwrap_or(rest.len()),
+            ),
+            _ if rest.starts_with("true") => (TokenKind::Literal, 4),
+            _ if rest.starts_with("false") => (TokenKind::Literal, 5),
+            _ if rest.starts_with("null") => (TokenKind::Literal, 4),
+            _ => (TokenKind::Plain, c.len_utf8()),
+        };
+        push_token(&mut tokens, kind, &rest[..len]);
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Length of the quoted string at the start of the text, including the quotes.
+/// An unterminated string (eg. cut off by the preview) lasts until the end of the line.
+fn string_len(text: &str) -> usize {
+    let quote = text.chars().next().unwrap_or('"');
+    let mut escaped = false;
+    for (idx, c) in text.char_indices().skip(1) {
+        match c {
+            '\\' if quote == '"' => escaped = !escaped,
+            '\n' => return idx,
+            c if c == quote && !escaped => return idx + 1,
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
+
+fn highlight_yaml(text: &str) -> Vec<(TokenKind, String)> {
+    let mut tokens = Vec::new();
+    for line in text.split_inclusive('\n') {
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        let indent_len = content.len() - content.trim_start().len();
+        push_token(&mut tokens, TokenKind::Plain, &content[..indent_len]);
+        let mut rest = &content[indent_len..];
+        if rest.starts_with('#') {
+            push_token(&mut tokens, TokenKind::Comment, rest);
+            push_token(&mut tokens, TokenKind::Plain, newline);
+            continue;
+        }
+        while let Some(item) = rest.strip_prefix("- ") {
+            push_token(&mut tokens, TokenKind::Punctuation, "- ");
+            rest = item;
+        }
+        if let Some(colon) = yaml_key_end(rest) {
+            push_token(&mut tokens, TokenKind::Key, &rest[..colon]);
+            push_token(&mut tokens, TokenKind::Punctuation, ":");
+            rest = &rest[colon + 1..];
+        }
+        let (value, comment) = split_yaml_comment(rest);
+        let trimmed = value.trim();
+        let kind = if trimmed.starts_with('"') || trimmed.starts_with('\'') {
+            TokenKind::String
+        } else if trimmed.parse::<f64>().is_ok() {
+            TokenKind::Number
+        } else if matches!(trimmed, "true" | "false" | "null" | "~" | "yes" | "no") {
+            TokenKind::Literal
+        } else if matches!(trimmed, "---" | "..." | "|" | ">" | "[]" | "{}") {
+            TokenKind::Punctuation
+        } else {
+            TokenKind::Plain
+        };
+        let leading = value.len() - value.trim_start().len();
+        push_token(&mut tokens, TokenKind::Plain, &value[..leading]);
+        push_token(&mut tokens, kind, &value[leading..]);
+        push_token(&mut tokens, TokenKind::Comment, comment);
+        push_token(&mut tokens, TokenKind::Plain, newline);
+    }
+    tokens
+}
+
+/// Position of the colon ending the mapping key on the line, if the line has a key
+fn yaml_key_end(line: &str) -> Option<usize> {
+    if line.starts_with('"') || line.starts_with('\'') {
+        let len = string_len(line);
+        return line[len..].starts_with(':').then_some(len);
+    }
+    line.char_indices()
+        .take_while(|(_, c)| *c != '#' && *c != '"' && *c != '\'')
+        .find(|(idx, c)| *c == ':' && line[idx + 1..].chars().next().is_none_or(|next| next == ' '))
+        .map(|(idx, _)| idx)
+}
+
+/// Splits the value from the trailing comment, a `#` starts a comment only after a space
+fn split_yaml_comment(value: &str) -> (&str, &str) {
+    let trimmed = value.trim_start();
+    if trimmed.starts_with('"') || trimmed.starts_with('\'') {
+        let start = value.len() - trimmed.len();
+        let end = start + string_len(trimmed);
+        return match value[end..].find(" #") {
+            Some(idx) => value.split_at(end + idx),
+            None => (value, ""),
+        };
+    }
+    if trimmed.starts_with('#') {
+        return value.split_at(value.len() - trimmed.len());
+    }
+    match value.find(" #") {
+        Some(idx) => value.split_at(idx),
+        None => (value, ""),
+    }
+}
+
+/// Type detected for a table column from its values
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    Decimal,
+    Boolean,
+    Date,
+    Text,
+    /// No values in the previewed rows
+    Empty,
+}
+
+impl ColumnType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColumnType::Integer => "integer",
+            ColumnType::Decimal => "decimal",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+            ColumnType::Text => "text",
+            ColumnType::Empty => "empty",
+        }
+    }
+
+    fn of_value(value: &str) -> ColumnType {
+        let value = value.trim();
+        if value.is_empty() {
+            ColumnType::Empty
+        } else if value.parse::<i64>().is_ok() {
+            ColumnType::Integer
+        } else if value.parse::<f64>().is_ok() {
+            ColumnType::Decimal
+        } else if matches!(value.to_lowercase().as_str(), "true" | "false") {
+            ColumnType::Boolean
+        } else if is_date(value) {
+            ColumnType::Date
+        } else {
+            ColumnType::Text
+        }
+    }
+
+    /// Type of a column holding values of both types
+    fn merge(self, other: ColumnType) -> ColumnType {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (ColumnType::Empty, other) | (other, ColumnType::Empty) => other,
+            (ColumnType::Integer, ColumnType::Decimal) | (ColumnType::Decimal, ColumnType::Integer) => {
+                ColumnType::Decimal
+            }
+            _ => ColumnType::Text,
+        }
+    }
+}
+
+/// Dates like `2024-01-31`, optionally followed by the time
+fn is_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
+        && (bytes.len() == 10 || matches!(bytes[10], b'T' | b' '))
+}
+
+/// First rows of a delimited file with the detected layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct TablePreview {
+    pub delimiter: char,
+    /// Taken from the first row when it looks like a header, numbered otherwise
+    pub headers: Vec<String>,
+    pub column_types: Vec<ColumnType>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Picks the delimiter which splits the first lines into the same, highest number of columns
+pub fn detect_delimiter(sample: &str) -> char {
+    let lines: Vec<&str> = sample.lines().filter(|l| !l.trim().is_empty()).take(10).collect();
+    [',', '\t', ';', '|']
+        .into_iter()
+        .filter_map(|delimiter| {
+            let counts: Vec<usize> = lines.iter().map(|line| line.matches(delimiter).count()).collect();
+            let first = *counts.first()?;
+            (first > 0 && counts.iter().all(|count| *count == first)).then_some((delimiter, first))
+        })
+        .max_by_key(|(_, count)| *count)
+        .map(|(delimiter, _)| delimiter)
+        .unwrap_or(',')
+}
+
+/// Splits the text into records, quoted fields may contain delimiters, quotes (doubled) and newlines
+fn parse_records(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            c if c == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Parses the beginning of a delimited file. When only a part of the object was fetched
+/// the last record may be cut off, so it's left out.
+pub fn parse_table(text: &str, delimiter: Option<char>, complete: bool, max_rows: usize) -> TablePreview {
+    let delimiter = delimiter.unwrap_or_else(|| detect_delimiter(text));
+    let mut records = parse_records(text, delimiter);
+    if !complete && !text.ends_with('\n') {
+        records.pop();
+    }
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    let width = records.iter().map(Vec::len).max().unwrap_or(0);
+    let types_of = |rows: &[Vec<String>]| -> Vec<ColumnType> {
+        (0..width)
+            .map(|col| {
+                rows.iter()
+                    .map(|row| ColumnType::of_value(row.get(col).map(String::as_str).unwrap_or_default()))
+                    .fold(ColumnType::Empty, ColumnType::merge)
+            })
+            .collect()
+    };
+
+    // the first row is a header when it has no numbers, dates or booleans
+    let has_header = records.first().is_some_and(|first| {
+        let first_types = types_of(std::slice::from_ref(first));
+        first_types.contains(&ColumnType::Text)
+            && first_types.iter().all(|t| matches!(t, ColumnType::Text | ColumnType::Empty))
+    });
+    let headers = if has_header {
+        let mut headers = records.remove(0);
+        headers.resize_with(width, String::new);
+        headers
+    } else {
+        (1..=width).map(|col| format!("column {}", col)).collect()
+    };
+    records.truncate(max_rows);
+    for record in records.iter_mut() {
+        record.resize_with(width, String::new);
+    }
+    TablePreview {
+        delimiter,
+        headers,
+        column_types: types_of(&records),
+        rows: records,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_kind_by_extension_then_content_type() {
+        assert_eq!(PreviewKind::detect("a/b.PNG", None), PreviewKind::Image("image/png".into()));
+        assert_eq!(PreviewKind::detect("data.tsv", None), PreviewKind::Table(Some('\t')));
+        assert_eq!(PreviewKind::detect("x.parquet", None), PreviewKind::Parquet);
+        assert_eq!(PreviewKind::detect("noext", Some("application/json; charset=utf-8")), PreviewKind::Json);
+        assert_eq!(PreviewKind::detect("noext", Some("text/plain")), PreviewKind::Text);
+        assert_eq!(PreviewKind::detect("archive.zip", Some("application/zip")), PreviewKind::Unsupported);
+    }
+
+    #[test]
+    fn partial_text_drops_cut_character() {
+        let bytes = "zał".as_bytes();
+        assert_eq!(decode_text(&bytes[..3], false), "za");
+        assert_eq!(decode_text(bytes, true), "zał");
+    }
+
+    #[test]
+    fn json_keys_differ_from_values() {
+        let tokens = highlight(&PreviewKind::Json, r#"{"a": "b", "n": -1.5, "t": true}"#);
+        assert!(tokens.contains(&(TokenKind::Key, "\"a\"".into())));
+        assert!(tokens.contains(&(TokenKind::String, "\"b\"".into())));
+        assert!(tokens.contains(&(TokenKind::Number, "-1.5".into())));
+        assert!(tokens.contains(&(TokenKind::Literal, "true".into())));
+        let text: String = tokens.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(text, r#"{"a": "b", "n": -1.5, "t": true}"#);
+    }
+
+    #[test]
+    fn yaml_keys_values_and_comments() {
+        let source = "# config\nname: app # the name\nitems:\n  - port: 8080\n  - url: http://x\n";
+        let tokens = highlight(&PreviewKind::Yaml, source);
+        assert!(tokens.contains(&(TokenKind::Comment, "# config".into())));
+        assert!(tokens.contains(&(TokenKind::Key, "name".into())));
+        assert!(tokens.contains(&(TokenKind::Comment, " # the name".into())));
+        assert!(tokens.contains(&(TokenKind::Number, "8080".into())));
+        assert!(tokens.contains(&(TokenKind::Key, "url".into())));
+        let text: String = tokens.iter().map(|(_, t)| t.as_str()).collect();
+        assert_eq!(text, source);
+    }
+
+    #[test]
+    fn detects_delimiter() {
+        assert_eq!(detect_delimiter("a;b;c\n1;2;3\n"), ';');
+        assert_eq!(detect_delimiter("a\tb\n1\t2\n"), '\t');
+        assert_eq!(detect_delimiter("single\n"), ',');
+    }
+
+    #[test]
+    fn parses_quoted_fields_header_and_types() {
+        let text = "id,name,price,date\n1,\"Smith, \"\"J\"\"\",2.5,2024-01-31\n2,\"multi\nline\",3,2024-02-01\n3,cut";
+        let table = parse_table(text, None, false, 10);
+        assert_eq!(table.headers, vec!["id", "name", "price", "date"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0][1], "Smith, \"J\"");
+        assert_eq!(table.rows[1][1], "multi\nline");
+        assert_eq!(
+            table.column_types,
+            vec![ColumnType::Integer, ColumnType::Text, ColumnType::Decimal, ColumnType::Date]
+        );
+    }
+
+    #[test]
+    fn numeric_first_row_is_not_a_header() {
+        let table = parse_table("1,2\n3,4\n", Some(','), true, 10);
+        assert_eq!(table.headers, vec!["column 1", "column 2"]);
+        assert_eq!(table.rows.len(), 2);
+    }
+}
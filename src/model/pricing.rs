@@ -0,0 +1,225 @@
+//! This module provides the estimated monthly storage costs of the buckets, computed from
+//! their analytics and a local price table, and the projections of lifecycle transitions
+use serde::{Deserialize, Serialize};
+use crate::model::analytics::BucketAnalytics;
+
+/// Region of the prices used when the bucket's region has none of its own
+pub const ANY_REGION: &str = "*";
+
+/// Storage is billed per GB-month, where a GB is 2^30 bytes
+const GB: f64 = (1u64 << 30) as f64;
+
+/// Price of storing a GB for a month in the storage class, in USD
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoragePrice {
+    pub region: String,
+    pub storage_class: String,
+    pub per_gb_month: f64,
+}
+
+impl StoragePrice {
+    fn new(region: &str, storage_class: &str, per_gb_month: f64) -> StoragePrice {
+        StoragePrice { region: region.to_string(), storage_class: storage_class.to_string(), per_gb_month }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceTable {
+    pub prices: Vec<StoragePrice>,
+}
+
+impl Default for PriceTable {
+    /// The AWS list prices of us-east-1 for every region, they're edited to match the others
+    fn default() -> PriceTable {
+        PriceTable {
+            prices: vec![
+                StoragePrice::new(ANY_REGION, "STANDARD", 0.023),
+                StoragePrice::new(ANY_REGION, "INTELLIGENT_TIERING", 0.023),
+                StoragePrice::new(ANY_REGION, "STANDARD_IA", 0.0125),
+                StoragePrice::new(ANY_REGION, "ONEZONE_IA", 0.01),
+                StoragePrice::new(ANY_REGION, "GLACIER_IR", 0.004),
+                StoragePrice::new(ANY_REGION, "GLACIER", 0.0036),
+                StoragePrice::new(ANY_REGION, "DEEP_ARCHIVE", 0.00099),
+            ],
+        }
+    }
+}
+
+impl PriceTable {
+    /// Price of the class in the region, falling back to the prices for any region.
+    /// Unknown classes are priced as STANDARD.
+    pub fn price(&self, region: Option<&str>, storage_class: &str) -> f64 {
+        let find = |region: &str, storage_class: &str| {
+            self.prices
+                .iter()
+                .find(|price| price.region == region && price.storage_class == storage_class)
+                .map(|price| price.per_gb_month)
+        };
+        region
+            .and_then(|region| find(region, storage_class))
+            .or_else(|| find(ANY_REGION, storage_class))
+            .or_else(|| region.and_then(|region| find(region, "STANDARD")))
+            .or_else(|| find(ANY_REGION, "STANDARD"))
+            .unwrap_or_default()
+    }
+
+    pub fn monthly_cost(&self, region: Option<&str>, storage_class: &str, size: u64) -> f64 {
+        size as f64 / GB * self.price(region, storage_class)
+    }
+}
+
+/// Costs are shown with cents, in USD
+pub fn format_cost(cost: f64) -> String {
+    format!("$ {:.2}", cost)
+}
+
+/// Monthly cost of a group of objects, in USD
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostBreakdown {
+    pub label: String,
+    pub monthly_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub monthly_cost: f64,
+    /// The most expensive first
+    pub by_storage_class: Vec<CostBreakdown>,
+    pub by_prefix: Vec<CostBreakdown>,
+}
+
+fn add_cost(groups: &mut Vec<CostBreakdown>, label: &str, cost: f64) {
+    match groups.iter_mut().find(|group| group.label == label) {
+        Some(group) => group.monthly_cost += cost,
+        None => groups.push(CostBreakdown { label: label.to_string(), monthly_cost: cost }),
+    }
+}
+
+fn most_expensive_first(mut groups: Vec<CostBreakdown>) -> Vec<CostBreakdown> {
+    groups.sort_by(|a, b| b.monthly_cost.total_cmp(&a.monthly_cost).then_with(|| a.label.cmp(&b.label)));
+    groups
+}
+
+/// Estimated monthly storage cost of the bucket. Requests, transfers and the minimum
+/// object sizes and storage durations of the infrequent access classes aren't included.
+pub fn estimate_cost(analytics: &BucketAnalytics, prices: &PriceTable) -> CostEstimate {
+    let region = analytics.region.as_deref();
+    let mut by_storage_class = Vec::new();
+    let mut by_prefix = Vec::new();
+    for slice in &analytics.slices {
+        let cost = prices.monthly_cost(region, &slice.storage_class, slice.total_size);
+        add_cost(&mut by_storage_class, &slice.storage_class, cost);
+        add_cost(&mut by_prefix, &slice.prefix, cost);
+    }
+    // the parts of incomplete uploads are billed as STANDARD
+    let incomplete = prices.monthly_cost(region, "STANDARD", analytics.incomplete_upload_size());
+    if incomplete > 0.0 {
+        add_cost(&mut by_storage_class, "Incomplete uploads", incomplete);
+    }
+    CostEstimate {
+        monthly_cost: by_storage_class.iter().map(|group| group.monthly_cost).sum(),
+        by_storage_class: most_expensive_first(by_storage_class),
+        by_prefix: most_expensive_first(by_prefix),
+    }
+}
+
+/// Lifecycle transition of the objects older than `min_age_days` to the storage class.
+/// Like S3 lifecycle rules, only the objects in a more expensive class are moved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub min_age_days: i64,
+    pub storage_class: String,
+}
+
+/// Monthly cost of the bucket after the transition and the bytes which would be moved,
+/// without the one-time cost of the transition requests.
+/// The ages are only known by the bins of the histogram, so `min_age_days` is one of
+/// their starts for an exact projection.
+pub fn project_transition(analytics: &BucketAnalytics, prices: &PriceTable, transition: &Transition) -> (f64, u64) {
+    let region = analytics.region.as_deref();
+    let target_price = prices.price(region, &transition.storage_class);
+    let mut moved = 0;
+    let mut cost = prices.monthly_cost(region, "STANDARD", analytics.incomplete_upload_size());
+    for slice in &analytics.slices {
+        let moves = slice.min_age_days >= transition.min_age_days
+            && prices.price(region, &slice.storage_class) > target_price;
+        let storage_class = if moves {
+            moved += slice.total_size;
+            transition.storage_class.as_str()
+        } else {
+            slice.storage_class.as_str()
+        };
+        cost += prices.monthly_cost(region, storage_class, slice.total_size);
+    }
+    (cost, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::analytics::StorageSlice;
+
+    fn slice(prefix: &str, storage_class: &str, min_age_days: i64, gb: u64) -> StorageSlice {
+        StorageSlice {
+            prefix: prefix.to_string(),
+            storage_class: storage_class.to_string(),
+            min_age_days,
+            object_count: 1,
+            total_size: gb << 30,
+        }
+    }
+
+    fn analytics(slices: Vec<StorageSlice>) -> BucketAnalytics {
+        BucketAnalytics {
+            region: Some("eu-west-1".to_string()),
+            total_size: slices.iter().map(|s| s.total_size).sum(),
+            slices,
+            ..BucketAnalytics::default()
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn regional_prices_override_the_default_ones() {
+        let mut prices = PriceTable::default();
+        prices.prices.push(StoragePrice::new("eu-west-1", "STANDARD", 0.024));
+        assert_close(prices.price(Some("eu-west-1"), "STANDARD"), 0.024);
+        assert_close(prices.price(Some("us-east-2"), "STANDARD"), 0.023);
+        assert_close(prices.price(Some("eu-west-1"), "GLACIER"), 0.0036);
+        // unknown classes are priced like STANDARD in the region
+        assert_close(prices.price(Some("eu-west-1"), "EXPRESS_ONEZONE"), 0.024);
+        assert_close(prices.price(None, "EXPRESS_ONEZONE"), 0.023);
+    }
+
+    #[test]
+    fn estimates_cost_by_class_and_prefix() {
+        let estimate = estimate_cost(
+            &analytics(vec![
+                slice("logs/", "STANDARD", 0, 100),
+                slice("logs/", "GLACIER", 365, 1000),
+                slice("/", "STANDARD", 0, 10),
+            ]),
+            &PriceTable::default(),
+        );
+        assert_close(estimate.monthly_cost, 110.0 * 0.023 + 1000.0 * 0.0036);
+        assert_eq!(estimate.by_storage_class[0].label, "GLACIER");
+        assert_close(estimate.by_prefix[0].monthly_cost, 100.0 * 0.023 + 1000.0 * 0.0036);
+        assert_eq!(estimate.by_prefix[1].label, "/");
+    }
+
+    #[test]
+    fn transitions_move_only_old_objects_to_cheaper_classes() {
+        let bucket = analytics(vec![
+            slice("a/", "STANDARD", 0, 100),
+            slice("a/", "STANDARD", 90, 200),
+            slice("a/", "DEEP_ARCHIVE", 180, 300),
+        ]);
+        let transition = Transition { min_age_days: 90, storage_class: "GLACIER_IR".to_string() };
+        let (cost, moved) = project_transition(&bucket, &PriceTable::default(), &transition);
+        assert_eq!(moved, 200 << 30);
+        assert_close(cost, 100.0 * 0.023 + 200.0 * 0.004 + 300.0 * 0.00099);
+    }
+}
@@ -1,13 +1,20 @@
 //! This module provides functionality for representing s3 data
+use aws_sdk_s3::primitives::DateTime;
+use std::cmp::Ordering;
 
 /// Represents a file in s3 bucket
 #[derive(Debug, Clone)]
 pub struct FileInfo {
     pub file_name: String,
-    pub size: String,
+    /// Size in bytes, not known for directories and buckets
+    pub size: Option<u64>,
     pub file_type: String,
     pub path: String,
     pub is_directory: bool,
+    pub last_modified: Option<DateTime>,
+    pub e_tag: Option<String>,
+    pub storage_class: Option<String>,
+    pub owner: Option<String>,
 }
 /// Represents a bucket on s3
 #[derive(Debug, Clone)]
@@ -21,12 +28,26 @@ pub struct BucketInfo {
 pub struct S3DataItem {
     pub bucket: Option<String>,
     pub name: String,
-    pub size: String,
+    pub size: Option<u64>,
     pub file_type: String,
     pub path: String,
     pub is_directory: bool,
     pub is_bucket: bool,
     pub region: Option<String>,
+    pub last_modified: Option<DateTime>,
+    pub e_tag: Option<String>,
+    pub storage_class: Option<String>,
+    pub owner: Option<String>,
+}
+
+/// Columns the listings can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortColumn {
+    Name,
+    Size,
+    Type,
+    LastModified,
+    StorageClass,
 }
 
 impl S3DataItem {
@@ -40,13 +61,40 @@ impl S3DataItem {
             is_directory: file_info.is_directory,
             is_bucket: bucket_info.is_bucket,
             region: bucket_info.region,
+            last_modified: file_info.last_modified,
+            e_tag: file_info.e_tag,
+            storage_class: file_info.storage_class,
+            owner: file_info.owner,
         }
     }
-    pub fn to_columns(&self) -> Vec<String> {
-        vec![self.name.clone(), self.size.clone(), self.file_type.clone()]
+
+    /// Compares the items by the given column, directories always go before files
+    pub fn compare(&self, other: &S3DataItem, column: SortColumn) -> Ordering {
+        other.is_directory.cmp(&self.is_directory).then_with(|| {
+            let ordering = match column {
+                SortColumn::Name => self.name.to_lowercase().cmp(&other.name.to_lowercase()),
+                SortColumn::Size => self.size.cmp(&other.size),
+                SortColumn::Type => self.file_type.cmp(&other.file_type),
+                SortColumn::LastModified => self.last_modified.cmp(&other.last_modified),
+                SortColumn::StorageClass => self.storage_class.cmp(&other.storage_class),
+            };
+            ordering.then_with(|| self.name.cmp(&other.name))
+        })
     }
 }
 
+/// Sorts the listing by the column, keeping directories on top in both directions
+pub fn sort_items(items: &mut [S3DataItem], column: SortColumn, ascending: bool) {
+    items.sort_by(|a, b| {
+        let ordering = a.compare(b, column);
+        if ascending || a.is_directory != b.is_directory {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,10 +108,14 @@ mod tests {
         };
         let file_info = FileInfo {
             file_name: "file_name.txt".into(),
-            size: "23 MB".into(),
+            size: Some(23_000_000),
             file_type: "txt".into(),
             path: "/some/path".into(),
             is_directory: false,
+            last_modified: Some(DateTime::from_secs(1_700_000_000)),
+            e_tag: Some("\"abc\"".into()),
+            storage_class: Some("STANDARD".into()),
+            owner: Some("owner".into()),
         };
         let s3_data_item = S3DataItem::init(bucket_info.clone(), file_info.clone());
         assert_eq!(s3_data_item.bucket, bucket_info.bucket);
@@ -73,27 +125,55 @@ mod tests {
         assert_eq!(s3_data_item.file_type, file_info.file_type);
         assert_eq!(s3_data_item.path, file_info.path);
         assert_eq!(s3_data_item.is_directory, file_info.is_directory);
+        assert_eq!(s3_data_item.size, file_info.size);
+        assert_eq!(s3_data_item.last_modified, file_info.last_modified);
+        assert_eq!(s3_data_item.e_tag, file_info.e_tag);
+        assert_eq!(s3_data_item.storage_class, file_info.storage_class);
+        assert_eq!(s3_data_item.owner, file_info.owner);
+    }
+
+    fn item(name: &str, size: Option<u64>, is_directory: bool) -> S3DataItem {
+        S3DataItem::init(
+            BucketInfo {
+                bucket: Some("bucket".to_string()),
+                region: None,
+                is_bucket: false,
+            },
+            FileInfo {
+                file_name: name.into(),
+                size,
+                file_type: String::new(),
+                path: name.into(),
+                is_directory,
+                last_modified: None,
+                e_tag: None,
+                storage_class: None,
+                owner: None,
+            },
+        )
+    }
+
+    fn names(items: &[S3DataItem]) -> Vec<&str> {
+        items.iter().map(|i| i.name.as_str()).collect()
     }
 
     #[test]
-    fn to_columns_get_correct_vector() {
-        let bucket_info = BucketInfo {
-            bucket: Some("bucket".to_string()),
-            region: Some("region".to_string()),
-            is_bucket: true,
-        };
-        let file_info = FileInfo {
-            file_name: "file_name.txt".into(),
-            size: "23 MB".into(),
-            file_type: "txt".into(),
-            path: "/some/path".into(),
-            is_directory: false,
-        };
-        let s3_data_item = S3DataItem::init(bucket_info.clone(), file_info.clone());
-        let res = s3_data_item.to_columns();
-        assert_eq!(res.len(), 3);
-        assert_eq!(res[0], file_info.file_name);
-        assert_eq!(res[1], file_info.size);
-        assert_eq!(res[2], file_info.file_type);
+    fn sort_by_size_keeps_directories_first() {
+        let mut items = vec![
+            item("big.bin", Some(300), false),
+            item("docs/", None, true),
+            item("small.txt", Some(10), false),
+        ];
+        sort_items(&mut items, SortColumn::Size, true);
+        assert_eq!(names(&items), vec!["docs/", "small.txt", "big.bin"]);
+        sort_items(&mut items, SortColumn::Size, false);
+        assert_eq!(names(&items), vec!["docs/", "big.bin", "small.txt"]);
+    }
+
+    #[test]
+    fn sort_by_name_ignores_case() {
+        let mut items = vec![item("b.txt", None, false), item("A.txt", None, false)];
+        sort_items(&mut items, SortColumn::Name, true);
+        assert_eq!(names(&items), vec!["A.txt", "b.txt"]);
     }
 }
@@ -1,3 +1,4 @@
+use crate::model::encryption::EncryptionSettings;
 use crate::model::s3_data_item::S3DataItem;
 use crate::settings::file_credentials::FileCredential;
 
@@ -15,6 +16,8 @@ pub struct S3SelectedItem {
     pub progress: f64,
     pub children: Option<Vec<S3SelectedItem>>,
     pub error: Option<String>,
+    /// Overrides the account's encryption settings for this transfer
+    pub encryption: Option<EncryptionSettings>,
 }
 
 impl S3SelectedItem {
@@ -35,6 +38,7 @@ impl S3SelectedItem {
             progress: 0f64,
             children: None,
             error: None,
+            encryption: None,
         }
     }
     pub fn from_s3_data_item_with_children(
@@ -55,6 +59,7 @@ impl S3SelectedItem {
             progress: 0f64,
             children: Some(children),
             error: None,
+            encryption: None,
         }
     }
 }
@@ -87,16 +92,21 @@ mod tests {
             progress: 0f64,
             children: None,
             error: None,
+            encryption: None,
         };
         let s3_data_item = S3DataItem {
             bucket: Some("test-bucket".into()),
             name: "file1.txt".into(),
-            size: "100kB".into(),
+            size: Some(100_000),
             file_type: "txt".into(),
             path: "path/to/file1.txt".into(),
             is_directory: false,
             is_bucket: false,
             region: Some("eu-west-1".into()),
+            last_modified: None,
+            e_tag: None,
+            storage_class: None,
+            owner: None,
         };
         let creds = FileCredential {
             name: "personal".into(),
@@ -124,6 +134,7 @@ mod tests {
             progress: 0f64,
             children: None,
             error: None,
+            encryption: None,
         };
         let item = S3SelectedItem {
             bucket: Some("test-bucket".into()),
@@ -137,16 +148,21 @@ mod tests {
             progress: 0f64,
             children: Some(vec![child.clone()]),
             error: None,
+            encryption: None,
         };
         let s3_data_item = S3DataItem {
             bucket: Some("test-bucket".into()),
             name: "file1.txt".into(),
-            size: "100kB".into(),
+            size: Some(100_000),
             file_type: "txt".into(),
             path: "path/to/file1.txt".into(),
             is_directory: false,
             is_bucket: false,
             region: Some("eu-west-1".into()),
+            last_modified: None,
+            e_tag: None,
+            storage_class: None,
+            owner: None,
         };
         let creds = FileCredential {
             name: "personal".into(),
@@ -0,0 +1,216 @@
+//! This module provides the locations the command line tool works with, `s3://bucket/key`
+//! URIs or local paths, and the comparison deciding what `sync` has to transfer
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+const S3_SCHEME: &str = "s3://";
+
+/// Source or destination of a command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    /// Object or prefix in a bucket, the key is empty for the whole bucket
+    S3 { bucket: String, key: String },
+    Local(PathBuf),
+}
+
+impl Location {
+    /// Anything starting with `s3://` is a bucket location, everything else a local path
+    pub fn parse(value: &str) -> Result<Location, String> {
+        match value.strip_prefix(S3_SCHEME) {
+            Some(rest) => {
+                let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+                if bucket.is_empty() {
+                    return Err(format!("No bucket in '{}'", value));
+                }
+                Ok(Location::S3 { bucket: bucket.to_string(), key: key.to_string() })
+            }
+            None if value.is_empty() => Err("Empty path".to_string()),
+            None => Ok(Location::Local(PathBuf::from(value))),
+        }
+    }
+
+    /// Whether the location names a "directory", a bucket root or a key ending with `/`
+    pub fn is_prefix(&self) -> bool {
+        match self {
+            Location::S3 { key, .. } => key.is_empty() || key.ends_with('/'),
+            Location::Local(path) => path.is_dir(),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::S3 { bucket, key } => write!(f, "{}{}/{}", S3_SCHEME, bucket, key),
+            Location::Local(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Key of `relative` below `prefix`, s3 keys are separated with `/` whatever the OS
+pub fn join_key(prefix: &str, relative: &str) -> String {
+    let relative = relative.replace('\\', "/");
+    let relative = relative.trim_start_matches('/');
+    if prefix.is_empty() {
+        relative.to_string()
+    } else if prefix.ends_with('/') {
+        format!("{}{}", prefix, relative)
+    } else {
+        format!("{}/{}", prefix, relative)
+    }
+}
+
+/// Part of the key below the prefix, the whole key when it isn't below it
+pub fn relative_key<'a>(prefix: &str, key: &'a str) -> &'a str {
+    key.strip_prefix(prefix).map(|rest| rest.trim_start_matches('/')).unwrap_or(key)
+}
+
+/// Path of `relative`, a key below a prefix, inside the local `dir`. Keys come from the bucket,
+/// the ones with `..` or drive components would be written outside of `dir` and are refused.
+pub fn local_path(dir: &Path, relative: &str) -> Result<PathBuf, String> {
+    let mut path = dir.to_path_buf();
+    for segment in relative.split('/').filter(|segment| !segment.is_empty()) {
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => path.push(name),
+            _ => return Err(format!("Key '{}' would be written outside of {}", relative, dir.display())),
+        }
+    }
+    Ok(path)
+}
+
+/// File or object compared by `sync`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncEntry {
+    /// Path relative to the synchronized directory or prefix, separated with `/`
+    pub path: String,
+    pub size: u64,
+    /// Seconds since the epoch
+    pub modified: i64,
+    /// Size of the content of a client-side encrypted object, `size` is the one of its ciphertext
+    pub plaintext_size: Option<u64>,
+}
+
+impl SyncEntry {
+    /// Size of the file, or of the content the object downloads into
+    fn content_size(&self) -> u64 {
+        self.plaintext_size.unwrap_or(self.size)
+    }
+}
+
+/// Which way `sync` copies, between a local directory and a prefix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncDirection {
+    Upload,
+    Download,
+}
+
+/// What `sync` does, paths relative to the synchronized directories
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPlan {
+    pub transfer: Vec<String>,
+    pub delete: Vec<String>,
+}
+
+/// Entries missing in the destination, of a different size or newer in the source are
+/// transferred. With `delete` the entries missing in the source are removed.
+/// Files are compared with the content size of the objects, which differs from their listed
+/// size when they're client-side encrypted.
+pub fn plan_sync(source: &[SyncEntry], destination: &[SyncEntry], delete: bool, direction: SyncDirection) -> SyncPlan {
+    let existing: HashMap<&str, &SyncEntry> = destination.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    let transfer = source
+        .iter()
+        .filter(|entry| {
+            existing.get(entry.path.as_str()).is_none_or(|existing| {
+                let (file, object) = match direction {
+                    SyncDirection::Upload => (*entry, *existing),
+                    SyncDirection::Download => (*existing, *entry),
+                };
+                file.size != object.content_size() || entry.modified > existing.modified
+            })
+        })
+        .map(|entry| entry.path.clone())
+        .collect();
+    let delete = if delete {
+        let present: HashSet<&str> = source.iter().map(|entry| entry.path.as_str()).collect();
+        destination
+            .iter()
+            .filter(|existing| !present.contains(existing.path.as_str()))
+            .map(|existing| existing.path.clone())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    SyncPlan { transfer, delete }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_s3_and_local_locations() {
+        assert_eq!(
+            Location::parse("s3://bucket/dir/file.txt"),
+            Ok(Location::S3 { bucket: "bucket".to_string(), key: "dir/file.txt".to_string() })
+        );
+        assert_eq!(
+            Location::parse("s3://bucket"),
+            Ok(Location::S3 { bucket: "bucket".to_string(), key: String::new() })
+        );
+        assert_eq!(Location::parse("./file.txt"), Ok(Location::Local(PathBuf::from("./file.txt"))));
+        assert!(Location::parse("s3://").is_err());
+        assert!(Location::parse("s3:///key").is_err());
+    }
+
+    #[test]
+    fn joins_and_strips_prefixes() {
+        assert_eq!(join_key("", "a.txt"), "a.txt");
+        assert_eq!(join_key("dir", "sub\\a.txt"), "dir/sub/a.txt");
+        assert_eq!(join_key("dir/", "/a.txt"), "dir/a.txt");
+        assert_eq!(relative_key("dir/", "dir/sub/a.txt"), "sub/a.txt");
+        assert_eq!(relative_key("dir", "dir/a.txt"), "a.txt");
+        assert_eq!(relative_key("other/", "dir/a.txt"), "dir/a.txt");
+    }
+
+    #[test]
+    fn keys_stay_inside_the_local_directory() {
+        let dir = Path::new("downloads");
+        assert_eq!(local_path(dir, "sub/a.txt"), Ok(dir.join("sub").join("a.txt")));
+        assert_eq!(local_path(dir, "/sub//a.txt"), Ok(dir.join("sub").join("a.txt")));
+        assert!(local_path(dir, "a/../../.ssh/authorized_keys").is_err());
+        assert!(local_path(dir, "..").is_err());
+        assert!(local_path(dir, "./a.txt").is_err());
+    }
+
+    #[test]
+    fn sync_transfers_new_and_changed_entries() {
+        let entry = |path: &str, size, modified| SyncEntry { path: path.to_string(), size, modified, plaintext_size: None };
+        let source = vec![entry("same", 1, 10), entry("newer", 1, 20), entry("resized", 2, 10), entry("new", 1, 10)];
+        let destination = vec![entry("same", 1, 10), entry("newer", 1, 10), entry("resized", 1, 30), entry("stale", 1, 10)];
+
+        let plan = plan_sync(&source, &destination, false, SyncDirection::Upload);
+        assert_eq!(plan.transfer, vec!["newer", "resized", "new"]);
+        assert!(plan.delete.is_empty());
+        assert_eq!(plan_sync(&source, &destination, true, SyncDirection::Upload).delete, vec!["stale"]);
+    }
+
+    #[test]
+    fn sync_compares_files_with_the_content_size_of_encrypted_objects() {
+        let file = |size, modified| SyncEntry { path: "a".to_string(), size, modified, plaintext_size: None };
+        let encrypted = |plaintext_size, modified| SyncEntry {
+            path: "a".to_string(),
+            size: plaintext_size + 16,
+            modified,
+            plaintext_size: Some(plaintext_size),
+        };
+        assert!(plan_sync(&[file(100, 10)], &[encrypted(100, 20)], false, SyncDirection::Upload).transfer.is_empty());
+        assert!(plan_sync(&[encrypted(100, 20)], &[file(100, 30)], false, SyncDirection::Download).transfer.is_empty());
+        // a changed file of the size of the ciphertext is still different
+        assert_eq!(plan_sync(&[file(116, 10)], &[encrypted(100, 20)], false, SyncDirection::Upload).transfer, vec!["a"]);
+        assert_eq!(plan_sync(&[encrypted(100, 20)], &[file(116, 30)], false, SyncDirection::Download).transfer, vec!["a"]);
+        // plain objects are compared with their own size
+        assert_eq!(plan_sync(&[file(100, 10)], &[file(116, 20)], false, SyncDirection::Upload).transfer, vec!["a"]);
+    }
+}
@@ -0,0 +1,108 @@
+//! This module provides the patterns used by the global search to match bucket names and object keys
+use regex::Regex;
+
+/// What the search query is matched against the keys with.
+/// Plain text is a prefix, text with `*` or `?` is a glob and text wrapped in slashes is a regex.
+#[derive(Debug, Clone)]
+pub enum SearchPattern {
+    Prefix(String),
+    Glob { prefix: String, regex: Regex },
+    Regex(Regex),
+}
+
+impl SearchPattern {
+    pub fn parse(query: &str) -> Result<SearchPattern, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+        if query.len() > 2 && query.starts_with('/') && query.ends_with('/') {
+            return Regex::new(&query[1..query.len() - 1])
+                .map(SearchPattern::Regex)
+                .map_err(|e| format!("Invalid regular expression: {}", e));
+        }
+        if query.contains(['*', '?']) {
+            let prefix = query
+                .split(['*', '?'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            return Regex::new(&glob_to_regex(query))
+                .map(|regex| SearchPattern::Glob { prefix, regex })
+                .map_err(|e| format!("Invalid pattern: {}", e));
+        }
+        Ok(SearchPattern::Prefix(query.to_string()))
+    }
+
+    /// Prefix passed to `list_objects_v2` so only the keys which can match are listed
+    pub fn list_prefix(&self) -> Option<String> {
+        match self {
+            SearchPattern::Prefix(prefix) | SearchPattern::Glob { prefix, .. } => {
+                Some(prefix.clone()).filter(|p| !p.is_empty())
+            }
+            SearchPattern::Regex(_) => None,
+        }
+    }
+
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            SearchPattern::Prefix(prefix) => key.starts_with(prefix.as_str()),
+            SearchPattern::Glob { regex, .. } | SearchPattern::Regex(regex) => regex.is_match(key),
+        }
+    }
+
+    /// Bucket names are matched more loosely than keys, a plain query matches anywhere in the name
+    pub fn matches_bucket(&self, name: &str) -> bool {
+        match self {
+            SearchPattern::Prefix(text) => name.contains(text.as_str()),
+            _ => self.matches(name),
+        }
+    }
+}
+
+/// `*` matches any run of characters (including `/`), `?` matches a single character
+pub(crate) fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_query_is_prefix() {
+        let pattern = SearchPattern::parse("logs/2024").unwrap();
+        assert_eq!(pattern.list_prefix(), Some("logs/2024".into()));
+        assert!(pattern.matches("logs/2024/01/app.log"));
+        assert!(!pattern.matches("backup/logs/2024"));
+        assert!(pattern.matches_bucket("my-logs/2024-bucket"));
+    }
+
+    #[test]
+    fn glob_query_lists_by_literal_prefix() {
+        let pattern = SearchPattern::parse("logs/*.gz").unwrap();
+        assert_eq!(pattern.list_prefix(), Some("logs/".into()));
+        assert!(pattern.matches("logs/2024/app.gz"));
+        assert!(!pattern.matches("logs/app.gz.tmp"));
+        assert!(SearchPattern::parse("file?.txt").unwrap().matches("file1.txt"));
+    }
+
+    #[test]
+    fn slashes_make_a_regex() {
+        let pattern = SearchPattern::parse(r"/\.(jpe?g|png)$/").unwrap();
+        assert_eq!(pattern.list_prefix(), None);
+        assert!(pattern.matches("photos/cat.jpeg"));
+        assert!(!pattern.matches("photos/cat.gif"));
+        assert!(SearchPattern::parse("/[/").is_err());
+        assert!(SearchPattern::parse("  ").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! This module provides the storage classes which can be chosen for objects and
+//! the restore workflow of the archived (Glacier) ones
+
+/// Storage classes offered on upload and when changing the class of an object, as (value, label)
+pub const STORAGE_CLASSES: &[(&str, &str)] = &[
+    ("STANDARD", "Standard"),
+    ("INTELLIGENT_TIERING", "Intelligent-Tiering"),
+    ("STANDARD_IA", "Standard-IA"),
+    ("ONEZONE_IA", "One Zone-IA"),
+    ("GLACIER_IR", "Glacier Instant Retrieval"),
+    ("GLACIER", "Glacier Flexible Retrieval"),
+    ("DEEP_ARCHIVE", "Glacier Deep Archive"),
+];
+
+/// Retrieval tiers of the restore request, as (value, label)
+pub const RESTORE_TIERS: &[(&str, &str)] = &[
+    ("Expedited", "Expedited (1-5 minutes)"),
+    ("Standard", "Standard (3-5 hours)"),
+    ("Bulk", "Bulk (5-12 hours)"),
+];
+
+/// Objects in these classes can't be read until they are restored
+pub fn requires_restore(storage_class: Option<&str>) -> bool {
+    matches!(storage_class, Some("GLACIER") | Some("DEEP_ARCHIVE"))
+}
+
+/// Retrieval tiers available for the storage class, Deep Archive has no expedited retrieval
+pub fn restore_tiers(storage_class: Option<&str>) -> Vec<(&'static str, &'static str)> {
+    RESTORE_TIERS
+        .iter()
+        .filter(|(tier, _)| storage_class != Some("DEEP_ARCHIVE") || *tier != "Expedited")
+        .cloned()
+        .collect()
+}
+
+/// State of the temporary copy of an archived object
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestoreStatus {
+    NotRestored,
+    InProgress,
+    /// The temporary copy can be downloaded until the expiry date
+    Restored { expiry: Option<String> },
+}
+
+impl RestoreStatus {
+    /// Parses the `x-amz-restore` header returned by `head_object`, eg.
+    /// `ongoing-request="false", expiry-date="Fri, 21 Dec 2012 00:00:00 GMT"`
+    pub fn from_header(restore: Option<&str>) -> RestoreStatus {
+        let Some(restore) = restore else {
+            return RestoreStatus::NotRestored;
+        };
+        if restore.contains("ongoing-request=\"true\"") {
+            return RestoreStatus::InProgress;
+        }
+        let expiry = restore
+            .split_once("expiry-date=\"")
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .map(|(date, _)| date.to_string());
+        RestoreStatus::Restored { expiry }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            RestoreStatus::NotRestored => "Archived".to_string(),
+            RestoreStatus::InProgress => "Restoring".to_string(),
+            RestoreStatus::Restored { expiry: Some(expiry) } => format!("Restored until {}", expiry),
+            RestoreStatus::Restored { expiry: None } => "Restored".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_restore_header() {
+        assert_eq!(RestoreStatus::from_header(None), RestoreStatus::NotRestored);
+        assert_eq!(
+            RestoreStatus::from_header(Some("ongoing-request=\"true\"")),
+            RestoreStatus::InProgress
+        );
+        assert_eq!(
+            RestoreStatus::from_header(Some(
+                "ongoing-request=\"false\", expiry-date=\"Fri, 21 Dec 2012 00:00:00 GMT\""
+            )),
+            RestoreStatus::Restored {
+                expiry: Some("Fri, 21 Dec 2012 00:00:00 GMT".into())
+            }
+        );
+    }
+
+    #[test]
+    fn only_archive_classes_require_restore() {
+        assert!(requires_restore(Some("GLACIER")));
+        assert!(requires_restore(Some("DEEP_ARCHIVE")));
+        assert!(!requires_restore(Some("GLACIER_IR")));
+        assert!(!requires_restore(None));
+    }
+
+    #[test]
+    fn deep_archive_has_no_expedited_tier() {
+        assert_eq!(restore_tiers(Some("GLACIER")).len(), 3);
+        assert!(restore_tiers(Some("DEEP_ARCHIVE"))
+            .iter()
+            .all(|(tier, _)| *tier != "Expedited"));
+    }
+}
@@ -0,0 +1,93 @@
+//! This module provides the in-place editing of small text objects: the result of a save
+//! guarded by the ETag and the line diff shown when the object changed meanwhile
+
+/// Larger objects aren't opened in the editor, they'd better be downloaded
+pub const EDITOR_SIZE_LIMIT: u64 = 1024 * 1024;
+
+/// Result of saving an edited object
+#[derive(Debug, Clone, PartialEq)]
+pub enum SaveOutcome {
+    /// Written, the new ETag guards the next save
+    Saved { e_tag: Option<String> },
+    /// Somebody changed the object since it was opened, nothing was written
+    Conflict { remote_content: String, remote_e_tag: Option<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Same,
+    /// Only in the remote version
+    Removed,
+    /// Only in the edited version
+    Added,
+}
+
+impl DiffKind {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            DiffKind::Same => "  ",
+            DiffKind::Removed => "- ",
+            DiffKind::Added => "+ ",
+        }
+    }
+}
+
+/// Line diff of the remote and the edited text, based on the longest common subsequence
+pub fn diff_lines(remote: &str, edited: &str) -> Vec<(DiffKind, String)> {
+    let old: Vec<&str> = remote.lines().collect();
+    let new: Vec<&str> = edited.lines().collect();
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push((DiffKind::Same, old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push((DiffKind::Removed, old[i].to_string()));
+            i += 1;
+        } else {
+            diff.push((DiffKind::Added, new[j].to_string()));
+            j += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_of_changed_line() {
+        let diff = diff_lines("a\nb\nc\n", "a\nB\nc\nd\n");
+        assert_eq!(
+            diff,
+            vec![
+                (DiffKind::Same, "a".to_string()),
+                (DiffKind::Removed, "b".to_string()),
+                (DiffKind::Added, "B".to_string()),
+                (DiffKind::Same, "c".to_string()),
+                (DiffKind::Added, "d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_of_equal_and_empty_texts() {
+        assert!(diff_lines("x\ny", "x\ny").iter().all(|(kind, _)| *kind == DiffKind::Same));
+        assert_eq!(diff_lines("", "new"), vec![(DiffKind::Added, "new".to_string())]);
+        assert_eq!(diff_lines("old", ""), vec![(DiffKind::Removed, "old".to_string())]);
+    }
+}
@@ -0,0 +1,121 @@
+//! This module provides the options chosen for a whole batch of transfers, like the files
+//! dropped on a bucket at once or a `dios3-cli cp`, which are applied to every item of it
+use crate::model::encryption::EncryptionSettings;
+use crate::model::local_selected_item::LocalSelectedItem;
+use crate::model::storage_class::STORAGE_CLASSES;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferOptions {
+    /// Storage class of the written objects, the bucket's default if not set
+    pub storage_class: Option<String>,
+    /// Overrides the account's encryption settings, the SSE-C key is used to read objects too
+    pub encryption: Option<EncryptionSettings>,
+}
+
+impl TransferOptions {
+    /// Options given on the command line, the storage class is one of `STORAGE_CLASSES`.
+    /// `sse` is `AES256` or `aws:kms` like in the aws cli, a KMS key id implies `aws:kms`
+    /// and a customer key (SSE-C) can't be combined with either.
+    pub fn from_flags(
+        storage_class: Option<&str>,
+        sse: Option<&str>,
+        sse_kms_key_id: Option<&str>,
+        sse_c_key: Option<&str>,
+    ) -> Result<TransferOptions, String> {
+        let storage_class = storage_class
+            .map(|class| {
+                let class = class.trim().to_uppercase();
+                if STORAGE_CLASSES.iter().any(|(value, _)| *value == class) {
+                    Ok(class)
+                } else {
+                    let known: Vec<&str> = STORAGE_CLASSES.iter().map(|(value, _)| *value).collect();
+                    Err(format!("Unknown storage class '{}', expected one of {}", class, known.join(", ")))
+                }
+            })
+            .transpose()?;
+        let encryption = match (sse, sse_kms_key_id, sse_c_key) {
+            (None, None, None) => None,
+            (None, None, Some(key)) => Some(EncryptionSettings::SseC { key: key.trim().to_string() }),
+            (_, _, Some(_)) => return Err("--sse-c-key can't be combined with --sse or --sse-kms-key-id".to_string()),
+            (Some("AES256"), None, None) => Some(EncryptionSettings::SseS3),
+            (Some("aws:kms") | None, key_id, None) => Some(EncryptionSettings::SseKms { key_id: key_id.map(String::from) }),
+            (Some("AES256"), Some(_), None) => return Err("--sse-kms-key-id requires --sse aws:kms".to_string()),
+            (Some(other), _, None) => return Err(format!("Unknown encryption '{}', expected AES256 or aws:kms", other)),
+        };
+        if let Some(encryption) = &encryption {
+            encryption.validate()?;
+        }
+        Ok(TransferOptions { storage_class, encryption })
+    }
+
+    /// The upload with the options applied
+    pub fn apply_to(&self, item: LocalSelectedItem) -> LocalSelectedItem {
+        LocalSelectedItem {
+            storage_class: self.storage_class.clone().or(item.storage_class),
+            encryption: self.encryption.clone().or(item.encryption),
+            ..item
+        }
+    }
+
+    /// The uploads with the options applied
+    pub fn apply(&self, items: Vec<LocalSelectedItem>) -> Vec<LocalSelectedItem> {
+        items.into_iter().map(|item| self.apply_to(item)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_class_flag_must_be_known() {
+        assert_eq!(TransferOptions::from_flags(None, None, None, None), Ok(TransferOptions::default()));
+        assert_eq!(
+            TransferOptions::from_flags(Some("standard_ia"), None, None, None).unwrap().storage_class.as_deref(),
+            Some("STANDARD_IA")
+        );
+        assert!(TransferOptions::from_flags(Some("COLD"), None, None, None).unwrap_err().contains("DEEP_ARCHIVE"));
+    }
+
+    #[test]
+    fn encryption_flags_follow_the_aws_cli() {
+        let encryption = |sse, key_id, key| TransferOptions::from_flags(None, sse, key_id, key).map(|o| o.encryption);
+        assert_eq!(encryption(Some("AES256"), None, None), Ok(Some(EncryptionSettings::SseS3)));
+        assert_eq!(encryption(Some("aws:kms"), None, None), Ok(Some(EncryptionSettings::SseKms { key_id: None })));
+        let key_id = Some("alias/data".to_string());
+        assert_eq!(encryption(None, Some("alias/data"), None), Ok(Some(EncryptionSettings::SseKms { key_id })));
+        assert!(encryption(Some("AES256"), Some("alias/data"), None).is_err());
+        assert!(encryption(Some("aws:kms:dsse"), None, None).is_err());
+
+        let key = EncryptionSettings::generate_customer_key();
+        assert_eq!(encryption(None, None, Some(&key)), Ok(Some(EncryptionSettings::SseC { key: key.clone() })));
+        assert!(encryption(Some("AES256"), None, Some(&key)).is_err());
+        assert!(encryption(None, None, Some("c2hvcnQ=")).is_err());
+    }
+
+    #[test]
+    fn options_are_applied_to_every_upload() {
+        let item = |name: &str| {
+            LocalSelectedItem::new(name.into(), name.into(), false, "bucket".into(), name.into(), Default::default(), None)
+        };
+        let options = TransferOptions {
+            storage_class: Some("GLACIER".to_string()),
+            encryption: Some(EncryptionSettings::SseS3),
+        };
+        let items = options.apply(vec![item("a"), item("b")]);
+        assert!(items.iter().all(|item| item.storage_class.as_deref() == Some("GLACIER")));
+        assert!(items.iter().all(|item| item.encryption == Some(EncryptionSettings::SseS3)));
+    }
+
+    #[test]
+    fn items_keep_their_own_settings_without_options() {
+        let item = LocalSelectedItem {
+            storage_class: Some("STANDARD_IA".to_string()),
+            encryption: Some(EncryptionSettings::SseS3),
+            ..LocalSelectedItem::new("a".into(), "a".into(), false, "bucket".into(), "a".into(), Default::default(), None)
+        };
+        let item = TransferOptions::default().apply_to(item);
+        assert_eq!(item.storage_class.as_deref(), Some("STANDARD_IA"));
+        assert_eq!(item.encryption, Some(EncryptionSettings::SseS3));
+    }
+}
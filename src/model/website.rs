@@ -0,0 +1,160 @@
+//! This module provides the static website hosting configuration of buckets, the bucket policy
+//! making the site readable by everyone and the publishing of local folders
+use std::fs;
+use std::path::Path;
+use crate::model::content_type::{detect_content_type, UploadHeaders};
+use crate::model::encryption::EncryptionSettings;
+use crate::model::local_selected_item::{items_for_upload, LocalSelectedItem};
+
+/// Regions whose website endpoints are separated from the region with a dash instead of a dot
+const DASHED_WEBSITE_REGIONS: &[&str] = &[
+    "us-east-1",
+    "us-west-1",
+    "us-west-2",
+    "ap-southeast-1",
+    "ap-southeast-2",
+    "ap-northeast-1",
+    "eu-west-1",
+    "sa-east-1",
+    "us-gov-west-1",
+];
+
+/// Redirect of the requests matching a condition, the fields set to `None` keep the request's value
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoutingRule {
+    /// Condition: the key starts with this prefix
+    pub key_prefix_equals: Option<String>,
+    /// Condition: the site would answer with this HTTP error code (eg. 404)
+    pub http_error_code_equals: Option<String>,
+    pub host_name: Option<String>,
+    pub replace_key_prefix_with: Option<String>,
+    pub http_redirect_code: Option<String>,
+}
+
+/// Static website hosting of a bucket
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebsiteSettings {
+    pub index_document: String,
+    pub error_document: Option<String>,
+    /// Host all the requests are redirected to, instead of serving the bucket
+    pub redirect_all_to: Option<String>,
+    pub routing_rules: Vec<RoutingRule>,
+}
+
+impl WebsiteSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.redirect_all_to.is_none() && self.index_document.trim().is_empty() {
+            return Err("The index document is required, unless all requests are redirected".to_string());
+        }
+        if self.index_document.contains('/') {
+            return Err("The index document can't contain a slash, it's looked up in every folder".to_string());
+        }
+        let empty_condition = |rule: &&RoutingRule| rule.key_prefix_equals.is_none() && rule.http_error_code_equals.is_none();
+        if self.routing_rules.iter().any(|rule| empty_condition(&rule)) {
+            return Err("Every routing rule needs a key prefix or an error code to match".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Address of the website of a bucket hosted by AWS
+pub fn website_endpoint(bucket: &str, region: &str) -> String {
+    let separator = if DASHED_WEBSITE_REGIONS.contains(&region) { '-' } else { '.' };
+    format!("http://{}.s3-website{}{}.amazonaws.com", bucket, separator, region)
+}
+
+/// Bucket policy allowing everyone to read the objects of the bucket, which a website needs
+pub fn public_read_policy(bucket: &str) -> String {
+    let policy = serde_json::json!({
+        "Version": "2012-10-17",
+        "Statement": [{
+            "Sid": "PublicReadGetObject",
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": "s3:GetObject",
+            "Resource": format!("arn:aws:s3:::{}/*", bucket),
+        }],
+    });
+    serde_json::to_string_pretty(&policy).unwrap_or_default()
+}
+
+/// Uploads of the files inside the folder, keyed relative to it under `prefix`. Html pages get
+/// their own Cache-Control, so new versions of the site show up while the assets stay cached.
+/// Visitors read the site anonymously, so it's never client-side encrypted and always encrypted
+/// with SSE-S3: objects under SSE-KMS or a customer key can't be read without credentials.
+pub fn publish_items(
+    folder: &Path,
+    bucket: &str,
+    prefix: &str,
+    html_cache_control: &str,
+    asset_cache_control: &str,
+) -> Vec<LocalSelectedItem> {
+    let children: Vec<_> = fs::read_dir(folder)
+        .map(|entries| entries.filter_map(Result::ok).map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    let mut items = items_for_upload(&children, bucket, prefix);
+    for item in items.iter_mut() {
+        let content_type = detect_content_type(Path::new(&item.path));
+        let cache_control = match content_type {
+            Some(content_type) if content_type.starts_with("text/html") => html_cache_control,
+            _ => asset_cache_control,
+        };
+        item.headers = UploadHeaders {
+            content_type: content_type.map(String::from),
+            cache_control: Some(cache_control.trim().to_string()).filter(|value| !value.is_empty()),
+            ..Default::default()
+        };
+        item.encryption = Some(EncryptionSettings::SseS3);
+        item.client_side_encryption = false;
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn website_endpoints_depend_on_the_region() {
+        assert_eq!(website_endpoint("site", "us-east-1"), "http://site.s3-website-us-east-1.amazonaws.com");
+        assert_eq!(website_endpoint("site", "eu-central-1"), "http://site.s3-website.eu-central-1.amazonaws.com");
+    }
+
+    #[test]
+    fn policy_grants_public_read_of_the_bucket_objects() {
+        let policy: serde_json::Value = serde_json::from_str(&public_read_policy("site")).unwrap();
+        assert_eq!(policy["Statement"][0]["Resource"], "arn:aws:s3:::site/*");
+        assert_eq!(policy["Statement"][0]["Action"], "s3:GetObject");
+    }
+
+    #[test]
+    fn routing_rules_need_a_condition() {
+        let settings = WebsiteSettings { index_document: "index.html".to_string(), ..Default::default() };
+        assert_eq!(settings.validate(), Ok(()));
+        let settings = WebsiteSettings { routing_rules: vec![RoutingRule::default()], ..settings };
+        assert!(settings.validate().is_err());
+        assert!(WebsiteSettings::default().validate().is_err());
+    }
+
+    #[test]
+    fn published_folders_keep_their_structure_with_web_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("assets")).unwrap();
+        fs::write(dir.path().join("index.html"), b"<html></html>").unwrap();
+        fs::write(dir.path().join("assets").join("app.css"), b"body {}").unwrap();
+
+        let items = publish_items(dir.path(), "site", "", "no-cache", "max-age=86400");
+        let published: Vec<(&str, Option<&str>, Option<&str>)> = items
+            .iter()
+            .map(|item| (item.destination_path.as_str(), item.headers.content_type.as_deref(), item.headers.cache_control.as_deref()))
+            .collect();
+        assert_eq!(
+            published,
+            vec![
+                ("assets/app.css", Some("text/css; charset=utf-8"), Some("max-age=86400")),
+                ("index.html", Some("text/html; charset=utf-8"), Some("no-cache")),
+            ]
+        );
+        assert!(items.iter().all(|item| item.encryption == Some(EncryptionSettings::SseS3) && !item.client_side_encryption));
+    }
+}
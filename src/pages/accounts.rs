@@ -6,12 +6,13 @@ use tokio::task::spawn_blocking;
 use crate::model::account::Account;
 use crate::repositories::account_repo;
 use crate::repositories::account_repo::{delete_account, fetch_accounts, fetch_accounts_paginated};
-use crate::utils::CURRENT_ACCOUNT;
+use crate::state::{check_account_health, db, open_account, reload_open_accounts, AccountHealth, ACCOUNT_HEALTH};
+use crate::Route;
 
 const S3_IMG: Asset = asset!("/assets/aws_logo.png");
 
 async fn list_accounts(page: Option<usize>, page_size: Option<usize>) -> (Vec<Account>, usize) {
-    let result = spawn_blocking(move || fetch_accounts_paginated(page, page_size)).await;
+    let result = spawn_blocking(move || fetch_accounts_paginated(db(), page, page_size)).await;
     match result {
         Ok((accounts, total)) => (accounts, total),
         Err(e) => {
@@ -53,6 +54,8 @@ pub fn Accounts() -> Element {
                 let (account_data, total) = list_accounts(Some(page), Some(size)).await;
                 accounts_signal.set(account_data);
                 total_accounts.set(total);
+                // the sidebar shows the edited names and closes the deleted accounts
+                reload_open_accounts();
                 refresh_accounts.set(false);
             });
         }
@@ -70,6 +73,15 @@ pub fn Accounts() -> Element {
         });
     });
 
+    // the accounts which weren't tested in this session yet are tested when they're listed
+    use_effect(move || {
+        for account in accounts.read().iter() {
+            if !ACCOUNT_HEALTH.peek().contains_key(&account.id) {
+                check_account_health(account.clone());
+            }
+        }
+    });
+
     rsx!(
         if *show_modal.read() {
                     AccountModal {
@@ -97,7 +109,7 @@ pub fn Accounts() -> Element {
                                     let mut refresh_accounts = refresh_accounts.clone();
                                     move |_| {
                                         spawn_blocking(move || {
-                                            delete_account(account_id);
+                                            delete_account(db(), account_id);
                                         });
                                         account_to_delete.set(None);
                                         refresh_accounts.set(true);
@@ -169,6 +181,7 @@ fn AccountsTable(accounts: Vec<Account>, selected_account: Signal<Option<Account
                         th { class: "px-4 py-3", "Access Key" }
                         th { class: "px-4 py-3", "Secret Key" }
                         th { class: "px-4 py-3", "Region" }
+                        th { class: "px-4 py-3", "Status" }
                         th { class: "px-4 py-3", "Last Accessed" }
                         th { class: "px-4 py-3", "Default?" }
                         th { class: "px-4 py-3", "Actions" }
@@ -206,7 +219,44 @@ fn AccountsTable(accounts: Vec<Account>, selected_account: Signal<Option<Account
                                     "{acc.masked_secret_key()}"
                                 }
                             }
-                            td { class: "px-4 py-3 text-sm", "{acc.default_region}" }
+                            td { class: "px-4 py-3 text-sm",
+                                "{acc.default_region}"
+                                if let Some(endpoint) = &acc.endpoint_url {
+                                    p { class: "text-xs text-gray-600 dark:text-gray-400", "{endpoint}" }
+                                }
+                            }
+                            td { class: "px-4 py-3 text-xs",
+                                button {
+                                    class: "focus:outline-none",
+                                    title: "Test the connection again",
+                                    onclick: {
+                                        let acc_for_check = acc.clone();
+                                        move |_| check_account_health(acc_for_check.clone())
+                                    },
+                                    match ACCOUNT_HEALTH.read().get(&acc.id) {
+                                        Some(AccountHealth::Healthy(identity)) => rsx! {
+                                            span {
+                                                class: "px-2 py-1 font-semibold leading-tight text-green-700 bg-green-100 rounded-full dark:bg-green-700 dark:text-green-100",
+                                                title: "{identity}",
+                                                "Connected"
+                                            }
+                                        },
+                                        Some(AccountHealth::Failed(error)) => rsx! {
+                                            span {
+                                                class: "px-2 py-1 font-semibold leading-tight text-red-700 bg-red-100 rounded-full dark:bg-red-700 dark:text-red-100",
+                                                title: "{error}",
+                                                "Failed"
+                                            }
+                                        },
+                                        _ => rsx! {
+                                            span {
+                                                class: "px-2 py-1 font-semibold leading-tight text-gray-700 bg-gray-100 rounded-full dark:bg-gray-700 dark:text-gray-100",
+                                                "Checking..."
+                                            }
+                                        },
+                                    }
+                                }
+                            }
                             td { class: "px-4 py-3 text-sm", "-" }
                             td { class: "px-4 py-3 text-sm",
                                 input {
@@ -217,6 +267,18 @@ fn AccountsTable(accounts: Vec<Account>, selected_account: Signal<Option<Account
                                 }
                             }
                             td { class: "px-4 py-3 space-x-2",
+                        button {
+                            class: "px-2 py-1 text-sm text-white bg-purple-600 rounded hover:bg-purple-700 focus:outline-none",
+                            title: "Open the account in the sidebar",
+                            onclick: {
+                                let acc_for_open = acc.clone();
+                                move |_| {
+                                    open_account(acc_for_open.clone());
+                                    navigator().push(Route::Buckets { account: acc_for_open.id });
+                                }
+                            },
+                            "Open"
+                        }
                         button {
                             class: "px-2 py-1 text-sm text-white bg-blue-500 rounded hover:bg-blue-600 focus:outline-none",
                             onclick: {
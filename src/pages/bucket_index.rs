@@ -0,0 +1,191 @@
+use dioxus::prelude::*;
+use aws_sdk_s3::primitives::DateTime;
+use humansize::{file_size_opts, FileSize};
+use crate::model::object_index::{IndexInfo, IndexQuery, IndexSortColumn, IndexedObject, StorageClassStats};
+use crate::repositories::index_repo::{delete_index, fetch_storage_class_stats, get_index_info, query_index};
+use crate::state::{start_crawl, CrawlState, CRAWLS};
+use crate::Route;
+use crate::state::db;
+
+/// Number of indexed objects shown at once
+const RESULTS_LIMIT: usize = 1000;
+
+fn format_size(size: u64) -> String {
+    size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()
+}
+
+/// Local index of a bucket: its state, size statistics and instant filtering of the keys
+#[component]
+pub fn BucketIndex(account: i64, bucket: String) -> Element {
+    let mut filter = use_signal(String::new);
+    let mut sort = use_signal(|| IndexSortColumn::Key);
+    let mut ascending = use_signal(|| true);
+    let mut info = use_signal(|| None as Option<IndexInfo>);
+    let mut stats = use_signal(Vec::<StorageClassStats>::new);
+    let mut objects = use_signal(Vec::<IndexedObject>::new);
+    let mut reload = use_signal(|| 0u32);
+
+    let current_bucket = bucket.clone();
+    use_effect(use_reactive!(|(account, current_bucket)| {
+        let _ = reload.read();
+        // refreshed with every batch written by the crawler
+        let _ = CRAWLS.read();
+        let account_id = account;
+        let query = IndexQuery {
+            contains: filter.read().trim().to_string(),
+            sort: *sort.read(),
+            ascending: *ascending.read(),
+            limit: RESULTS_LIMIT,
+        };
+        info.set(get_index_info(db(), account_id, &current_bucket));
+        stats.set(fetch_storage_class_stats(db(), account_id, &current_bucket));
+        objects.set(query_index(db(), account_id, &current_bucket, &query));
+    }));
+
+    let crawl_state = CRAWLS.read().get(&(account, bucket.clone())).cloned();
+    let is_crawling = matches!(crawl_state, Some(CrawlState::Running(_)));
+    let bucket_for_crawl = bucket.clone();
+    let bucket_for_delete = bucket.clone();
+
+    rsx!(
+        main { class: "h-full overflow-y-auto",
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
+                        Link { to: Route::Buckets { account }, "Buckets" }
+                        " / "
+                        Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: String::new(), selected: String::new() }, "{bucket}" }
+                        " / Index"
+                    }
+                    div { class: "space-x-2",
+                        button {
+                            class: "px-4 py-2 text-sm font-medium text-white bg-purple-600 rounded-lg hover:bg-purple-700 focus:outline-none disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: is_crawling,
+                            onclick: move |_| start_crawl(account, bucket_for_crawl.clone()),
+                            if info.read().is_some() { "Refresh index" } else { "Build index" }
+                        }
+                        button {
+                            class: "px-4 py-2 text-sm font-medium text-white bg-red-500 rounded-lg hover:bg-red-600 focus:outline-none disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: is_crawling || info.read().is_none(),
+                            onclick: move |_| {
+                                delete_index(db(), account, &bucket_for_delete);
+                                *reload.write() += 1;
+                            },
+                            "Delete index"
+                        }
+                    }
+                }
+
+                match crawl_state {
+                    Some(CrawlState::Running(count)) => rsx!(
+                        div { class: "mb-4 p-3 bg-blue-100 border border-blue-400 text-blue-700 rounded",
+                            "Indexing... {count} objects so far"
+                        }
+                    ),
+                    Some(CrawlState::Done(count)) => rsx!(
+                        div { class: "mb-4 p-3 bg-green-100 border border-green-400 text-green-700 rounded",
+                            "Indexed {count} objects"
+                        }
+                    ),
+                    Some(CrawlState::Failed(error)) => rsx!(
+                        div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                            "Indexing failed: {error}"
+                        }
+                    ),
+                    None => rsx!(),
+                }
+
+                div { class: "mb-6 text-sm text-gray-700 dark:text-gray-400",
+                    match info.read().clone() {
+                        Some(IndexInfo { last_indexed: Some(last_indexed), object_count, total_size, .. }) => rsx!(
+                            p { "Last indexed: {DateTime::from_secs(last_indexed)}" }
+                            p { "{object_count} objects, {format_size(total_size)}" }
+                        ),
+                        Some(_) => rsx!( p { "The first indexing hasn't finished yet." } ),
+                        None => rsx!( p { "The bucket isn't indexed. Build the index to filter and sort all its objects instantly." } ),
+                    }
+                }
+
+                if !stats.read().is_empty() {
+                    h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Size by storage class" }
+                    table { class: "mb-6 text-sm text-gray-700 dark:text-gray-400",
+                        tbody {
+                            {stats.read().iter().map(|s| rsx!(
+                                tr {
+                                    td { class: "py-1 pr-6 font-semibold", "{s.storage_class}" }
+                                    td { class: "py-1 pr-6", "{s.object_count} objects" }
+                                    td { class: "py-1", "{format_size(s.total_size)}" }
+                                }
+                            ))}
+                        }
+                    }
+                }
+
+                div { class: "flex items-center mb-4 space-x-2",
+                    input {
+                        class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                        r#type: "text",
+                        placeholder: "Filter keys",
+                        value: "{filter}",
+                        oninput: move |e| {
+                            filter.set(e.value());
+                        },
+                    }
+                    select {
+                        class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                        value: sort.read().name(),
+                        oninput: move |e| {
+                            sort.set(IndexSortColumn::from_name(&e.value()));
+                        },
+                        option { value: "key", "Key" }
+                        option { value: "size", "Size" }
+                        option { value: "last_modified", "Last modified" }
+                        option { value: "storage_class", "Storage class" }
+                    }
+                    button {
+                        class: "px-3 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                        onclick: move |_| {
+                            let current = *ascending.read();
+                            ascending.set(!current);
+                        },
+                        if *ascending.read() { "▲" } else { "▼" }
+                    }
+                }
+
+                div { class: "w-full overflow-hidden rounded-lg shadow-xs mb-8",
+                    div { class: "w-full overflow-x-auto",
+                        table { class: "w-full whitespace-no-wrap",
+                            thead {
+                                tr {
+                                    class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                                    th { class: "px-4 py-3", "Key" }
+                                    th { class: "px-4 py-3", "Size" }
+                                    th { class: "px-4 py-3", "Last modified" }
+                                    th { class: "px-4 py-3", "Storage class" }
+                                }
+                            }
+                            tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
+                                {objects.read().clone().into_iter().map(|object| {
+                                    let target = Route::Objects {
+                                        account,
+                                        bucket: bucket.clone(),
+                                        prefix: object.key.rfind('/').map(|idx| object.key[..=idx].to_string()).unwrap_or_default(),
+                                        selected: object.key.clone(),
+                                    };
+                                    rsx!(
+                                        tr { class: "text-gray-700 dark:text-gray-400",
+                                            td { class: "px-4 py-3 text-sm break-all", Link { to: target, "{object.key}" } }
+                                            td { class: "px-4 py-3 text-sm", "{format_size(object.size)}" }
+                                            td { class: "px-4 py-3 text-sm", {object.last_modified.map(|lm| DateTime::from_secs(lm).to_string())} }
+                                            td { class: "px-4 py-3 text-sm", {object.storage_class.clone().unwrap_or("STANDARD".to_string())} }
+                                        }
+                                    )
+                                })}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
@@ -0,0 +1,740 @@
+use std::path::Path;
+use dioxus::prelude::*;
+use crate::components::key_value_editor::{entries_to_map, KeyValueEditor};
+use crate::components::transfer_queue::dropped_paths;
+use crate::components::TransferQueue;
+use crate::model::header_rule::HeaderRule;
+use crate::model::object_lock::{BucketObjectLock, DefaultRetention, RetentionPeriod, RETENTION_MODES};
+use crate::model::website::{public_read_policy, publish_items, website_endpoint, RoutingRule, WebsiteSettings};
+use crate::repositories::account_repo::fetch_accounts;
+use crate::repositories::header_rule_repo::{delete_header_rule, fetch_header_rules, save_header_rule};
+use crate::state::{account_fetcher, db, queue_uploads};
+use crate::Route;
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|value| !value.is_empty())
+}
+
+/// Settings of a bucket which apply to all of its objects
+#[component]
+pub fn BucketSettings(account: i64, bucket: String) -> Element {
+    rsx!(
+        main { class: "h-full overflow-y-auto",
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
+                        Link { to: Route::Buckets { account }, "Buckets" }
+                        " / "
+                        Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: String::new(), selected: String::new() }, "{bucket}" }
+                        " / Settings"
+                    }
+                }
+                ObjectLockSettings { account, bucket: bucket.clone() }
+                WebsiteHosting { account, bucket: bucket.clone() }
+                UploadHeaderRules { account, bucket: bucket.clone() }
+            }
+        }
+    )
+}
+
+/// Object lock of the bucket and the retention applied to the new objects
+#[component]
+fn ObjectLockSettings(account: i64, bucket: String) -> Element {
+    let mut lock = use_signal(|| None as Option<BucketObjectLock>);
+    let mut mode = use_signal(String::new);
+    let mut period = use_signal(|| "30".to_string());
+    let mut unit = use_signal(|| "days".to_string());
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut info_message = use_signal(|| None as Option<String>);
+    let mut is_working = use_signal(|| false);
+    let mut reload = use_signal(|| 0u32);
+
+    let current_bucket = bucket.clone();
+    use_effect(use_reactive!(|(account, current_bucket)| {
+        let _ = reload.read();
+        let bucket = current_bucket.clone();
+        spawn(async move {
+            let Some(fetcher) = account_fetcher(account) else {
+                error_message.set(Some("The account doesn't exist anymore".to_string()));
+                return;
+            };
+            match fetcher.get_object_lock(&bucket).await {
+                Ok(loaded) => {
+                    if let Some(retention) = &loaded.default_retention {
+                        mode.set(retention.mode.clone());
+                        let (value, name) = match retention.period {
+                            RetentionPeriod::Days(days) => (days, "days"),
+                            RetentionPeriod::Years(years) => (years, "years"),
+                        };
+                        period.set(value.to_string());
+                        unit.set(name.to_string());
+                    }
+                    lock.set(Some(loaded));
+                }
+                Err(e) => error_message.set(Some(format!("Failed to read the object lock configuration: {}", e))),
+            }
+        });
+    }));
+
+    let bucket_for_save = bucket.clone();
+    let save = use_callback(move |enable_only: bool| {
+        let retention = if enable_only || mode.read().is_empty() {
+            None
+        } else {
+            let Ok(value) = period.read().trim().parse::<i32>() else {
+                error_message.set(Some("The retention period must be a number".to_string()));
+                return;
+            };
+            if value < 1 {
+                error_message.set(Some("The retention period must be at least 1".to_string()));
+                return;
+            }
+            let period = if *unit.read() == "years" { RetentionPeriod::Years(value) } else { RetentionPeriod::Days(value) };
+            Some(DefaultRetention { mode: mode.read().clone(), period })
+        };
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_save.clone();
+        is_working.set(true);
+        error_message.set(None);
+        info_message.set(None);
+        spawn(async move {
+            let message = match &retention {
+                Some(retention) => format!("New objects are retained for {} in {} mode", retention.period.label(), retention.mode.to_lowercase()),
+                None if enable_only => "Object lock is enabled".to_string(),
+                None => "New objects aren't retained by default".to_string(),
+            };
+            match fetcher.set_default_retention(&bucket, retention).await {
+                Ok(()) => {
+                    info_message.set(Some(message));
+                    *reload.write() += 1;
+                }
+                // existing buckets need versioning before object lock can be enabled
+                Err(e) => error_message.set(Some(format!("Failed to save the object lock configuration: {}", e))),
+            }
+            is_working.set(false);
+        });
+    });
+
+    rsx!(
+        div { class: "p-4 mb-6 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Object lock" }
+            if let Some(error) = error_message.read().as_ref() {
+                div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded", "{error}" }
+            }
+            if let Some(info) = info_message.read().as_ref() {
+                div { class: "mb-4 p-3 bg-green-100 border border-green-400 text-green-700 rounded", "{info}" }
+            }
+            match lock.read().clone() {
+                None => rsx!( p { class: "text-sm text-gray-700 dark:text-gray-400", "Loading..." } ),
+                Some(BucketObjectLock { enabled: false, .. }) => rsx!(
+                    p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                        "Object lock is disabled. Once enabled it can't be disabled again, and the bucket needs versioning."
+                    }
+                    button {
+                        class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: *is_working.read(),
+                        onclick: move |_| save.call(true),
+                        "Enable object lock"
+                    }
+                ),
+                Some(BucketObjectLock { enabled: true, default_retention }) => rsx!(
+                    p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                        match default_retention {
+                            Some(retention) => rsx!("New objects are retained for {retention.period.label()} in {retention.mode.to_lowercase()} mode."),
+                            None => rsx!("Object lock is enabled, new objects aren't retained unless they're given a retention."),
+                        }
+                    }
+                    div { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-400",
+                        select {
+                            class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{mode}",
+                            oninput: move |e| mode.set(e.value()),
+                            option { value: "", "No default retention" }
+                            {RETENTION_MODES.iter().map(|(value, label)| rsx!(
+                                option { value: "{value}", "{label}" }
+                            ))}
+                        }
+                        if !mode.read().is_empty() {
+                            input {
+                                class: "w-20 px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "number",
+                                min: "1",
+                                value: "{period}",
+                                oninput: move |e| period.set(e.value()),
+                            }
+                            select {
+                                class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                value: "{unit}",
+                                oninput: move |e| unit.set(e.value()),
+                                option { value: "days", "days" }
+                                option { value: "years", "years" }
+                            }
+                        }
+                        button {
+                            class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: *is_working.read(),
+                            onclick: move |_| save.call(false),
+                            "Save default retention"
+                        }
+                    }
+                ),
+            }
+        }
+    )
+}
+
+/// Static website hosting of the bucket, its public read policy and the publishing of a local folder
+#[component]
+fn WebsiteHosting(account: i64, bucket: String) -> Element {
+    let mut enabled = use_signal(|| false);
+    let mut index_document = use_signal(|| "index.html".to_string());
+    let mut error_document = use_signal(String::new);
+    let mut redirect_all_to = use_signal(String::new);
+    let mut routing_rules = use_signal(Vec::<RoutingRule>::new);
+    let mut policy = use_signal(String::new);
+    let mut allow_public = use_signal(|| true);
+    let mut endpoint = use_signal(|| None as Option<String>);
+    let mut folder = use_signal(String::new);
+    let mut publish_prefix = use_signal(String::new);
+    let mut html_cache_control = use_signal(|| "no-cache".to_string());
+    let mut asset_cache_control = use_signal(|| "public, max-age=86400".to_string());
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut info_message = use_signal(|| None as Option<String>);
+    let mut is_working = use_signal(|| false);
+    let mut reload = use_signal(|| 0u32);
+
+    let current_bucket = bucket.clone();
+    use_effect(use_reactive!(|(account, current_bucket)| {
+        let _ = reload.read();
+        let bucket = current_bucket.clone();
+        spawn(async move {
+            let Some(fetcher) = account_fetcher(account) else {
+                error_message.set(Some("The account doesn't exist anymore".to_string()));
+                return;
+            };
+            let mut failures = Vec::new();
+            match fetcher.get_website(&bucket).await {
+                Ok(Some(settings)) => {
+                    enabled.set(true);
+                    index_document.set(settings.index_document);
+                    error_document.set(settings.error_document.unwrap_or_default());
+                    redirect_all_to.set(settings.redirect_all_to.unwrap_or_default());
+                    routing_rules.set(settings.routing_rules);
+                }
+                Ok(None) => enabled.set(false),
+                Err(e) => failures.push(format!("Failed to read the website configuration: {}", e)),
+            }
+            match fetcher.get_bucket_policy(&bucket).await {
+                Ok(current) => policy.set(current.unwrap_or_else(|| public_read_policy(&bucket))),
+                Err(e) => failures.push(format!("Failed to read the bucket policy: {}", e)),
+            }
+            if !failures.is_empty() {
+                error_message.set(Some(failures.join(". ")));
+            }
+            // s3 compatible services don't have website endpoints of their own
+            let on_aws = fetch_accounts(db()).into_iter().any(|a| a.id == account && a.endpoint_url.is_none());
+            if on_aws {
+                if let Ok(region) = fetcher.get_bucket_location(&bucket).await {
+                    endpoint.set(Some(website_endpoint(&bucket, &region)));
+                }
+            }
+        });
+    }));
+
+    let bucket_for_save = bucket.clone();
+    let save_website = move |_| {
+        let settings = WebsiteSettings {
+            index_document: index_document.read().trim().to_string(),
+            error_document: non_empty(&error_document.read()),
+            redirect_all_to: non_empty(&redirect_all_to.read()),
+            routing_rules: routing_rules.read().clone(),
+        };
+        if let Err(err) = settings.validate() {
+            error_message.set(Some(err));
+            return;
+        }
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_save.clone();
+        is_working.set(true);
+        error_message.set(None);
+        info_message.set(None);
+        spawn(async move {
+            match fetcher.put_website(&bucket, &settings).await {
+                Ok(()) => {
+                    info_message.set(Some("Website hosting is enabled".to_string()));
+                    *reload.write() += 1;
+                }
+                Err(e) => error_message.set(Some(format!("Failed to save the website configuration: {}", e))),
+            }
+            is_working.set(false);
+        });
+    };
+
+    let bucket_for_disable = bucket.clone();
+    let disable_website = move |_| {
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_disable.clone();
+        is_working.set(true);
+        error_message.set(None);
+        info_message.set(None);
+        spawn(async move {
+            match fetcher.delete_website(&bucket).await {
+                Ok(()) => {
+                    info_message.set(Some("Website hosting is disabled, the bucket policy is kept".to_string()));
+                    *reload.write() += 1;
+                }
+                Err(e) => error_message.set(Some(format!("Failed to disable website hosting: {}", e))),
+            }
+            is_working.set(false);
+        });
+    };
+
+    let bucket_for_policy = bucket.clone();
+    let apply_policy = move |_| {
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_policy.clone();
+        let document = policy.read().clone();
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&document) {
+            error_message.set(Some(format!("The policy isn't valid JSON: {}", e)));
+            return;
+        }
+        let allow_public = *allow_public.read();
+        is_working.set(true);
+        error_message.set(None);
+        info_message.set(None);
+        spawn(async move {
+            match fetcher.put_bucket_policy(&bucket, &document, allow_public).await {
+                Ok(()) => info_message.set(Some("The bucket policy is applied".to_string())),
+                Err(e) => error_message.set(Some(format!("Failed to apply the bucket policy: {}", e))),
+            }
+            is_working.set(false);
+        });
+    };
+
+    let bucket_for_publish = bucket.clone();
+    let publish = move |_| {
+        let source = folder.read().trim().to_string();
+        if !Path::new(&source).is_dir() {
+            error_message.set(Some(format!("'{}' isn't a folder", source)));
+            return;
+        }
+        let items = publish_items(
+            Path::new(&source),
+            &bucket_for_publish,
+            publish_prefix.read().trim(),
+            &html_cache_control.read(),
+            &asset_cache_control.read(),
+        );
+        if items.is_empty() {
+            error_message.set(Some(format!("'{}' has no files to publish", source)));
+            return;
+        }
+        error_message.set(None);
+        let message = format!("Publishing {} files", items.len());
+        match queue_uploads(account, items) {
+            Ok(()) => info_message.set(Some(message)),
+            Err(e) => error_message.set(Some(e)),
+        }
+    };
+
+    let bucket_for_policy_reset = bucket.clone();
+    let rules = routing_rules.read().clone();
+
+    rsx!(
+        div { class: "p-4 mb-6 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Static website hosting" }
+            if let Some(error) = error_message.read().as_ref() {
+                div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded whitespace-pre-line", "{error}" }
+            }
+            if let Some(info) = info_message.read().as_ref() {
+                div { class: "mb-4 p-3 bg-green-100 border border-green-400 text-green-700 rounded", "{info}" }
+            }
+            p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                if *enabled.read() {
+                    "The bucket is hosted as a website"
+                    if let Some(endpoint) = endpoint.read().as_ref() {
+                        " at "
+                        a { class: "text-purple-600 hover:underline", href: "{endpoint}", target: "_blank", "{endpoint}" }
+                    }
+                    "."
+                } else {
+                    "Website hosting is disabled."
+                }
+            }
+
+            div { class: "mb-6 space-y-3",
+                div { class: "grid grid-cols-2 gap-4",
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Index document" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            value: "{index_document}",
+                            oninput: move |e| index_document.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Error document" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. 404.html",
+                            value: "{error_document}",
+                            oninput: move |e| error_document.set(e.value()),
+                        }
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Redirect all requests to" }
+                    input {
+                        class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                        r#type: "text",
+                        placeholder: "Host name, eg. www.example.com (empty to serve the bucket)",
+                        value: "{redirect_all_to}",
+                        oninput: move |e| redirect_all_to.set(e.value()),
+                    }
+                }
+                if redirect_all_to.read().trim().is_empty() {
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Routing rules" }
+                        div { class: "space-y-2",
+                            {rules.into_iter().enumerate().map(|(idx, rule)| rsx!(
+                                div { class: "flex items-center space-x-2",
+                                    input {
+                                        class: "w-full px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        placeholder: "Key prefix",
+                                        value: rule.key_prefix_equals.clone().unwrap_or_default(),
+                                        oninput: move |e| routing_rules.write()[idx].key_prefix_equals = non_empty(&e.value()),
+                                    }
+                                    input {
+                                        class: "w-20 px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        placeholder: "Error",
+                                        value: rule.http_error_code_equals.clone().unwrap_or_default(),
+                                        oninput: move |e| routing_rules.write()[idx].http_error_code_equals = non_empty(&e.value()),
+                                    }
+                                    span { class: "text-sm text-gray-500", "→" }
+                                    input {
+                                        class: "w-full px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        placeholder: "Host name",
+                                        value: rule.host_name.clone().unwrap_or_default(),
+                                        oninput: move |e| routing_rules.write()[idx].host_name = non_empty(&e.value()),
+                                    }
+                                    input {
+                                        class: "w-full px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        placeholder: "New prefix",
+                                        value: rule.replace_key_prefix_with.clone().unwrap_or_default(),
+                                        oninput: move |e| routing_rules.write()[idx].replace_key_prefix_with = non_empty(&e.value()),
+                                    }
+                                    input {
+                                        class: "w-20 px-2 py-1 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                        r#type: "text",
+                                        placeholder: "301",
+                                        value: rule.http_redirect_code.clone().unwrap_or_default(),
+                                        oninput: move |e| routing_rules.write()[idx].http_redirect_code = non_empty(&e.value()),
+                                    }
+                                    button {
+                                        class: "px-2 py-1 text-sm text-white bg-red-500 rounded hover:bg-red-600",
+                                        onclick: move |_| {
+                                            routing_rules.write().remove(idx);
+                                        },
+                                        "×"
+                                    }
+                                }
+                            ))}
+                            button {
+                                class: "px-3 py-1 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                                onclick: move |_| routing_rules.write().push(RoutingRule::default()),
+                                "Add rule"
+                            }
+                        }
+                    }
+                }
+                div { class: "flex space-x-2",
+                    button {
+                        class: "px-4 py-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: *is_working.read(),
+                        onclick: save_website,
+                        if *enabled.read() { "Save website" } else { "Enable website" }
+                    }
+                    if *enabled.read() {
+                        button {
+                            class: "px-4 py-2 text-sm text-white bg-red-500 rounded hover:bg-red-600 disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: *is_working.read(),
+                            onclick: disable_website,
+                            "Disable website"
+                        }
+                    }
+                }
+            }
+
+            h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Bucket policy" }
+            div { class: "mb-6 space-y-3",
+                textarea {
+                    class: "w-full h-48 px-3 py-2 font-mono text-xs border rounded-md dark:bg-gray-700 dark:text-white",
+                    value: "{policy}",
+                    oninput: move |e| policy.set(e.value()),
+                }
+                label { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-300",
+                    input {
+                        class: "h-4 w-4 text-purple-600 rounded",
+                        r#type: "checkbox",
+                        checked: *allow_public.read(),
+                        onchange: move |e| allow_public.set(e.checked()),
+                    }
+                    span { "Allow public policies (turns off the public policy part of Block Public Access)" }
+                }
+                div { class: "flex space-x-2",
+                    button {
+                        class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900",
+                        onclick: move |_| policy.set(public_read_policy(&bucket_for_policy_reset)),
+                        "Generate public read policy"
+                    }
+                    button {
+                        class: "px-4 py-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700 disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: *is_working.read(),
+                        onclick: apply_policy,
+                        "Apply policy"
+                    }
+                }
+            }
+
+            h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Publish folder" }
+            div { class: "space-y-3",
+                div {
+                    label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Local folder" }
+                    input {
+                        class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                        r#type: "text",
+                        placeholder: "Path of the built site, or drop the folder here",
+                        value: "{folder}",
+                        oninput: move |e| folder.set(e.value()),
+                        ondragover: move |e| e.prevent_default(),
+                        ondrop: move |e| {
+                            e.prevent_default();
+                            if let Some(path) = dropped_paths(&e).first() {
+                                folder.set(path.to_string_lossy().to_string());
+                            }
+                        },
+                    }
+                }
+                div { class: "grid grid-cols-3 gap-4",
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Prefix" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "Empty for the root",
+                            value: "{publish_prefix}",
+                            oninput: move |e| publish_prefix.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Cache-Control of pages" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            value: "{html_cache_control}",
+                            oninput: move |e| html_cache_control.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Cache-Control of assets" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            value: "{asset_cache_control}",
+                            oninput: move |e| asset_cache_control.set(e.value()),
+                        }
+                    }
+                }
+                button {
+                    class: "px-4 py-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700",
+                    onclick: publish,
+                    "Publish"
+                }
+                TransferQueue { account: Some(account), bucket: Some(bucket.clone()) }
+            }
+        }
+    )
+}
+
+/// Rules setting the headers of the objects uploaded, synced or published to the bucket
+#[component]
+fn UploadHeaderRules(account: i64, bucket: String) -> Element {
+    let mut reload = use_signal(|| 0u32);
+    let mut pattern = use_signal(String::new);
+    let mut only_this_bucket = use_signal(|| true);
+    let mut content_type = use_signal(String::new);
+    let mut cache_control = use_signal(String::new);
+    let mut content_encoding = use_signal(String::new);
+    let mut metadata = use_signal(Vec::<(String, String)>::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+
+    let _ = reload.read();
+    let rules: Vec<HeaderRule> = fetch_header_rules(db(), account)
+        .into_iter()
+        .filter(|rule| rule.bucket.as_deref().is_none_or(|rule_bucket| rule_bucket == bucket))
+        .collect();
+
+    let bucket_for_add = bucket.clone();
+    let add_rule = move |_| {
+        let mut rule = HeaderRule {
+            id: 0,
+            account_id: account,
+            bucket: only_this_bucket().then(|| bucket_for_add.clone()),
+            pattern: pattern.read().trim().to_string(),
+            content_type: non_empty(&content_type.read()),
+            cache_control: non_empty(&cache_control.read()),
+            content_encoding: non_empty(&content_encoding.read()),
+            metadata: entries_to_map(&metadata.read()),
+        };
+        if let Err(e) = rule.validate() {
+            error_message.set(Some(e));
+            return;
+        }
+        save_header_rule(db(), &mut rule);
+        error_message.set(None);
+        pattern.set(String::new());
+        content_type.set(String::new());
+        cache_control.set(String::new());
+        content_encoding.set(String::new());
+        metadata.set(Vec::new());
+        *reload.write() += 1;
+    };
+
+    rsx!(
+        div { class: "p-4 mb-6 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Upload header rules" }
+            if let Some(error) = error_message.read().as_ref() {
+                div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded", "{error}" }
+            }
+            p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                "The Content-Type of uploads is detected from their extension or content. "
+                "The rules matching the key override it, the rules of the bucket win over the ones of all buckets."
+            }
+            if rules.is_empty() {
+                p { class: "mb-4 text-sm text-gray-500", "No rules yet." }
+            } else {
+                table { class: "w-full mb-4 text-sm whitespace-no-wrap",
+                    thead {
+                        tr { class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                            th { class: "px-2 py-2", "Pattern" }
+                            th { class: "px-2 py-2", "Scope" }
+                            th { class: "px-2 py-2", "Headers" }
+                            th { class: "px-2 py-2" }
+                        }
+                    }
+                    tbody { class: "divide-y dark:divide-gray-700 text-gray-700 dark:text-gray-400",
+                        for rule in rules {
+                            tr { key: "{rule.id}",
+                                td { class: "px-2 py-2 font-mono", "{rule.pattern}" }
+                                td { class: "px-2 py-2", "{rule.scope_label()}" }
+                                td { class: "px-2 py-2",
+                                    if let Some(value) = &rule.content_type {
+                                        div { "Content-Type: {value}" }
+                                    }
+                                    if let Some(value) = &rule.cache_control {
+                                        div { "Cache-Control: {value}" }
+                                    }
+                                    if let Some(value) = &rule.content_encoding {
+                                        div { "Content-Encoding: {value}" }
+                                    }
+                                    for (key, value) in rule.metadata.clone() {
+                                        div { "x-amz-meta-{key}: {value}" }
+                                    }
+                                }
+                                td { class: "px-2 py-2 text-right",
+                                    button {
+                                        class: "px-2 py-1 text-sm text-white bg-red-500 rounded hover:bg-red-600",
+                                        onclick: move |_| {
+                                            delete_header_rule(db(), rule.id);
+                                            *reload.write() += 1;
+                                        },
+                                        "Delete"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div { class: "space-y-3",
+                div { class: "grid grid-cols-2 gap-4",
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Pattern" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. *.css or assets/*",
+                            value: "{pattern}",
+                            oninput: move |e| pattern.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Applies to" }
+                        select {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            onchange: move |e| only_this_bucket.set(e.value() == "bucket"),
+                            option { value: "bucket", selected: only_this_bucket(), "This bucket" }
+                            option { value: "account", selected: !only_this_bucket(), "All buckets of the account" }
+                        }
+                    }
+                }
+                div { class: "grid grid-cols-3 gap-4",
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Content-Type" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "Detected",
+                            value: "{content_type}",
+                            oninput: move |e| content_type.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Cache-Control" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. max-age=31536000, immutable",
+                            value: "{cache_control}",
+                            oninput: move |e| cache_control.set(e.value()),
+                        }
+                    }
+                    div {
+                        label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Content-Encoding" }
+                        input {
+                            class: "w-full px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "text",
+                            placeholder: "eg. gzip",
+                            value: "{content_encoding}",
+                            oninput: move |e| content_encoding.set(e.value()),
+                        }
+                    }
+                }
+                div {
+                    label { class: "block text-sm font-medium text-gray-700 dark:text-gray-300", "Metadata" }
+                    KeyValueEditor { entries: metadata }
+                }
+                button {
+                    class: "px-4 py-2 text-sm text-white bg-purple-600 rounded hover:bg-purple-700",
+                    onclick: add_rule,
+                    "Add rule"
+                }
+            }
+        }
+    )
+}
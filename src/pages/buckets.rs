@@ -1,41 +1,41 @@
 use std::time::Duration;
 use dioxus::prelude::*;
 use dioxus::hooks::{use_coroutine, use_signal};
-use crate::components::{AccountCard, BucketModal, ClientsCard, ContactsCard, SalesCard};
+use crate::components::{AccountCard, BucketModal, ClientsCard, ContactsCard, SalesCard, TransferQueue, TransferSettings};
+use crate::components::transfer_queue::dropped_paths;
 use tokio::task::spawn_blocking;
 use crate::components::github_star_action::GithubStarAction;
 use crate::model::bucket::Bucket;
+use crate::model::local_selected_item::items_for_upload;
+use crate::model::page_cursor::PageCursor;
 use crate::services::s3_data_fetcher::S3DataFetcher;
-use crate::utils::CURRENT_ACCOUNT;
+use crate::Route;
+use crate::state::{account_fetcher, queue_uploads, OPEN_ACCOUNTS, TRANSFER_OPTIONS};
 
 const BUCKET_ICON: Asset = asset!("/assets/bucket_icon.png");
 
-async fn list_buckets(page: Option<usize>, page_size: Option<usize>) -> (Vec<Bucket>, usize) {
-    if let Some(fetcher) = S3DataFetcher::from_db_account() {
-        match fetcher.list_buckets(page, page_size).await {
-            Ok((s3_buckets, total_count)) => {
-                // Return buckets immediately without regions
-                let buckets = s3_buckets.iter().map(|s3_bucket| {
-                    Bucket {
-                        name: s3_bucket.name.clone(),
-                        region: None, // Will be populated asynchronously
-                    }
-                }).collect();
-                (buckets, total_count)
-            }
-            Err(e) => {
-                println!("Failed to list buckets: {}", e);
-                (Vec::new(), 0)
-            }
+async fn list_buckets(account: i64, page_size: usize, continuation_token: Option<String>) -> Result<(Vec<Bucket>, Option<String>), String> {
+    let Some(fetcher) = account_fetcher(account) else {
+        return Err("The account doesn't exist anymore".to_string());
+    };
+    match fetcher.list_buckets(Some(page_size), continuation_token).await {
+        Ok(page) => {
+            // Return buckets immediately without regions
+            let buckets = page.items.iter().map(|s3_bucket| {
+                Bucket {
+                    name: s3_bucket.name.clone(),
+                    region: None, // Will be populated asynchronously
+                }
+            }).collect();
+            Ok((buckets, page.next_token))
         }
-    } else {
-        println!("No S3DataFetcher available - no default account configured");
-        (Vec::new(), 0)
+        // a typo in the credentials would otherwise look like an account without buckets
+        Err(e) => Err(format!("Failed to list buckets: {}", e)),
     }
 }
 
-async fn fetch_bucket_regions(buckets_signal: Signal<Vec<Bucket>>) {
-    if let Some(fetcher) = S3DataFetcher::from_db_account() {
+async fn fetch_bucket_regions(account: i64, buckets_signal: Signal<Vec<Bucket>>) {
+    if let Some(fetcher) = account_fetcher(account) {
         let current_buckets = buckets_signal.read().clone();
         
         for bucket in current_buckets.iter() {
@@ -71,8 +71,8 @@ async fn fetch_bucket_regions(buckets_signal: Signal<Vec<Bucket>>) {
     }
 }
 
-async fn delete_bucket(bucket_name: String) -> Result<(), String> {
-    if let Some(fetcher) = S3DataFetcher::from_db_account() {
+async fn delete_bucket(account: i64, bucket_name: String) -> Result<(), String> {
+    if let Some(fetcher) = account_fetcher(account) {
         match fetcher.delete_data(true, None, bucket_name.clone(), false).await {
             Ok(None) => {
                 println!("Bucket '{}' deleted successfully", bucket_name);
@@ -89,68 +89,91 @@ async fn delete_bucket(bucket_name: String) -> Result<(), String> {
             }
         }
     } else {
-        let error_msg = "No default account configured. Please set up an AWS account first.".to_string();
+        let error_msg = "The account doesn't exist anymore".to_string();
         println!("{}", error_msg);
         Err(error_msg)
     }
 }
 
-/// Home page
+/// Buckets of one of the open accounts
 #[component]
-pub fn Buckets() -> Element {
+pub fn Buckets(account: i64) -> Element {
     let mut show_modal = use_signal(|| false);
     let buckets = use_signal(|| Vec::<Bucket>::new());
     let mut refresh_buckets = use_signal(|| false);
     let mut bucket_to_delete = use_signal(|| None as Option<Bucket>);
-    let mut current_page = use_signal(|| 0usize);
+    let mut cursor = use_signal(PageCursor::default);
     let mut page_size = use_signal(|| 20usize);
-    let mut total_buckets = use_signal(|| 0usize);
+    let mut error_message = use_signal(|| None as Option<String>);
+    // only a different page reloads the buckets, not learning the token of the next one
+    let page_token = use_memo(move || cursor.read().current_token());
+
+    // another account starts from its first page
+    let mut account_id = use_signal(|| account);
+    use_effect(use_reactive!(|account| {
+        if *account_id.peek() != account {
+            account_id.set(account);
+            cursor.write().reset();
+        }
+    }));
 
+    // Effect to load buckets when the account, the page or page_size changes
     use_effect(move || {
         let mut buckets_signal = buckets.clone();
-        let page = current_page.read().clone();
+        let account = *account_id.read();
+        let token = page_token.read().clone();
         let size = page_size.read().clone();
         spawn(async move {
-            let (bucket_data, total) = list_buckets(Some(page), Some(size)).await;
-            buckets_signal.set(bucket_data);
-            total_buckets.set(total);
-            // Fetch regions asynchronously after buckets are loaded
-            fetch_bucket_regions(buckets_signal).await;
+            match list_buckets(account, size, token).await {
+                Ok((bucket_data, next_token)) => {
+                    error_message.set(None);
+                    buckets_signal.set(bucket_data);
+                    cursor.write().set_next_token(next_token);
+                    // Fetch regions asynchronously after buckets are loaded
+                    fetch_bucket_regions(account, buckets_signal).await;
+                }
+                Err(e) => {
+                    error_message.set(Some(e));
+                    buckets_signal.set(Vec::new());
+                }
+            }
         });
     });
 
     use_effect(move || {
         if *refresh_buckets.read() {
             let mut buckets_signal = buckets.clone();
-            let page = current_page.read().clone();
-            let size = page_size.read().clone();
+            let account = *account_id.peek();
+            let token = page_token.peek().clone();
+            let size = page_size.peek().clone();
             spawn(async move {
-                let (bucket_data, total) = list_buckets(Some(page), Some(size)).await;
-                buckets_signal.set(bucket_data);
-                total_buckets.set(total);
+                let listing = list_buckets(account, size, token).await;
                 refresh_buckets.set(false);
-                // Fetch regions asynchronously after buckets are refreshed
-                fetch_bucket_regions(buckets_signal).await;
+                match listing {
+                    Ok((bucket_data, next_token)) => {
+                        error_message.set(None);
+                        buckets_signal.set(bucket_data);
+                        cursor.write().set_next_token(next_token);
+                        // Fetch regions asynchronously after buckets are refreshed
+                        fetch_bucket_regions(account, buckets_signal).await;
+                    }
+                    Err(e) => error_message.set(Some(e)),
+                }
             });
         }
     });
 
-    // Effect to reload buckets when page or page_size changes
-    use_effect(move || {
-        let mut buckets_signal = buckets.clone();
-        let page = current_page.read().clone();
-        let size = page_size.read().clone();
-        spawn(async move {
-            let (bucket_data, total) = list_buckets(Some(page), Some(size)).await;
-            buckets_signal.set(bucket_data);
-            total_buckets.set(total);
-            fetch_bucket_regions(buckets_signal).await;
-        });
-    });
+    let account_name = OPEN_ACCOUNTS
+        .read()
+        .iter()
+        .find(|open| open.id == account)
+        .map(|open| open.name.clone())
+        .unwrap_or_default();
 
     rsx!(
         if *show_modal.read() {
                     BucketModal {
+                        account,
                         show_modal: show_modal.clone(),
                         refresh_buckets: refresh_buckets.clone(),
                     }
@@ -175,14 +198,14 @@ pub fn Buckets() -> Element {
                                     move |_| {
                                         let name = bucket_name.clone();
                                         spawn(async move {
-                                            match delete_bucket(name).await {
+                                            match delete_bucket(account, name).await {
                                                 Ok(()) => {
                                                     bucket_to_delete.set(None);
                                                     refresh_buckets.set(true);
                                                 }
                                                 Err(err) => {
-                                                    // TODO: Show error message to user
                                                     println!("Delete failed: {}", err);
+                                                    error_message.set(Some(err));
                                                     bucket_to_delete.set(None);
                                                 }
                                             }
@@ -200,7 +223,7 @@ pub fn Buckets() -> Element {
                     div { class: "flex items-center justify-between mt-6 mb-5",
                         h2 {
                             class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
-                            "Buckets"
+                            "Buckets of {account_name}"
                         },
                         div { class: "flex items-center space-x-4",
                             div { class: "flex items-center space-x-2",
@@ -211,7 +234,7 @@ pub fn Buckets() -> Element {
                                     onchange: move |e| {
                                         if let Ok(new_size) = e.value().parse::<usize>() {
                                             page_size.set(new_size);
-                                            current_page.set(0); // Reset to first page
+                                            cursor.write().reset(); // Reset to first page
                                         }
                                     },
                                     option { value: "10", "10" }
@@ -228,13 +251,21 @@ pub fn Buckets() -> Element {
                         }
                     }
                     GithubStarAction {},
+                    if let Some(error) = error_message.read().as_ref() {
+                        div {
+                            class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded break-words",
+                            "{error}"
+                        }
+                    }
+                    TransferQueue { account: Some(account), bucket: None }
+                    TransferSettings {}
                     BucketsTable { 
+                        account,
                         buckets: buckets.read().clone(), 
                         bucket_to_delete: bucket_to_delete.clone(), 
                         refresh_buckets: refresh_buckets.clone(),
-                        current_page: current_page.clone(),
-                        page_size: page_size.clone(),
-                        total_buckets: total_buckets.clone(),
+                        cursor: cursor.clone(),
+                        error_message,
                     }
                 }
             }
@@ -242,7 +273,10 @@ pub fn Buckets() -> Element {
 }
 
 #[component]
-fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>, refresh_buckets: Signal<bool>, current_page: Signal<usize>, page_size: Signal<usize>, total_buckets: Signal<usize>) -> Element {
+fn BucketsTable(account: i64, buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>, refresh_buckets: Signal<bool>, mut cursor: Signal<PageCursor>, mut error_message: Signal<Option<String>>) -> Element {
+    let buckets_count = buckets.len();
+    // bucket under the files dragged from the desktop
+    let mut drop_target = use_signal(|| None as Option<String>);
     rsx! {
     div { class: "w-full overflow-hidden rounded-lg shadow-xs",
         div { class: "w-full overflow-x-auto",
@@ -258,8 +292,31 @@ fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>,
                 tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
                     {buckets.into_iter().map(|bck| {
                         let bck_for_delete = bck.clone();
+                        let bucket_for_target = bck.name.clone();
+                        let bucket_for_drop = bck.name.clone();
+                        let is_drop_target = drop_target.read().as_deref() == Some(bck.name.as_str());
                         rsx!(
-                        tr { class: "text-gray-700 dark:text-gray-400",
+                        tr {
+                            class: if is_drop_target { "text-gray-700 bg-purple-100 dark:text-gray-400 dark:bg-purple-900" } else { "text-gray-700 dark:text-gray-400" },
+                            title: "Drop files or folders here to upload them into the bucket",
+                            ondragover: move |e| {
+                                e.prevent_default();
+                                if drop_target.peek().as_deref() != Some(bucket_for_target.as_str()) {
+                                    drop_target.set(Some(bucket_for_target.clone()));
+                                }
+                            },
+                            ondragleave: move |_| drop_target.set(None),
+                            ondrop: move |e| {
+                                e.prevent_default();
+                                drop_target.set(None);
+                                let paths = dropped_paths(&e);
+                                if !paths.is_empty() {
+                                    let items = TRANSFER_OPTIONS.peek().apply(items_for_upload(&paths, &bucket_for_drop, ""));
+                                    if let Err(e) = queue_uploads(account, items) {
+                                        error_message.set(Some(e));
+                                    }
+                                }
+                            },
                                 td { class: "px-4 py-3",
                                     div { class: "flex items-center text-sm",
                                         div { class: "relative hidden w-8 h-8 mr-3 rounded-full md:block",
@@ -272,7 +329,9 @@ fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>,
                                             div { class: "absolute inset-0 rounded-full shadow-inner", aria_hidden: "true" }
                                         }
                                         div {
-                                            p { class: "font-semibold", "{bck.name}" }
+                                            p { class: "font-semibold",
+                                                Link { to: Route::Objects { account, bucket: bck.name.clone(), prefix: String::new(), selected: String::new() }, "{bck.name}" }
+                                            }
                                         }
                                     }
                                 }
@@ -325,14 +384,11 @@ fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>,
         // Pagination
         div {
             class: "grid px-4 py-3 text-xs font-semibold tracking-wide text-gray-500 uppercase border-t dark:border-gray-700 bg-gray-50 sm:grid-cols-9 dark:text-gray-400 dark:bg-gray-800",
-            span { class: "flex items-center col-span-3", 
+            span { class: "flex items-center col-span-3",
                 {
-                    let current = *current_page.read();
-                    let size = *page_size.read();
-                    let total = *total_buckets.read();
-                    let start = if total > 0 { current * size + 1 } else { 0 };
-                    let end = std::cmp::min((current + 1) * size, total);
-                    format!("Showing {}-{} of {}", start, end, total)
+                    let count = buckets_count;
+                    let page = cursor.read().page() + 1;
+                    format!("Page {} ({} buckets)", page, count)
                 }
             }
             span { class: "col-span-2" }
@@ -343,12 +399,9 @@ fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>,
                             button {
                                 class: "px-3 py-1 rounded-md rounded-l-lg focus:outline-none focus:shadow-outline-purple disabled:opacity-50 disabled:cursor-not-allowed",
                                 aria_label: "Previous",
-                                disabled: *current_page.read() == 0,
+                                disabled: !cursor.read().has_previous(),
                                 onclick: move |_| {
-                                    let current = *current_page.read();
-                                    if current > 0 {
-                                        current_page.set(current - 1);
-                                    }
+                                    cursor.write().previous();
                                 },
                                 svg {
                                     class: "w-4 h-4 fill-current",
@@ -361,66 +414,13 @@ fn BucketsTable(buckets: Vec<Bucket>, bucket_to_delete: Signal<Option<Bucket>>,
                                 }
                             }
                         }
-                        {
-                            // Calculate page numbers to display
-                            let current = *current_page.read();
-                            let size = *page_size.read();
-                            let total = *total_buckets.read();
-                            let total_pages = if total > 0 { ((total as f64) / (size as f64)).ceil() as usize } else { 1 };
-                            
-                            // Calculate start and end pages (max 7 pages)
-                            let max_visible = 7;
-                            let half_visible = max_visible / 2; // 3
-                            
-                            let (start_page, end_page) = if total_pages <= max_visible {
-                                (0, total_pages)
-                            } else if current < half_visible {
-                                (0, max_visible)
-                            } else if current >= total_pages - half_visible {
-                                (total_pages - max_visible, total_pages)
-                            } else {
-                                (current - half_visible, current + half_visible + 1)
-                            };
-                            
-                            let current_page_signal = current_page.clone();
-                            
-                            (start_page..end_page).map(move |page| {
-                                let is_current = page == current;
-                                let mut page_signal = current_page_signal.clone();
-                                rsx!(
-                                    li {
-                                        button {
-                                            class: if is_current {
-                                                "px-3 py-1 text-white transition-colors duration-150 bg-purple-600 border border-r-0 border-purple-600 rounded-md focus:outline-none focus:shadow-outline-purple"
-                                            } else {
-                                                "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple hover:bg-purple-100 dark:hover:bg-purple-900"
-                                            },
-                                            onclick: move |_| page_signal.set(page),
-                                            "{page + 1}"
-                                        }
-                                    }
-                                )
-                            })
-                        }
                         li {
                             button {
                                 class: "px-3 py-1 rounded-md rounded-r-lg focus:outline-none focus:shadow-outline-purple disabled:opacity-50 disabled:cursor-not-allowed",
                                 aria_label: "Next",
-                                disabled: {
-                                    let current = *current_page.read();
-                                    let size = *page_size.read();
-                                    let total = *total_buckets.read();
-                                    let max_page = if total > 0 { (total - 1) / size } else { 0 };
-                                    current >= max_page
-                                },
+                                disabled: !cursor.read().has_next(),
                                 onclick: move |_| {
-                                    let current = *current_page.read();
-                                    let size = *page_size.read();
-                                    let total = *total_buckets.read();
-                                    let max_page = if total > 0 { (total - 1) / size } else { 0 };
-                                    if current < max_page {
-                                        current_page.set(current + 1);
-                                    }
+                                    cursor.write().advance();
                                 },
                                 svg {
                                     class: "w-4 h-4 fill-current",
@@ -0,0 +1,651 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
+use crate::components::{TransferQueue, TransferSettings};
+use crate::model::cross_account::CrossAccountTransfer;
+use crate::model::local_data_item::LocalDataItem;
+use crate::model::local_selected_item::items_for_upload;
+use crate::model::navigation_state::NavigationState;
+use crate::model::pane_history::PaneHistory;
+use crate::model::s3_data_item::S3DataItem;
+use crate::model::s3_uri::{join_key, local_path, relative_key, Location};
+use crate::repositories::bookmark_repo::{add_bookmark, delete_bookmark, fetch_bookmarks, Bookmark};
+use crate::services::s3_data_fetcher::S3DataFetcher;
+use crate::state::{
+    account_fetcher, db, first_open_account_id, queue_cross_account_transfers, queue_downloads, queue_uploads,
+    TransferDirection, TransferState, OPEN_ACCOUNTS, TRANSFERS, TRANSFER_OPTIONS,
+};
+use crate::utils::get_home_dir;
+
+/// Side of the commander, the copy goes from the active one to the other
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pane {
+    Left,
+    Right,
+}
+
+impl Pane {
+    fn other(self) -> Pane {
+        match self {
+            Pane::Left => Pane::Right,
+            Pane::Right => Pane::Left,
+        }
+    }
+}
+
+/// What a pane browses, the local disk or the buckets of one of the open accounts
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PaneSource {
+    Local,
+    Account(i64),
+}
+
+impl PaneSource {
+    /// Value of the source in the pane's select
+    fn value(self) -> String {
+        match self {
+            PaneSource::Local => "local".to_string(),
+            PaneSource::Account(id) => id.to_string(),
+        }
+    }
+
+    fn parse(value: &str) -> PaneSource {
+        value.parse().map(PaneSource::Account).unwrap_or(PaneSource::Local)
+    }
+}
+
+/// State of a side, it keeps the history of both kinds of sources while switching between them
+#[derive(Clone, Copy, PartialEq)]
+struct PaneState {
+    source: Signal<PaneSource>,
+    local_history: Signal<PaneHistory<PathBuf>>,
+    s3_history: Signal<PaneHistory<NavigationState>>,
+    selected: Signal<BTreeSet<String>>,
+}
+
+impl PaneState {
+    fn new(source: PaneSource) -> Self {
+        PaneState {
+            source: Signal::new(source),
+            local_history: Signal::new(PaneHistory::new(get_home_dir())),
+            s3_history: Signal::new(PaneHistory::new(NavigationState::new(None, None))),
+            selected: Signal::new(BTreeSet::new()),
+        }
+    }
+}
+
+/// Finished transfers in the directions, the pane they copy into is reloaded when it changes
+fn finished_transfers(directions: &[TransferDirection]) -> usize {
+    TRANSFERS
+        .read()
+        .iter()
+        .filter(|transfer| directions.contains(&transfer.direction) && transfer.state == TransferState::Done)
+        .count()
+}
+
+/// Objects under the keys, the directories are copied with everything under them
+async fn expand_keys(fetcher: &S3DataFetcher, bucket: &str, keys: Vec<String>) -> Result<Vec<String>, String> {
+    let mut objects = Vec::new();
+    for key in keys {
+        if !key.ends_with('/') {
+            objects.push(key);
+            continue;
+        }
+        let items = fetcher
+            .list_all_objects(bucket, Some(key.clone()))
+            .await
+            .map_err(|e| format!("Failed to list '{}': {}", key, e))?;
+        objects.extend(items.into_iter().filter(|item| !item.path.ends_with('/')).map(|item| item.path));
+    }
+    Ok(objects)
+}
+
+/// Location of the S3 pane, `None` while it shows the buckets
+fn s3_location(navigation: &NavigationState) -> Option<Location> {
+    navigation.current_bucket.as_ref().map(|bucket| Location::S3 {
+        bucket: bucket.clone(),
+        key: navigation.current_prefix.clone().unwrap_or_default(),
+    })
+}
+
+/// Prefix above the given one, `a/b/` is below `a/` which is below the bucket root
+fn parent_prefix(prefix: &str) -> String {
+    let trimmed = prefix.trim_end_matches('/');
+    match trimmed.rfind('/') {
+        Some(index) => trimmed[..=index].to_string(),
+        None => String::new(),
+    }
+}
+
+/// Two browsers side by side, each one on the local disk or on one of the open accounts.
+/// The selection of the active pane is copied to the location of the other one with F5.
+/// Each pane keeps its own history.
+#[component]
+pub fn Commander() -> Element {
+    let left = use_hook(|| PaneState::new(PaneSource::Local));
+    let right = use_hook(|| PaneState::new(first_open_account_id().map(PaneSource::Account).unwrap_or(PaneSource::Local)));
+    let active = use_signal(|| Pane::Left);
+    let bookmarks = use_signal(|| fetch_bookmarks(db()));
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut info_message = use_signal(|| None as Option<String>);
+
+    let side = move |pane: Pane| match pane {
+        Pane::Left => left,
+        Pane::Right => right,
+    };
+
+    let mut copy = move || {
+        error_message.set(None);
+        info_message.set(None);
+        let active = *active.peek();
+        let (mut from, to) = (side(active), side(active.other()));
+        let keys: Vec<String> = from.selected.peek().iter().cloned().collect();
+        if keys.is_empty() {
+            error_message.set(Some("Select what to copy first".to_string()));
+            return;
+        }
+        let source = *from.source.peek();
+        let destination = *to.source.peek();
+        let origin = from.s3_history.peek().current().clone();
+        let target = to.s3_history.peek().current().clone();
+        match (source, destination) {
+            (PaneSource::Local, PaneSource::Local) => {
+                error_message.set(Some("Copying between local directories isn't supported, open an account in one of the panes".to_string()));
+            }
+            (PaneSource::Local, PaneSource::Account(account)) => {
+                let Some(bucket) = target.current_bucket else {
+                    error_message.set(Some("Open a bucket in the other pane to copy the files into".to_string()));
+                    return;
+                };
+                let paths: Vec<PathBuf> = keys.iter().map(PathBuf::from).collect();
+                let items = TRANSFER_OPTIONS.peek().apply(items_for_upload(&paths, &bucket, &target.current_prefix.unwrap_or_default()));
+                let message = format!("Queued {} uploads to s3://{}", items.len(), bucket);
+                match queue_uploads(account, items) {
+                    Ok(()) => info_message.set(Some(message)),
+                    Err(e) => error_message.set(Some(e)),
+                }
+                from.selected.write().clear();
+            }
+            (PaneSource::Account(account), destination) => {
+                let Some(bucket) = origin.current_bucket else {
+                    error_message.set(Some("Open a bucket to copy its objects".to_string()));
+                    return;
+                };
+                let prefix = origin.current_prefix.unwrap_or_default();
+                let dir = to.local_history.peek().current().clone();
+                if let PaneSource::Account(_) = destination {
+                    if target.current_bucket.is_none() {
+                        error_message.set(Some("Open a bucket in the other pane to copy the objects into".to_string()));
+                        return;
+                    }
+                }
+                from.selected.write().clear();
+                spawn(async move {
+                    let Some(fetcher) = account_fetcher(account) else {
+                        error_message.set(Some("The account doesn't exist anymore".to_string()));
+                        return;
+                    };
+                    let objects = match expand_keys(&fetcher, &bucket, keys).await {
+                        Ok(objects) => objects,
+                        Err(e) => {
+                            error_message.set(Some(e));
+                            return;
+                        }
+                    };
+                    match destination {
+                        PaneSource::Local => {
+                            let mut downloads = Vec::new();
+                            let mut refused = Vec::new();
+                            for key in objects {
+                                match local_path(&dir, relative_key(&prefix, &key)) {
+                                    Ok(path) => downloads.push((bucket.clone(), key, path)),
+                                    Err(e) => refused.push(e),
+                                }
+                            }
+                            if !refused.is_empty() {
+                                error_message.set(Some(refused.join("\n")));
+                            }
+                            let message = format!("Queued {} downloads to {}", downloads.len(), dir.display());
+                            match queue_downloads(account, downloads, TRANSFER_OPTIONS.peek().encryption.clone()) {
+                                Ok(()) => info_message.set(Some(message)),
+                                Err(e) => error_message.set(Some(e)),
+                            }
+                        }
+                        PaneSource::Account(destination_account) => {
+                            let destination_bucket = target.current_bucket.unwrap_or_default();
+                            let destination_prefix = target.current_prefix.unwrap_or_default();
+                            info_message.set(Some(format!("Queued {} copies to s3://{}", objects.len(), destination_bucket)));
+                            queue_cross_account_transfers(
+                                objects
+                                    .iter()
+                                    .map(|key| {
+                                        let destination_key = join_key(&destination_prefix, relative_key(&prefix, key));
+                                        CrossAccountTransfer::new(account, &bucket, key, destination_account, &destination_bucket, &destination_key)
+                                    })
+                                    .collect(),
+                            );
+                        }
+                    }
+                });
+            }
+        }
+    };
+
+    let copy_label = match *active.read() {
+        Pane::Left => "Copy → (F5)",
+        Pane::Right => "← Copy (F5)",
+    };
+
+    rsx!(
+        main { class: "h-full overflow-y-auto outline-none",
+            tabindex: "0",
+            onkeydown: move |e| {
+                if e.key() == Key::F5 {
+                    e.prevent_default();
+                    copy();
+                }
+            },
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200", "Commander" }
+                    button {
+                        class: "px-4 py-2 text-sm font-medium text-white bg-purple-600 rounded-lg hover:bg-purple-700 focus:outline-none focus:ring",
+                        onclick: move |_| copy(),
+                        "{copy_label}"
+                    }
+                }
+                if let Some(error) = error_message.read().as_ref() {
+                    div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded", "{error}" }
+                }
+                if let Some(info) = info_message.read().as_ref() {
+                    div { class: "mb-4 p-3 bg-green-100 border border-green-400 text-green-700 rounded", "{info}" }
+                }
+                TransferQueue { account: None, bucket: None }
+                TransferSettings {}
+                div { class: "grid gap-6 mb-8 md:grid-cols-2",
+                    CommanderPane { pane: Pane::Left, state: left, active, bookmarks }
+                    CommanderPane { pane: Pane::Right, state: right, active, bookmarks }
+                }
+            }
+        }
+    )
+}
+
+/// A side of the commander with the choice of what it browses
+#[component]
+fn CommanderPane(pane: Pane, state: PaneState, active: Signal<Pane>, bookmarks: Signal<Vec<Bookmark>>) -> Element {
+    let mut source = state.source;
+    let mut selected = state.selected;
+    let mut s3_history = state.s3_history;
+    let current = *source.read();
+    // the account of the pane may have been closed in the sidebar
+    let is_open = match current {
+        PaneSource::Local => true,
+        PaneSource::Account(id) => OPEN_ACCOUNTS.read().iter().any(|account| account.id == id),
+    };
+
+    rsx!(
+        div { class: pane_class(*active.read() == pane),
+            onclick: move |_| active.set(pane),
+            select {
+                class: "w-full px-3 py-1 mb-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                value: "{current.value()}",
+                onchange: move |e| {
+                    // the buckets of another account are browsed from their list
+                    s3_history.set(PaneHistory::new(NavigationState::new(None, None)));
+                    source.set(PaneSource::parse(&e.value()));
+                    selected.write().clear();
+                },
+                option { value: "local", "Local disk" }
+                for account in OPEN_ACCOUNTS.read().iter() {
+                    option { value: "{account.id}", "{account.name}" }
+                }
+                if !is_open {
+                    option { value: "{current.value()}", "Closed account" }
+                }
+            }
+            match current {
+                PaneSource::Local => rsx!( LocalPane { history: state.local_history, selected, bookmarks } ),
+                PaneSource::Account(account) if is_open => rsx!( S3Pane { account, history: s3_history, selected, bookmarks } ),
+                PaneSource::Account(_) => rsx!( p { class: "text-sm text-gray-700 dark:text-gray-400", "Open the account in the sidebar or choose another one." } ),
+            }
+        }
+    )
+}
+
+/// Navigation of a pane: history, parent directory and the bookmarks of its kind
+#[component]
+fn PaneToolbar(
+    label: String,
+    location: Option<Location>,
+    can_go_back: bool,
+    can_go_forward: bool,
+    on_back: EventHandler<()>,
+    on_forward: EventHandler<()>,
+    on_up: EventHandler<()>,
+    on_open: EventHandler<Location>,
+    is_local: bool,
+    bookmarks: Signal<Vec<Bookmark>>,
+) -> Element {
+    let shown: Vec<Bookmark> = bookmarks
+        .read()
+        .iter()
+        .filter(|bookmark| matches!(bookmark.location, Location::Local(_)) == is_local)
+        .cloned()
+        .collect();
+    let is_bookmarked = location.as_ref().is_some_and(|location| shown.iter().any(|bookmark| bookmark.location == *location));
+    let nav_button = "px-2 py-1 text-sm rounded-md hover:bg-purple-100 disabled:opacity-50 disabled:cursor-not-allowed dark:hover:bg-purple-900";
+
+    rsx!(
+        div { class: "flex items-center mb-2 space-x-1 text-gray-700 dark:text-gray-400",
+            button { class: nav_button, disabled: !can_go_back, title: "Back", onclick: move |_| on_back.call(()), "◀" }
+            button { class: nav_button, disabled: !can_go_forward, title: "Forward", onclick: move |_| on_forward.call(()), "▶" }
+            button { class: nav_button, title: "Up", onclick: move |_| on_up.call(()), "▲" }
+            span { class: "flex-1 px-2 text-sm font-semibold break-all", "{label}" }
+            button {
+                class: nav_button,
+                disabled: location.is_none() || is_bookmarked,
+                title: "Bookmark this location",
+                onclick: move |_| {
+                    if let Some(location) = &location {
+                        add_bookmark(db(), location);
+                        bookmarks.set(fetch_bookmarks(db()));
+                    }
+                },
+                if is_bookmarked { "★" } else { "☆" }
+            }
+        }
+        if !shown.is_empty() {
+            div { class: "flex flex-wrap mb-2 text-xs",
+                for bookmark in shown {
+                    span { key: "{bookmark.id}", class: "inline-flex items-center px-2 py-1 mb-1 mr-1 text-purple-700 bg-purple-100 rounded-full dark:text-purple-100 dark:bg-purple-700",
+                        button {
+                            class: "hover:underline",
+                            onclick: {
+                                let location = bookmark.location.clone();
+                                move |_| on_open.call(location.clone())
+                            },
+                            "{bookmark.location}"
+                        }
+                        button {
+                            class: "ml-1",
+                            title: "Remove the bookmark",
+                            onclick: move |_| {
+                                delete_bookmark(db(), bookmark.id);
+                                bookmarks.set(fetch_bookmarks(db()));
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
+
+/// Frame of a pane, highlighted while it's the active one
+fn pane_class(active: bool) -> &'static str {
+    if active {
+        "p-4 bg-white rounded-lg shadow-xs ring-2 ring-purple-500 dark:bg-gray-800"
+    } else {
+        "p-4 bg-white rounded-lg shadow-xs dark:bg-gray-800"
+    }
+}
+
+#[component]
+fn LocalPane(history: Signal<PaneHistory<PathBuf>>, selected: Signal<BTreeSet<String>>, bookmarks: Signal<Vec<Bookmark>>) -> Element {
+    let downloaded = use_memo(|| finished_transfers(&[TransferDirection::Download]));
+    let listing = use_memo(move || {
+        let _ = downloaded.read();
+        LocalDataItem::list_directory(history.read().current()).map_err(|e| e.to_string())
+    });
+    let mut navigate = move |path: PathBuf| {
+        history.write().navigate(path);
+        selected.write().clear();
+    };
+
+    let (dir, can_go_back, can_go_forward) = {
+        let history = history.read();
+        (history.current().clone(), history.can_go_back(), history.can_go_forward())
+    };
+
+    rsx!(
+        PaneToolbar {
+            label: dir.display().to_string(),
+            location: Some(Location::Local(dir.clone())),
+            can_go_back,
+            can_go_forward,
+            on_back: move |_| {
+                history.write().back();
+                selected.write().clear();
+            },
+            on_forward: move |_| {
+                history.write().forward();
+                selected.write().clear();
+            },
+            on_up: {
+                let dir = dir.clone();
+                move |_| {
+                    if let Some(parent) = dir.parent() {
+                        navigate(parent.to_path_buf());
+                    }
+                }
+            },
+            on_open: move |location| {
+                if let Location::Local(path) = location {
+                    navigate(path);
+                }
+            },
+            is_local: true,
+            bookmarks,
+        }
+        match &*listing.read() {
+            Err(error) => rsx!( p { class: "text-sm text-red-600", "{error}" } ),
+            Ok(items) => rsx!(
+                PaneTable {
+                    {items.iter().map(|item| {
+                        let path = item.path.clone();
+                        let path_for_open = PathBuf::from(&item.path);
+                        let is_selected = selected.read().contains(&path);
+                        rsx!(
+                            tr { key: "{item.path}", class: "text-gray-700 dark:text-gray-400",
+                                td { class: "px-2 py-1",
+                                    input {
+                                        r#type: "checkbox",
+                                        class: "form-checkbox h-4 w-4 text-purple-600",
+                                        checked: is_selected,
+                                        onchange: move |e| {
+                                            if e.checked() {
+                                                selected.write().insert(path.clone());
+                                            } else {
+                                                selected.write().remove(&path);
+                                            }
+                                        },
+                                    }
+                                }
+                                td { class: "px-2 py-1 text-sm",
+                                    if item.is_directory {
+                                        button {
+                                            class: "font-semibold hover:underline",
+                                            onclick: move |_| navigate(path_for_open.clone()),
+                                            "{item.name}/"
+                                        }
+                                    } else {
+                                        "{item.name}"
+                                    }
+                                }
+                                td { class: "px-2 py-1 text-sm", "{item.size}" }
+                                td { class: "px-2 py-1 text-sm", "{item.file_type}" }
+                            }
+                        )
+                    })}
+                }
+            ),
+        }
+    )
+}
+
+#[component]
+fn S3Pane(
+    account: i64,
+    history: Signal<PaneHistory<NavigationState>>,
+    selected: Signal<BTreeSet<String>>,
+    bookmarks: Signal<Vec<Bookmark>>,
+) -> Element {
+    let uploaded = use_memo(|| finished_transfers(&[TransferDirection::Upload, TransferDirection::CrossAccount]));
+    let mut listing = use_signal(|| None as Option<Result<Vec<S3DataItem>, String>>);
+    let mut listing_task = use_signal(|| None as Option<Task>);
+
+    use_effect(use_reactive!(|account| {
+        let _ = uploaded.read();
+        let navigation = history.read().current().clone();
+        // the listing of the previous location must not replace the new one
+        if let Some(task) = listing_task.write().take() {
+            task.cancel();
+        }
+        listing.set(None);
+        let task = spawn(async move {
+            let Some(fetcher) = account_fetcher(account) else {
+                listing.set(Some(Err("The account doesn't exist anymore".to_string())));
+                return;
+            };
+            let result = match navigation.current_bucket {
+                None => fetcher.list_buckets(None, None).await.map(|page| page.items),
+                Some(bucket) => {
+                    let prefix = navigation.current_prefix.clone().filter(|prefix| !prefix.is_empty());
+                    fetcher.list_objects(&bucket, prefix).await
+                }
+            };
+            // the placeholder of the directory itself isn't listed in it
+            let current = navigation.current_prefix.unwrap_or_default();
+            listing.set(Some(
+                result
+                    .map(|items| items.into_iter().filter(|item| current.is_empty() || item.path != current).collect())
+                    .map_err(|e| e.to_string()),
+            ));
+        });
+        listing_task.set(Some(task));
+    }));
+
+    let mut navigate = move |navigation: NavigationState| {
+        history.write().navigate(navigation);
+        selected.write().clear();
+    };
+
+    let (navigation, can_go_back, can_go_forward) = {
+        let history = history.read();
+        (history.current().clone(), history.can_go_back(), history.can_go_forward())
+    };
+    let location = s3_location(&navigation);
+    let label = location.as_ref().map(|location| location.to_string()).unwrap_or("Buckets".to_string());
+    let bucket = navigation.current_bucket.clone();
+
+    rsx!(
+        PaneToolbar {
+            label,
+            location,
+            can_go_back,
+            can_go_forward,
+            on_back: move |_| {
+                history.write().back();
+                selected.write().clear();
+            },
+            on_forward: move |_| {
+                history.write().forward();
+                selected.write().clear();
+            },
+            on_up: {
+                let navigation = navigation.clone();
+                move |_| {
+                    let prefix = navigation.current_prefix.clone().unwrap_or_default();
+                    let up = if prefix.is_empty() {
+                        NavigationState::new(None, None)
+                    } else {
+                        NavigationState::new(navigation.current_bucket.clone(), Some(parent_prefix(&prefix)))
+                    };
+                    navigate(up);
+                }
+            },
+            on_open: move |location| {
+                if let Location::S3 { bucket, key } = location {
+                    navigate(NavigationState::new(Some(bucket), Some(key)));
+                }
+            },
+            is_local: false,
+            bookmarks,
+        }
+        match &*listing.read() {
+            None => rsx!( p { class: "text-sm text-gray-700 dark:text-gray-400", "Loading..." } ),
+            Some(Err(error)) => rsx!( p { class: "text-sm text-red-600", "{error}" } ),
+            Some(Ok(items)) => rsx!(
+                PaneTable {
+                    {items.iter().map(|item| {
+                        let key = item.path.clone();
+                        let target = if item.is_bucket {
+                            NavigationState::new(Some(item.name.clone()), None)
+                        } else {
+                            NavigationState::new(bucket.clone(), Some(item.path.clone()))
+                        };
+                        let is_selected = selected.read().contains(&key);
+                        let size = item.size.map(|size| size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default());
+                        rsx!(
+                            tr { key: "{item.path}", class: "text-gray-700 dark:text-gray-400",
+                                td { class: "px-2 py-1",
+                                    if !item.is_bucket {
+                                        input {
+                                            r#type: "checkbox",
+                                            class: "form-checkbox h-4 w-4 text-purple-600",
+                                            checked: is_selected,
+                                            onchange: move |e| {
+                                                if e.checked() {
+                                                    selected.write().insert(key.clone());
+                                                } else {
+                                                    selected.write().remove(&key);
+                                                }
+                                            },
+                                        }
+                                    }
+                                }
+                                td { class: "px-2 py-1 text-sm",
+                                    if item.is_bucket || item.is_directory {
+                                        button {
+                                            class: "font-semibold hover:underline",
+                                            onclick: move |_| navigate(target.clone()),
+                                            if item.is_directory { "{item.name}/" } else { "{item.name}" }
+                                        }
+                                    } else {
+                                        "{item.name}"
+                                    }
+                                }
+                                td { class: "px-2 py-1 text-sm", {size} }
+                                td { class: "px-2 py-1 text-sm", "{item.file_type}" }
+                            }
+                        )
+                    })}
+                }
+            ),
+        }
+    )
+}
+
+/// Listing of a pane, the rows are passed as children
+#[component]
+fn PaneTable(children: Element) -> Element {
+    rsx!(
+        div { class: "w-full overflow-x-auto max-h-[32rem] overflow-y-auto",
+            table { class: "w-full whitespace-no-wrap",
+                thead {
+                    tr {
+                        class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                        th { class: "px-2 py-2", "" }
+                        th { class: "px-2 py-2", "Name" }
+                        th { class: "px-2 py-2", "Size" }
+                        th { class: "px-2 py-2", "Type" }
+                    }
+                }
+                tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800", {children} }
+            }
+        }
+    )
+}
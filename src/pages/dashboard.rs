@@ -1,160 +1,408 @@
-use std::arch::aarch64::uint32x2_t;
-use std::time::Duration;
 use dioxus::prelude::*;
-use dioxus::hooks::{use_coroutine, use_signal};
-use crate::components::{AccountCard, ClientsCard, ContactsCard, GithubStarAction, SalesCard};
+use aws_sdk_s3::primitives::DateTime;
+use humansize::{file_size_opts, FileSize};
+use crate::components::{AccountCard, ClientsCard, ContactsCard, CostCard, GithubStarAction, PriceTableModal, SalesCard};
+use crate::model::analytics::{age_thresholds, BucketAnalytics, Breakdown};
+use crate::model::pricing::{estimate_cost, format_cost, project_transition, CostBreakdown, PriceTable, Transition};
+use crate::model::storage_class::STORAGE_CLASSES;
+use crate::repositories::analytics_repo::{delete_analytics, fetch_analytics, save_analytics};
+use crate::repositories::pricing_repo::fetch_price_table;
+use crate::services::analytics::analyze_bucket;
+use crate::state::{account_fetcher, db, OPEN_ACCOUNTS};
+use crate::Route;
 
-/// Home page
+fn format_size(size: u64) -> String {
+    size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()
+}
+
+/// Share of the total in percents, used as the width of the bars
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Home page, storage analytics of the buckets of one of the open accounts
 #[component]
 pub fn Dashboard() -> Element {
-    rsx!(
-        main { class: "h-full overflow-y-auto",
-                div { class: "container px-6 mx-auto grid",
-                    h2 { class: "my-6 text-2xl font-semibold text-gray-700 dark:text-gray-200",
-                        "Dashboard"
+    let mut chosen_account = use_signal(|| None as Option<i64>);
+    // the first open account until another one is chosen, or when the chosen one is closed
+    let account = use_memo(move || {
+        let open = OPEN_ACCOUNTS.read();
+        chosen_account
+            .read()
+            .filter(|id| open.iter().any(|account| account.id == *id))
+            .or(open.first().map(|account| account.id))
+    });
+    let mut analytics = use_signal(Vec::<BucketAnalytics>::new);
+    let mut selected = use_signal(|| None as Option<String>);
+    let mut progress = use_signal(|| None as Option<String>);
+    let mut errors = use_signal(Vec::<String>::new);
+    let prices = use_signal(|| fetch_price_table(db()));
+    let mut show_prices = use_signal(|| false);
+
+    use_effect(move || {
+        // reloaded when another account is chosen
+        let cached = (*account.read()).map(|account_id| fetch_analytics(db(), account_id)).unwrap_or_default();
+        analytics.set(cached);
+        selected.set(None);
+    });
+
+    let refresh = move |_| {
+        let Some(account_id) = *account.peek() else {
+            errors.set(vec!["Open an account in the sidebar first".to_string()]);
+            return;
+        };
+        let Some(fetcher) = account_fetcher(account_id) else {
+            errors.set(vec!["The account doesn't exist anymore".to_string()]);
+            return;
+        };
+        errors.set(Vec::new());
+        progress.set(Some("Listing buckets...".to_string()));
+        spawn(async move {
+            let buckets = match fetcher.list_all_buckets().await {
+                Ok(buckets) => buckets,
+                Err(e) => {
+                    errors.write().push(format!("Failed to list buckets: {}", e));
+                    progress.set(None);
+                    return;
+                }
+            };
+            // the buckets removed since the last refresh
+            let names: Vec<String> = buckets.into_iter().map(|bucket| bucket.name).collect();
+            for gone in analytics.read().iter().filter(|cached| !names.contains(&cached.bucket)) {
+                delete_analytics(db(), account_id, &gone.bucket);
+            }
+            analytics.write().retain(|cached| names.contains(&cached.bucket));
+
+            for bucket in names {
+                let on_progress = |count| {
+                    // signals are Copy, the copy keeps the closure `Fn`
+                    let mut progress = progress;
+                    progress.set(Some(format!("Analyzing {}: {} objects", bucket, count)));
+                };
+                match analyze_bucket(&fetcher, &bucket, on_progress).await {
+                    Ok(result) => {
+                        save_analytics(db(), account_id, &result);
+                        let mut cached = analytics.write();
+                        match cached.iter_mut().find(|cached| cached.bucket == bucket) {
+                            Some(entry) => *entry = result,
+                            None => {
+                                cached.push(result);
+                                cached.sort_by(|a, b| a.bucket.cmp(&b.bucket));
+                            }
+                        }
                     }
-                    GithubStarAction {},
-                    Cards {},
-                    Table {}
+                    Err(e) => errors.write().push(format!("Failed to analyze bucket '{}': {}", bucket, e)),
                 }
             }
-    )
-}
+            progress.set(None);
+        });
+    };
 
-#[component]
-fn Cards() -> Element {
-    let clients = use_signal(|| None as Option<u32>);
-    let loading = use_signal(|| true);
-
-    use_coroutine({
-        let mut clients = clients.clone();
-        let mut loading = loading.clone();
-        move |_: UnboundedReceiver<u32>| async move {
-            // Simulate fetching from a remote source
-            // Replace with real fetch later using surf, reqwest, etc.
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            clients.set(Some(8421)); // Example fetched number
-            loading.set(false);
-        }
-    });
+    let all = analytics.read().clone();
+    let computed = !all.is_empty();
+    let total_size = computed.then(|| all.iter().map(|a| a.total_size).sum::<u64>());
+    let object_count = computed.then(|| all.iter().map(|a| a.object_count).sum::<u64>());
+    let incomplete_size = computed.then(|| all.iter().map(|a| a.incomplete_upload_size()).sum::<u64>());
+    let costs: Vec<f64> = all.iter().map(|a| estimate_cost(a, &prices.read()).monthly_cost).collect();
+    let monthly_cost = computed.then(|| costs.iter().sum::<f64>());
+    let largest_bucket = all.iter().map(|a| a.total_size).max().unwrap_or_default();
+    let current = selected
+        .read()
+        .as_ref()
+        .and_then(|bucket| all.iter().find(|a| &a.bucket == bucket))
+        .or(all.first())
+        .cloned();
 
     rsx!(
-        div {
-            class: "grid gap-6 mb-8 md:grid-cols-2 xl:grid-cols-4",
-            // Card 1
-            ClientsCard {}
-            // Card 2
-            AccountCard {}
-            // Card 3
-            SalesCard {}
-            // Card 4
-            ContactsCard {}
-}
-    )
-}
+        main { class: "h-full overflow-y-auto",
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
+                        "Dashboard"
+                    }
+                    div { class: "space-x-2",
+                        select {
+                            class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            title: "Account analyzed",
+                            onchange: move |e| chosen_account.set(e.value().parse().ok()),
+                            for open in OPEN_ACCOUNTS.read().iter() {
+                                option { value: "{open.id}", selected: Some(open.id) == *account.read(), "{open.name}" }
+                            }
+                        }
+                        button {
+                            class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded-lg hover:bg-purple-100 dark:hover:bg-purple-900",
+                            onclick: move |_| show_prices.set(true),
+                            "Edit prices"
+                        }
+                        button {
+                            class: "px-4 py-2 text-sm font-medium text-white bg-purple-600 rounded-lg hover:bg-purple-700 focus:outline-none disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: progress.read().is_some(),
+                            onclick: refresh,
+                            "Refresh analytics"
+                        }
+                    }
+                }
+                if *show_prices.read() {
+                    PriceTableModal { show_modal: show_prices, prices }
+                }
+                GithubStarAction {},
+                div { class: "grid gap-6 mb-8 md:grid-cols-2 xl:grid-cols-5",
+                    if let Some(account) = *account.read() {
+                        ClientsCard { key: "{account}", account }
+                    }
+                    AccountCard { total_size }
+                    CostCard { monthly_cost }
+                    SalesCard { object_count }
+                    ContactsCard { incomplete_size }
+                }
 
+                if let Some(message) = progress.read().clone() {
+                    div { class: "mb-4 p-3 bg-blue-100 border border-blue-400 text-blue-700 rounded", "{message}" }
+                }
+                for error in errors.read().clone() {
+                    div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded", "{error}" }
+                }
+                for bucket in all.iter().filter(|bucket| bucket.incomplete_uploads_error.is_some()) {
+                    div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                        "The incomplete uploads of bucket '{bucket.bucket}' couldn't be listed, they're missing from the numbers: "
+                        {bucket.incomplete_uploads_error.clone()}
+                    }
+                }
 
-#[component]
-fn Table() -> Element {
-    rsx! {
-    div { class: "w-full overflow-hidden rounded-lg shadow-xs",
-        div { class: "w-full overflow-x-auto",
-            table { class: "w-full whitespace-no-wrap",
-                thead {
-                    tr {
-                        class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
-                        th { class: "px-4 py-3", "Client" }
-                        th { class: "px-4 py-3", "Amount" }
-                        th { class: "px-4 py-3", "Status" }
-                        th { class: "px-4 py-3", "Date" }
+                if !computed {
+                    p { class: "mb-8 text-sm text-gray-700 dark:text-gray-400",
+                        "The buckets haven't been analyzed yet. Refresh the analytics to list them and see where the space goes."
                     }
                 }
-                tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
-                    tr { class: "text-gray-700 dark:text-gray-400",
-                        td { class: "px-4 py-3",
-                            div { class: "flex items-center text-sm",
-                                div { class: "relative hidden w-8 h-8 mr-3 rounded-full md:block",
-                                    img {
-                                        class: "object-cover w-full h-full rounded-full",
-                                        src: "https://images.unsplash.com/flagged/photo-1570612861542-284f4c12e75f?ixlib=rb-1.2.1&q=80&fm=jpg&crop=entropy&cs=tinysrgb&w=200&fit=max&ixid=eyJhcHBfaWQiOjE3Nzg0fQ",
-                                        alt: "",
-                                        loading: "lazy"
+
+                if computed {
+                    h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Buckets" }
+                    div { class: "w-full overflow-hidden rounded-lg shadow-xs mb-8",
+                        div { class: "w-full overflow-x-auto",
+                            table { class: "w-full whitespace-no-wrap",
+                                thead {
+                                    tr {
+                                        class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                                        th { class: "px-4 py-3", "Bucket" }
+                                        th { class: "px-4 py-3", "Objects" }
+                                        th { class: "px-4 py-3", "Size" }
+                                        th { class: "px-4 py-3 w-1/3", "" }
+                                        th { class: "px-4 py-3", "Monthly cost" }
+                                        th { class: "px-4 py-3", "Incomplete uploads" }
+                                        th { class: "px-4 py-3", "Analyzed" }
                                     }
-                                    div { class: "absolute inset-0 rounded-full shadow-inner", aria_hidden: "true" }
                                 }
-                                div {
-                                    p { class: "font-semibold", "Hans Burger" }
-                                    p { class: "text-xs text-gray-600 dark:text-gray-400", "10x Developer" }
+                                tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
+                                    {all.iter().zip(costs).map(|(bucket, cost)| {
+                                        let name = bucket.bucket.clone();
+                                        let is_current = current.as_ref().is_some_and(|c| c.bucket == bucket.bucket);
+                                        let row_class = if is_current {
+                                            "text-gray-700 dark:text-gray-400 bg-purple-50 dark:bg-gray-700 cursor-pointer"
+                                        } else {
+                                            "text-gray-700 dark:text-gray-400 cursor-pointer"
+                                        };
+                                        rsx!(
+                                            tr { class: row_class,
+                                                onclick: move |_| selected.set(Some(name.clone())),
+                                                td { class: "px-4 py-3 text-sm font-semibold", "{bucket.bucket}" }
+                                                td { class: "px-4 py-3 text-sm", "{bucket.object_count}" }
+                                                td { class: "px-4 py-3 text-sm", "{format_size(bucket.total_size)}" }
+                                                td { class: "px-4 py-3", Bar { percent: percent(bucket.total_size, largest_bucket) } }
+                                                td { class: "px-4 py-3 text-sm", "{format_cost(cost)}" }
+                                                td { class: "px-4 py-3 text-sm",
+                                                    if bucket.incomplete_uploads_error.is_some() {
+                                                        "unknown"
+                                                    } else if bucket.incomplete_uploads.is_empty() {
+                                                        "-"
+                                                    } else {
+                                                        "{bucket.incomplete_uploads.len()} ({format_size(bucket.incomplete_upload_size())})"
+                                                    }
+                                                }
+                                                td { class: "px-4 py-3 text-sm", "{DateTime::from_secs(bucket.computed_at)}" }
+                                            }
+                                        )
+                                    })}
                                 }
                             }
                         }
-                        td { class: "px-4 py-3 text-sm", "$ 863.45" }
-                        td { class: "px-4 py-3 text-xs",
-                            span {
-                                class: "px-2 py-1 font-semibold leading-tight text-green-700 bg-green-100 rounded-full dark:bg-green-700 dark:text-green-100",
-                                "Approved"
-                            }
-                        }
-                        td { class: "px-4 py-3 text-sm", "6/10/2020" }
                     }
                 }
+
+                if let (Some(account_id), Some(bucket)) = (*account.read(), current) {
+                    BucketDetails { account_id, analytics: bucket, prices: prices.read().clone() }
+                }
             }
         }
+    )
+}
 
-        // Pagination
-        div {
-            class: "grid px-4 py-3 text-xs font-semibold tracking-wide text-gray-500 uppercase border-t dark:border-gray-700 bg-gray-50 sm:grid-cols-9 dark:text-gray-400 dark:bg-gray-800",
-            span { class: "flex items-center col-span-3", "Showing 21-30 of 100" }
-            span { class: "col-span-2" }
-            span { class: "flex col-span-4 mt-2 sm:mt-auto sm:justify-end",
-                nav { aria_label: "Table navigation",
-                    ul { class: "inline-flex items-center",
-                        li {
-                            button {
-                                class: "px-3 py-1 rounded-md rounded-l-lg focus:outline-none focus:shadow-outline-purple",
-                                aria_label: "Previous",
-                                svg {
-                                    class: "w-4 h-4 fill-current",
-                                    view_box: "0 0 20 20",
-                                    path {
-                                        d: "M12.707 5.293a1 1 0 010 1.414L9.414 10l3.293 3.293a1 1 0 01-1.414 1.414l-4-4a1 1 0 010-1.414l4-4a1 1 0 011.414 0z",
-                                        clip_rule: "evenodd",
-                                        fill_rule: "evenodd"
-                                    }
-                                }
-                            }
-                        }
-                        li { button { class: "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple", "1" } }
-                        li { button { class: "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple", "2" } }
-                        li {
-                            button {
-                                class: "px-3 py-1 text-white transition-colors duration-150 bg-purple-600 border border-r-0 border-purple-600 rounded-md focus:outline-none focus:shadow-outline-purple",
-                                "3"
-                            }
+/// Charts and tables of a single bucket
+#[component]
+fn BucketDetails(account_id: i64, analytics: BucketAnalytics, prices: PriceTable) -> Element {
+    let estimate = estimate_cost(&analytics, &prices);
+    rsx!(
+        h3 { class: "mb-4 text-lg font-semibold text-gray-700 dark:text-gray-200",
+            "{analytics.bucket}"
+            if let Some(region) = &analytics.region {
+                span { class: "ml-2 text-sm font-normal text-gray-500", "({region})" }
+            }
+        }
+        div { class: "grid gap-6 mb-8 md:grid-cols-2",
+            BarChart { title: "By storage class", groups: analytics.by_storage_class.clone(), total: analytics.total_size }
+            BarChart { title: "By age", groups: analytics.by_age.clone(), total: analytics.total_size }
+            BarChart { title: "By top-level prefix", groups: analytics.by_prefix.clone(), total: analytics.total_size }
+            BarChart { title: "By extension", groups: analytics.by_extension.clone(), total: analytics.total_size }
+        }
+
+        h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200",
+            "Estimated monthly cost: {format_cost(estimate.monthly_cost)}"
+        }
+        p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+            "Storage only, requests, transfers and minimum storage durations aren't included."
+        }
+        if analytics.slices.is_empty() && analytics.object_count > 0 {
+            p { class: "mb-8 text-sm text-gray-700 dark:text-gray-400",
+                "These analytics are older than the cost estimates, refresh them to see the costs."
+            }
+        } else {
+            div { class: "grid gap-6 mb-8 md:grid-cols-2",
+                CostChart { title: "By storage class", groups: estimate.by_storage_class.clone(), total: estimate.monthly_cost }
+                CostChart { title: "By top-level prefix", groups: estimate.by_prefix.clone(), total: estimate.monthly_cost }
+            }
+            WhatIf { analytics: analytics.clone(), prices: prices.clone(), current_cost: estimate.monthly_cost }
+        }
+
+        if !analytics.largest.is_empty() {
+            h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Largest objects" }
+            table { class: "w-full mb-8 text-sm text-gray-700 dark:text-gray-400",
+                tbody {
+                    {analytics.largest.iter().map(|object| rsx!(
+                        tr {
+                            td { class: "py-1 pr-6 break-all", "{object.key}" }
+                            td { class: "py-1 pr-6 whitespace-nowrap", "{format_size(object.size)}" }
+                            td { class: "py-1 pr-6", {object.storage_class.clone().unwrap_or("STANDARD".to_string())} }
+                            td { class: "py-1 whitespace-nowrap", {object.last_modified.map(|lm| DateTime::from_secs(lm).to_string())} }
                         }
-                        li { button { class: "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple", "4" } }
-                        li { span { class: "px-3 py-1", "..." } }
-                        li { button { class: "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple", "8" } }
-                        li { button { class: "px-3 py-1 rounded-md focus:outline-none focus:shadow-outline-purple", "9" } }
-                        li {
-                            button {
-                                class: "px-3 py-1 rounded-md rounded-r-lg focus:outline-none focus:shadow-outline-purple",
-                                aria_label: "Next",
-                                svg {
-                                    class: "w-4 h-4 fill-current",
-                                    view_box: "0 0 20 20",
-                                    path {
-                                        d: "M7.293 14.707a1 1 0 010-1.414L10.586 10 7.293 6.707a1 1 0 011.414-1.414l4 4a1 1 0 010 1.414l-4 4a1 1 0 01-1.414 0z",
-                                        clip_rule: "evenodd",
-                                        fill_rule: "evenodd"
-                                    }
-                                }
+                    ))}
+                }
+            }
+        }
+
+        if !analytics.incomplete_uploads.is_empty() {
+            h3 { class: "mb-2 font-semibold text-gray-700 dark:text-gray-200", "Incomplete multipart uploads" }
+            p { class: "mb-2 text-sm text-gray-600 dark:text-gray-400",
+                "The uploaded parts are billed until the uploads are completed or aborted. "
+                Link { class: "text-purple-600 hover:underline", to: Route::MultipartUploads { account: account_id, bucket: analytics.bucket.clone() }, "Clean them up" }
+            }
+            table { class: "w-full mb-8 text-sm text-gray-700 dark:text-gray-400",
+                tbody {
+                    {analytics.incomplete_uploads.iter().map(|upload| rsx!(
+                        tr {
+                            td { class: "py-1 pr-6 break-all", "{upload.key}" }
+                            td { class: "py-1 pr-6 whitespace-nowrap", "{format_size(upload.size)}" }
+                            td { class: "py-1 whitespace-nowrap",
+                                {upload.initiated.map(|initiated| format!("started {}", DateTime::from_secs(initiated)))}
                             }
                         }
+                    ))}
+                }
+            }
+        }
+    )
+}
+
+/// Horizontal bar chart of the groups' sizes
+#[component]
+fn BarChart(title: String, groups: Vec<Breakdown>, total: u64) -> Element {
+    rsx!(
+        div { class: "p-4 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-4 font-semibold text-gray-800 dark:text-gray-300", "{title}" }
+            if groups.iter().all(|group| group.object_count == 0) {
+                p { class: "text-sm text-gray-500", "No objects" }
+            }
+            for group in groups.iter().filter(|group| group.object_count > 0) {
+                div { class: "mb-2 text-sm text-gray-700 dark:text-gray-400",
+                    div { class: "flex justify-between",
+                        span { class: "truncate", "{group.label}" }
+                        span { class: "ml-2 whitespace-nowrap", "{format_size(group.total_size)} · {group.object_count} objects" }
                     }
+                    Bar { percent: percent(group.total_size, total) }
                 }
             }
         }
-    }
+    )
+}
+
+/// Horizontal bar chart of the groups' monthly costs
+#[component]
+fn CostChart(title: String, groups: Vec<CostBreakdown>, total: f64) -> Element {
+    rsx!(
+        div { class: "p-4 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-4 font-semibold text-gray-800 dark:text-gray-300", "{title}" }
+            for group in groups {
+                div { class: "mb-2 text-sm text-gray-700 dark:text-gray-400",
+                    div { class: "flex justify-between",
+                        span { class: "truncate", "{group.label}" }
+                        span { class: "ml-2 whitespace-nowrap", "{format_cost(group.monthly_cost)}" }
+                    }
+                    Bar { percent: if total > 0.0 { group.monthly_cost * 100.0 / total } else { 0.0 } }
+                }
+            }
+        }
+    )
+}
+
+/// Projection of a lifecycle transition, eg. moving the objects older than 90 days to Glacier IR
+#[component]
+fn WhatIf(analytics: BucketAnalytics, prices: PriceTable, current_cost: f64) -> Element {
+    let mut min_age_days = use_signal(|| 90i64);
+    let mut storage_class = use_signal(|| "GLACIER_IR".to_string());
+    let transition = Transition { min_age_days: *min_age_days.read(), storage_class: storage_class.read().clone() };
+    let (projected, moved) = project_transition(&analytics, &prices, &transition);
+    let savings = current_cost - projected;
+
+    rsx!(
+        div { class: "p-4 mb-8 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+            h4 { class: "mb-4 font-semibold text-gray-800 dark:text-gray-300", "What if" }
+            div { class: "flex flex-wrap items-center gap-2 mb-4 text-sm text-gray-700 dark:text-gray-400",
+                span { "Move objects older than" }
+                select {
+                    class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                    oninput: move |e| min_age_days.set(e.value().parse().unwrap_or(90)),
+                    for days in age_thresholds() {
+                        option { value: "{days}", selected: days == *min_age_days.read(), "{days} days" }
+                    }
+                }
+                span { "to" }
+                select {
+                    class: "px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                    oninput: move |e| storage_class.set(e.value()),
+                    for (value, label) in STORAGE_CLASSES.iter() {
+                        option { value: *value, selected: *value == storage_class.read().as_str(), "{label}" }
+                    }
+                }
+            }
+            p { class: "text-sm text-gray-700 dark:text-gray-400",
+                "{format_size(moved)} would be moved, the monthly cost would be {format_cost(projected)} "
+                if savings > 0.0 {
+                    span { class: "font-semibold text-green-600", "(saving {format_cost(savings)})" }
+                } else {
+                    span { "(no savings)" }
+                }
+            }
+        }
+    )
+}
+
+#[component]
+fn Bar(percent: f64) -> Element {
+    rsx!(
+        div { class: "w-full h-2 bg-gray-200 rounded dark:bg-gray-700",
+            div { class: "h-2 bg-purple-600 rounded", style: "width: {percent:.1}%" }
+        }
+    )
 }
-}
\ No newline at end of file
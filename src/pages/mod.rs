@@ -2,7 +2,17 @@ pub mod dashboard;
 pub mod buckets;
 
 pub mod accounts;
+pub mod objects;
+pub mod bucket_index;
+pub mod multipart_uploads;
+pub mod bucket_settings;
+pub mod commander;
 
 pub use dashboard::Dashboard;
 pub use buckets::Buckets;
 pub use accounts::Accounts;
+pub use objects::Objects;
+pub use bucket_index::BucketIndex;
+pub use multipart_uploads::MultipartUploads;
+pub use bucket_settings::BucketSettings;
+pub use commander::Commander;
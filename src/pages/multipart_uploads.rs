@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use dioxus::prelude::*;
+use aws_sdk_s3::primitives::DateTime;
+use humansize::{file_size_opts, FileSize};
+use crate::model::analytics::IncompleteUpload;
+use crate::state::account_fetcher;
+use crate::Route;
+
+fn format_size(size: u64) -> String {
+    size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default()
+}
+
+/// Incomplete multipart uploads of a bucket, their parts are billed until they are aborted
+#[component]
+pub fn MultipartUploads(account: i64, bucket: String) -> Element {
+    let mut uploads = use_signal(|| None as Option<Vec<IncompleteUpload>>);
+    let mut rule_days = use_signal(|| None as Option<i32>);
+    let mut days_input = use_signal(|| "7".to_string());
+    let mut selected = use_signal(HashSet::<String>::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let mut info_message = use_signal(|| None as Option<String>);
+    let mut is_working = use_signal(|| false);
+    let mut reload = use_signal(|| 0u32);
+
+    let current_bucket = bucket.clone();
+    use_effect(use_reactive!(|(account, current_bucket)| {
+        let _ = reload.read();
+        let bucket = current_bucket.clone();
+        spawn(async move {
+            let Some(fetcher) = account_fetcher(account) else {
+                error_message.set(Some("The account doesn't exist anymore".to_string()));
+                return;
+            };
+            match fetcher.list_multipart_uploads(&bucket).await {
+                Ok(mut listed) => {
+                    listed.sort_by_key(|upload| upload.initiated);
+                    uploads.set(Some(listed));
+                }
+                Err(e) => error_message.set(Some(format!("Failed to list the uploads: {}", e))),
+            }
+            match fetcher.get_abort_uploads_days(&bucket).await {
+                Ok(days) => {
+                    if let Some(days) = days {
+                        days_input.set(days.to_string());
+                    }
+                    rule_days.set(days);
+                }
+                Err(e) => error_message.set(Some(format!("Failed to read the lifecycle rules: {}", e))),
+            }
+        });
+    }));
+
+    let bucket_for_abort = bucket.clone();
+    let abort_selected = move |_| {
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_abort.clone();
+        let to_abort: Vec<IncompleteUpload> = uploads
+            .read()
+            .iter()
+            .flatten()
+            .filter(|upload| selected.read().contains(&upload.upload_id))
+            .cloned()
+            .collect();
+        is_working.set(true);
+        error_message.set(None);
+        spawn(async move {
+            let mut failed = Vec::new();
+            for upload in &to_abort {
+                if let Err(e) = fetcher.abort_multipart_upload(&bucket, &upload.key, &upload.upload_id).await {
+                    failed.push(format!("{}: {}", upload.key, e));
+                }
+            }
+            let aborted = to_abort.len() - failed.len();
+            info_message.set(Some(format!("Aborted {} uploads", aborted)));
+            if !failed.is_empty() {
+                error_message.set(Some(format!("Failed to abort {}", failed.join(", "))));
+            }
+            selected.write().clear();
+            is_working.set(false);
+            *reload.write() += 1;
+        });
+    };
+
+    let bucket_for_rule = bucket.clone();
+    let create_rule = move |_| {
+        let days = match days_input.read().trim().parse::<i32>() {
+            Ok(days) if days > 0 => days,
+            _ => {
+                error_message.set(Some("The number of days has to be a positive number".to_string()));
+                return;
+            }
+        };
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let bucket = bucket_for_rule.clone();
+        is_working.set(true);
+        error_message.set(None);
+        spawn(async move {
+            match fetcher.set_abort_uploads_rule(&bucket, days).await {
+                Ok(()) => {
+                    rule_days.set(Some(days));
+                    info_message.set(Some(format!("Incomplete uploads will be aborted {} days after they start", days)));
+                }
+                Err(e) => error_message.set(Some(format!("Failed to save the lifecycle rule: {}", e))),
+            }
+            is_working.set(false);
+        });
+    };
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or_default();
+    let listed = uploads.read().clone();
+    let all_ids: HashSet<String> = listed.iter().flatten().map(|upload| upload.upload_id.clone()).collect();
+    let all_selected = !all_ids.is_empty() && all_ids.iter().all(|id| selected.read().contains(id));
+    let selected_count = selected.read().len();
+    let total_size: u64 = listed.iter().flatten().map(|upload| upload.size).sum();
+
+    rsx!(
+        main { class: "h-full overflow-y-auto",
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
+                        Link { to: Route::Buckets { account }, "Buckets" }
+                        " / "
+                        Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: String::new(), selected: String::new() }, "{bucket}" }
+                        " / Multipart uploads"
+                    }
+                    button {
+                        class: "px-4 py-2 text-sm font-medium text-white bg-red-500 rounded-lg hover:bg-red-600 focus:outline-none disabled:opacity-50 disabled:cursor-not-allowed",
+                        disabled: selected_count == 0 || *is_working.read(),
+                        onclick: abort_selected,
+                        "Abort selected ({selected_count})"
+                    }
+                }
+
+                if let Some(error) = error_message.read().as_ref() {
+                    div { class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded", "{error}" }
+                }
+                if let Some(info) = info_message.read().as_ref() {
+                    div { class: "mb-4 p-3 bg-green-100 border border-green-400 text-green-700 rounded", "{info}" }
+                }
+
+                div { class: "p-4 mb-6 bg-white rounded-lg shadow-xs dark:bg-gray-800",
+                    h4 { class: "mb-2 font-semibold text-gray-800 dark:text-gray-300", "Automatic cleanup" }
+                    p { class: "mb-4 text-sm text-gray-600 dark:text-gray-400",
+                        match *rule_days.read() {
+                            Some(days) => rsx!("A lifecycle rule aborts the incomplete uploads {days} days after they start."),
+                            None => rsx!("No lifecycle rule aborts the incomplete uploads of this bucket."),
+                        }
+                    }
+                    div { class: "flex items-center space-x-2 text-sm text-gray-700 dark:text-gray-400",
+                        span { "Abort incomplete uploads after" }
+                        input {
+                            class: "w-20 px-3 py-2 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            r#type: "number",
+                            min: "1",
+                            value: "{days_input}",
+                            oninput: move |e| days_input.set(e.value()),
+                        }
+                        span { "days" }
+                        button {
+                            class: "px-4 py-2 text-sm text-purple-600 border border-purple-600 rounded hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: *is_working.read(),
+                            onclick: create_rule,
+                            if rule_days.read().is_some() { "Update lifecycle rule" } else { "Create lifecycle rule" }
+                        }
+                    }
+                }
+
+                match listed {
+                    None => rsx!( p { class: "text-sm text-gray-700 dark:text-gray-400", "Loading..." } ),
+                    Some(listed) if listed.is_empty() => rsx!(
+                        p { class: "text-sm text-gray-700 dark:text-gray-400", "There are no incomplete multipart uploads in this bucket." }
+                    ),
+                    Some(listed) => rsx!(
+                        p { class: "mb-2 text-sm text-gray-700 dark:text-gray-400",
+                            "{listed.len()} incomplete uploads, {format_size(total_size)} of uploaded parts"
+                        }
+                        div { class: "w-full overflow-hidden rounded-lg shadow-xs mb-8",
+                            div { class: "w-full overflow-x-auto",
+                                table { class: "w-full whitespace-no-wrap",
+                                    thead {
+                                        tr {
+                                            class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                                            th { class: "px-4 py-3",
+                                                input {
+                                                    r#type: "checkbox",
+                                                    class: "form-checkbox h-4 w-4 text-purple-600",
+                                                    checked: all_selected,
+                                                    onchange: move |e| {
+                                                        if e.checked() {
+                                                            selected.set(all_ids.clone());
+                                                        } else {
+                                                            selected.write().clear();
+                                                        }
+                                                    },
+                                                }
+                                            }
+                                            th { class: "px-4 py-3", "Key" }
+                                            th { class: "px-4 py-3", "Started" }
+                                            th { class: "px-4 py-3", "Age" }
+                                            th { class: "px-4 py-3", "Parts" }
+                                            th { class: "px-4 py-3", "Size" }
+                                        }
+                                    }
+                                    tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
+                                        {listed.into_iter().map(|upload| {
+                                            let upload_id = upload.upload_id.clone();
+                                            let is_selected = selected.read().contains(&upload_id);
+                                            rsx!(
+                                                tr { class: "text-gray-700 dark:text-gray-400",
+                                                    td { class: "px-4 py-3",
+                                                        input {
+                                                            r#type: "checkbox",
+                                                            class: "form-checkbox h-4 w-4 text-purple-600",
+                                                            checked: is_selected,
+                                                            onchange: move |e| {
+                                                                if e.checked() {
+                                                                    selected.write().insert(upload_id.clone());
+                                                                } else {
+                                                                    selected.write().remove(&upload_id);
+                                                                }
+                                                            },
+                                                        }
+                                                    }
+                                                    td { class: "px-4 py-3 text-sm break-all", "{upload.key}" }
+                                                    td { class: "px-4 py-3 text-sm", {upload.initiated.map(|initiated| DateTime::from_secs(initiated).to_string())} }
+                                                    td { class: "px-4 py-3 text-sm", {upload.age_days(now).map(|days| format!("{} days", days))} }
+                                                    td { class: "px-4 py-3 text-sm", "{upload.part_count}" }
+                                                    td { class: "px-4 py-3 text-sm", "{format_size(upload.size)}" }
+                                                }
+                                            )
+                                        })}
+                                    }
+                                }
+                            }
+                        }
+                    ),
+                }
+            }
+        }
+    )
+}
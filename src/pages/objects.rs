@@ -0,0 +1,856 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use dioxus::prelude::*;
+use humansize::{file_size_opts, FileSize};
+use tokio_stream::StreamExt;
+use crate::components::transfer_queue::dropped_paths;
+use crate::components::{BulkEditModal, CrossAccountModal, GithubStarAction, ObjectDetailsPanel, PreviewPane, TextEditorPane, TransferQueue, TransferSettings};
+use crate::model::download_progress_item::DownloadProgressItem;
+use crate::model::local_selected_item::items_for_upload;
+use crate::model::page_cursor::PageCursor;
+use crate::model::preview::PreviewKind;
+use crate::model::s3_data_item::{sort_items, S3DataItem, SortColumn};
+use crate::model::s3_selected_item::S3SelectedItem;
+use crate::model::storage_class::{requires_restore, RestoreStatus};
+use crate::model::text_edit::EDITOR_SIZE_LIMIT;
+use crate::services::s3_data_fetcher::ListingMode;
+use crate::utils::pick_folder;
+use crate::Route;
+use crate::state::{account_fetcher, queue_uploads, TransferDirection, TransferState, DOWNLOAD_DIR, TRANSFERS, TRANSFER_OPTIONS};
+
+/// How often the status of the ongoing restores is checked
+const RESTORE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Status of the objects whose restore couldn't be checked, until a later check succeeds
+const RESTORE_STATUS_UNAVAILABLE: &str = "Restore status unavailable";
+
+/// Rows rendered at once, more are added when the listing is scrolled to the bottom
+const RENDER_BATCH: usize = 200;
+
+/// Distance from the bottom of the listing (in pixels) at which more rows are rendered
+const SCROLL_THRESHOLD: f64 = 400.0;
+
+/// Page sizes offered by the browser, 0 streams the whole listing
+const PAGE_SIZES: &[usize] = &[0, 100, 500, 1000];
+
+/// What part of the listing is loaded
+#[derive(Debug, Clone, PartialEq)]
+enum ListingScope {
+    /// Everything, shown progressively as the pages arrive
+    All,
+    /// A single page of the given size
+    Page { size: usize, token: Option<String>, start_after: Option<String> },
+}
+
+/// Shows the listed items and checks the restore status of the archived ones among them
+fn show_items(
+    account: i64,
+    bucket: &str,
+    items: Vec<S3DataItem>,
+    mut objects: Signal<Vec<S3DataItem>>,
+    restore_status: Signal<HashMap<String, RestoreStatus>>,
+    queued_downloads: Signal<HashMap<String, S3DataItem>>,
+    downloads: Signal<HashMap<String, String>>,
+) {
+    let archived = items
+        .iter()
+        .filter(|i| requires_restore(i.storage_class.as_deref()))
+        .map(|i| i.path.clone())
+        .collect();
+    fetch_restore_status(account, bucket.to_string(), archived, restore_status, queued_downloads, downloads);
+    objects.write().extend(items);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_objects(
+    account: i64,
+    bucket: String,
+    prefix: String,
+    mode: ListingMode,
+    scope: ListingScope,
+    mut cursor: Signal<PageCursor>,
+    mut objects: Signal<Vec<S3DataItem>>,
+    mut error_message: Signal<Option<String>>,
+    mut is_loading: Signal<bool>,
+    mut encryption: Signal<HashMap<String, Result<String, String>>>,
+    mut restore_status: Signal<HashMap<String, RestoreStatus>>,
+    queued_downloads: Signal<HashMap<String, S3DataItem>>,
+    downloads: Signal<HashMap<String, String>>,
+) -> Task {
+    is_loading.set(true);
+    objects.write().clear();
+    encryption.write().clear();
+    restore_status.write().clear();
+    error_message.set(None);
+    spawn(async move {
+        let Some(fetcher) = account_fetcher(account) else {
+            error_message.set(Some("The account doesn't exist anymore".to_string()));
+            is_loading.set(false);
+            return;
+        };
+        let prefix = Some(prefix).filter(|p| !p.is_empty());
+        match scope {
+            // every page is shown as soon as it arrives, the listing continues in the background
+            ListingScope::All => match fetcher.stream_objects(&bucket, prefix, mode).await {
+                Ok(mut pages) => {
+                    while let Some(page) = pages.next().await {
+                        match page {
+                            Ok(items) => show_items(account, &bucket, items, objects, restore_status, queued_downloads, downloads),
+                            Err(err) => {
+                                error_message.set(Some(format!("Failed to list objects: {}", err)));
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(err) => error_message.set(Some(format!("Failed to list objects: {}", err))),
+            },
+            ListingScope::Page { size, token, start_after } => {
+                match fetcher.list_objects_page(&bucket, prefix, mode, size, token, start_after).await {
+                    Ok(page) => {
+                        show_items(account, &bucket, page.items, objects, restore_status, queued_downloads, downloads);
+                        cursor.write().set_next_token(page.next_token);
+                    }
+                    Err(err) => error_message.set(Some(format!("Failed to list objects: {}", err))),
+                }
+            }
+        }
+        is_loading.set(false);
+    })
+}
+
+/// Fetches the encryption of the objects in the background, one HEAD request per object.
+/// Objects which are being fetched already are skipped.
+fn fetch_object_encryption(account: i64, bucket: String, items: Vec<S3DataItem>, mut encryption: Signal<HashMap<String, Result<String, String>>>) {
+    let Some(fetcher) = account_fetcher(account) else {
+        return;
+    };
+    let keys: Vec<String> = items
+        .into_iter()
+        .filter(|i| !i.is_directory && !encryption.peek().contains_key(&i.path))
+        .map(|i| i.path)
+        .collect();
+    if keys.is_empty() {
+        return;
+    }
+    for key in &keys {
+        encryption.write().insert(key.clone(), Ok("...".to_string()));
+    }
+    let (results_tx, mut results_rx) = tokio::sync::mpsc::unbounded_channel();
+    spawn(async move {
+        fetcher.get_objects_encryption(&bucket, keys, results_tx).await;
+    });
+    spawn(async move {
+        while let Some((key, label)) = results_rx.recv().await {
+            encryption.write().insert(key, label.map_err(|e| e.to_string()));
+        }
+    });
+}
+
+/// Fetches the restore status of the archived objects, downloads queued for the objects
+/// which are restored already are started
+fn fetch_restore_status(
+    account: i64,
+    bucket: String,
+    keys: Vec<String>,
+    restore_status: Signal<HashMap<String, RestoreStatus>>,
+    queued_downloads: Signal<HashMap<String, S3DataItem>>,
+    downloads: Signal<HashMap<String, String>>,
+) {
+    let Some(fetcher) = account_fetcher(account) else {
+        return;
+    };
+    for key in keys {
+        let fetcher = fetcher.clone();
+        let bucket = bucket.clone();
+        let mut restore_status = restore_status;
+        let mut queued_downloads = queued_downloads;
+        let mut downloads = downloads;
+        spawn(async move {
+            match fetcher.get_restore_status(&bucket, &key).await {
+                Ok(status) => {
+                    if downloads.peek().get(&key).is_some_and(|status| status.starts_with(RESTORE_STATUS_UNAVAILABLE)) {
+                        downloads.write().remove(&key);
+                    }
+                    if matches!(status, RestoreStatus::Restored { .. }) {
+                        if let Some(item) = queued_downloads.write().remove(&key) {
+                            start_download(account, item, downloads);
+                        }
+                    }
+                    restore_status.write().insert(key, status);
+                }
+                Err(e) => {
+                    // a queued download would wait for a restore nobody can see
+                    queued_downloads.write().remove(&key);
+                    downloads.write().insert(key, format!("{}: {}", RESTORE_STATUS_UNAVAILABLE, e));
+                }
+            }
+        });
+    }
+}
+
+/// Downloads the object into the chosen download folder, keeping its status up to date
+fn start_download(account: i64, item: S3DataItem, mut downloads: Signal<HashMap<String, String>>) {
+    let key = item.path.clone();
+    downloads.write().insert(key.clone(), "Downloading".to_string());
+    spawn(async move {
+        let Some(fetcher) = account_fetcher(account) else {
+            return;
+        };
+        let (download_tx, mut download_rx) = tokio::sync::mpsc::unbounded_channel::<DownloadProgressItem>();
+        let progress_key = key.clone();
+        spawn(async move {
+            while let Some(progress) = download_rx.recv().await {
+                downloads
+                    .write()
+                    .insert(progress_key.clone(), format!("Downloading {:.0}%", progress.progress));
+            }
+        });
+        let destination_dir = DOWNLOAD_DIR.peek().to_string_lossy().to_string();
+        let selected = S3SelectedItem {
+            encryption: TRANSFER_OPTIONS.peek().encryption.clone(),
+            ..S3SelectedItem::from_s3_data_item(item, Default::default(), destination_dir)
+        };
+        let status = match fetcher.download_item(selected, download_tx).await {
+            Ok(_) => "Downloaded".to_string(),
+            Err(e) => format!("Download failed: {}", e),
+        };
+        downloads.write().insert(key, status);
+    });
+}
+
+/// Downloads the object, archived objects which aren't restored yet are queued until they are
+fn request_download(
+    account: i64,
+    item: S3DataItem,
+    restore: Option<RestoreStatus>,
+    mut queued_downloads: Signal<HashMap<String, S3DataItem>>,
+    mut downloads: Signal<HashMap<String, String>>,
+) {
+    let restored = matches!(restore, Some(RestoreStatus::Restored { .. }));
+    if requires_restore(item.storage_class.as_deref()) && !restored {
+        // archived objects can't be read, the download starts once the restore completes
+        downloads.write().insert(item.path.clone(), "Queued until restored".to_string());
+        queued_downloads.write().insert(item.path.clone(), item);
+    } else {
+        start_download(account, item, downloads);
+    }
+}
+
+/// Column header which sorts the listing when clicked, clicking again reverses the order
+#[component]
+fn SortableHeader(label: String, column: SortColumn, sort: Signal<(SortColumn, bool)>) -> Element {
+    let (current, ascending) = *sort.read();
+    let indicator = match (current == column, ascending) {
+        (false, _) => "",
+        (true, true) => " ▲",
+        (true, false) => " ▼",
+    };
+    rsx! {
+        th {
+            class: "px-4 py-3 cursor-pointer select-none",
+            onclick: move |_| {
+                let (current, ascending) = *sort.read();
+                sort.set((column, current != column || !ascending));
+            },
+            "{label}{indicator}"
+        }
+    }
+}
+
+/// Splits the prefix into (name, prefix) pairs used for the breadcrumb navigation
+fn breadcrumbs(prefix: &str) -> Vec<(String, String)> {
+    let mut current = String::new();
+    prefix
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            current.push_str(part);
+            current.push('/');
+            (part.to_string(), current.clone())
+        })
+        .collect()
+}
+
+/// Object browser for a single bucket, `selected` opens the details of the object (eg. a search result)
+#[component]
+pub fn Objects(account: i64, bucket: String, prefix: String, selected: String) -> Element {
+    let objects = use_signal(Vec::<S3DataItem>::new);
+    let mut error_message = use_signal(|| None as Option<String>);
+    let is_loading = use_signal(|| true);
+    let mut refresh_objects = use_signal(|| false);
+    let mut selected_keys = use_signal(BTreeSet::<String>::new);
+    let mut details_key = use_signal(|| None as Option<String>);
+    let mut preview_key = use_signal(|| None as Option<String>);
+    let mut edit_key = use_signal(|| None as Option<String>);
+    let mut show_bulk_edit = use_signal(|| false);
+    let mut show_cross_account = use_signal(|| false);
+    let encryption = use_signal(HashMap::<String, Result<String, String>>::new);
+    let restore_status = use_signal(HashMap::<String, RestoreStatus>::new);
+    let queued_downloads = use_signal(HashMap::<String, S3DataItem>::new);
+    let downloads = use_signal(HashMap::<String, String>::new);
+    // objects dragged from the listing, they're downloaded when dropped on the download zone
+    let mut dragged_keys = use_signal(Vec::<String>::new);
+    // prefix under the files dragged from the desktop, highlighted as the upload destination
+    let mut drop_target = use_signal(|| None as Option<String>);
+    let sort = use_signal(|| (SortColumn::Name, true));
+    let mut flat = use_signal(|| false);
+    let mut visible_count = use_signal(|| RENDER_BATCH);
+    let mut listing_task = use_signal(|| None as Option<Task>);
+    let mut scroll_container = use_signal(|| None as Option<std::rc::Rc<MountedData>>);
+    let mut page_size = use_signal(|| 0usize);
+    let mut cursor = use_signal(PageCursor::default);
+    let mut start_after = use_signal(String::new);
+    let mut start_after_input = use_signal(String::new);
+    // learning the token of the next page must not reload the current one
+    let page_token = use_memo(move || cursor.read().current_token());
+
+    let mut location = use_signal(|| (account, bucket.clone(), prefix.clone()));
+
+    let (current_bucket, current_prefix) = (bucket.clone(), prefix.clone());
+    use_effect(use_reactive!(|(account, current_bucket, current_prefix)| {
+        let current = (account, current_bucket, current_prefix);
+        if *location.peek() != current {
+            location.set(current);
+            cursor.write().reset();
+            start_after.set(String::new());
+            start_after_input.set(String::new());
+        }
+    }));
+
+    use_effect(use_reactive!(|selected| {
+        if !selected.is_empty() {
+            details_key.set(Some(selected));
+        }
+    }));
+
+    let scope = move |size: usize, token: Option<String>, start_after: String| {
+        if size == 0 {
+            ListingScope::All
+        } else {
+            // the start key only applies to the first page, the token continues after it
+            let start_after = Some(start_after).filter(|key| token.is_none() && !key.is_empty());
+            ListingScope::Page { size, token, start_after }
+        }
+    };
+
+    let mut reload = move |(account, bucket, prefix): (i64, String, String), mode: ListingMode, scope: ListingScope| {
+        // the listing of the previous location must not append to the new one
+        if let Some(task) = listing_task.write().take() {
+            task.cancel();
+        }
+        visible_count.set(RENDER_BATCH);
+        let task = load_objects(account, bucket, prefix, mode, scope, cursor, objects, error_message, is_loading, encryption, restore_status, queued_downloads, downloads);
+        listing_task.set(Some(task));
+    };
+
+    use_effect(move || {
+        let location = location.read().clone();
+        let mode = if *flat.read() { ListingMode::Flat } else { ListingMode::Delimited };
+        let scope = scope(*page_size.read(), page_token.read().clone(), start_after.read().clone());
+        selected_keys.write().clear();
+        reload(location, mode, scope);
+    });
+
+    use_effect(move || {
+        if *refresh_objects.read() {
+            let location = location.peek().clone();
+            let mode = if *flat.peek() { ListingMode::Flat } else { ListingMode::Delimited };
+            let scope = scope(*page_size.peek(), page_token.peek().clone(), start_after.peek().clone());
+            reload(location, mode, scope);
+            refresh_objects.set(false);
+        }
+    });
+
+    // the listing is reloaded whenever an upload into the bucket finishes
+    let uploaded_count = use_memo(move || {
+        let (account, bucket, _) = location.read().clone();
+        TRANSFERS
+            .read()
+            .iter()
+            .filter(|transfer| {
+                transfer.direction == TransferDirection::Upload
+                    && transfer.account_id == account
+                    && transfer.bucket == bucket
+                    && transfer.state == TransferState::Done
+            })
+            .count()
+    });
+    let mut seen_uploads = use_signal(|| *uploaded_count.peek());
+    use_effect(move || {
+        let count = *uploaded_count.read();
+        if count != *seen_uploads.peek() {
+            let uploaded = count > *seen_uploads.peek();
+            seen_uploads.set(count);
+            if uploaded {
+                refresh_objects.set(true);
+            }
+        }
+    });
+
+    let mut upload_dropped = move |evt: DragEvent, prefix: String| {
+        evt.prevent_default();
+        evt.stop_propagation();
+        drop_target.set(None);
+        let paths = dropped_paths(&evt);
+        if paths.is_empty() {
+            return;
+        }
+        let (account, bucket, _) = location.peek().clone();
+        if let Err(e) = queue_uploads(account, TRANSFER_OPTIONS.peek().apply(items_for_upload(&paths, &bucket, &prefix))) {
+            error_message.set(Some(e));
+        }
+    };
+
+    let download_dragged = move |evt: DragEvent| {
+        evt.prevent_default();
+        let keys: Vec<String> = dragged_keys.write().drain(..).collect();
+        let items: Vec<S3DataItem> = objects.peek().iter().filter(|obj| keys.contains(&obj.path)).cloned().collect();
+        for item in items {
+            let restore = restore_status.peek().get(&item.path).cloned();
+            request_download(account, item, restore, queued_downloads, downloads);
+        }
+    };
+
+    // the encryption needs a request per object, so it's fetched only for the rendered rows
+    use_effect(move || {
+        let mut rendered = objects.read().clone();
+        let (sort_column, ascending) = *sort.read();
+        sort_items(&mut rendered, sort_column, ascending);
+        rendered.truncate(*visible_count.read());
+        let (account, bucket, _) = location.peek().clone();
+        fetch_object_encryption(account, bucket, rendered, encryption);
+    });
+
+    // restores take hours, the ones in progress are checked periodically
+    use_coroutine(move |_: UnboundedReceiver<()>| async move {
+        loop {
+            tokio::time::sleep(RESTORE_POLL_INTERVAL).await;
+            let (account, bucket, _) = location.peek().clone();
+            let pending: Vec<String> = restore_status
+                .peek()
+                .iter()
+                .filter(|(_, status)| **status == RestoreStatus::InProgress)
+                .map(|(key, _)| key.clone())
+                .collect();
+            if !pending.is_empty() {
+                fetch_restore_status(account, bucket, pending, restore_status, queued_downloads, downloads);
+            }
+        }
+    });
+
+    let mut sorted_objects = objects.read().clone();
+    let (sort_column, ascending) = *sort.read();
+    sort_items(&mut sorted_objects, sort_column, ascending);
+    let total_count = sorted_objects.len();
+    sorted_objects.truncate(*visible_count.read());
+    let is_flat = *flat.read();
+    let sorted_objects_len = sorted_objects.len();
+    let is_paged = *page_size.read() > 0;
+    let (page_number, has_previous, has_next) = {
+        let cursor = cursor.read();
+        (cursor.page() + 1, cursor.has_previous(), cursor.has_next())
+    };
+
+    let checked_keys: Vec<String> = selected_keys.read().iter().cloned().collect();
+    let selected_count = checked_keys.len();
+    let is_dragging = !dragged_keys.read().is_empty();
+    let listing_prefix = prefix.clone();
+    let listing_highlight = if drop_target.read().as_deref() == Some(prefix.as_str()) {
+        "ring-2 ring-purple-500"
+    } else {
+        ""
+    };
+    let download_dir = DOWNLOAD_DIR.read().to_string_lossy().to_string();
+
+    rsx!(
+        if *show_bulk_edit.read() {
+            BulkEditModal {
+                account,
+                bucket: bucket.clone(),
+                keys: checked_keys.clone(),
+                show_modal: show_bulk_edit,
+                refresh_objects: refresh_objects,
+            }
+        },
+        if *show_cross_account.read() {
+            CrossAccountModal {
+                account,
+                bucket: bucket.clone(),
+                prefix: prefix.clone(),
+                keys: checked_keys.clone(),
+                show_modal: show_cross_account,
+            }
+        },
+        if details_key.read().is_some() {
+            ObjectDetailsPanel {
+                account,
+                bucket: bucket.clone(),
+                selected_key: details_key,
+                refresh_objects: refresh_objects,
+            }
+        },
+        if preview_key.read().is_some() {
+            PreviewPane {
+                account,
+                bucket: bucket.clone(),
+                selected_key: preview_key,
+            }
+        },
+        if edit_key.read().is_some() {
+            TextEditorPane {
+                account,
+                bucket: bucket.clone(),
+                selected_key: edit_key,
+                refresh_objects: refresh_objects,
+            }
+        },
+        main { class: "h-full overflow-y-auto",
+            onmounted: move |e| scroll_container.set(Some(e.data())),
+            onscroll: move |_| async move {
+                let Some(container) = scroll_container.peek().clone() else {
+                    return;
+                };
+                let (Ok(offset), Ok(size), Ok(rect)) = (
+                    container.get_scroll_offset().await,
+                    container.get_scroll_size().await,
+                    container.get_client_rect().await,
+                ) else {
+                    return;
+                };
+                let near_bottom = offset.y + rect.height() >= size.height - SCROLL_THRESHOLD;
+                if near_bottom && *visible_count.peek() < objects.peek().len() {
+                    *visible_count.write() += RENDER_BATCH;
+                }
+            },
+            div { class: "container px-6 mx-auto grid",
+                div { class: "flex items-center justify-between mt-6 mb-5",
+                    h2 { class: "text-2xl font-semibold text-gray-700 dark:text-gray-200",
+                        Link { to: Route::Buckets { account }, "Buckets" }
+                        " / "
+                        Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: String::new(), selected: String::new() }, "{bucket}" }
+                        {breadcrumbs(&prefix).into_iter().map(|(name, path)| rsx!(
+                            " / "
+                            Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: path, selected: String::new() }, "{name}" }
+                        ))}
+                    }
+                    div { class: "flex items-center",
+                        label { class: "mr-4 text-sm text-gray-700 dark:text-gray-400",
+                            input {
+                                r#type: "checkbox",
+                                class: "mr-1 form-checkbox text-purple-600",
+                                checked: is_flat,
+                                onchange: move |e| {
+                                    flat.set(e.checked());
+                                    cursor.write().reset();
+                                },
+                            }
+                            "Flat (recursive)"
+                        }
+                        label { class: "mr-2 text-sm text-gray-700 dark:text-gray-400", "Page size:" }
+                        select {
+                            class: "px-3 py-1 mr-4 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                            value: "{page_size}",
+                            onchange: move |e| {
+                                if let Ok(size) = e.value().parse::<usize>() {
+                                    page_size.set(size);
+                                    cursor.write().reset();
+                                }
+                            },
+                            for size in PAGE_SIZES.iter() {
+                                option { value: "{size}",
+                                    if *size == 0 { "All" } else { "{size}" }
+                                }
+                            }
+                        }
+                        if is_paged {
+                            input {
+                                class: "px-3 py-1 mr-4 text-sm border rounded-md dark:bg-gray-700 dark:text-white",
+                                r#type: "text",
+                                placeholder: "Start after key",
+                                value: "{start_after_input}",
+                                oninput: move |e| start_after_input.set(e.value()),
+                                onkeydown: move |e| {
+                                    if e.key() == Key::Enter {
+                                        start_after.set(start_after_input.peek().clone());
+                                        cursor.write().reset();
+                                    }
+                                },
+                            }
+                        }
+                        Link {
+                            class: "px-4 py-2 text-sm font-medium text-purple-600 border border-purple-600 rounded-lg hover:bg-purple-100 dark:hover:bg-purple-900",
+                            to: Route::BucketIndex { account, bucket: bucket.clone() },
+                            "Index"
+                        }
+                        Link {
+                            class: "px-4 py-2 ml-2 text-sm font-medium text-purple-600 border border-purple-600 rounded-lg hover:bg-purple-100 dark:hover:bg-purple-900",
+                            to: Route::MultipartUploads { account, bucket: bucket.clone() },
+                            "Uploads"
+                        }
+                        Link {
+                            class: "px-4 py-2 ml-2 text-sm font-medium text-purple-600 border border-purple-600 rounded-lg hover:bg-purple-100 dark:hover:bg-purple-900",
+                            to: Route::BucketSettings { account, bucket: bucket.clone() },
+                            "Settings"
+                        }
+                        button {
+                            class: "px-4 py-2 ml-2 text-sm font-medium text-white bg-purple-600 rounded-lg hover:bg-purple-700 focus:outline-none focus:ring disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: selected_count == 0,
+                            onclick: move |_| show_bulk_edit.set(true),
+                            "Edit selected ({selected_count})"
+                        }
+                        button {
+                            class: "px-4 py-2 ml-2 text-sm font-medium text-purple-600 border border-purple-600 rounded-lg hover:bg-purple-100 dark:hover:bg-purple-900 disabled:opacity-50 disabled:cursor-not-allowed",
+                            disabled: selected_count == 0,
+                            onclick: move |_| show_cross_account.set(true),
+                            "Copy to account"
+                        }
+                    }
+                }
+                GithubStarAction {},
+                if let Some(error) = error_message.read().as_ref() {
+                    div {
+                        class: "mb-4 p-3 bg-red-100 border border-red-400 text-red-700 rounded",
+                        "{error}"
+                    }
+                }
+                TransferQueue { account: Some(account), bucket: Some(bucket.clone()) }
+                if is_dragging {
+                    div {
+                        class: "mb-4 p-6 text-center text-sm text-green-700 bg-green-100 border-2 border-dashed border-green-400 rounded",
+                        ondragover: move |e| e.prevent_default(),
+                        ondrop: download_dragged,
+                        "Drop here to download into {download_dir}"
+                    }
+                }
+                p { class: "mb-2 text-xs text-gray-500 dark:text-gray-400",
+                    "Drop files or folders from the desktop on the listing to upload them here, or on a folder to upload them into it. "
+                    "Objects are downloaded into {download_dir}, drag them onto the download zone to download several at once. "
+                    button {
+                        class: "text-purple-600 hover:underline",
+                        onclick: move |_| {
+                            spawn(async move {
+                                let current = DOWNLOAD_DIR.peek().clone();
+                                if let Some(folder) = pick_folder(&current).await {
+                                    *DOWNLOAD_DIR.write() = folder;
+                                }
+                            });
+                        },
+                        "Change folder"
+                    }
+                }
+                TransferSettings {}
+                div { class: "w-full overflow-hidden rounded-lg shadow-xs {listing_highlight}",
+                    ondragover: move |e| {
+                        e.prevent_default();
+                        if dragged_keys.peek().is_empty() && drop_target.peek().is_none() {
+                            drop_target.set(Some(location.peek().2.clone()));
+                        }
+                    },
+                    ondragleave: move |_| drop_target.set(None),
+                    ondrop: move |e| upload_dropped(e, listing_prefix.clone()),
+                    div { class: "w-full overflow-x-auto",
+                        table { class: "w-full whitespace-no-wrap",
+                            thead {
+                                tr {
+                                    class: "text-xs font-semibold tracking-wide text-left text-gray-500 uppercase border-b dark:border-gray-700 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                                    th { class: "px-4 py-3", "" }
+                                    SortableHeader { label: "Name", column: SortColumn::Name, sort }
+                                    SortableHeader { label: "Size", column: SortColumn::Size, sort }
+                                    SortableHeader { label: "Type", column: SortColumn::Type, sort }
+                                    SortableHeader { label: "Last modified", column: SortColumn::LastModified, sort }
+                                    th { class: "px-4 py-3", "Encryption" }
+                                    SortableHeader { label: "Storage class", column: SortColumn::StorageClass, sort }
+                                    th { class: "px-4 py-3", "Status" }
+                                    th { class: "px-4 py-3", "Actions" }
+                                }
+                            }
+                            tbody { class: "bg-white divide-y dark:divide-gray-700 dark:bg-gray-800",
+                                if *is_loading.read() && total_count == 0 {
+                                    tr { td { class: "px-4 py-3 text-sm text-gray-500", colspan: "9", "Loading..." } }
+                                }
+                                {sorted_objects.into_iter().map(|obj| {
+                                    let key = obj.path.clone();
+                                    let key_for_select = key.clone();
+                                    let key_for_details = key.clone();
+                                    let key_for_preview = key.clone();
+                                    let key_for_edit = key.clone();
+                                    let is_editable = !obj.is_directory
+                                        && obj.size.is_some_and(|size| size <= EDITOR_SIZE_LIMIT)
+                                        && matches!(
+                                            PreviewKind::detect(&obj.path, None),
+                                            PreviewKind::Text | PreviewKind::Json | PreviewKind::Yaml | PreviewKind::Table(_)
+                                        );
+                                    let item_for_download = obj.clone();
+                                    let key_for_drag = key.clone();
+                                    let key_for_drop = key.clone();
+                                    let key_for_target = key.clone();
+                                    let is_drop_target = obj.is_directory && drop_target.read().as_deref() == Some(key.as_str());
+                                    let archived = requires_restore(obj.storage_class.as_deref());
+                                    let restore = restore_status.read().get(&key).cloned();
+                                    let status = downloads
+                                        .read()
+                                        .get(&key)
+                                        .cloned()
+                                        .or_else(|| archived.then(|| restore.clone().map(|r| r.label()).unwrap_or("...".to_string())))
+                                        .unwrap_or_default();
+                                    let is_selected = selected_keys.read().contains(&key);
+                                    rsx!(
+                                        tr {
+                                            class: if is_drop_target { "text-gray-700 bg-purple-100 dark:text-gray-400 dark:bg-purple-900" } else { "text-gray-700 dark:text-gray-400" },
+                                            draggable: !obj.is_directory,
+                                            ondragstart: move |_| {
+                                                // dragging a selected object takes the whole selection along
+                                                let keys = if selected_keys.peek().contains(&key_for_drag) {
+                                                    selected_keys.peek().iter().cloned().collect()
+                                                } else {
+                                                    vec![key_for_drag.clone()]
+                                                };
+                                                dragged_keys.set(keys);
+                                            },
+                                            ondragend: move |_| dragged_keys.write().clear(),
+                                            ondragover: move |e| {
+                                                if obj.is_directory {
+                                                    e.prevent_default();
+                                                    e.stop_propagation();
+                                                    if dragged_keys.peek().is_empty() && drop_target.peek().as_deref() != Some(key_for_target.as_str()) {
+                                                        drop_target.set(Some(key_for_target.clone()));
+                                                    }
+                                                }
+                                            },
+                                            ondrop: move |e| {
+                                                if obj.is_directory {
+                                                    upload_dropped(e, key_for_drop.clone());
+                                                }
+                                            },
+                                            td { class: "px-4 py-3",
+                                                if !obj.is_directory {
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        class: "form-checkbox h-4 w-4 text-purple-600",
+                                                        checked: is_selected,
+                                                        onchange: move |e| {
+                                                            if e.checked() {
+                                                                selected_keys.write().insert(key_for_select.clone());
+                                                            } else {
+                                                                selected_keys.write().remove(&key_for_select);
+                                                            }
+                                                        },
+                                                    }
+                                                }
+                                            }
+                                            td { class: "px-4 py-3 text-sm font-semibold",
+                                                if obj.is_directory {
+                                                    Link { to: Route::Objects { account, bucket: bucket.clone(), prefix: key.clone(), selected: String::new() }, "{obj.name}" }
+                                                } else if is_flat {
+                                                    {obj.path.strip_prefix(prefix.as_str()).unwrap_or(&obj.path).to_string()}
+                                                } else {
+                                                    "{obj.name}"
+                                                }
+                                            }
+                                            td { class: "px-4 py-3 text-sm",
+                                                {obj.size.map(|size| size.file_size(file_size_opts::CONVENTIONAL).unwrap_or_default())}
+                                            }
+                                            td { class: "px-4 py-3 text-sm", "{obj.file_type}" }
+                                            td { class: "px-4 py-3 text-sm",
+                                                {obj.last_modified.map(|lm| lm.to_string())}
+                                            }
+                                            td { class: "px-4 py-3 text-sm",
+                                                if !obj.is_directory {
+                                                    match encryption.read().get(&key).cloned() {
+                                                        Some(Ok(label)) => rsx!("{label}"),
+                                                        Some(Err(error)) => rsx!(
+                                                            span { class: "text-red-600", title: "{error}", "Unavailable" }
+                                                        ),
+                                                        None => rsx!("..."),
+                                                    }
+                                                }
+                                            }
+                                            td { class: "px-4 py-3 text-sm", {obj.storage_class.clone().unwrap_or_default()} }
+                                            td { class: "px-4 py-3 text-sm", "{status}" }
+                                            td { class: "px-4 py-3 space-x-2",
+                                                if !obj.is_directory {
+                                                    button {
+                                                        class: "px-2 py-1 text-sm text-white bg-blue-500 rounded hover:bg-blue-600 focus:outline-none",
+                                                        onclick: move |_| {
+                                                            preview_key.set(None);
+                                                            edit_key.set(None);
+                                                            details_key.set(Some(key_for_details.clone()));
+                                                        },
+                                                        "Details"
+                                                    }
+                                                    button {
+                                                        class: "px-2 py-1 text-sm text-white bg-purple-500 rounded hover:bg-purple-600 focus:outline-none",
+                                                        onclick: move |_| {
+                                                            details_key.set(None);
+                                                            edit_key.set(None);
+                                                            preview_key.set(Some(key_for_preview.clone()));
+                                                        },
+                                                        "Preview"
+                                                    }
+                                                    if is_editable {
+                                                        button {
+                                                            class: "px-2 py-1 text-sm text-white bg-yellow-500 rounded hover:bg-yellow-600 focus:outline-none",
+                                                            onclick: move |_| {
+                                                                details_key.set(None);
+                                                                preview_key.set(None);
+                                                                edit_key.set(Some(key_for_edit.clone()));
+                                                            },
+                                                            "Edit"
+                                                        }
+                                                    }
+                                                    button {
+                                                        class: "px-2 py-1 text-sm text-white bg-green-500 rounded hover:bg-green-600 focus:outline-none",
+                                                        onclick: move |_| {
+                                                            request_download(account, item_for_download.clone(), restore.clone(), queued_downloads, downloads);
+                                                        },
+                                                        "Download"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    )
+                                })}
+                            }
+                        }
+                    }
+                    div { class: "px-4 py-3 text-xs text-gray-600 bg-gray-50 dark:text-gray-400 dark:bg-gray-800",
+                        if *is_loading.read() {
+                            "Showing {sorted_objects_len} of {total_count} objects, listing more..."
+                        } else {
+                            "Showing {sorted_objects_len} of {total_count} objects"
+                        }
+                        if sorted_objects_len < total_count {
+                            button {
+                                class: "ml-2 text-purple-600 hover:underline",
+                                onclick: move |_| *visible_count.write() += RENDER_BATCH,
+                                "Show more"
+                            }
+                        }
+                        if is_paged {
+                            span { class: "float-right space-x-2",
+                                button {
+                                    class: "px-2 py-1 rounded-md hover:bg-purple-100 disabled:opacity-50 disabled:cursor-not-allowed dark:hover:bg-purple-900",
+                                    disabled: !has_previous || *is_loading.read(),
+                                    onclick: move |_| {
+                                        cursor.write().previous();
+                                    },
+                                    "Previous"
+                                }
+                                span { "Page {page_number}" }
+                                button {
+                                    class: "px-2 py-1 rounded-md hover:bg-purple-100 disabled:opacity-50 disabled:cursor-not-allowed dark:hover:bg-purple-900",
+                                    disabled: !has_next || *is_loading.read(),
+                                    onclick: move |_| {
+                                        cursor.write().advance();
+                                    },
+                                    "Next"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    )
+}
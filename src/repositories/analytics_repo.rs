@@ -0,0 +1,38 @@
+use crate::model::analytics::BucketAnalytics;
+use crate::repositories::database::Database;
+
+/// Replaces the cached analytics of the bucket
+pub fn save_analytics(db: &Database, account_id: i64, analytics: &BucketAnalytics) {
+    let data = serde_json::to_string(analytics).expect("Failed to serialize analytics");
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO bucket_analytics (account_id, bucket, computed_at, data) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(account_id, bucket) DO UPDATE SET computed_at = excluded.computed_at, data = excluded.data",
+        rusqlite::params![account_id, analytics.bucket, analytics.computed_at, data],
+    )
+    .expect("Failed to save analytics");
+}
+
+/// Cached analytics of the account's buckets, by bucket name.
+/// Entries which can't be read anymore (eg. written by an older version) are skipped.
+pub fn fetch_analytics(db: &Database, account_id: i64) -> Vec<BucketAnalytics> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT data FROM bucket_analytics WHERE account_id = ?1 ORDER BY bucket")
+        .expect("prepare failed");
+    stmt.query_map([account_id], |row| row.get::<_, String>(0))
+        .expect("Failed to query analytics")
+        .filter_map(Result::ok)
+        .filter_map(|data| serde_json::from_str(&data).ok())
+        .collect()
+}
+
+/// Forgets the analytics of buckets which don't exist anymore
+pub fn delete_analytics(db: &Database, account_id: i64, bucket: &str) {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM bucket_analytics WHERE account_id = ?1 AND bucket = ?2",
+        rusqlite::params![account_id, bucket],
+    )
+    .expect("Failed to delete analytics");
+}
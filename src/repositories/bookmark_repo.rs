@@ -0,0 +1,35 @@
+use crate::model::s3_uri::Location;
+use crate::repositories::database::Database;
+
+/// Bookmarked location of a commander pane
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub id: i64,
+    pub location: Location,
+}
+
+/// All the bookmarks by location, the ones which can't be parsed anymore are skipped
+pub fn fetch_bookmarks(db: &Database) -> Vec<Bookmark> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, location FROM bookmarks ORDER BY location")
+        .expect("prepare failed");
+    stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .expect("Failed to query bookmarks")
+        .filter_map(Result::ok)
+        .filter_map(|(id, location)| Location::parse(&location).ok().map(|location| Bookmark { id, location }))
+        .collect()
+}
+
+/// Bookmarking a location twice keeps a single bookmark
+pub fn add_bookmark(db: &Database, location: &Location) {
+    let conn = db.conn();
+    conn.execute("INSERT OR IGNORE INTO bookmarks (location) VALUES (?1)", [location.to_string()])
+        .expect("Failed to save bookmark");
+}
+
+pub fn delete_bookmark(db: &Database, id: i64) {
+    let conn = db.conn();
+    conn.execute("DELETE FROM bookmarks WHERE id = ?1", [id])
+        .expect("Failed to delete bookmark");
+}
@@ -0,0 +1,46 @@
+use crate::model::client_encryption::ClientKey;
+use crate::repositories::database::Database;
+
+pub fn fetch_client_keys(db: &Database) -> Vec<ClientKey> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT id, name, key FROM client_keys ORDER BY id")
+        .expect("prepare failed");
+    stmt.query_map([], |row| {
+        Ok(ClientKey {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            key: row.get(2)?,
+        })
+    })
+    .expect("Failed to query client keys")
+    .filter_map(Result::ok)
+    .collect()
+}
+
+pub fn get_client_key(db: &Database, key_id: i64) -> Option<ClientKey> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT id, name, key FROM client_keys WHERE id = ?1",
+        [key_id],
+        |row| {
+            Ok(ClientKey {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                key: row.get(2)?,
+            })
+        },
+    )
+    .ok()
+}
+
+/// Stores a new master key and returns its id
+pub fn save_client_key(db: &Database, name: &str, key: &str) -> Option<i64> {
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO client_keys (name, key) VALUES (?1, ?2)",
+        rusqlite::params![name, key],
+    )
+    .expect("Failed to insert client key");
+    Some(conn.last_insert_rowid())
+}
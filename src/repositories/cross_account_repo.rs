@@ -0,0 +1,69 @@
+use crate::model::cross_account::CrossAccountTransfer;
+use crate::repositories::database::Database;
+
+/// Saves the transfer, a new one gets its id assigned. Only the state of the upload
+/// changes once it's saved.
+pub fn save_transfer(db: &Database, transfer: &mut CrossAccountTransfer) {
+    let conn = db.conn();
+    if transfer.id == 0 {
+        conn.execute(
+            "INSERT INTO cross_account_transfers (source_account_id, source_bucket, source_key,
+                destination_account_id, destination_bucket, destination_key, size, source_e_tag, upload_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                transfer.source_account_id,
+                transfer.source_bucket,
+                transfer.source_key,
+                transfer.destination_account_id,
+                transfer.destination_bucket,
+                transfer.destination_key,
+                transfer.size as i64,
+                transfer.source_e_tag,
+                transfer.upload_id,
+            ],
+        )
+        .expect("Failed to save transfer");
+        transfer.id = conn.last_insert_rowid();
+    } else {
+        conn.execute(
+            "UPDATE cross_account_transfers SET size = ?1, source_e_tag = ?2, upload_id = ?3 WHERE id = ?4",
+            rusqlite::params![transfer.size as i64, transfer.source_e_tag, transfer.upload_id, transfer.id],
+        )
+        .expect("Failed to update transfer");
+    }
+}
+
+/// Transfers which didn't complete, the oldest first
+pub fn fetch_transfers(db: &Database) -> Vec<CrossAccountTransfer> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, source_account_id, source_bucket, source_key, destination_account_id,
+                destination_bucket, destination_key, size, source_e_tag, upload_id
+             FROM cross_account_transfers ORDER BY id",
+        )
+        .expect("prepare failed");
+    stmt.query_map([], |row| {
+        Ok(CrossAccountTransfer {
+            id: row.get(0)?,
+            source_account_id: row.get(1)?,
+            source_bucket: row.get(2)?,
+            source_key: row.get(3)?,
+            destination_account_id: row.get(4)?,
+            destination_bucket: row.get(5)?,
+            destination_key: row.get(6)?,
+            size: row.get::<_, i64>(7)? as u64,
+            source_e_tag: row.get(8)?,
+            upload_id: row.get(9)?,
+        })
+    })
+    .expect("Failed to query transfers")
+    .filter_map(Result::ok)
+    .collect()
+}
+
+pub fn delete_transfer(db: &Database, id: i64) {
+    let conn = db.conn();
+    conn.execute("DELETE FROM cross_account_transfers WHERE id = ?1", [id])
+        .expect("Failed to delete transfer");
+}
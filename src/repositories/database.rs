@@ -0,0 +1,192 @@
+//! Handle of the SQLite database keeping the accounts, the client-side encryption keys,
+//! the local index, the cached storage analytics and the storage prices.
+//! The repositories get it passed in, so they work outside the app too.
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use color_eyre::eyre;
+use rusqlite::Connection;
+
+/// Shared connection to the database, cheap to clone
+#[derive(Clone)]
+pub struct Database {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl Database {
+    /// Opens the database file, creating it (and its directory) when missing.
+    /// The schema is migrated to the current version.
+    pub fn open(path: &Path) -> eyre::Result<Database> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Database living only as long as the handle, eg. for tests
+    pub fn open_in_memory() -> eyre::Result<Database> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> eyre::Result<Database> {
+        migrate(&conn)?;
+        Ok(Database { conn: Arc::new(Mutex::new(conn)) })
+    }
+
+    /// Locks the connection for the duration of a query
+    pub(crate) fn conn(&self) -> MutexGuard<'_, Connection> {
+        // a panic while holding the lock doesn't leave the connection unusable
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Database")
+    }
+}
+
+impl PartialEq for Database {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.conn, &other.conn)
+    }
+}
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS accounts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            description TEXT,
+            access_key TEXT,
+            secret_key TEXT,
+            is_default INTEGER,
+            default_region TEXT
+        )",
+        [],
+    )?;
+
+    // Columns added after the first release, existing databases need to be migrated
+    add_column_if_missing(conn, "accounts", "sse_mode", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "sse_kms_key_id", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "sse_customer_key", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "cse_key_id", "INTEGER")?;
+    add_column_if_missing(conn, "accounts", "endpoint_url", "TEXT")?;
+
+    // Master keys of the client-side encryption, objects can't be decrypted without them
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS client_keys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            key TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // Local index of bucket contents, filled by the crawler
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bucket_index (
+            account_id INTEGER NOT NULL,
+            bucket TEXT NOT NULL,
+            last_indexed INTEGER,
+            generation INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (account_id, bucket)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS object_index (
+            account_id INTEGER NOT NULL,
+            bucket TEXT NOT NULL,
+            key TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            last_modified INTEGER,
+            e_tag TEXT,
+            storage_class TEXT,
+            generation INTEGER NOT NULL,
+            PRIMARY KEY (account_id, bucket, key)
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS object_index_size ON object_index (account_id, bucket, size)",
+        [],
+    )?;
+
+    // Storage analytics of the buckets as of their last refresh, serialized as JSON
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bucket_analytics (
+            account_id INTEGER NOT NULL,
+            bucket TEXT NOT NULL,
+            computed_at INTEGER NOT NULL,
+            data TEXT NOT NULL,
+            PRIMARY KEY (account_id, bucket)
+        )",
+        [],
+    )?;
+
+    // Edited prices of the cost estimates, the built-in ones are used while it's empty
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS storage_prices (
+            region TEXT NOT NULL,
+            storage_class TEXT NOT NULL,
+            per_gb_month REAL NOT NULL,
+            PRIMARY KEY (region, storage_class)
+        )",
+        [],
+    )?;
+
+    // Bookmarks of the commander panes, local paths or s3:// URIs
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            location TEXT NOT NULL UNIQUE
+        )",
+        [],
+    )?;
+
+    // Rules setting the headers of uploads by key, for all the buckets of an account when bucket is NULL.
+    // The metadata is serialized as a JSON object.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS header_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id INTEGER NOT NULL,
+            bucket TEXT,
+            pattern TEXT NOT NULL,
+            content_type TEXT,
+            cache_control TEXT,
+            content_encoding TEXT,
+            metadata TEXT NOT NULL DEFAULT '{}'
+        )",
+        [],
+    )?;
+
+    // Cross-account transfers with a multipart upload in progress, kept until they complete
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS cross_account_transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_account_id INTEGER NOT NULL,
+            source_bucket TEXT NOT NULL,
+            source_key TEXT NOT NULL,
+            destination_account_id INTEGER NOT NULL,
+            destination_bucket TEXT NOT NULL,
+            destination_key TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            source_e_tag TEXT,
+            upload_id TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Adds the column to the table unless it's already there
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let exists = conn
+        .prepare(&format!("SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1", table))
+        .and_then(|mut stmt| stmt.exists([column]))?;
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    }
+    Ok(())
+}
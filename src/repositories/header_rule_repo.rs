@@ -0,0 +1,72 @@
+use crate::model::header_rule::HeaderRule;
+use crate::repositories::database::Database;
+
+/// Saves the rule, a new one gets its id assigned
+pub fn save_header_rule(db: &Database, rule: &mut HeaderRule) {
+    let metadata = serde_json::to_string(&rule.metadata).expect("Failed to serialize metadata");
+    let conn = db.conn();
+    if rule.id == 0 {
+        conn.execute(
+            "INSERT INTO header_rules (account_id, bucket, pattern, content_type, cache_control, content_encoding, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                rule.account_id,
+                rule.bucket,
+                rule.pattern,
+                rule.content_type,
+                rule.cache_control,
+                rule.content_encoding,
+                metadata,
+            ],
+        )
+        .expect("Failed to save header rule");
+        rule.id = conn.last_insert_rowid();
+    } else {
+        conn.execute(
+            "UPDATE header_rules SET bucket = ?1, pattern = ?2, content_type = ?3, cache_control = ?4,
+                content_encoding = ?5, metadata = ?6 WHERE id = ?7",
+            rusqlite::params![
+                rule.bucket,
+                rule.pattern,
+                rule.content_type,
+                rule.cache_control,
+                rule.content_encoding,
+                metadata,
+                rule.id,
+            ],
+        )
+        .expect("Failed to update header rule");
+    }
+}
+
+/// Rules of the account, the ones of all buckets first, in the order they were added
+pub fn fetch_header_rules(db: &Database, account_id: i64) -> Vec<HeaderRule> {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, account_id, bucket, pattern, content_type, cache_control, content_encoding, metadata
+             FROM header_rules WHERE account_id = ?1 ORDER BY bucket IS NOT NULL, id",
+        )
+        .expect("prepare failed");
+    stmt.query_map([account_id], |row| {
+        Ok(HeaderRule {
+            id: row.get(0)?,
+            account_id: row.get(1)?,
+            bucket: row.get(2)?,
+            pattern: row.get(3)?,
+            content_type: row.get(4)?,
+            cache_control: row.get(5)?,
+            content_encoding: row.get(6)?,
+            metadata: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
+        })
+    })
+    .expect("Failed to query header rules")
+    .filter_map(Result::ok)
+    .collect()
+}
+
+pub fn delete_header_rule(db: &Database, id: i64) {
+    let conn = db.conn();
+    conn.execute("DELETE FROM header_rules WHERE id = ?1", [id])
+        .expect("Failed to delete header rule");
+}
@@ -0,0 +1,164 @@
+use crate::model::object_index::{IndexInfo, IndexQuery, IndexedObject, StorageClassStats};
+use crate::repositories::database::Database;
+
+fn indexed_object(row: &rusqlite::Row) -> rusqlite::Result<IndexedObject> {
+    Ok(IndexedObject {
+        key: row.get(0)?,
+        size: row.get::<_, i64>(1)? as u64,
+        last_modified: row.get(2)?,
+        e_tag: row.get(3)?,
+        storage_class: row.get(4)?,
+    })
+}
+
+/// Starts a new crawl of the bucket and returns its generation.
+/// Objects not seen by the crawl keep the previous generation and are removed when it finishes.
+pub fn start_index_generation(db: &Database, account_id: i64, bucket: &str) -> i64 {
+    let conn = db.conn();
+    conn.execute(
+        "INSERT INTO bucket_index (account_id, bucket, generation) VALUES (?1, ?2, 1)
+         ON CONFLICT(account_id, bucket) DO UPDATE SET generation = generation + 1",
+        rusqlite::params![account_id, bucket],
+    ).expect("Failed to start bucket indexing");
+    conn.query_row(
+        "SELECT generation FROM bucket_index WHERE account_id = ?1 AND bucket = ?2",
+        rusqlite::params![account_id, bucket],
+        |row| row.get(0),
+    ).expect("Failed to read index generation")
+}
+
+/// Stores a page of crawled objects, all in a single transaction
+pub fn save_indexed_objects(db: &Database, account_id: i64, bucket: &str, generation: i64, objects: &[IndexedObject]) {
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction().expect("Failed to start transaction");
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO object_index (account_id, bucket, key, size, last_modified, e_tag, storage_class, generation)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(account_id, bucket, key) DO UPDATE SET
+                size = excluded.size, last_modified = excluded.last_modified, e_tag = excluded.e_tag,
+                storage_class = excluded.storage_class, generation = excluded.generation",
+        ).expect("prepare failed");
+        for object in objects {
+            stmt.execute(rusqlite::params![
+                account_id,
+                bucket,
+                object.key,
+                object.size as i64,
+                object.last_modified,
+                object.e_tag,
+                object.storage_class,
+                generation
+            ]).expect("Failed to save indexed object");
+        }
+    }
+    tx.commit().expect("Failed to commit indexed objects");
+}
+
+/// Removes the objects which are gone from the bucket and marks the index as up to date
+pub fn finish_index_generation(db: &Database, account_id: i64, bucket: &str, generation: i64, indexed_at: i64) {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM object_index WHERE account_id = ?1 AND bucket = ?2 AND generation <> ?3",
+        rusqlite::params![account_id, bucket, generation],
+    ).expect("Failed to remove stale indexed objects");
+    conn.execute(
+        "UPDATE bucket_index SET last_indexed = ?3 WHERE account_id = ?1 AND bucket = ?2",
+        rusqlite::params![account_id, bucket, indexed_at],
+    ).expect("Failed to update bucket index");
+}
+
+pub fn get_index_info(db: &Database, account_id: i64, bucket: &str) -> Option<IndexInfo> {
+    let conn = db.conn();
+    conn.query_row(
+        "SELECT b.last_indexed, COUNT(o.key), COALESCE(SUM(o.size), 0)
+         FROM bucket_index b
+         LEFT JOIN object_index o ON o.account_id = b.account_id AND o.bucket = b.bucket
+         WHERE b.account_id = ?1 AND b.bucket = ?2
+         GROUP BY b.account_id, b.bucket",
+        rusqlite::params![account_id, bucket],
+        |row| {
+            Ok(IndexInfo {
+                bucket: bucket.to_string(),
+                last_indexed: row.get(0)?,
+                object_count: row.get::<_, i64>(1)? as u64,
+                total_size: row.get::<_, i64>(2)? as u64,
+            })
+        },
+    ).ok()
+}
+
+pub fn fetch_storage_class_stats(db: &Database, account_id: i64, bucket: &str) -> Vec<StorageClassStats> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT COALESCE(storage_class, 'STANDARD') AS class, COUNT(*), SUM(size)
+         FROM object_index WHERE account_id = ?1 AND bucket = ?2
+         GROUP BY class ORDER BY SUM(size) DESC",
+    ).expect("prepare failed");
+    stmt.query_map(rusqlite::params![account_id, bucket], |row| {
+        Ok(StorageClassStats {
+            storage_class: row.get(0)?,
+            object_count: row.get::<_, i64>(1)? as u64,
+            total_size: row.get::<_, i64>(2)? as u64,
+        })
+    })
+    .expect("Failed to query storage class stats")
+    .filter_map(Result::ok)
+    .collect()
+}
+
+pub fn query_index(db: &Database, account_id: i64, bucket: &str, query: &IndexQuery) -> Vec<IndexedObject> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT key, size, last_modified, e_tag, storage_class FROM object_index
+         WHERE account_id = ?1 AND bucket = ?2 AND key LIKE ?3 ESCAPE '\\'
+         ORDER BY {} LIMIT ?4",
+        query.order_by()
+    )).expect("prepare failed");
+    stmt.query_map(
+        rusqlite::params![account_id, bucket, query.like_pattern(), query.limit as i64],
+        indexed_object,
+    )
+    .expect("Failed to query index")
+    .filter_map(Result::ok)
+    .collect()
+}
+
+/// Goes through the indexed keys (starting with the prefix) and returns up to `limit` of them
+/// accepted by `matches`
+pub fn search_index(
+    db: &Database,
+    account_id: i64,
+    bucket: &str,
+    prefix: Option<&str>,
+    matches: impl Fn(&str) -> bool,
+    limit: usize,
+) -> Vec<IndexedObject> {
+    let conn = db.conn();
+    let mut stmt = conn.prepare(
+        "SELECT key, size, last_modified, e_tag, storage_class FROM object_index
+         WHERE account_id = ?1 AND bucket = ?2 AND key >= ?3
+         ORDER BY key",
+    ).expect("prepare failed");
+    let prefix = prefix.unwrap_or_default();
+    stmt.query_map(rusqlite::params![account_id, bucket, prefix], indexed_object)
+    .expect("Failed to search index")
+    .filter_map(Result::ok)
+    // keys are ordered, so the ones with the prefix are all at the start
+    .take_while(|object| object.key.starts_with(prefix))
+    .filter(|object| matches(&object.key))
+    .take(limit)
+    .collect()
+}
+
+pub fn delete_index(db: &Database, account_id: i64, bucket: &str) {
+    let conn = db.conn();
+    conn.execute(
+        "DELETE FROM object_index WHERE account_id = ?1 AND bucket = ?2",
+        rusqlite::params![account_id, bucket],
+    ).expect("Failed to delete indexed objects");
+    conn.execute(
+        "DELETE FROM bucket_index WHERE account_id = ?1 AND bucket = ?2",
+        rusqlite::params![account_id, bucket],
+    ).expect("Failed to delete bucket index");
+}
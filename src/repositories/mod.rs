@@ -1 +1,9 @@
-pub mod account_repo;
\ No newline at end of file
+pub mod account_repo;
+pub mod analytics_repo;
+pub mod bookmark_repo;
+pub mod client_key_repo;
+pub mod cross_account_repo;
+pub mod database;
+pub mod header_rule_repo;
+pub mod index_repo;
+pub mod pricing_repo;
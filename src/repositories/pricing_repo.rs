@@ -0,0 +1,49 @@
+use crate::model::pricing::{PriceTable, StoragePrice};
+use crate::repositories::database::Database;
+
+/// The edited price table, the built-in one until it's edited
+pub fn fetch_price_table(db: &Database) -> PriceTable {
+    let conn = db.conn();
+    let mut stmt = conn
+        .prepare("SELECT region, storage_class, per_gb_month FROM storage_prices ORDER BY region, storage_class")
+        .expect("prepare failed");
+    let prices: Vec<StoragePrice> = stmt
+        .query_map([], |row| {
+            Ok(StoragePrice {
+                region: row.get(0)?,
+                storage_class: row.get(1)?,
+                per_gb_month: row.get(2)?,
+            })
+        })
+        .expect("Failed to query storage prices")
+        .filter_map(Result::ok)
+        .collect();
+    if prices.is_empty() {
+        PriceTable::default()
+    } else {
+        PriceTable { prices }
+    }
+}
+
+/// Replaces the whole price table, rows with the same region and class are stored once
+pub fn save_price_table(db: &Database, table: &PriceTable) {
+    let conn = db.conn();
+    let tx = conn.unchecked_transaction().expect("Failed to start transaction");
+    tx.execute("DELETE FROM storage_prices", []).expect("Failed to clear storage prices");
+    {
+        let mut stmt = tx
+            .prepare("INSERT OR REPLACE INTO storage_prices (region, storage_class, per_gb_month) VALUES (?1, ?2, ?3)")
+            .expect("prepare failed");
+        for price in &table.prices {
+            stmt.execute(rusqlite::params![price.region, price.storage_class, price.per_gb_month])
+                .expect("Failed to save storage price");
+        }
+    }
+    tx.commit().expect("Failed to commit storage prices");
+}
+
+/// Goes back to the built-in prices
+pub fn reset_price_table(db: &Database) {
+    let conn = db.conn();
+    conn.execute("DELETE FROM storage_prices", []).expect("Failed to clear storage prices");
+}
@@ -0,0 +1,34 @@
+//! Storage analytics of the buckets, computed from their listings
+use std::time::{SystemTime, UNIX_EPOCH};
+use color_eyre::eyre;
+use tokio_stream::StreamExt;
+use crate::model::analytics::{AnalyticsBuilder, BucketAnalytics};
+use crate::services::s3_data_fetcher::{ListingMode, S3DataFetcher};
+
+/// Lists the whole bucket and its incomplete multipart uploads.
+/// `on_progress` gets the number of objects analyzed so far after every page.
+pub async fn analyze_bucket(
+    fetcher: &S3DataFetcher,
+    bucket: &str,
+    on_progress: impl Fn(u64),
+) -> eyre::Result<BucketAnalytics> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let region = fetcher.get_bucket_location(bucket).await.ok();
+    let mut builder = AnalyticsBuilder::new(bucket, region, now);
+
+    let mut count = 0;
+    let mut pages = fetcher.stream_objects(bucket, None, ListingMode::Flat).await?;
+    while let Some(page) = pages.next().await {
+        let items = page?;
+        count += items.len() as u64;
+        items.iter().for_each(|item| builder.add(item));
+        on_progress(count);
+    }
+
+    // not every s3 compatible service supports listing the uploads, the rest is still useful
+    match fetcher.list_multipart_uploads(bucket).await {
+        Ok(uploads) => uploads.into_iter().for_each(|upload| builder.add_incomplete_upload(upload)),
+        Err(e) => builder.incomplete_uploads_failed(e.to_string()),
+    }
+    Ok(builder.finish())
+}
@@ -0,0 +1,310 @@
+//! Copies objects between buckets of different accounts, possibly on different s3 compatible
+//! services. Server-side copies can't cross credentials, so the objects are streamed from a
+//! `get_object` of the source into an upload on the destination without touching the disk.
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::Client;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::{Bytes, BytesMut};
+use color_eyre::{eyre, Report};
+use md5::{Digest, Md5};
+use crate::model::cross_account::{e_tag_digest, hex_e_tag, multipart_e_tag, part_size, reusable_parts, CrossAccountTransfer};
+use crate::model::encryption::EncryptionSettings;
+use crate::repositories::cross_account_repo::{delete_transfer, save_transfer};
+use crate::repositories::database::Database;
+use crate::services::s3_data_fetcher::S3DataFetcher;
+
+/// Objects bigger than a part are uploaded in parts of at least this size
+pub const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The Content-MD5 header of the data with this digest, s3 rejects the request when the data
+/// arrives damaged
+fn content_md5(digest: &[u8; 16]) -> String {
+    STANDARD.encode(digest)
+}
+
+/// The transfer completed, it's not offered to be resumed anymore
+fn forget_transfer(db: &Database, transfer: &mut CrossAccountTransfer) {
+    if transfer.id != 0 {
+        delete_transfer(db, transfer.id);
+    }
+    transfer.id = 0;
+    transfer.upload_id = None;
+}
+
+/// Copies the object, objects bigger than `min_part_size` are uploaded in parts.
+/// The multipart upload is saved as soon as it's created and forgotten once the copy
+/// completes, so a transfer which was interrupted continues after its uploaded parts
+/// when it's started again. `on_progress` gets the bytes copied so far and the size of the object.
+///
+/// The stored bytes are copied with the user metadata and the web headers and storage class
+/// of the source, so objects encrypted on the client side stay encrypted with the source
+/// account's key. The copy is encrypted on the server side with the destination account's
+/// settings and is verified with the digests of the uploaded data.
+pub async fn transfer_object(
+    db: &Database,
+    source: &S3DataFetcher,
+    destination: &S3DataFetcher,
+    transfer: &mut CrossAccountTransfer,
+    min_part_size: u64,
+    on_progress: impl Fn(u64, u64),
+) -> eyre::Result<()> {
+    let source_client = source.s3_client().await;
+    let (head, read_encryption) = source
+        .head_object_with_encryption(&source_client, &transfer.source_bucket, &transfer.source_key, &source.account_encryption())
+        .await?;
+    let size = head.content_length().unwrap_or_default().max(0) as u64;
+    let e_tag = head.e_tag().map(String::from);
+    if transfer.upload_id.is_some() && (transfer.size != size || transfer.source_e_tag != e_tag) {
+        // the object changed since the transfer started, its parts can't be reused
+        if let Some(upload_id) = transfer.upload_id.take() {
+            let _ = destination
+                .abort_multipart_upload(&transfer.destination_bucket, &transfer.destination_key, &upload_id)
+                .await;
+        }
+    }
+    transfer.size = size;
+    transfer.source_e_tag = e_tag.clone();
+
+    let destination_client = destination.s3_client().await;
+    let encryption = destination.account_encryption();
+    let part_size = part_size(size, min_part_size);
+    let mut parts = match transfer.upload_id.clone() {
+        Some(upload_id) => uploaded_parts(&destination_client, transfer, &upload_id, part_size).await?,
+        None => Vec::new(),
+    };
+    if transfer.upload_id.is_none() && size > part_size {
+        let upload = destination_client
+            .create_multipart_upload()
+            .bucket(&transfer.destination_bucket)
+            .key(&transfer.destination_key)
+            .set_content_type(head.content_type().map(String::from))
+            .set_cache_control(head.cache_control().map(String::from))
+            .set_content_encoding(head.content_encoding().map(String::from))
+            .set_content_disposition(head.content_disposition().map(String::from))
+            .set_storage_class(head.storage_class().cloned())
+            .set_metadata(head.metadata().cloned())
+            .set_server_side_encryption(encryption.server_side_encryption())
+            .set_ssekms_key_id(encryption.kms_key_id())
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        transfer.upload_id = Some(upload.upload_id().ok_or_else(|| Report::msg("Missing upload id"))?.to_string());
+        save_transfer(db, transfer);
+    }
+
+    let mut offset = parts.len() as u64 * part_size;
+    on_progress(offset, size);
+    // a resumed upload may have all its parts already
+    let mut body = if transfer.upload_id.is_none() || offset < size {
+        Some(read_object(&source_client, transfer, offset, e_tag, &read_encryption).await?)
+    } else {
+        None
+    };
+
+    let Some(upload_id) = transfer.upload_id.clone() else {
+        // a single part, sent with one request
+        let data = match body {
+            Some(body) => body.collect().await?.into_bytes(),
+            None => Bytes::new(),
+        };
+        let digest: [u8; 16] = Md5::digest(&data).into();
+        destination_client
+            .put_object()
+            .bucket(&transfer.destination_bucket)
+            .key(&transfer.destination_key)
+            .content_md5(content_md5(&digest))
+            .set_content_type(head.content_type().map(String::from))
+            .set_cache_control(head.cache_control().map(String::from))
+            .set_content_encoding(head.content_encoding().map(String::from))
+            .set_content_disposition(head.content_disposition().map(String::from))
+            .set_storage_class(head.storage_class().cloned())
+            .set_metadata(head.metadata().cloned())
+            .set_server_side_encryption(encryption.server_side_encryption())
+            .set_ssekms_key_id(encryption.kms_key_id())
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+            .body(data.into())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        on_progress(size, size);
+        // a resumed transfer ends here too when the source changed and became small
+        forget_transfer(db, transfer);
+        return verify(&destination_client, transfer, &encryption, Some(hex_e_tag(&digest))).await;
+    };
+
+    // the listed ETags of the reused parts are their digests unless they're encrypted with KMS
+    // or a customer key, the copy is verified with its size only then
+    let mut digests: Vec<Option<[u8; 16]>> =
+        parts.iter().map(|part| part.e_tag().and_then(e_tag_digest)).collect();
+    // at most one part is kept in memory, the failed uploads are kept to be resumed
+    let mut buffer = BytesMut::with_capacity(part_size as usize);
+    loop {
+        let chunk = match &mut body {
+            Some(body) => body.try_next().await?,
+            None => None,
+        };
+        if let Some(chunk) = &chunk {
+            buffer.extend_from_slice(chunk);
+        }
+        while buffer.len() as u64 >= part_size || (chunk.is_none() && !buffer.is_empty()) {
+            let length = buffer.len().min(part_size as usize);
+            let data = buffer.split_to(length).freeze();
+            let part_number = parts.len() as i32 + 1;
+            let digest: [u8; 16] = Md5::digest(&data).into();
+            digests.push(Some(digest));
+            parts.push(upload_part(&destination_client, transfer, &upload_id, part_number, data, &digest, &encryption).await?);
+            offset += length as u64;
+            on_progress(offset, size);
+        }
+        if chunk.is_none() {
+            break;
+        }
+    }
+    if offset != size {
+        return Err(Report::msg(format!("The source object ended after {} of {} bytes", offset, size)));
+    }
+
+    destination_client
+        .complete_multipart_upload()
+        .bucket(&transfer.destination_bucket)
+        .key(&transfer.destination_key)
+        .upload_id(&upload_id)
+        .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+        .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+        .set_sse_customer_key(encryption.sse_customer_key())
+        .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+        .send()
+        .await
+        .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+    forget_transfer(db, transfer);
+    let expected_e_tag = digests.into_iter().collect::<Option<Vec<_>>>().map(|digests| multipart_e_tag(&digests));
+    verify(&destination_client, transfer, &encryption, expected_e_tag).await
+}
+
+/// Body of the source object from the offset on, the read fails when the object is replaced
+async fn read_object(
+    client: &Client,
+    transfer: &CrossAccountTransfer,
+    offset: u64,
+    e_tag: Option<String>,
+    read_encryption: &EncryptionSettings,
+) -> eyre::Result<ByteStream> {
+    let object = client
+        .get_object()
+        .bucket(&transfer.source_bucket)
+        .key(&transfer.source_key)
+        .set_range((offset > 0).then(|| format!("bytes={}-", offset)))
+        .set_if_match(e_tag)
+        .set_sse_customer_algorithm(read_encryption.sse_customer_algorithm())
+        .set_sse_customer_key(read_encryption.sse_customer_key())
+        .set_sse_customer_key_md5(read_encryption.sse_customer_key_md5())
+        .send()
+        .await
+        .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+    Ok(object.body)
+}
+
+async fn upload_part(
+    client: &Client,
+    transfer: &CrossAccountTransfer,
+    upload_id: &str,
+    part_number: i32,
+    data: Bytes,
+    digest: &[u8; 16],
+    encryption: &EncryptionSettings,
+) -> eyre::Result<CompletedPart> {
+    let part = client
+        .upload_part()
+        .bucket(&transfer.destination_bucket)
+        .key(&transfer.destination_key)
+        .upload_id(upload_id)
+        .part_number(part_number)
+        .content_md5(content_md5(digest))
+        .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+        .set_sse_customer_key(encryption.sse_customer_key())
+        .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+        .body(data.into())
+        .send()
+        .await
+        .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+    Ok(CompletedPart::builder()
+        .set_e_tag(part.e_tag().map(String::from))
+        .part_number(part_number)
+        .build())
+}
+
+/// Parts of the saved upload the transfer continues after
+async fn uploaded_parts(
+    client: &Client,
+    transfer: &CrossAccountTransfer,
+    upload_id: &str,
+    part_size: u64,
+) -> eyre::Result<Vec<CompletedPart>> {
+    let mut pages = client
+        .list_parts()
+        .bucket(&transfer.destination_bucket)
+        .key(&transfer.destination_key)
+        .upload_id(upload_id)
+        .into_paginator()
+        .send();
+    let mut parts = Vec::new();
+    while let Some(page) = pages.next().await {
+        let page = page.map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        parts.extend(page.parts().iter().map(|part| {
+            (part.part_number().unwrap_or_default(), part.size().unwrap_or_default().max(0) as u64, part.e_tag().map(String::from))
+        }));
+    }
+    parts.sort_by_key(|(number, _, _)| *number);
+    let sizes: Vec<(i32, u64)> = parts.iter().map(|(number, size, _)| (*number, *size)).collect();
+    let reusable = reusable_parts(&sizes, part_size);
+    Ok(parts
+        .into_iter()
+        .take(reusable)
+        .map(|(number, _, e_tag)| CompletedPart::builder().set_e_tag(e_tag).part_number(number).build())
+        .collect())
+}
+
+/// The copy has to have the size of the source object and the ETag of the uploaded data.
+/// The ETags of objects encrypted with KMS or a customer key aren't digests of their data,
+/// only their size is compared.
+async fn verify(
+    client: &Client,
+    transfer: &CrossAccountTransfer,
+    encryption: &EncryptionSettings,
+    expected_e_tag: Option<String>,
+) -> eyre::Result<()> {
+    let head = client
+        .head_object()
+        .bucket(&transfer.destination_bucket)
+        .key(&transfer.destination_key)
+        .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+        .set_sse_customer_key(encryption.sse_customer_key())
+        .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+        .send()
+        .await
+        .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+    let copied = head.content_length().unwrap_or_default().max(0) as u64;
+    if copied != transfer.size {
+        return Err(Report::msg(format!(
+            "The copy of '{}' has {} bytes instead of {}",
+            transfer.source_key, copied, transfer.size
+        )));
+    }
+    let digest_e_tag = head.sse_customer_algorithm().is_none()
+        && !head.server_side_encryption().is_some_and(|sse| sse.as_str().starts_with("aws:kms"));
+    let e_tag = head.e_tag().unwrap_or_default().trim_matches('"');
+    match expected_e_tag {
+        Some(expected) if digest_e_tag && e_tag != expected => Err(Report::msg(format!(
+            "The copy of '{}' doesn't match the uploaded data, its ETag is {} instead of {}",
+            transfer.source_key, e_tag, expected
+        ))),
+        _ => Ok(()),
+    }
+}
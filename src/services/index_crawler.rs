@@ -0,0 +1,63 @@
+//! Crawler filling the local index of bucket contents
+use std::time::{SystemTime, UNIX_EPOCH};
+use color_eyre::{eyre, Report};
+use crate::model::object_index::IndexedObject;
+use crate::model::s3_data_item::S3DataItem;
+use crate::model::search::SearchPattern;
+use crate::repositories::database::Database;
+use crate::repositories::index_repo::{finish_index_generation, save_indexed_objects, start_index_generation};
+use crate::services::s3_data_fetcher::S3DataFetcher;
+
+/// Crawled objects are written to the index in batches of this size
+const BATCH_SIZE: usize = 1000;
+
+/// Lists the whole bucket, writing the objects to the index page by page,
+/// so the index stays usable (with the previous results) while it's refreshed.
+/// `on_progress` gets the number of objects indexed so far after every batch.
+pub async fn crawl_bucket(
+    fetcher: &S3DataFetcher,
+    db: &Database,
+    bucket: &str,
+    on_progress: impl Fn(usize),
+) -> eyre::Result<usize> {
+    let account_id = fetcher.account().id;
+    let generation = {
+        let (db, bucket) = (db.clone(), bucket.to_string());
+        tokio::task::spawn_blocking(move || start_index_generation(&db, account_id, &bucket)).await?
+    };
+
+    let everything = SearchPattern::Prefix(String::new());
+    let (objects_tx, mut objects_rx) = tokio::sync::mpsc::unbounded_channel::<S3DataItem>();
+    let listing = fetcher.search_objects(bucket, &everything, objects_tx);
+    let indexing = async {
+        let mut count = 0;
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        loop {
+            let item = objects_rx.recv().await;
+            if let Some(item) = &item {
+                batch.push(IndexedObject::from_s3_data_item(item));
+            }
+            if batch.len() >= BATCH_SIZE || (item.is_none() && !batch.is_empty()) {
+                count += batch.len();
+                let objects = std::mem::take(&mut batch);
+                let (db, bucket_name) = (db.clone(), bucket.to_string());
+                tokio::task::spawn_blocking(move || {
+                    save_indexed_objects(&db, account_id, &bucket_name, generation, &objects)
+                })
+                .await?;
+                on_progress(count);
+            }
+            if item.is_none() {
+                return Ok::<usize, Report>(count);
+            }
+        }
+    };
+    let (listed, indexed) = tokio::join!(listing, indexing);
+    listed?;
+    let count = indexed?;
+
+    let indexed_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let (db, bucket) = (db.clone(), bucket.to_string());
+    tokio::task::spawn_blocking(move || finish_index_generation(&db, account_id, &bucket, generation, indexed_at)).await?;
+    Ok(count)
+}
@@ -2,3 +2,6 @@
 //! as your local machine
 
 pub mod s3_data_fetcher;
+pub mod index_crawler;
+pub mod analytics;
+pub mod cross_account;
@@ -1,37 +1,108 @@
+use crate::model::bucket::{BucketOptions, DEFAULT_BUCKET_REGION};
+use crate::model::content_type::{detect_content_type, UploadHeaders};
+use crate::model::header_rule::resolve_headers;
 use crate::model::local_selected_item::LocalSelectedItem;
 use crate::model::s3_data_item::{BucketInfo, FileInfo, S3DataItem};
 use crate::model::s3_selected_item::S3SelectedItem;
-use crate::settings::file_credentials::FileCredential;
 use aws_sdk_s3::config::{Credentials, Region};
-use dioxus::prelude::*;
 use std::fs::File;
+use std::collections::{BTreeMap, HashMap};
 use std::io::Write;
 use std::path::Path;
 use std::{
     fs,
     path::PathBuf,
-    pin::Pin
 };
 use tokio::sync::mpsc::UnboundedSender;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use bytes::Bytes;
 
+use crate::model::client_encryption::{
+    is_client_encrypted, ChunkDecryptor, ChunkEncryptor, ClientKey, EnvelopeHeader, DEFAULT_CHUNK_SIZE,
+};
 use crate::model::download_progress_item::DownloadProgressItem;
+use crate::model::encryption::{encryption_label, EncryptionSettings};
+use crate::model::search::SearchPattern;
+use crate::model::storage_class::RestoreStatus;
+use crate::model::transfer_options::TransferOptions;
+use crate::model::page_cursor::ListingPage;
+use crate::model::text_edit::{SaveOutcome, EDITOR_SIZE_LIMIT};
+use crate::model::object_details::{ObjectDetails, ObjectMetadataUpdate, ObjectTagsUpdate};
+use crate::model::object_lock::{is_locked, lock_error_message, locked_message, BucketObjectLock, DefaultRetention, RetentionPeriod};
 use crate::model::upload_progress_item::UploadProgressItem;
+use crate::model::website::{RoutingRule, WebsiteSettings};
 use aws_config::meta::region::RegionProviderChain;
-use aws_sdk_s3::types::{BucketLocationConstraint, CreateBucketConfiguration};
+use aws_config::SdkConfig;
+use aws_sdk_s3::operation::head_object::HeadObjectOutput;
+use aws_sdk_s3::operation::list_objects_v2::ListObjectsV2Output;
+use aws_sdk_s3::types::{
+    AbortIncompleteMultipartUpload, BucketLifecycleConfiguration, BucketLocationConstraint, BucketVersioningStatus,
+    CompletedMultipartUpload, CompletedPart, Condition, CreateBucketConfiguration, ErrorDocument, ExpirationStatus,
+    GlacierJobParameters, IndexDocument, Redirect, RedirectAllRequestsTo, WebsiteConfiguration,
+    LifecycleRule, LifecycleRuleFilter, MetadataDirective, Object, ObjectLockConfiguration, ObjectLockEnabled,
+    ObjectLockLegalHold, ObjectLockLegalHoldStatus, ObjectLockRetention, ObjectLockRetentionMode, ObjectLockRule,
+    PublicAccessBlockConfiguration, RestoreRequest,
+    ServerSideEncryption, ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule,
+    StorageClass, Tag, Tagging, Tier, VersioningConfiguration,
+};
 use aws_sdk_s3::{
-    primitives::ByteStream,
+    primitives::{ByteStream, DateTime, Length},
     Client,
 };
-use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata};
+use aws_sdk_s3::presigning::PresigningConfig;
 use color_eyre::{eyre, Report};
-use crate::repositories::account_repo::get_default_account;
-use crate::utils::CURRENT_ACCOUNT;
+use crate::repositories::client_key_repo::get_client_key;
+use crate::repositories::header_rule_repo::fetch_header_rules;
+use crate::repositories::database::Database;
+use crate::model::account::Account;
+use crate::model::analytics::IncompleteUpload;
+
+/// Id of the lifecycle rule aborting the incomplete multipart uploads, managed by the app
+pub const ABORT_UPLOADS_RULE_ID: &str = "dios3-abort-incomplete-multipart-uploads";
+
+/// Files bigger than this are uploaded in parts
+const MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// A multiple of the chunks of client-side encryption, so encrypted parts are made of whole chunks
+const MULTIPART_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+/// HEAD requests looking up the encryption of the listed objects at the same time
+const ENCRYPTION_LOOKUPS: usize = 8;
+/// Largest object `copy_object` accepts, bigger ones have to be copied in parts
+const MAX_COPY_OBJECT_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Reads `length` bytes of the file from `offset`
+async fn read_range(path: &Path, offset: u64, length: u64) -> eyre::Result<Vec<u8>> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    let mut buffer = vec![0u8; length as usize];
+    file.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+/// How the listing walks through the "directories" of a bucket
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListingMode {
+    /// One level under the prefix, deeper prefixes are returned as directories
+    Delimited,
+    /// Every object under the prefix, without a delimiter
+    Flat,
+}
 
-/// Handles interactions with the s3 services through AWS sdk
+/// Pages of listed objects, as they arrive from s3
+pub type ObjectPages = UnboundedReceiverStream<eyre::Result<Vec<S3DataItem>>>;
+
+/// Handles interactions with the s3 services through AWS sdk, on behalf of a single account
 #[derive(Clone)]
 pub struct S3DataFetcher {
     pub default_region: String,
     credentials: Credentials,
+    account: Account,
+    /// Storage of the client-side encryption keys
+    db: Database,
+    /// Custom endpoint of an s3 compatible service (eg. MinIO), AWS when not set
+    endpoint_url: Option<String>,
 }
 
 /*
@@ -42,28 +113,59 @@ pub struct S3DataFetcher {
  */
 
 impl S3DataFetcher {
-    pub fn from_db_account() -> Option<Self> {
-        if let Some(acc) = get_default_account() {
-            let credentials = Credentials::new(
-                acc.access_key,
-                acc.secret_key,
-                None,
-                None,
-                "db_account",
-            );
-            let default_region = acc.default_region;
-            Some(S3DataFetcher {
-                default_region,
-                credentials,
-            })
-        } else {
-            None
+    pub fn new(account: Account, db: Database) -> Self {
+        let credentials = Credentials::new(
+            account.access_key.clone(),
+            account.secret_key.clone(),
+            None,
+            None,
+            "account_struct",
+        );
+        S3DataFetcher {
+            default_region: account.default_region.clone(),
+            credentials,
+            endpoint_url: account.endpoint_url.clone(),
+            account,
+            db,
+        }
+    }
+
+    /// Region used for the requests not bound to a bucket, the account's default otherwise
+    pub fn with_region(mut self, region: impl Into<String>) -> Self {
+        self.default_region = region.into();
+        self
+    }
+
+    /// Sends the requests to an s3 compatible service instead of AWS, with path-style addressing
+    pub fn with_endpoint(mut self, endpoint_url: impl Into<String>) -> Self {
+        self.endpoint_url = Some(endpoint_url.into());
+        self
+    }
+
+    /// The account the requests are made with
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    /// Encryption settings of the account the requests are made with
+    pub(crate) fn account_encryption(&self) -> EncryptionSettings {
+        self.account.encryption.clone()
+    }
+
+    /// Master key used for client-side encryption of uploads, if the account has it enabled
+    fn account_client_key(&self) -> eyre::Result<Option<ClientKey>> {
+        let key_id = self.account.client_key_id;
+        match key_id {
+            Some(id) => get_client_key(&self.db, id)
+                .map(Some)
+                .ok_or_else(|| Report::msg(format!("Client-side encryption key {} not found", id))),
+            None => Ok(None),
         }
     }
 
     /*
     this function handles only simple files as of now.
-    - not sure when and if necessary to use multipart uploads,
+    - files bigger than MULTIPART_THRESHOLD are sent with multipart upload
     - no directory handling
      */
     pub async fn upload_item(
@@ -71,26 +173,92 @@ impl S3DataFetcher {
         item: LocalSelectedItem,
         upload_tx: UnboundedSender<UploadProgressItem>,
     ) -> eyre::Result<bool> {
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client = self.get_s3_client_with_account(account).await;
-        let body = ByteStream::read_from()
-            .path(item.path)
-            // https://github.com/awslabs/aws-sdk-rust/blob/main/examples/examples/s3/src/bin/put-object-progress.rs
-            // Artificially limit the buffer size to ensure the file has multiple
-            // progress steps.
-            // .buffer_size(2048)
-            .build()
-            .await?;
+        let client = self.s3_client().await;
+        let encryption = item.encryption.clone().unwrap_or_else(|| self.account_encryption());
+        encryption.validate().map_err(Report::msg)?;
+        let storage_class = item.storage_class.as_deref().map(StorageClass::from);
         let key = if item.destination_path == "/" {
             item.name
         } else {
             item.destination_path
         }; //Self::combine_paths(Path::new(&item.destination_path), Path::new(&item.name));
         //destination_path
+        let headers = self.upload_headers(&item.path, &item.headers, &item.destination_bucket, &key);
+        let mut metadata: HashMap<String, String> = headers.metadata.clone().into_iter().collect();
+        let path = Path::new(&item.path);
+        let file_size = fs::metadata(path)?.len();
+        // client-side encryption, the content is encrypted as it's read into the request bodies
+        let master = if item.client_side_encryption { self.account_client_key()? } else { None };
+        let encryptor = match master {
+            Some(master) => {
+                let (encryptor, header) = ChunkEncryptor::new(&master, file_size, DEFAULT_CHUNK_SIZE)?;
+                metadata.extend(header.to_metadata());
+                Some(encryptor)
+            }
+            None => None,
+        };
+        let metadata = Some(metadata).filter(|metadata| !metadata.is_empty());
+        self.put_file(&client, &item.destination_bucket, &key, path, file_size, encryptor, &encryption, metadata, storage_class, &headers, upload_tx)
+            .await
+    }
+
+    /// Headers of the upload: its content type detected from the local file, then the headers
+    /// of the item and the header rules of the account matching the key
+    fn upload_headers(&self, path: &str, headers: &UploadHeaders, bucket: &str, key: &str) -> UploadHeaders {
+        let rules = fetch_header_rules(&self.db, self.account.id);
+        resolve_headers(headers, detect_content_type(Path::new(path)), &rules, bucket, key)
+    }
+
+    /// Uploads the file with a single request or, for files bigger than MULTIPART_THRESHOLD,
+    /// with multipart upload
+    #[allow(clippy::too_many_arguments)]
+    async fn put_file(
+        &self,
+        client: &Client,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        file_size: u64,
+        encryptor: Option<ChunkEncryptor>,
+        encryption: &EncryptionSettings,
+        metadata: Option<HashMap<String, String>>,
+        storage_class: Option<StorageClass>,
+        headers: &UploadHeaders,
+        upload_tx: UnboundedSender<UploadProgressItem>,
+    ) -> eyre::Result<bool> {
+        // encrypted content is held in memory, a part at most
+        let threshold = if encryptor.is_some() { MULTIPART_CHUNK_SIZE } else { MULTIPART_THRESHOLD };
+        if file_size > threshold {
+            return self
+                .multipart_upload(client, bucket, key, path, file_size, encryptor, encryption, metadata, storage_class, headers, upload_tx)
+                .await;
+        }
+        let body = match encryptor {
+            Some(mut encryptor) => ByteStream::from(encryptor.encrypt(&tokio::fs::read(path).await?)?),
+            None => ByteStream::read_from()
+                .path(path)
+                // https://github.com/awslabs/aws-sdk-rust/blob/main/examples/examples/s3/src/bin/put-object-progress.rs
+                // Artificially limit the buffer size to ensure the file has multiple
+                // progress steps.
+                // .buffer_size(2048)
+                .build()
+                .await?,
+        };
+        let uri = format!("s3://{}/{}", bucket, key);
         let request = client
             .put_object()
-            .bucket(item.destination_bucket)
+            .bucket(bucket)
             .key(key)
+            .set_metadata(metadata)
+            .set_storage_class(storage_class)
+            .set_content_type(headers.content_type.clone())
+            .set_cache_control(headers.cache_control.clone())
+            .set_content_encoding(headers.content_encoding.clone())
+            .set_server_side_encryption(encryption.server_side_encryption())
+            .set_ssekms_key_id(encryption.kms_key_id())
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
             .body(body);
 
         // let customized = request
@@ -98,11 +266,132 @@ impl S3DataFetcher {
         //     .map_request(move |req| ProgressBody::<SdkBody>::replace(req, upload_tx.clone()));
 
         match request.send().await {
-            Ok(_a) => Ok(true),
-            Err(e) => {
-                // println!("Upload SdkError: {:?}", e);
-                Err(Report::msg(e.into_service_error().to_string()))
+            Ok(_a) => {
+                let _ = upload_tx.send(UploadProgressItem { progress: 100.0, uri });
+                Ok(true)
+            }
+            Err(e) => Err(Report::msg(e.into_service_error().to_string())),
+        }
+    }
+
+    /// Uploads the file in MULTIPART_CHUNK_SIZE parts, reporting the progress after every part.
+    /// The upload is aborted if any of the parts fails, so no orphaned parts are left behind.
+    #[allow(clippy::too_many_arguments)]
+    async fn multipart_upload(
+        &self,
+        client: &Client,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        file_size: u64,
+        mut encryptor: Option<ChunkEncryptor>,
+        encryption: &EncryptionSettings,
+        metadata: Option<HashMap<String, String>>,
+        storage_class: Option<StorageClass>,
+        headers: &UploadHeaders,
+        upload_tx: UnboundedSender<UploadProgressItem>,
+    ) -> eyre::Result<bool> {
+        let upload = client
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .set_metadata(metadata)
+            .set_storage_class(storage_class)
+            .set_content_type(headers.content_type.clone())
+            .set_cache_control(headers.cache_control.clone())
+            .set_content_encoding(headers.content_encoding.clone())
+            .set_server_side_encryption(encryption.server_side_encryption())
+            .set_ssekms_key_id(encryption.kms_key_id())
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        let upload_id = upload
+            .upload_id()
+            .ok_or_else(|| Report::msg("Missing upload id"))?
+            .to_string();
+        let uri = format!("s3://{}/{}", bucket, key);
+
+        let mut completed_parts = Vec::new();
+        let mut offset = 0u64;
+        let mut part_number = 1;
+        while offset < file_size {
+            let length = std::cmp::min(MULTIPART_CHUNK_SIZE, file_size - offset);
+            let body = match encryptor.as_mut() {
+                Some(encryptor) => match read_range(path, offset, length).await {
+                    Ok(plaintext) => encryptor.encrypt(&plaintext).map(ByteStream::from),
+                    Err(e) => Err(e),
+                },
+                None => ByteStream::read_from()
+                    .path(path)
+                    .offset(offset)
+                    .length(Length::Exact(length))
+                    .build()
+                    .await
+                    .map_err(Report::from),
+            };
+            let part = match body {
+                Ok(body) => client
+                    .upload_part()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .part_number(part_number)
+                    .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+                    .set_sse_customer_key(encryption.sse_customer_key())
+                    .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+                    .body(body)
+                    .send()
+                    .await
+                    .map_err(|e| Report::msg(e.into_service_error().to_string())),
+                Err(e) => Err(e),
+            };
+            match part {
+                Ok(part) => completed_parts.push(
+                    CompletedPart::builder()
+                        .set_e_tag(part.e_tag().map(String::from))
+                        .part_number(part_number)
+                        .build(),
+                ),
+                Err(e) => {
+                    let _ = client
+                        .abort_multipart_upload()
+                        .bucket(bucket)
+                        .key(key)
+                        .upload_id(&upload_id)
+                        .send()
+                        .await;
+                    return Err(e);
+                }
             }
+            offset += length;
+            part_number += 1;
+            let _ = upload_tx.send(UploadProgressItem {
+                progress: Self::calculate_download_percentage(file_size as i64, offset as usize),
+                uri: uri.clone(),
+            });
+        }
+
+        match client
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(completed_parts))
+                    .build(),
+            )
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(e) => Err(Report::msg(e.into_service_error().to_string())),
         }
     }
 
@@ -115,6 +404,37 @@ impl S3DataFetcher {
 
         Ok(())
     }
+
+    /// Heads the object. Objects encrypted with a customer provided key (SSE-C) can only be
+    /// read with the key, so when the plain request fails and an SSE-C key is configured
+    /// the request is retried with the key.
+    /// Returns the response and the settings which have to be used for reading the object.
+    pub(crate) async fn head_object_with_encryption(
+        &self,
+        client: &Client,
+        bucket: &str,
+        key: &str,
+        encryption: &EncryptionSettings,
+    ) -> eyre::Result<(HeadObjectOutput, EncryptionSettings)> {
+        match client.head_object().bucket(bucket).key(key).send().await {
+            Ok(head_obj) => Ok((head_obj, EncryptionSettings::None)),
+            Err(_) if encryption.sse_customer_key().is_some() => {
+                let head_obj = client
+                    .head_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+                    .set_sse_customer_key(encryption.sse_customer_key())
+                    .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+                    .send()
+                    .await
+                    .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+                Ok((head_obj, encryption.clone()))
+            }
+            Err(e) => Err(Report::msg(e.into_service_error().to_string())),
+        }
+    }
+
     /*
     this function handles only simple files as of now.
     - no directory or full bucket handling
@@ -124,23 +444,47 @@ impl S3DataFetcher {
         item: S3SelectedItem,
         download_tx: UnboundedSender<DownloadProgressItem>,
     ) -> eyre::Result<bool> {
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client = self.get_s3_client_with_account(account).await;
         let mut path = PathBuf::from(item.destination_dir);
         path.push(item.path.clone().unwrap_or(item.name.clone()));
-        self.create_directory_structure(&path)?;
-        let mut file = File::create(&path)?;
         let bucket = item.bucket.expect("bucket must be defined").clone();
-        let head_obj = client
-            .head_object()
-            .bucket(bucket.clone())
-            .key(item.path.clone().unwrap_or(item.name.clone()))
-            .send()
+        let key = item.path.clone().unwrap_or(item.name.clone());
+        self.download_to_file(&bucket, &key, &path, item.encryption, download_tx).await
+    }
+
+    /// Downloads the object into the given file, objects encrypted on the client side are
+    /// decrypted on the way. `encryption` overrides the account's settings.
+    pub async fn download_to_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        path: &Path,
+        encryption: Option<EncryptionSettings>,
+        download_tx: UnboundedSender<DownloadProgressItem>,
+    ) -> eyre::Result<bool> {
+        let encryption = encryption.unwrap_or_else(|| self.account_encryption());
+        let client = self.s3_client().await;
+        self.create_directory_structure(path)?;
+        let (head_obj, read_encryption) = self
+            .head_object_with_encryption(&client, bucket, key, &encryption)
             .await?;
+        // objects encrypted on the client side are decrypted while downloading
+        let mut decryptor = match head_obj.metadata().and_then(EnvelopeHeader::from_metadata) {
+            Some(header) => {
+                let master = get_client_key(&self.db, header.key_id).ok_or_else(|| {
+                    Report::msg(format!("Client-side encryption key {} used for this object is not available", header.key_id))
+                })?;
+                Some(ChunkDecryptor::new(&master, &header)?)
+            }
+            None => None,
+        };
+        let mut file = File::create(path)?;
         match client
             .get_object()
-            .bucket(bucket.clone())
-            .key(item.path.clone().unwrap_or(item.name.clone()))
+            .bucket(bucket)
+            .key(key)
+            .set_sse_customer_algorithm(read_encryption.sse_customer_algorithm())
+            .set_sse_customer_key(read_encryption.sse_customer_key())
+            .set_sse_customer_key_md5(read_encryption.sse_customer_key_md5())
             .send()
             .await
         {
@@ -149,25 +493,147 @@ impl S3DataFetcher {
                 let total = head_obj.content_length.unwrap_or(0i64);
                 while let Some(bytes) = object.body.try_next().await? {
                     let bytes_len = bytes.len();
-                    file.write_all(&bytes)?;
+                    match decryptor.as_mut() {
+                        Some(decryptor) => file.write_all(&Self::remove_on_error(path, decryptor.push(&bytes))?)?,
+                        None => file.write_all(&bytes)?,
+                    }
                     byte_count += bytes_len;
                     let progress = Self::calculate_download_percentage(total, byte_count);
                     let download_progress_item = DownloadProgressItem {
-                        name: item.path.clone().unwrap_or(item.name.clone()),
-                        bucket: bucket.clone(),
+                        name: key.to_string(),
+                        bucket: bucket.to_string(),
                         progress,
                     };
                     let _ = download_tx.send(download_progress_item);
                 }
+                if let Some(decryptor) = decryptor {
+                    file.write_all(&Self::remove_on_error(path, decryptor.finish())?)?;
+                }
                 Ok(true)
             }
+            Err(e) => Err(Report::msg(e.into_service_error().to_string())),
+        }
+    }
+
+    /// Fetches the given byte range of the object, eg. for previews. `range` is inclusive,
+    /// like the `Range` header. Objects encrypted on the client side can't be read partially.
+    pub async fn get_object_range(&self, bucket: &str, key: &str, range: std::ops::RangeInclusive<u64>) -> eyre::Result<Bytes> {
+        let client = self.s3_client().await;
+        let (head_obj, read_encryption) = self
+            .head_object_with_encryption(&client, bucket, key, &self.account_encryption())
+            .await?;
+        if head_obj.metadata().is_some_and(is_client_encrypted) {
+            return Err(Report::msg("Objects encrypted on the client side can't be previewed, download them instead"));
+        }
+        let object = client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .range(format!("bytes={}-{}", range.start(), range.end()))
+            .set_sse_customer_algorithm(read_encryption.sse_customer_algorithm())
+            .set_sse_customer_key(read_encryption.sse_customer_key())
+            .set_sse_customer_key_md5(read_encryption.sse_customer_key_md5())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(object.body.collect().await?.into_bytes())
+    }
+
+    /// Reads a small text object for editing, together with the ETag of the content read
+    pub async fn get_text_object(&self, bucket: &str, key: &str) -> eyre::Result<(String, Option<String>)> {
+        let client = self.s3_client().await;
+        let (head_obj, read_encryption) = self
+            .head_object_with_encryption(&client, bucket, key, &self.account_encryption())
+            .await?;
+        if head_obj.metadata().is_some_and(is_client_encrypted) {
+            return Err(Report::msg("Objects encrypted on the client side can't be edited"));
+        }
+        if head_obj.content_length().unwrap_or(0) as u64 > EDITOR_SIZE_LIMIT {
+            return Err(Report::msg("The object is too large to be edited, download it instead"));
+        }
+        let object = client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .set_sse_customer_algorithm(read_encryption.sse_customer_algorithm())
+            .set_sse_customer_key(read_encryption.sse_customer_key())
+            .set_sse_customer_key_md5(read_encryption.sse_customer_key_md5())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        let e_tag = object.e_tag().map(String::from);
+        let bytes = object.body.collect().await?.into_bytes();
+        let text = String::from_utf8(bytes.to_vec()).map_err(|_| Report::msg("The object isn't UTF-8 text"))?;
+        Ok((text, e_tag))
+    }
+
+    /// Writes the edited text back, keeping the properties of the object. The write is
+    /// conditional on the ETag the content was read with (`If-Match`), storages which don't
+    /// support conditional writes get the ETag checked right before the write instead.
+    /// When the object changed meanwhile nothing is written and its current content is returned.
+    pub async fn save_text_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        content: String,
+        expected_e_tag: Option<String>,
+    ) -> eyre::Result<SaveOutcome> {
+        let client = self.s3_client().await;
+        let (head_obj, read_encryption) = self
+            .head_object_with_encryption(&client, bucket, key, &self.account_encryption())
+            .await?;
+        let put = |if_match: Option<String>| {
+            client
+                .put_object()
+                .bucket(bucket)
+                .key(key)
+                .set_if_match(if_match)
+                .set_content_type(head_obj.content_type().map(String::from))
+                .set_cache_control(head_obj.cache_control().map(String::from))
+                .set_content_encoding(head_obj.content_encoding().map(String::from))
+                .set_content_disposition(head_obj.content_disposition().map(String::from))
+                .set_metadata(head_obj.metadata().cloned())
+                .set_storage_class(head_obj.storage_class().cloned())
+                .set_server_side_encryption(head_obj.server_side_encryption().cloned())
+                .set_ssekms_key_id(head_obj.ssekms_key_id().map(String::from))
+                .set_sse_customer_algorithm(read_encryption.sse_customer_algorithm())
+                .set_sse_customer_key(read_encryption.sse_customer_key())
+                .set_sse_customer_key_md5(read_encryption.sse_customer_key_md5())
+                .body(ByteStream::from(content.clone().into_bytes()))
+                .send()
+        };
+        let conflict = || async {
+            let (remote_content, remote_e_tag) = self.get_text_object(bucket, key).await?;
+            Ok(SaveOutcome::Conflict { remote_content, remote_e_tag })
+        };
+        match put(expected_e_tag.clone()).await {
+            Ok(output) => Ok(SaveOutcome::Saved { e_tag: output.e_tag().map(String::from) }),
             Err(e) => {
-                // println!("Download SdkError: {:?}", e);
-                Err(Report::msg(e.into_service_error().to_string()))
+                let err = e.into_service_error();
+                match err.code() {
+                    Some("PreconditionFailed") => conflict().await,
+                    Some("NotImplemented") => {
+                        if head_obj.e_tag() != expected_e_tag.as_deref() {
+                            return conflict().await;
+                        }
+                        let output = put(None).await.map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+                        Ok(SaveOutcome::Saved { e_tag: output.e_tag().map(String::from) })
+                    }
+                    _ => Err(Report::msg(err.to_string())),
+                }
             }
         }
     }
 
+    /// Removes the partially written file when the content can't be decrypted,
+    /// so no corrupted file is left behind
+    fn remove_on_error<T>(path: &Path, result: eyre::Result<T>) -> eyre::Result<T> {
+        if result.is_err() {
+            let _ = fs::remove_file(path);
+        }
+        result
+    }
+
     fn calculate_download_percentage(total: i64, byte_count: usize) -> f64 {
         if total == 0 {
             0.0 // Return 0% if total is 0 to avoid division by zero
@@ -181,7 +647,6 @@ impl S3DataFetcher {
         bucket: Option<String>,
         prefix: Option<String>,
     ) -> eyre::Result<Vec<S3DataItem>> {
-        println!("list_current_location");
         match (bucket, prefix) {
             (None, None) => self.list_all_buckets().await,
             (Some(bucket), None) => self.list_objects(bucket.as_str(), None).await,
@@ -191,125 +656,167 @@ impl S3DataFetcher {
     }
 
     pub async fn get_bucket_location(&self, bucket: &str) -> eyre::Result<String> {
-        let default_region = self.default_region.clone();
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client = self.get_s3_client_with_account(account).await;
-        
-        println!("🔍 Getting location for bucket: '{}'", bucket);
-        println!("🌍 Default region: '{}'", default_region);
-        
+        let client = self.s3_client().await;
         let head_obj = client.get_bucket_location().bucket(bucket).send().await?;
-        
-        println!("📡 AWS response for bucket '{}': {:?}", bucket, head_obj);
-        
-        let location_constraint = head_obj.location_constraint();
-        println!("🗺️  Location constraint for bucket '{}': {:?}", bucket, location_constraint);
-        
-        let location = location_constraint
-            .map(|lc| {
-                let region_str = lc.to_string();
-                println!("🔄 Converted location constraint to string: '{}'", region_str);
-                region_str
-            })
-            .unwrap_or_else(|| {
-                println!("⚠️  No location constraint found, using default region: '{}'", default_region);
-                default_region.to_string()
-            });
-            
-        println!("✅ Final region for bucket '{}': '{}'", bucket, location);
+        let location = head_obj
+            .location_constraint()
+            .map(|lc| lc.to_string())
+            .unwrap_or_else(|| self.default_region.clone());
         Ok(location)
     }
 
-    // Example async method to fetch data from an external service with pagination
-    pub async fn list_buckets(&self, page: Option<usize>, page_size: Option<usize>) -> eyre::Result<(Vec<S3DataItem>, usize)> {
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client = self.get_s3_client_with_account(account).await;
-        let mut fetched_data: Vec<S3DataItem> = vec![];
-        
-        if let Ok(res) = client.list_buckets().send().await {
-            fetched_data = res.buckets.as_ref().map_or_else(
-                Vec::new, // In case there is no buckets field (it's None), return an empty Vec
-                |buckets| {
-                    buckets
-                        .iter()
-                        .filter_map(|bucket| {
-                            // Filter out buckets where name is None, and map those with a name to a Vec<String>
-                            bucket.name.as_ref().map(|name| {
-                                let file_info = FileInfo {
-                                    file_name: name.clone(),
-                                    size: "".to_string(),
-                                    file_type: "Bucket".to_string(),
-                                    path: name.clone(),
-                                    is_directory: false,
-                                };
-                                let bucket_info = BucketInfo {
-                                    bucket: None,
-                                    region: None,
-                                    is_bucket: true,
-                                };
-                                S3DataItem::init(bucket_info, file_info)
-                            })
-                        })
-                        .collect()
-                },
-            )
-        }
-
-        // Apply client-side pagination
-        let total_count = fetched_data.len();
-        
-        if let (Some(page_num), Some(size)) = (page, page_size) {
-            let start_index = page_num * size;
-            let end_index = std::cmp::min(start_index + size, total_count);
-            
-            if start_index < total_count {
-                fetched_data = fetched_data[start_index..end_index].to_vec();
-            } else {
-                fetched_data = vec![]; // Page beyond available data
-            }
-        }
-        
-        Ok((fetched_data, total_count))
+    /// Fetches one page of buckets, without a page size all the buckets are returned at once
+    pub async fn list_buckets(&self, page_size: Option<usize>, continuation_token: Option<String>) -> eyre::Result<ListingPage> {
+        let client = self.s3_client().await;
+        let res = client
+            .list_buckets()
+            .set_max_buckets(page_size.and_then(|size| i32::try_from(size).ok()))
+            .set_continuation_token(continuation_token)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        let items = res.buckets().iter()
+            .filter_map(|bucket| {
+                // Filter out buckets where name is None, and map those with a name to a Vec<String>
+                bucket.name.as_ref().map(|name| {
+                    let file_info = FileInfo {
+                        file_name: name.clone(),
+                        size: None,
+                        file_type: "Bucket".to_string(),
+                        path: name.clone(),
+                        is_directory: false,
+                        last_modified: bucket.creation_date().cloned(),
+                        e_tag: None,
+                        storage_class: None,
+                        owner: res.owner().and_then(|o| o.display_name()).map(String::from),
+                    };
+                    let bucket_info = BucketInfo {
+                        bucket: None,
+                        region: None,
+                        is_bucket: true,
+                    };
+                    S3DataItem::init(bucket_info, file_info)
+                })
+            })
+            .collect();
+        Ok(ListingPage {
+            items,
+            next_token: res.continuation_token().map(String::from),
+        })
     }
 
     // Convenience method to get all buckets (maintains backward compatibility)
     pub async fn list_all_buckets(&self) -> eyre::Result<Vec<S3DataItem>> {
-        let (buckets, _) = self.list_buckets(None, None).await?;
-        Ok(buckets)
+        let mut buckets = Vec::new();
+        let mut token = None;
+        loop {
+            let page = self.list_buckets(None, token).await?;
+            buckets.extend(page.items);
+            match page.next_token {
+                Some(next) => token = Some(next),
+                None => return Ok(buckets),
+            }
+        }
     }
 
+    /// Creates the bucket and applies the options to it. Returns the error message of s3
+    /// when the bucket can't be created or an option can't be applied to the created bucket.
     pub async fn create_bucket(
         &self,
         name: String,
         region: String,
+        options: &BucketOptions,
     ) -> eyre::Result<Option<String>> {
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client = self.get_s3_client_with_account(account).await;
-        let constraint = BucketLocationConstraint::from(region.as_str());
-        let cfg = CreateBucketConfiguration::builder()
-            .location_constraint(constraint)
-            .build();
-        match client
+        let client = self.s3_client().await;
+        // us-east-1 is the region of the buckets without a location constraint
+        let cfg = (region != DEFAULT_BUCKET_REGION).then(|| {
+            CreateBucketConfiguration::builder()
+                .location_constraint(BucketLocationConstraint::from(region.as_str()))
+                .build()
+        });
+        let request = client
             .create_bucket()
-            .create_bucket_configuration(cfg)
-            .bucket(name.clone())
-            .send()
-            .await
-        {
-            Ok(_) => {
-                // println!("Bucket created");
-                Ok(None)
-            }
+            .set_create_bucket_configuration(cfg)
+            .bucket(name.clone());
+        let request = if options.object_lock { request.object_lock_enabled_for_bucket(true) } else { request };
+        match request.send().await {
+            Ok(_) => {}
             Err(e) => {
-                println!("Cannot create bucket");
-                Ok(Some(
+                return Ok(Some(
                     e.into_service_error()
                         .message()
                         .unwrap_or("Cannot create bucket")
                         .to_string(),
-                ))
+                ));
             }
         }
+        match self.apply_bucket_options(&client, &name, options).await {
+            Ok(()) => Ok(None),
+            Err(e) => Ok(Some(format!("Bucket '{}' was created, but {}", name, e))),
+        }
+    }
+
+    /// Applies the options which have their own request, object lock is set by the creation itself
+    async fn apply_bucket_options(&self, client: &Client, bucket: &str, options: &BucketOptions) -> eyre::Result<()> {
+        if options.versioning && !options.object_lock {
+            let versioning = VersioningConfiguration::builder().status(BucketVersioningStatus::Enabled).build();
+            client
+                .put_bucket_versioning()
+                .bucket(bucket)
+                .versioning_configuration(versioning)
+                .send()
+                .await
+                .map_err(|e| Report::msg(format!("enabling versioning failed: {}", e.into_service_error())))?;
+        }
+        if let Some(algorithm) = options.encryption.server_side_encryption() {
+            let default = ServerSideEncryptionByDefault::builder()
+                .sse_algorithm(algorithm)
+                .set_kms_master_key_id(options.encryption.kms_key_id())
+                .build()?;
+            let rule = ServerSideEncryptionRule::builder()
+                .apply_server_side_encryption_by_default(default)
+                // bucket keys cut the KMS requests of the objects
+                .bucket_key_enabled(options.encryption.mode() == "sse-kms")
+                .build();
+            let configuration = ServerSideEncryptionConfiguration::builder().rules(rule).build()?;
+            client
+                .put_bucket_encryption()
+                .bucket(bucket)
+                .server_side_encryption_configuration(configuration)
+                .send()
+                .await
+                .map_err(|e| Report::msg(format!("setting the default encryption failed: {}", e.into_service_error())))?;
+        }
+        if !options.tags.is_empty() {
+            let tag_set = options
+                .tags
+                .iter()
+                .map(|(k, v)| Tag::builder().key(k).value(v).build())
+                .collect::<Result<Vec<Tag>, _>>()?;
+            client
+                .put_bucket_tagging()
+                .bucket(bucket)
+                .tagging(Tagging::builder().set_tag_set(Some(tag_set)).build()?)
+                .send()
+                .await
+                .map_err(|e| Report::msg(format!("tagging failed: {}", e.into_service_error())))?;
+        }
+        if options.block_public_access {
+            let configuration = PublicAccessBlockConfiguration::builder()
+                .block_public_acls(true)
+                .ignore_public_acls(true)
+                .block_public_policy(true)
+                .restrict_public_buckets(true)
+                .build();
+            client
+                .put_public_access_block()
+                .bucket(bucket)
+                .public_access_block_configuration(configuration)
+                .send()
+                .await
+                .map_err(|e| Report::msg(format!("blocking public access failed: {}", e.into_service_error())))?;
+        }
+        Ok(())
     }
 
     pub async fn delete_data(
@@ -320,39 +827,26 @@ impl S3DataFetcher {
         _is_directory: bool,
     ) -> eyre::Result<Option<String>> {
         if is_bucket {
-            let location = self.get_bucket_location(&name).await?;
-            let creds = self.credentials.clone();
-            let temp_file_creds = FileCredential {
-                name: "temp".to_string(),
-                access_key: creds.access_key_id().to_string(),
-                secret_key: creds.secret_access_key().to_string(),
-                default_region: location.clone(),
-                selected: false,
-            };
-            let account = CURRENT_ACCOUNT.read().clone();
-            let client_with_location = self.get_s3_client_with_account(account).await;
+            let client_with_location = self.s3_client().await;
             let response = client_with_location
                 .delete_bucket()
                 .bucket(name.clone())
                 .send()
                 .await;
             match response {
-                Ok(_) => {
-                    println!("bucket deleted: {}", name);
-                    Ok(None)
-                }
+                Ok(_) => Ok(None),
                 Err(e) => {
-                    println!("error deleting bucket: {}, {:?}", name, e);
-                    Ok(Some(
-                        e.into_service_error()
-                            .message()
-                            .unwrap_or("Error deleting bucket")
-                            .to_string(),
-                    ))
+                    let err = e.into_service_error();
+                    if err.code() == Some("BucketNotEmpty") && self.get_object_lock(&name).await.is_ok_and(|lock| lock.enabled) {
+                        return Ok(Some(format!(
+                            "Bucket '{}' still has object versions, the versions under retention or legal hold can't be deleted until they're released",
+                            name
+                        )));
+                    }
+                    Ok(Some(err.message().unwrap_or("Error deleting bucket").to_string()))
                 }
             }
         } else {
-            println!("Deleting object: {:?}, {:?}", name, bucket);
             match bucket {
                 Some(b) => self.delete_single_item(&b, &name).await,
                 None => Ok(Some("No bucket specified!".into())),
@@ -360,18 +854,77 @@ impl S3DataFetcher {
         }
     }
 
-    async fn delete_single_item(&self, bucket: &str, name: &str) -> eyre::Result<Option<String>> {
-        let location = self.get_bucket_location(bucket).await?;
-        let creds = self.credentials.clone();
-        let temp_file_creds = FileCredential {
-            name: "temp".to_string(),
-            access_key: creds.access_key_id().to_string(),
-            secret_key: creds.secret_access_key().to_string(),
-            default_region: location.clone(),
-            selected: false,
+    /// Copies the object within s3 with the storage class and encryption of the options,
+    /// the bucket's default class and the account's encryption when they're not set.
+    /// The source is read with the customer key only when it's encrypted with one (SSE-C),
+    /// s3 rejects the key for any other object.
+    pub async fn copy_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        bucket: &str,
+        key: &str,
+        options: &TransferOptions,
+    ) -> eyre::Result<()> {
+        let client = self.s3_client().await;
+        let encryption = options.encryption.clone().unwrap_or_else(|| self.account_encryption());
+        let (head_obj, read_encryption) = self
+            .head_object_with_encryption(&client, source_bucket, source_key, &encryption)
+            .await?;
+        let source_encryption = if head_obj.sse_customer_algorithm().is_some() {
+            read_encryption
+        } else {
+            EncryptionSettings::None
         };
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client_with_location = self.get_s3_client_with_account(account).await;
+        client
+            .copy_object()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(format!("{}/{}", source_bucket, urlencoding::encode(source_key)))
+            .set_storage_class(options.storage_class.as_deref().map(StorageClass::from))
+            .set_server_side_encryption(encryption.server_side_encryption())
+            .set_ssekms_key_id(encryption.kms_key_id())
+            .set_copy_source_sse_customer_algorithm(source_encryption.sse_customer_algorithm())
+            .set_copy_source_sse_customer_key(source_encryption.sse_customer_key())
+            .set_copy_source_sse_customer_key_md5(source_encryption.sse_customer_key_md5())
+            .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+            .set_sse_customer_key(encryption.sse_customer_key())
+            .set_sse_customer_key_md5(encryption.sse_customer_key_md5())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Creates a URL which downloads the object without credentials until it expires.
+    /// The URL is signed for the bucket's region, otherwise s3 rejects it.
+    pub async fn presign_get_object(&self, bucket: &str, key: &str, expires_in: std::time::Duration) -> eyre::Result<String> {
+        let location = self.get_bucket_location(bucket).await?;
+        let config = self.s3_config().await.region(Region::new(location)).build();
+        let request = Client::from_conf(config)
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .presigned(PresigningConfig::expires_in(expires_in)?)
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(request.uri().to_string())
+    }
+
+    async fn delete_single_item(&self, bucket: &str, name: &str) -> eyre::Result<Option<String>> {
+        let client_with_location = self.s3_client().await;
+        // object lock buckets are always versioned, a plain delete of a locked object would
+        // only add a delete marker on top of the retained version, so nothing is deleted
+        if let Ok((head_obj, _)) = self
+            .head_object_with_encryption(&client_with_location, bucket, name, &self.account_encryption())
+            .await
+        {
+            let legal_hold = head_obj.object_lock_legal_hold_status() == Some(&ObjectLockLegalHoldStatus::On);
+            let now = DateTime::from(std::time::SystemTime::now());
+            if is_locked(head_obj.object_lock_retain_until_date(), legal_hold, now) {
+                return Ok(Some(locked_message(name)));
+            }
+        }
         let response = client_with_location
             .delete_object()
             .key(name)
@@ -379,108 +932,773 @@ impl S3DataFetcher {
             .send()
             .await;
         match response {
-            Ok(_) => {
-                println!("S3 Object deleted, bucket: {:?}, name: {:?}", bucket, name);
-                Ok(None)
+            Ok(_) => Ok(None),
+            Err(e) => {
+                let err = e.into_service_error();
+                match lock_error_message(name, err.code(), err.message()) {
+                    Some(message) => Ok(Some(message)),
+                    None => Ok(Some(format!("Cannot delete object, {:?}", err.message().unwrap_or("")))),
+                }
+            }
+        }
+    }
+
+    /// Collects everything we know about a single object: the response of `head_object`
+    /// together with the object's tag set
+    pub async fn get_object_details(&self, bucket: &str, key: &str) -> eyre::Result<ObjectDetails> {
+        let client = self.s3_client().await;
+        let (head_obj, _) = self
+            .head_object_with_encryption(&client, bucket, key, &self.account_encryption())
+            .await?;
+        let tags = match client
+            .get_object_tagging()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(output) => output
+                .tag_set()
+                .iter()
+                .map(|tag| (tag.key().to_string(), tag.value().to_string()))
+                .collect(),
+            // shown without tags when they can't be read, eg. without the GetObjectTagging permission
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(ObjectDetails {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            size: head_obj.content_length().and_then(|l| u64::try_from(l).ok()),
+            e_tag: head_obj.e_tag().map(String::from),
+            content_type: head_obj.content_type().map(String::from),
+            cache_control: head_obj.cache_control().map(String::from),
+            content_encoding: head_obj.content_encoding().map(String::from),
+            content_disposition: head_obj.content_disposition().map(String::from),
+            storage_class: head_obj.storage_class().map(|sc| sc.to_string()),
+            restore: head_obj.restore().map(String::from),
+            server_side_encryption: head_obj.server_side_encryption().map(|sse| sse.to_string()),
+            sse_kms_key_id: head_obj.ssekms_key_id().map(String::from),
+            sse_customer_algorithm: head_obj.sse_customer_algorithm().map(String::from),
+            last_modified: head_obj.last_modified().cloned(),
+            version_id: head_obj.version_id().map(String::from),
+            retention_mode: head_obj.object_lock_mode().map(|mode| mode.to_string()),
+            retain_until: head_obj.object_lock_retain_until_date().cloned(),
+            legal_hold: head_obj.object_lock_legal_hold_status() == Some(&ObjectLockLegalHoldStatus::On),
+            metadata: head_obj
+                .metadata()
+                .map(|m| m.clone().into_iter().collect())
+                .unwrap_or_default(),
+            tags,
+        })
+    }
+
+    /// S3 doesn't allow changing metadata of an existing object, so the object is copied onto itself
+    /// with the `REPLACE` metadata directive. Everything which is not part of the update
+    /// (storage class, encryption, other headers) is carried over from the current object.
+    pub async fn update_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        update: &ObjectMetadataUpdate,
+    ) -> eyre::Result<Option<String>> {
+        let current = self.get_object_details(bucket, key).await?;
+        if current.size.is_some_and(|size| size > MAX_COPY_OBJECT_SIZE) {
+            return Ok(Some(format!(
+                "Cannot update metadata of {}: objects over 5 GiB can't be copied onto themselves in one request",
+                key
+            )));
+        }
+        let updated = update.apply(&current);
+        let client = self.s3_client().await;
+        let encryption = self.account_encryption();
+        let mut request = client
+            .copy_object()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(format!("{}/{}", bucket, urlencoding::encode(key)))
+            .metadata_directive(MetadataDirective::Replace)
+            .set_metadata(Some(updated.metadata.into_iter().collect()))
+            .set_content_type(updated.content_type)
+            .set_cache_control(updated.cache_control)
+            .set_content_encoding(updated.content_encoding)
+            .set_content_disposition(updated.content_disposition)
+            .set_storage_class(updated.storage_class.as_deref().map(StorageClass::from));
+        if updated.sse_customer_algorithm.is_some() {
+            // the copy has to be decrypted and encrypted again with the same customer key
+            request = request
+                .set_copy_source_sse_customer_algorithm(encryption.sse_customer_algorithm())
+                .set_copy_source_sse_customer_key(encryption.sse_customer_key())
+                .set_copy_source_sse_customer_key_md5(encryption.sse_customer_key_md5())
+                .set_sse_customer_algorithm(encryption.sse_customer_algorithm())
+                .set_sse_customer_key(encryption.sse_customer_key())
+                .set_sse_customer_key_md5(encryption.sse_customer_key_md5());
+        } else if updated.sse_kms_key_id.is_some() {
+            request = request
+                .server_side_encryption(ServerSideEncryption::AwsKms)
+                .set_ssekms_key_id(updated.sse_kms_key_id);
+        } else {
+            request = request.set_server_side_encryption(
+                updated.server_side_encryption.as_deref().map(ServerSideEncryption::from),
+            );
+        }
+        match request.send().await {
+            Ok(_) => Ok(None),
+            Err(e) => {
+                Ok(Some(format!(
+                    "Cannot update metadata of {}: {}",
+                    key,
+                    e.into_service_error().message().unwrap_or("")
+                )))
+            }
+        }
+    }
+
+    /// Replaces the object's tag set with the current tags changed by the given update
+    pub async fn update_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+        update: &ObjectTagsUpdate,
+    ) -> eyre::Result<Option<String>> {
+        let current = self.get_object_details(bucket, key).await?;
+        let tag_set = update
+            .apply(&current.tags)
+            .into_iter()
+            .map(|(k, v)| Tag::builder().key(k).value(v).build())
+            .collect::<Result<Vec<Tag>, _>>()?;
+        let tagging = Tagging::builder().set_tag_set(Some(tag_set)).build()?;
+        let client = self.s3_client().await;
+        match client
+            .put_object_tagging()
+            .bucket(bucket)
+            .key(key)
+            .tagging(tagging)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(None),
+            Err(e) => {
+                Ok(Some(format!(
+                    "Cannot update tags of {}: {}",
+                    key,
+                    e.into_service_error().message().unwrap_or("")
+                )))
+            }
+        }
+    }
+
+    /// Applies the same metadata and/or tags update to every given object.
+    /// Returns the list of errors, one for every object which couldn't be updated.
+    pub async fn update_objects(
+        &self,
+        bucket: &str,
+        keys: Vec<String>,
+        metadata_update: Option<ObjectMetadataUpdate>,
+        tags_update: Option<ObjectTagsUpdate>,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+        for key in keys {
+            if let Some(update) = metadata_update.as_ref().filter(|u| !u.is_empty()) {
+                match self.update_object_metadata(bucket, &key, update).await {
+                    Ok(None) => {}
+                    Ok(Some(err)) => errors.push(err),
+                    Err(e) => errors.push(format!("Cannot update metadata of {}: {}", key, e)),
+                }
+            }
+            if let Some(update) = tags_update.as_ref().filter(|u| !u.is_empty()) {
+                match self.update_object_tags(bucket, &key, update).await {
+                    Ok(None) => {}
+                    Ok(Some(err)) => errors.push(err),
+                    Err(e) => errors.push(format!("Cannot update tags of {}: {}", key, e)),
+                }
             }
+        }
+        errors
+    }
+
+    /// Requests a temporary copy of an archived (Glacier, Deep Archive) object,
+    /// available for download for the given number of days once the restore completes
+    pub async fn restore_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        days: i32,
+        tier: &str,
+    ) -> eyre::Result<Option<String>> {
+        let client = self.s3_client().await;
+        let request = RestoreRequest::builder()
+            .days(days)
+            .glacier_job_parameters(GlacierJobParameters::builder().tier(Tier::from(tier)).build()?)
+            .build();
+        match client
+            .restore_object()
+            .bucket(bucket)
+            .key(key)
+            .restore_request(request)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(None),
             Err(e) => {
-                println!(
-                    "Cannot delete object, bucket: {:?}, name: {:?}, error: {:?}",
-                    bucket,
-                    name,
-                    e
-                );
                 Ok(Some(format!(
-                    "Cannot delete object, {:?}",
+                    "Cannot restore {}: {}",
+                    key,
                     e.into_service_error().message().unwrap_or("")
                 )))
             }
         }
     }
 
+    /// Returns the state of the restore of an archived object
+    pub async fn get_restore_status(&self, bucket: &str, key: &str) -> eyre::Result<RestoreStatus> {
+        let client = self.s3_client().await;
+        let (head_obj, _) = self
+            .head_object_with_encryption(&client, bucket, key, &self.account_encryption())
+            .await?;
+        Ok(RestoreStatus::from_header(head_obj.restore()))
+    }
+
+    /// Looks up the encryption of the objects with a HEAD request per object, at most
+    /// `ENCRYPTION_LOOKUPS` of them at a time. Every label is sent as soon as it's known,
+    /// the lookups stop when the receiver is gone.
+    pub async fn get_objects_encryption(
+        &self,
+        bucket: &str,
+        keys: Vec<String>,
+        results_tx: UnboundedSender<(String, eyre::Result<String>)>,
+    ) {
+        let client = self.s3_client().await;
+        let mut keys = keys.into_iter();
+        let mut lookups = tokio::task::JoinSet::new();
+        loop {
+            while lookups.len() < ENCRYPTION_LOOKUPS {
+                let Some(key) = keys.next() else {
+                    break;
+                };
+                let (fetcher, client, bucket) = (self.clone(), client.clone(), bucket.to_string());
+                lookups.spawn(async move {
+                    let label = fetcher.object_encryption_label(&client, &bucket, &key).await;
+                    (key, label)
+                });
+            }
+            match lookups.join_next().await {
+                Some(Ok(result)) => {
+                    if results_tx.send(result).is_err() {
+                        return;
+                    }
+                }
+                Some(Err(_)) => {}
+                None => return,
+            }
+        }
+    }
+
+    /// Returns the label of the encryption used for the object (eg. SSE-S3, SSE-KMS, SSE-C).
+    /// Listings don't include this information so it has to be fetched for every object separately.
+    async fn object_encryption_label(&self, client: &Client, bucket: &str, key: &str) -> eyre::Result<String> {
+        let (head_obj, _) = self
+            .head_object_with_encryption(client, bucket, key, &self.account_encryption())
+            .await?;
+        let label = encryption_label(
+            head_obj.server_side_encryption().map(|sse| sse.as_str()),
+            head_obj.sse_customer_algorithm(),
+        );
+        match (head_obj.metadata().is_some_and(is_client_encrypted), label.as_str()) {
+            (true, "None") => Ok("Client-side".to_string()),
+            (true, _) => Ok(format!("Client-side + {}", label)),
+            (false, _) => Ok(label),
+        }
+    }
+
+    /// Size of the content of the client-side encrypted objects among the keys, read from
+    /// their envelope with a HEAD request per object, at most `ENCRYPTION_LOOKUPS` at a time.
+    /// The objects which aren't encrypted on the client side are left out.
+    pub async fn plaintext_sizes(&self, bucket: &str, keys: Vec<String>) -> eyre::Result<HashMap<String, u64>> {
+        let client = self.s3_client().await;
+        let encryption = self.account_encryption();
+        let mut keys = keys.into_iter();
+        let mut lookups = tokio::task::JoinSet::new();
+        let mut sizes = HashMap::new();
+        loop {
+            while lookups.len() < ENCRYPTION_LOOKUPS {
+                let Some(key) = keys.next() else {
+                    break;
+                };
+                let (fetcher, client, bucket) = (self.clone(), client.clone(), bucket.to_string());
+                let encryption = encryption.clone();
+                lookups.spawn(async move {
+                    let (head_obj, _) = fetcher
+                        .head_object_with_encryption(&client, &bucket, &key, &encryption)
+                        .await?;
+                    let header = head_obj.metadata().and_then(EnvelopeHeader::from_metadata);
+                    eyre::Ok((key, header.map(|header| header.plaintext_size)))
+                });
+            }
+            match lookups.join_next().await {
+                Some(result) => {
+                    if let (key, Some(size)) = result?? {
+                        sizes.insert(key, size);
+                    }
+                }
+                None => return Ok(sizes),
+            }
+        }
+    }
+
+
+    /// Object lock configuration of the bucket, disabled for the services which don't support it
+    pub async fn get_object_lock(&self, bucket: &str) -> eyre::Result<BucketObjectLock> {
+        let client = self.s3_client().await;
+        match client.get_object_lock_configuration().bucket(bucket).send().await {
+            Ok(output) => {
+                let Some(configuration) = output.object_lock_configuration() else {
+                    return Ok(BucketObjectLock::default());
+                };
+                let default_retention = configuration
+                    .rule()
+                    .and_then(|rule| rule.default_retention())
+                    .and_then(|retention| {
+                        let period = match (retention.days(), retention.years()) {
+                            (Some(days), _) => RetentionPeriod::Days(days),
+                            (None, Some(years)) => RetentionPeriod::Years(years),
+                            (None, None) => return None,
+                        };
+                        Some(DefaultRetention { mode: retention.mode()?.to_string(), period })
+                    });
+                Ok(BucketObjectLock {
+                    enabled: configuration.object_lock_enabled() == Some(&ObjectLockEnabled::Enabled),
+                    default_retention,
+                })
+            }
+            Err(e) => {
+                let err = e.into_service_error();
+                match err.code() {
+                    Some("ObjectLockConfigurationNotFoundError") | Some("NotImplemented") => Ok(BucketObjectLock::default()),
+                    _ => Err(Report::msg(err.to_string())),
+                }
+            }
+        }
+    }
+
+    /// Sets or removes the retention of the objects written without one, object lock has to be enabled
+    pub async fn set_default_retention(&self, bucket: &str, retention: Option<DefaultRetention>) -> eyre::Result<()> {
+        let rule = match retention {
+            Some(retention) => {
+                let default = aws_sdk_s3::types::DefaultRetention::builder().mode(ObjectLockRetentionMode::from(retention.mode.as_str()));
+                let default = match retention.period {
+                    RetentionPeriod::Days(days) => default.days(days),
+                    RetentionPeriod::Years(years) => default.years(years),
+                };
+                Some(ObjectLockRule::builder().default_retention(default.build()).build())
+            }
+            None => None,
+        };
+        let configuration = ObjectLockConfiguration::builder()
+            .object_lock_enabled(ObjectLockEnabled::Enabled)
+            .set_rule(rule)
+            .build();
+        let client = self.s3_client().await;
+        client
+            .put_object_lock_configuration()
+            .bucket(bucket)
+            .object_lock_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Retains the version until the given date, shortening a governance retention needs the bypass
+    pub async fn set_object_retention(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<String>,
+        mode: &str,
+        retain_until: DateTime,
+        bypass_governance: bool,
+    ) -> eyre::Result<()> {
+        let retention = ObjectLockRetention::builder()
+            .mode(ObjectLockRetentionMode::from(mode))
+            .retain_until_date(retain_until)
+            .build();
+        let client = self.s3_client().await;
+        client
+            .put_object_retention()
+            .bucket(bucket)
+            .key(key)
+            .set_version_id(version_id)
+            .retention(retention)
+            .bypass_governance_retention(bypass_governance)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn set_legal_hold(&self, bucket: &str, key: &str, version_id: Option<String>, on: bool) -> eyre::Result<()> {
+        let status = if on { ObjectLockLegalHoldStatus::On } else { ObjectLockLegalHoldStatus::Off };
+        let client = self.s3_client().await;
+        client
+            .put_object_legal_hold()
+            .bucket(bucket)
+            .key(key)
+            .set_version_id(version_id)
+            .legal_hold(ObjectLockLegalHold::builder().status(status).build())
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Static website hosting of the bucket, `None` when it isn't enabled
+    pub async fn get_website(&self, bucket: &str) -> eyre::Result<Option<WebsiteSettings>> {
+        let client = self.s3_client().await;
+        let output = match client.get_bucket_website().bucket(bucket).send().await {
+            Ok(output) => output,
+            Err(e) => {
+                let err = e.into_service_error();
+                return match err.code() {
+                    Some("NoSuchWebsiteConfiguration") => Ok(None),
+                    _ => Err(Report::msg(err.to_string())),
+                };
+            }
+        };
+        let routing_rules = output
+            .routing_rules()
+            .iter()
+            .map(|rule| RoutingRule {
+                key_prefix_equals: rule.condition().and_then(|c| c.key_prefix_equals()).map(String::from),
+                http_error_code_equals: rule.condition().and_then(|c| c.http_error_code_returned_equals()).map(String::from),
+                host_name: rule.redirect().and_then(|r| r.host_name()).map(String::from),
+                replace_key_prefix_with: rule.redirect().and_then(|r| r.replace_key_prefix_with()).map(String::from),
+                http_redirect_code: rule.redirect().and_then(|r| r.http_redirect_code()).map(String::from),
+            })
+            .collect();
+        Ok(Some(WebsiteSettings {
+            index_document: output.index_document().map(|d| d.suffix().to_string()).unwrap_or_default(),
+            error_document: output.error_document().map(|d| d.key().to_string()),
+            redirect_all_to: output.redirect_all_requests_to().map(|r| r.host_name().to_string()),
+            routing_rules,
+        }))
+    }
+
+    pub async fn put_website(&self, bucket: &str, settings: &WebsiteSettings) -> eyre::Result<()> {
+        let configuration = match &settings.redirect_all_to {
+            Some(host_name) => WebsiteConfiguration::builder()
+                .redirect_all_requests_to(RedirectAllRequestsTo::builder().host_name(host_name).build()?)
+                .build(),
+            None => {
+                let mut rules = Vec::new();
+                for rule in &settings.routing_rules {
+                    let condition = Condition::builder()
+                        .set_key_prefix_equals(rule.key_prefix_equals.clone())
+                        .set_http_error_code_returned_equals(rule.http_error_code_equals.clone())
+                        .build();
+                    let redirect = Redirect::builder()
+                        .set_host_name(rule.host_name.clone())
+                        .set_replace_key_prefix_with(rule.replace_key_prefix_with.clone())
+                        .set_http_redirect_code(rule.http_redirect_code.clone())
+                        .build();
+                    rules.push(aws_sdk_s3::types::RoutingRule::builder().condition(condition).redirect(redirect).build());
+                }
+                let error_document = match &settings.error_document {
+                    Some(key) => Some(ErrorDocument::builder().key(key).build()?),
+                    None => None,
+                };
+                WebsiteConfiguration::builder()
+                    .index_document(IndexDocument::builder().suffix(&settings.index_document).build()?)
+                    .set_error_document(error_document)
+                    .set_routing_rules(Some(rules).filter(|rules| !rules.is_empty()))
+                    .build()
+            }
+        };
+        let client = self.s3_client().await;
+        client
+            .put_bucket_website()
+            .bucket(bucket)
+            .website_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn delete_website(&self, bucket: &str) -> eyre::Result<()> {
+        let client = self.s3_client().await;
+        client
+            .delete_bucket_website()
+            .bucket(bucket)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Policy of the bucket as JSON, `None` when it has none
+    pub async fn get_bucket_policy(&self, bucket: &str) -> eyre::Result<Option<String>> {
+        let client = self.s3_client().await;
+        match client.get_bucket_policy().bucket(bucket).send().await {
+            Ok(output) => Ok(output.policy().map(String::from)),
+            Err(e) => {
+                let err = e.into_service_error();
+                match err.code() {
+                    Some("NoSuchBucketPolicy") => Ok(None),
+                    _ => Err(Report::msg(err.to_string())),
+                }
+            }
+        }
+    }
+
+    /// Replaces the policy of the bucket. Public policies are refused while the public access
+    /// block of the bucket blocks them, `allow_public` lifts that part of the block first.
+    pub async fn put_bucket_policy(&self, bucket: &str, policy: &str, allow_public: bool) -> eyre::Result<()> {
+        let client = self.s3_client().await;
+        if allow_public {
+            let configuration = PublicAccessBlockConfiguration::builder()
+                .block_public_acls(true)
+                .ignore_public_acls(true)
+                .block_public_policy(false)
+                .restrict_public_buckets(false)
+                .build();
+            client
+                .put_public_access_block()
+                .bucket(bucket)
+                .public_access_block_configuration(configuration)
+                .send()
+                .await
+                .map_err(|e| Report::msg(format!("Cannot allow public policies: {}", e.into_service_error())))?;
+        }
+        client
+            .put_bucket_policy()
+            .bucket(bucket)
+            .policy(policy)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Checks the credentials of the account and describes who they belong to:
+    /// the ARN of the caller on AWS, the number of buckets on s3 compatible services,
+    /// which don't implement STS
+    pub async fn test_connection(&self) -> eyre::Result<String> {
+        match &self.endpoint_url {
+            Some(endpoint_url) => {
+                let client = self.s3_client().await;
+                let res = client
+                    .list_buckets()
+                    .send()
+                    .await
+                    .map_err(|e| Report::msg(DisplayErrorContext(&e).to_string()))?;
+                Ok(format!("{} buckets at {}", res.buckets().len(), endpoint_url))
+            }
+            None => {
+                let client = aws_sdk_sts::Client::new(&self.sdk_config().await);
+                let identity = client
+                    .get_caller_identity()
+                    .send()
+                    .await
+                    .map_err(|e| Report::msg(DisplayErrorContext(&e).to_string()))?;
+                Ok(identity.arn().unwrap_or("Unknown identity").to_string())
+            }
+        }
+    }
+
+    /// Lists the KMS keys available for SSE-KMS, as (alias, key id) pairs
+    pub async fn list_kms_keys(&self) -> eyre::Result<Vec<(String, String)>> {
+        let client = aws_sdk_kms::Client::new(&self.sdk_config().await);
+        let mut keys = Vec::new();
+        let mut aliases = client.list_aliases().into_paginator().send();
+        while let Some(page) = aliases.next().await {
+            let page = page.map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+            for alias in page.aliases() {
+                // aliases of AWS managed keys (other than aws/s3) can't be used by s3
+                if let (Some(name), Some(key_id)) = (alias.alias_name(), alias.target_key_id()) {
+                    if !name.starts_with("alias/aws/") || name == "alias/aws/s3" {
+                        keys.push((name.to_string(), key_id.to_string()));
+                    }
+                }
+            }
+        }
+        Ok(keys)
+    }
+
     /// Lists all object in the given bucket (or filtered by prefix) and constructs the items
     /// representing directories
     /// This method is used for displaying bucket/prefix content while browsing s3 and
     /// it's not fetching all the contents behind prefixes together
-    async fn list_objects(
+    pub async fn list_objects(
         &self,
         bucket: &str,
         prefix: Option<String>,
     ) -> eyre::Result<Vec<S3DataItem>> {
         let mut all_objects = Vec::new();
+        let mut pages = self.stream_objects(bucket, prefix, ListingMode::Delimited).await?;
+        while let Some(page) = pages.next().await {
+            all_objects.extend(page?);
+        }
+        Ok(all_objects)
+    }
+
+    /// Lists the bucket (or the part under the prefix) as a stream of pages, each page is sent
+    /// as soon as it arrives so the caller can show it while the rest is still being listed.
+    /// The listing stops after the first error or when the stream is dropped.
+    pub async fn stream_objects(
+        &self,
+        bucket: &str,
+        prefix: Option<String>,
+        mode: ListingMode,
+    ) -> eyre::Result<ObjectPages> {
         let location = self.get_bucket_location(bucket).await?;
-        let creds = self.credentials.clone();
-        let temp_file_creds = FileCredential {
-            name: "temp".to_string(),
-            access_key: creds.access_key_id().to_string(),
-            secret_key: creds.secret_access_key().to_string(),
-            default_region: location.clone(),
-            selected: false,
+        let client_with_location = self.s3_client().await;
+        let delimiter = match mode {
+            ListingMode::Delimited => Some("/".to_string()),
+            ListingMode::Flat => None,
         };
-        let account = CURRENT_ACCOUNT.read().clone();
-        let client_with_location = self.get_s3_client_with_account(account).await;
-        let mut response = client_with_location
+        let bucket = bucket.to_string();
+        let (pages_tx, pages_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut response = client_with_location
+                .list_objects_v2()
+                .fetch_owner(true)
+                .set_delimiter(delimiter)
+                .set_prefix(prefix)
+                .bucket(&bucket)
+                .into_paginator()
+                .send();
+            while let Some(result) = response.next().await {
+                let page = result
+                    .map(|output| Self::page_to_items(&bucket, &location, &output))
+                    .map_err(|e| Report::msg(e.into_service_error().to_string()));
+                let failed = page.is_err();
+                if pages_tx.send(page).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(UnboundedReceiverStream::new(pages_rx))
+    }
+
+    /// Fetches one page of the listing. The first page starts with the key after `start_after`
+    /// (if given), the following ones with the continuation token of the previous page.
+    pub async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: Option<String>,
+        mode: ListingMode,
+        page_size: usize,
+        continuation_token: Option<String>,
+        start_after: Option<String>,
+    ) -> eyre::Result<ListingPage> {
+        let location = self.get_bucket_location(bucket).await?;
+        let client_with_location = self.s3_client().await;
+        let output = client_with_location
             .list_objects_v2()
-            .delimiter("/")
+            .fetch_owner(true)
+            .set_delimiter((mode == ListingMode::Delimited).then(|| "/".to_string()))
             .set_prefix(prefix)
-            .bucket(bucket.to_owned())
+            .max_keys(i32::try_from(page_size).unwrap_or(i32::MAX))
+            .set_continuation_token(continuation_token)
+            .set_start_after(start_after)
+            .bucket(bucket)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(ListingPage {
+            items: Self::page_to_items(bucket, &location, &output),
+            next_token: output.next_continuation_token().map(String::from),
+        })
+    }
+
+    /// Converts one page of `list_objects_v2` into items, common prefixes become directories
+    fn page_to_items(bucket: &str, location: &str, output: &ListObjectsV2Output) -> Vec<S3DataItem> {
+        let mut items: Vec<S3DataItem> = output
+            .contents()
+            .iter()
+            .map(|object| Self::object_to_item(bucket, Some(location.to_string()), object))
+            .collect();
+        for object in output.common_prefixes() {
+            let key = object.prefix().unwrap_or_default();
+            if key != "/" {
+                let file_info = FileInfo {
+                    file_name: Self::get_last_directory(key).unwrap_or_default(),
+                    size: None,
+                    file_type: "Dir".to_string(),
+                    path: key.to_string(),
+                    is_directory: true,
+                    last_modified: None,
+                    e_tag: None,
+                    storage_class: None,
+                    owner: None,
+                };
+                let bucket_info = BucketInfo {
+                    bucket: Some(bucket.to_string()),
+                    region: Some(location.to_string()),
+                    is_bucket: false,
+                };
+                items.push(S3DataItem::init(bucket_info, file_info));
+            }
+        }
+        items
+    }
+
+    /// Builds the item representing an object returned by `list_objects_v2`
+    fn object_to_item(bucket: &str, region: Option<String>, object: &Object) -> S3DataItem {
+        let key = object.key().unwrap_or_default();
+        let file_extension = Path::new(key)
+            .extension()
+            .and_then(|ext| ext.to_str()) // Convert the OsStr to a &str
+            .unwrap_or("");
+        let file_info = FileInfo {
+            file_name: Self::get_filename(key).unwrap_or_default(),
+            size: object.size().and_then(|size| u64::try_from(size).ok()),
+            file_type: file_extension.to_string(),
+            path: key.to_string(),
+            is_directory: false,
+            last_modified: object.last_modified().cloned(),
+            e_tag: object.e_tag().map(String::from),
+            storage_class: object.storage_class().map(|sc| sc.to_string()),
+            owner: object.owner().and_then(|o| o.display_name().or(o.id())).map(String::from),
+        };
+        let bucket_info = BucketInfo {
+            bucket: Some(bucket.to_string()),
+            region,
+            is_bucket: false,
+        };
+        S3DataItem::init(bucket_info, file_info)
+    }
+
+    /// Lists the whole bucket (or the part under the pattern's prefix) page by page and sends
+    /// every matching object as soon as its page arrives. Stops early when the receiver is gone,
+    /// eg. when the search got cancelled or has enough results.
+    pub async fn search_objects(
+        &self,
+        bucket: &str,
+        pattern: &SearchPattern,
+        results_tx: UnboundedSender<S3DataItem>,
+    ) -> eyre::Result<()> {
+        let client = self.s3_client().await;
+        let mut response = client
+            .list_objects_v2()
+            .set_prefix(pattern.list_prefix())
+            .bucket(bucket)
             .into_paginator()
             .send();
-
         while let Some(result) = response.next().await {
-            match result {
-                Ok(output) => {
-                    for object in output.contents() {
-                        let key = object.key().unwrap_or_default();
-                        //todo: get size of the file
-                        let size = object
-                            .size()
-                            .map_or(String::new(), |value| value.to_string());
-                        let path = Path::new(key);
-                        let file_extension = path
-                            .extension()
-                            .and_then(|ext| ext.to_str()) // Convert the OsStr to a &str
-                            .unwrap_or("");
-                        let file_info = FileInfo {
-                            file_name: Self::get_filename(key).unwrap_or_default(),
-                            size,
-                            file_type: file_extension.to_string(),
-                            path: key.to_string(),
-                            is_directory: false,
-                        };
-                        let bucket_info = BucketInfo {
-                            bucket: Some(bucket.to_string()),
-                            region: Some(location.clone()),
-                            is_bucket: false,
-                        };
-                        all_objects.push(S3DataItem::init(bucket_info, file_info));
-                    }
-                    for object in output.common_prefixes() {
-                        let key = object.prefix().unwrap_or_default();
-                        if key != "/" {
-                            let file_info = FileInfo {
-                                file_name: Self::get_last_directory(key).unwrap_or_default(),
-                                size: "".to_string(),
-                                file_type: "Dir".to_string(),
-                                path: key.to_string(),
-                                is_directory: true,
-                            };
-                            let bucket_info = BucketInfo {
-                                bucket: Some(bucket.to_string()),
-                                region: Some(location.clone()),
-                                is_bucket: false,
-                            };
-                            all_objects.push(S3DataItem::init(bucket_info, file_info));
-                        }
-                    }
-                }
-                Err(err) => {
-                    println!("Err: {:?}", err) // Return the error immediately if encountered
+            let output = result.map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+            for object in output.contents() {
+                if pattern.matches(object.key().unwrap_or_default())
+                    && results_tx.send(Self::object_to_item(bucket, None, object)).is_err()
+                {
+                    return Ok(());
                 }
             }
+            if results_tx.is_closed() {
+                return Ok(());
+            }
         }
-
-        Ok(all_objects)
+        Ok(())
     }
 
     fn get_last_directory(path: &str) -> Option<String> {
@@ -503,157 +1721,177 @@ impl S3DataFetcher {
     /// This method is similar to `list_objects` but it fetches all the data recursively
     /// including data behind the prefixes.
     /// Designed to be used mainly when selecting whole bucket/prefix for download or delete.
+    /// Lists without a delimiter, so it's a single chain of requests instead of one per prefix.
     pub async fn list_all_objects(
         &self,
         bucket: &str,
         prefix: Option<String>,
     ) -> eyre::Result<Vec<S3DataItem>> {
         let mut all_objects = Vec::new();
-        let location = self.get_bucket_location(bucket).await?;
-        self.recursive_list_objects(bucket, prefix, &location, &mut all_objects)
-            .await?;
+        let mut pages = self.stream_objects(bucket, prefix, ListingMode::Flat).await?;
+        while let Some(page) = pages.next().await {
+            all_objects.extend(page?);
+        }
         Ok(all_objects)
     }
-    fn recursive_list_objects<'a>(
-        &'a self,
-        bucket: &'a str,
-        prefix: Option<String>,
-        location: &'a str,
-        all_objects: &'a mut Vec<S3DataItem>,
-    ) -> Pin<Box<dyn std::future::Future<Output=Result<(), Report>> + Send + 'a>> {
-        let account = CURRENT_ACCOUNT.read().clone();
-        Box::pin(async move {
-            let creds = self.credentials.clone();
-            let temp_file_creds = FileCredential {
-                name: "temp".to_string(),
-                access_key: creds.access_key_id().to_string(),
-                secret_key: creds.secret_access_key().to_string(),
-                default_region: location.to_string(),
-                selected: false,
-            };
-            let client_with_location = self.get_s3_client_with_account(account).await;
-            let mut response = client_with_location
-                .list_objects_v2()
-                .delimiter("/")
-                .set_prefix(prefix.clone())
-                .bucket(bucket.to_owned())
-                .into_paginator()
-                .send();
 
-            while let Some(result) = response.next().await {
-                match result {
-                    Ok(output) => {
-                        for object in output.contents() {
-                            let key = object.key().unwrap_or_default();
-                            let size = object
-                                .size()
-                                .map_or(String::new(), |value| value.to_string());
-                            let path = Path::new(key);
-                            let file_extension =
-                                path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-                            let file_info = FileInfo {
-                                file_name: Self::get_filename(key).unwrap_or_default(),
-                                size,
-                                file_type: file_extension.to_string(),
-                                path: key.to_string(),
-                                is_directory: false,
-                            };
-                            let bucket_info = BucketInfo {
-                                bucket: Some(bucket.to_string()),
-                                region: Some(location.to_string()),
-                                is_bucket: false,
-                            };
-                            all_objects.push(S3DataItem::init(bucket_info, file_info));
-                        }
-                        for common_prefix in output.common_prefixes() {
-                            let prefix = common_prefix.prefix().unwrap_or_default().to_string();
-                            self.recursive_list_objects(
-                                bucket,
-                                Some(prefix),
-                                location,
-                                all_objects,
-                            )
-                                .await?;
-                        }
-                    }
-                    Err(err) => {
-                        println!("Err: {:?}", err); // Return the error immediately if encountered
-                        return Err(err.into());
-                    }
-                }
+    /// Lists the multipart uploads of the bucket which were neither completed nor aborted,
+    /// with the size of the parts uploaded so far
+    pub async fn list_multipart_uploads(&self, bucket: &str) -> eyre::Result<Vec<IncompleteUpload>> {
+        let client = self.s3_client().await;
+        let mut uploads = Vec::new();
+        let (mut key_marker, mut upload_id_marker) = (None, None);
+        loop {
+            let output = client
+                .list_multipart_uploads()
+                .bucket(bucket)
+                .set_key_marker(key_marker)
+                .set_upload_id_marker(upload_id_marker)
+                .send()
+                .await
+                .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+            for upload in output.uploads() {
+                let (Some(key), Some(upload_id)) = (upload.key(), upload.upload_id()) else {
+                    continue;
+                };
+                let (part_count, size) = self.get_upload_parts(bucket, key, upload_id).await?;
+                uploads.push(IncompleteUpload {
+                    key: key.to_string(),
+                    upload_id: upload_id.to_string(),
+                    initiated: upload.initiated().map(|time| time.secs()),
+                    size,
+                    part_count,
+                });
             }
-            Ok(())
-        })
+            if !output.is_truncated().unwrap_or(false) {
+                return Ok(uploads);
+            }
+            key_marker = output.next_key_marker().map(String::from);
+            upload_id_marker = output.next_upload_id_marker().map(String::from);
+        }
     }
 
-    async fn get_s3_client(&self, creds: Option<FileCredential>) -> Client {
-        let credentials: Credentials;
-        let default_region: String;
-        if let Some(crd) = creds {
-            let access_key = crd.access_key;
-            let secret_access_key = crd.secret_key;
-            default_region = crd.default_region;
-            credentials = Credentials::new(
-                access_key,
-                secret_access_key,
-                None,     // Token, if using temporary credentials (like STS)
-                None,     // Expiry time, if applicable
-                "manual", // Source, just a label for debugging
-            );
-        } else {
-            credentials = self.credentials.clone();
-            default_region = self.default_region.clone();
+    /// Number and total size of the parts uploaded so far
+    pub async fn get_upload_parts(&self, bucket: &str, key: &str, upload_id: &str) -> eyre::Result<(usize, u64)> {
+        let client = self.s3_client().await;
+        let mut pages = client
+            .list_parts()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .into_paginator()
+            .send();
+        let (mut count, mut size) = (0, 0);
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+            count += page.parts().len();
+            size += page.parts().iter().filter_map(|part| part.size()).map(|size| size.max(0) as u64).sum::<u64>();
         }
-        let region_provider = RegionProviderChain::first_try(Region::new(default_region))
-            .or_default_provider()
-            .or_else(Region::new("eu-north-1"));
-        let shared_config = aws_config::from_env()
-            .credentials_provider(credentials)
-            .region(region_provider)
-            .load()
-            .await;
-        Client::new(&shared_config)
-    }
-
-    /// Like get_s3_client, but takes Option<Account> (from DB or given), not FileCredential.
-    pub async fn get_s3_client_with_account(&self, account: Option<crate::model::account::Account>) -> Client {
-        let credentials: Credentials;
-        let default_region: String;
-        if let Some(acc) = account {
-            let access_key = acc.access_key;
-            let secret_access_key = acc.secret_key;
-            default_region = self.default_region.clone();
-            credentials = Credentials::new(
-                access_key,
-                secret_access_key,
-                None,     // Token, if using temporary credentials (like STS)
-                None,     // Expiry time, if applicable
-                "account_struct", // Source, just a label for debugging
-            );
-        } else {
-            if let Some(acc) = get_default_account() {
-                credentials = Credentials::new(
-                    acc.access_key,
-                    acc.secret_key,
-                    None,
-                    None,
-                    "db_account",
-                );
-                default_region = self.default_region.clone();
-            } else {
-                // fallback to self.credentials if no account found
-                credentials = self.credentials.clone();
-                default_region = self.default_region.clone();
+        Ok((count, size))
+    }
+
+    /// Aborts the upload, its parts are removed and no longer billed
+    pub async fn abort_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str) -> eyre::Result<()> {
+        let client = self.s3_client().await;
+        client
+            .abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    /// Lifecycle rules of the bucket, empty when it has no lifecycle configuration
+    async fn get_lifecycle_rules(&self, bucket: &str) -> eyre::Result<Vec<LifecycleRule>> {
+        let client = self.s3_client().await;
+        match client.get_bucket_lifecycle_configuration().bucket(bucket).send().await {
+            Ok(output) => Ok(output.rules().to_vec()),
+            Err(e) => {
+                let err = e.into_service_error();
+                match err.code() {
+                    Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+                    _ => Err(Report::msg(err.to_string())),
+                }
             }
         }
-        let region_provider = RegionProviderChain::first_try(Region::new(default_region))
+    }
+
+    /// Days after which the incomplete multipart uploads of the whole bucket are aborted
+    /// by an enabled lifecycle rule, if there's one
+    pub async fn get_abort_uploads_days(&self, bucket: &str) -> eyre::Result<Option<i32>> {
+        let rules = self.get_lifecycle_rules(bucket).await?;
+        Ok(rules
+            .iter()
+            .filter(|rule| *rule.status() == ExpirationStatus::Enabled)
+            .filter(|rule| {
+                // rules created before filters existed keep their prefix on the rule itself,
+                // s3 still returns them that way
+                #[allow(deprecated)]
+                let prefix = rule.filter().map_or(rule.prefix(), |filter| filter.prefix());
+                let filtered = rule.filter().is_some_and(|filter| filter.tag().is_some() || filter.and().is_some());
+                prefix.unwrap_or_default().is_empty() && !filtered
+            })
+            .filter_map(|rule| rule.abort_incomplete_multipart_upload()?.days_after_initiation())
+            .min())
+    }
+
+    /// Adds (or updates) the lifecycle rule aborting the incomplete multipart uploads of the
+    /// whole bucket after the given days, the other rules of the bucket are kept
+    pub async fn set_abort_uploads_rule(&self, bucket: &str, days: i32) -> eyre::Result<()> {
+        let mut rules: Vec<LifecycleRule> = self
+            .get_lifecycle_rules(bucket)
+            .await?
+            .into_iter()
+            .filter(|rule| rule.id() != Some(ABORT_UPLOADS_RULE_ID))
+            .collect();
+        rules.push(
+            LifecycleRule::builder()
+                .id(ABORT_UPLOADS_RULE_ID)
+                .status(ExpirationStatus::Enabled)
+                .filter(LifecycleRuleFilter::builder().prefix("").build())
+                .abort_incomplete_multipart_upload(
+                    AbortIncompleteMultipartUpload::builder().days_after_initiation(days).build(),
+                )
+                .build()?,
+        );
+        let configuration = BucketLifecycleConfiguration::builder().set_rules(Some(rules)).build()?;
+        let client = self.s3_client().await;
+        client
+            .put_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .lifecycle_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| Report::msg(e.into_service_error().to_string()))?;
+        Ok(())
+    }
+
+    pub async fn s3_client(&self) -> Client {
+        Client::from_conf(self.s3_config().await.build())
+    }
+
+    /// Configuration of the s3 clients, pointing at the custom endpoint if there is one
+    async fn s3_config(&self) -> aws_sdk_s3::config::Builder {
+        let config = aws_sdk_s3::config::Builder::from(&self.sdk_config().await);
+        match &self.endpoint_url {
+            Some(endpoint_url) => config.endpoint_url(endpoint_url).force_path_style(true),
+            None => config,
+        }
+    }
+
+    /// Shared AWS configuration (credentials and region) of the account,
+    /// used to create the s3 client and the clients of other services
+    async fn sdk_config(&self) -> SdkConfig {
+        let region_provider = RegionProviderChain::first_try(Region::new(self.default_region.clone()))
             .or_default_provider()
             .or_else(Region::new("eu-north-1"));
-        let shared_config = aws_config::from_env()
-            .credentials_provider(credentials)
+        aws_config::from_env()
+            .credentials_provider(self.credentials.clone())
             .region(region_provider)
             .load()
-            .await;
-        Client::new(&shared_config)
+            .await
     }
 }
@@ -0,0 +1,364 @@
+//! State of the running app: the database and the accounts opened in the sidebar.
+//! The core library gets them passed in, this is the only place they're global.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Once, OnceLock};
+use dioxus::prelude::*;
+use crate::model::account::Account;
+use crate::model::cross_account::CrossAccountTransfer;
+use crate::model::download_progress_item::DownloadProgressItem;
+use crate::model::encryption::EncryptionSettings;
+use crate::model::local_selected_item::LocalSelectedItem;
+use crate::model::transfer_options::TransferOptions;
+use crate::model::upload_progress_item::UploadProgressItem;
+use crate::repositories::account_repo::fetch_accounts;
+use crate::repositories::cross_account_repo::delete_transfer;
+use crate::repositories::database::Database;
+use crate::services::cross_account::{transfer_object, DEFAULT_PART_SIZE};
+use crate::services::index_crawler::crawl_bucket;
+use crate::services::s3_data_fetcher::S3DataFetcher;
+use crate::utils::{default_db_path, get_download_dir};
+
+static INIT: Once = Once::new();
+static DATABASE: OnceLock<Database> = OnceLock::new();
+
+/// Accounts opened in the sidebar, in the order they were opened. Every page and pane
+/// browses one of them, the account is part of its route or of its own state.
+pub static OPEN_ACCOUNTS: GlobalSignal<Vec<Account>> = Signal::global(Vec::new);
+
+pub fn init_db() {
+    let db = Database::open(&default_db_path()).expect("Failed to open SQLite database");
+    let _ = DATABASE.set(db);
+}
+
+/// The database opened at startup
+pub fn db() -> &'static Database {
+    DATABASE.get().expect("init_db is called before the app starts")
+}
+
+pub fn init_state() {
+    INIT.call_once(|| {
+        // the session starts with the default account opened
+        if let Some(account) = fetch_accounts(db()).into_iter().find(|a| a.is_default) {
+            open_account(account);
+        }
+    });
+}
+
+/// Adds the account to the sidebar, unless it's open already
+pub fn open_account(account: Account) {
+    if !OPEN_ACCOUNTS.read().iter().any(|open| open.id == account.id) {
+        OPEN_ACCOUNTS.write().push(account);
+    }
+}
+
+pub fn close_account(account_id: i64) {
+    OPEN_ACCOUNTS.write().retain(|account| account.id != account_id);
+}
+
+/// Picks up the edits of the open accounts, the deleted ones are closed
+pub fn reload_open_accounts() {
+    let accounts = fetch_accounts(db());
+    let reloaded: Vec<_> = OPEN_ACCOUNTS
+        .peek()
+        .iter()
+        .filter_map(|open| accounts.iter().find(|account| account.id == open.id).cloned())
+        .collect();
+    *OPEN_ACCOUNTS.write() = reloaded;
+}
+
+/// The account opened first, used by the pages which aren't bound to an account yet
+pub fn first_open_account_id() -> Option<i64> {
+    OPEN_ACCOUNTS.read().first().map(|account| account.id)
+}
+
+/// Fetcher for any of the saved accounts, with its latest credentials
+pub fn account_fetcher(account_id: i64) -> Option<S3DataFetcher> {
+    let account = fetch_accounts(db()).into_iter().find(|account| account.id == account_id)?;
+    Some(S3DataFetcher::new(account, db().clone()))
+}
+
+/// Result of the last connection test of an account
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountHealth {
+    Checking,
+    /// Identity the credentials belong to
+    Healthy(String),
+    Failed(String),
+}
+
+/// Health of the accounts tested in this session, by account id
+pub static ACCOUNT_HEALTH: GlobalSignal<HashMap<i64, AccountHealth>> = Signal::global(HashMap::new);
+
+/// Tests the connection of the account in the background, unless it's being tested already
+pub fn check_account_health(account: Account) {
+    if ACCOUNT_HEALTH.peek().get(&account.id) == Some(&AccountHealth::Checking) {
+        return;
+    }
+    let account_id = account.id;
+    ACCOUNT_HEALTH.write().insert(account_id, AccountHealth::Checking);
+    let fetcher = S3DataFetcher::new(account, db().clone());
+    spawn_forever(async move {
+        let health = match fetcher.test_connection().await {
+            Ok(identity) => AccountHealth::Healthy(identity),
+            Err(e) => AccountHealth::Failed(e.to_string()),
+        };
+        ACCOUNT_HEALTH.write().insert(account_id, health);
+    });
+}
+
+/// State of the crawls started in this session, by bucket
+#[derive(Debug, Clone, PartialEq)]
+pub enum CrawlState {
+    /// Number of objects indexed so far
+    Running(usize),
+    /// Number of objects the finished crawl indexed
+    Done(usize),
+    Failed(String),
+}
+
+/// Crawls by account and bucket, the same bucket name can exist on different services
+pub static CRAWLS: GlobalSignal<HashMap<(i64, String), CrawlState>> = Signal::global(HashMap::new);
+
+/// Starts indexing the bucket in the background, unless it's being indexed already.
+/// The crawl keeps going when the user navigates away from the page which started it.
+pub fn start_crawl(account_id: i64, bucket: String) {
+    let crawl = (account_id, bucket);
+    if matches!(CRAWLS.read().get(&crawl), Some(CrawlState::Running(_))) {
+        return;
+    }
+    let Some(fetcher) = account_fetcher(account_id) else {
+        CRAWLS.write().insert(crawl, CrawlState::Failed("The account doesn't exist anymore".to_string()));
+        return;
+    };
+    CRAWLS.write().insert(crawl.clone(), CrawlState::Running(0));
+    spawn_forever(async move {
+        let progress = |count| {
+            CRAWLS.write().insert(crawl.clone(), CrawlState::Running(count));
+        };
+        let state = match crawl_bucket(&fetcher, db(), &crawl.1, progress).await {
+            Ok(count) => CrawlState::Done(count),
+            Err(e) => CrawlState::Failed(e.to_string()),
+        };
+        CRAWLS.write().insert(crawl, state);
+    });
+}
+
+/// State of a transfer queued in this session
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferState {
+    Queued,
+    /// Percentage transferred so far
+    Running(f64),
+    Done,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+    /// From a bucket of the account into a bucket of another account
+    CrossAccount,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedTransfer {
+    pub id: u64,
+    pub direction: TransferDirection,
+    /// Account of the bucket, the source account of copies
+    pub account_id: i64,
+    /// The object on the account's side, the source of downloads and copies
+    pub bucket: String,
+    pub key: String,
+    /// The local file uploaded from or downloaded into, or the copy in the other account
+    pub other_side: String,
+    pub state: TransferState,
+}
+
+/// Folder the objects are downloaded into from the object browser, it can be changed for the session
+pub static DOWNLOAD_DIR: GlobalSignal<PathBuf> = Signal::global(get_download_dir);
+
+/// Options of the transfers started from the app, kept for the session
+pub static TRANSFER_OPTIONS: GlobalSignal<TransferOptions> = Signal::global(TransferOptions::default);
+
+static NEXT_TRANSFER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Uploads and downloads queued in this session, in the order they were queued
+pub static TRANSFERS: GlobalSignal<Vec<QueuedTransfer>> = Signal::global(Vec::new);
+
+fn set_transfer_state(id: u64, state: TransferState) {
+    if let Some(transfer) = TRANSFERS.write().iter_mut().find(|transfer| transfer.id == id) {
+        transfer.state = state;
+    }
+}
+
+/// Progress of the transfer, unless it's finished already (the progress may arrive later)
+fn set_transfer_progress(id: u64, progress: f64) {
+    let running = TRANSFERS
+        .read()
+        .iter()
+        .any(|transfer| transfer.id == id && matches!(transfer.state, TransferState::Running(_)));
+    if running {
+        set_transfer_state(id, TransferState::Running(progress));
+    }
+}
+
+/// Adds the transfers to the list, returns their ids
+fn add_transfers(
+    direction: TransferDirection,
+    transfers: impl Iterator<Item = (i64, String, String, String)>,
+) -> Vec<u64> {
+    let mut ids = Vec::new();
+    let mut list = TRANSFERS.write();
+    for (account_id, bucket, key, other_side) in transfers {
+        let id = NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed);
+        list.push(QueuedTransfer { id, direction, account_id, bucket, key, other_side, state: TransferState::Queued });
+        ids.push(id);
+    }
+    ids
+}
+
+/// Uploads the items with the account one after the other in the background.
+/// Like the crawls, they keep going when the user navigates away from the page which queued them.
+/// Nothing is queued when the account was removed meanwhile.
+pub fn queue_uploads(account_id: i64, items: Vec<LocalSelectedItem>) -> Result<(), String> {
+    let Some(fetcher) = account_fetcher(account_id) else {
+        return Err(format!("The account doesn't exist anymore, {} files are not uploaded", items.len()));
+    };
+    let ids = add_transfers(
+        TransferDirection::Upload,
+        items.iter().map(|item| {
+            (account_id, item.destination_bucket.clone(), item.destination_path.clone(), item.path.clone())
+        }),
+    );
+    spawn_forever(async move {
+        for (id, item) in ids.into_iter().zip(items) {
+            let (upload_tx, mut upload_rx) = tokio::sync::mpsc::unbounded_channel::<UploadProgressItem>();
+            set_transfer_state(id, TransferState::Running(0.0));
+            spawn_forever(async move {
+                while let Some(progress) = upload_rx.recv().await {
+                    set_transfer_progress(id, progress.progress);
+                }
+            });
+            let state = match fetcher.upload_item(item, upload_tx).await {
+                Ok(_) => TransferState::Done,
+                Err(e) => TransferState::Failed(e.to_string()),
+            };
+            set_transfer_state(id, state);
+        }
+    });
+    Ok(())
+}
+
+/// Downloads the (bucket, key, local file) objects one after the other in the background,
+/// `encryption` overrides the account's settings (the SSE-C key reading the objects)
+pub fn queue_downloads(
+    account_id: i64,
+    objects: Vec<(String, String, PathBuf)>,
+    encryption: Option<EncryptionSettings>,
+) -> Result<(), String> {
+    let Some(fetcher) = account_fetcher(account_id) else {
+        return Err(format!("The account doesn't exist anymore, {} objects are not downloaded", objects.len()));
+    };
+    let ids = add_transfers(
+        TransferDirection::Download,
+        objects
+            .iter()
+            .map(|(bucket, key, path)| (account_id, bucket.clone(), key.clone(), path.display().to_string())),
+    );
+    spawn_forever(async move {
+        for (id, (bucket, key, path)) in ids.into_iter().zip(objects) {
+            let (download_tx, mut download_rx) = tokio::sync::mpsc::unbounded_channel::<DownloadProgressItem>();
+            set_transfer_state(id, TransferState::Running(0.0));
+            spawn_forever(async move {
+                while let Some(progress) = download_rx.recv().await {
+                    set_transfer_progress(id, progress.progress);
+                }
+            });
+            let state = match fetcher.download_to_file(&bucket, &key, &path, encryption.clone(), download_tx).await {
+                Ok(_) => TransferState::Done,
+                Err(e) => TransferState::Failed(e.to_string()),
+            };
+            set_transfer_state(id, state);
+        }
+    });
+    Ok(())
+}
+
+/// The copy of the transfer as listed with the transfers, with the name of its account
+fn cross_account_destination(accounts: &[Account], transfer: &CrossAccountTransfer) -> String {
+    let account = accounts
+        .iter()
+        .find(|account| account.id == transfer.destination_account_id)
+        .map(|account| account.name.clone())
+        .unwrap_or_default();
+    format!("{}: s3://{}/{}", account, transfer.destination_bucket, transfer.destination_key)
+}
+
+/// Copies the objects into buckets of other accounts one after the other in the background.
+/// The transfers which were interrupted before are resumed after their uploaded parts.
+pub fn queue_cross_account_transfers(transfers: Vec<CrossAccountTransfer>) {
+    let accounts = fetch_accounts(db());
+    let ids = add_transfers(
+        TransferDirection::CrossAccount,
+        transfers.iter().map(|transfer| {
+            let destination = cross_account_destination(&accounts, transfer);
+            (transfer.source_account_id, transfer.source_bucket.clone(), transfer.source_key.clone(), destination)
+        }),
+    );
+    let transfers: Vec<_> = ids
+        .into_iter()
+        .zip(transfers)
+        .map(|(id, transfer)| {
+            let fetchers = account_fetcher(transfer.source_account_id).zip(account_fetcher(transfer.destination_account_id));
+            (id, transfer, fetchers)
+        })
+        .collect();
+    spawn_forever(async move {
+        for (id, mut transfer, fetchers) in transfers {
+            let Some((source, destination)) = fetchers else {
+                set_transfer_state(id, TransferState::Failed("The account doesn't exist anymore".to_string()));
+                continue;
+            };
+            set_transfer_state(id, TransferState::Running(0.0));
+            let progress = |bytes: u64, size: u64| set_transfer_progress(id, bytes as f64 * 100.0 / size.max(1) as f64);
+            let result = transfer_object(db(), &source, &destination, &mut transfer, DEFAULT_PART_SIZE, progress).await;
+            let state = match result {
+                Ok(()) => TransferState::Done,
+                Err(e) => TransferState::Failed(e.to_string()),
+            };
+            set_transfer_state(id, state);
+        }
+    });
+}
+
+/// Forgets the interrupted transfer and aborts its multipart upload in the background, the
+/// uploaded parts are billed until it's aborted. A failed abort is listed with the transfers.
+pub fn discard_cross_account_transfer(transfer: CrossAccountTransfer) {
+    delete_transfer(db(), transfer.id);
+    let (Some(upload_id), Some(fetcher)) = (transfer.upload_id.clone(), account_fetcher(transfer.destination_account_id)) else {
+        return;
+    };
+    spawn_forever(async move {
+        let result = fetcher
+            .abort_multipart_upload(&transfer.destination_bucket, &transfer.destination_key, &upload_id)
+            .await;
+        if let Err(e) = result {
+            let destination = cross_account_destination(&fetch_accounts(db()), &transfer);
+            let ids = add_transfers(
+                TransferDirection::CrossAccount,
+                [(transfer.source_account_id, transfer.source_bucket, transfer.source_key, destination)].into_iter(),
+            );
+            for id in ids {
+                set_transfer_state(id, TransferState::Failed(format!("Failed to abort the upload: {}", e)));
+            }
+        }
+    });
+}
+
+/// Forgets the transfers which are done or failed, the queued and running ones are kept
+pub fn clear_finished_transfers() {
+    TRANSFERS.write().retain(|transfer| !matches!(transfer.state, TransferState::Done | TransferState::Failed(_)));
+}
@@ -1,69 +1,51 @@
-use std::path::PathBuf;
-use directories::ProjectDirs;
+use std::path::{Path, PathBuf};
+use directories::{ProjectDirs, UserDirs};
 
-use once_cell::sync::Lazy;
-use rusqlite::Connection;
-use std::sync::Mutex;
-use dioxus::prelude::{GlobalSignal, Signal};
-use crate::model::account::Account;
-use crate::repositories::account_repo::fetch_accounts;
-use std::sync::Once;
-
-static INIT: Once = Once::new();
-
-pub static DB: Lazy<Mutex<Option<Connection>>> = Lazy::new(|| Mutex::new(None));
-pub static CURRENT_ACCOUNT: GlobalSignal<Option<Account>> = Signal::global(|| None);
-
-pub fn init_state() {
-    INIT.call_once(|| {
-        let accounts = fetch_accounts();
-        if let Some(account) = accounts.iter().find(|a| a.is_default) {
-            CURRENT_ACCOUNT.write().insert(account.clone());
-        }
-    });
+/// Location of the database with the accounts, shared by the app and the command line tool
+pub fn default_db_path() -> PathBuf {
+    get_data_dir().join("dios3").join("accounts.db")
 }
 
-pub fn init_db() {
-    let db_path = get_data_dir().join("dios3").join("accounts.db");
-    let db_dir = db_path.parent().unwrap();
-
-    // Add a debug check
-    println!("Ensuring DB directory exists: {}", db_dir.display());
-
-    // Explicitly create everything in the parent path
-    std::fs::create_dir_all(db_dir).expect("Failed to create DB directory");
-
-    assert!(db_dir.exists(), "DB directory does not exist after create_dir_all");
-    println!("DB Directory: {}", db_dir.display());
+fn project_directory() -> Option<ProjectDirs> {
+    ProjectDirs::from("com", "softberries", env!("CARGO_PKG_NAME"))
+}
 
-    let conn = Connection::open(&db_path).expect("Failed to open SQLite database");
+/// Gets the directory downloaded objects are saved to, the user's downloads folder if there is one
+pub fn get_download_dir() -> PathBuf {
+    UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(|dir| dir.to_path_buf()))
+        .unwrap_or_else(|| get_data_dir().join("downloads"))
+}
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS accounts (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            description TEXT,
-            access_key TEXT,
-            secret_key TEXT,
-            is_default INTEGER,
-            default_region TEXT
-        )",
-        [],
-    ).expect("Failed to create accounts table");
+/// Lets the user pick a folder in the dialog of the OS, `None` when it's cancelled
+#[cfg(feature = "desktop")]
+pub async fn pick_folder(start: &Path) -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_directory(start)
+        .pick_folder()
+        .await
+        .map(|folder| folder.path().to_path_buf())
+}
 
-    *DB.lock().unwrap() = Some(conn);
+/// Without a desktop there's no dialog to pick a folder with
+#[cfg(not(feature = "desktop"))]
+pub async fn pick_folder(_start: &Path) -> Option<PathBuf> {
+    None
 }
 
-fn project_directory() -> Option<ProjectDirs> {
-    ProjectDirs::from("com", "softberries", env!("CARGO_PKG_NAME"))
+/// Gets the user's home directory, where the local side of the commander starts
+pub fn get_home_dir() -> PathBuf {
+    UserDirs::new()
+        .map(|dirs| dirs.home_dir().to_path_buf())
+        .unwrap_or_else(get_download_dir)
 }
 
 /// Gets the user specified data directory
 /// Eventually takes the system default location
 pub fn get_data_dir() -> PathBuf {
-    let PROJECT_NAME: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
-    let DATA_FOLDER: Option<PathBuf> = std::env::var(format!("{}_DATA", PROJECT_NAME.clone())).ok().map(PathBuf::from);
-    let directory = if let Some(s) = DATA_FOLDER.clone() {
+    let project_name: String = env!("CARGO_CRATE_NAME").to_uppercase().to_string();
+    let data_folder: Option<PathBuf> = std::env::var(format!("{}_DATA", project_name)).ok().map(PathBuf::from);
+    let directory = if let Some(s) = data_folder {
         s
     } else if let Some(proj_dirs) = project_directory() {
         proj_dirs.data_local_dir().to_path_buf()
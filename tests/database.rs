@@ -0,0 +1,262 @@
+//! The repositories against an in-memory database, without the app around them
+use dios3::model::analytics::BucketAnalytics;
+use dios3::model::cross_account::CrossAccountTransfer;
+use dios3::model::encryption::EncryptionSettings;
+use dios3::model::header_rule::HeaderRule;
+use dios3::model::object_index::IndexedObject;
+use dios3::model::pricing::{PriceTable, StoragePrice};
+use dios3::model::s3_uri::Location;
+use dios3::repositories::account_repo::{delete_account, fetch_accounts, get_default_account, save_account_to_db};
+use dios3::repositories::analytics_repo::{delete_analytics, fetch_analytics, save_analytics};
+use dios3::repositories::bookmark_repo::{add_bookmark, delete_bookmark, fetch_bookmarks};
+use dios3::repositories::client_key_repo::{fetch_client_keys, get_client_key, save_client_key};
+use dios3::repositories::cross_account_repo::{fetch_transfers, save_transfer};
+use dios3::repositories::database::Database;
+use dios3::repositories::header_rule_repo::{delete_header_rule, fetch_header_rules, save_header_rule};
+use dios3::repositories::index_repo::{
+    finish_index_generation, get_index_info, save_indexed_objects, search_index, start_index_generation,
+};
+use dios3::repositories::pricing_repo::{fetch_price_table, reset_price_table, save_price_table};
+
+fn save_account(db: &Database, id: Option<i64>, name: &str, is_default: bool) {
+    let encryption = EncryptionSettings::default();
+    save_account_to_db(db, id, name, "", "access", "secret", is_default, "eu-west-1", &encryption, None, None);
+}
+
+#[test]
+fn only_one_account_is_the_default() {
+    let db = Database::open_in_memory().unwrap();
+    assert!(get_default_account(&db).is_none());
+
+    save_account(&db, None, "first", true);
+    save_account(&db, None, "second", true);
+    let accounts = fetch_accounts(&db);
+    assert_eq!(accounts.len(), 2);
+    assert_eq!(get_default_account(&db).map(|acc| acc.name), Some("second".to_string()));
+
+    let first = accounts.iter().find(|acc| acc.name == "first").unwrap();
+    save_account(&db, Some(first.id), "renamed", true);
+    assert_eq!(get_default_account(&db).map(|acc| acc.name), Some("renamed".to_string()));
+
+    delete_account(&db, first.id);
+    assert_eq!(fetch_accounts(&db).len(), 1);
+    assert!(get_default_account(&db).is_none());
+}
+
+#[test]
+fn deleting_an_account_leaves_nothing_of_it_behind() {
+    let db = Database::open_in_memory().unwrap();
+    save_account(&db, None, "deleted", false);
+    save_account(&db, None, "kept", false);
+    let accounts = fetch_accounts(&db);
+    let (deleted, kept) = (accounts[0].id, accounts[1].id);
+
+    for account_id in [deleted, kept] {
+        let generation = start_index_generation(&db, account_id, "photos");
+        save_indexed_objects(&db, account_id, "photos", generation, &[indexed("a.jpg", 1)]);
+        finish_index_generation(&db, account_id, "photos", generation, 100);
+        save_analytics(&db, account_id, &BucketAnalytics { bucket: "photos".to_string(), ..Default::default() });
+        save_header_rule(&db, &mut HeaderRule { account_id, pattern: "*".to_string(), ..Default::default() });
+    }
+    save_transfer(&db, &mut CrossAccountTransfer::new(deleted, "photos", "a.jpg", kept, "photos", "a.jpg"));
+    save_transfer(&db, &mut CrossAccountTransfer::new(kept, "photos", "a.jpg", deleted, "photos", "a.jpg"));
+    save_transfer(&db, &mut CrossAccountTransfer::new(kept, "photos", "a.jpg", kept, "backup", "a.jpg"));
+
+    delete_account(&db, deleted);
+
+    assert!(get_index_info(&db, deleted, "photos").is_none());
+    assert!(search_index(&db, deleted, "photos", None, |_| true, usize::MAX).is_empty());
+    assert!(fetch_analytics(&db, deleted).is_empty());
+    assert!(fetch_header_rules(&db, deleted).is_empty());
+    let transfers = fetch_transfers(&db);
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].destination_bucket, "backup");
+
+    assert!(get_index_info(&db, kept, "photos").is_some());
+    assert_eq!(search_index(&db, kept, "photos", None, |_| true, usize::MAX).len(), 1);
+    assert_eq!(fetch_analytics(&db, kept).len(), 1);
+    assert_eq!(fetch_header_rules(&db, kept).len(), 1);
+}
+
+#[test]
+fn accounts_of_compatible_services_keep_their_endpoint() {
+    let db = Database::open_in_memory().unwrap();
+    let encryption = EncryptionSettings::default();
+    save_account_to_db(&db, None, "aws", "", "access", "secret", true, "eu-west-1", &encryption, None, None);
+    save_account_to_db(&db, None, "minio", "", "access", "secret", false, "us-east-1", &encryption, None, Some("http://localhost:9000"));
+
+    let endpoints: Vec<_> = fetch_accounts(&db).into_iter().map(|acc| (acc.name, acc.endpoint_url)).collect();
+    assert_eq!(endpoints, vec![
+        ("aws".to_string(), None),
+        ("minio".to_string(), Some("http://localhost:9000".to_string())),
+    ]);
+}
+
+#[test]
+fn databases_are_independent() {
+    let db = Database::open_in_memory().unwrap();
+    let other = Database::open_in_memory().unwrap();
+    let key_id = save_client_key(&db, "key", "c2VjcmV0").unwrap();
+
+    assert_eq!(get_client_key(&db, key_id).map(|key| key.name), Some("key".to_string()));
+    assert!(get_client_key(&other, key_id).is_none());
+    assert!(fetch_client_keys(&other).is_empty());
+}
+
+#[test]
+fn reopening_a_file_keeps_the_accounts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("accounts.db");
+    save_account(&Database::open(&path).unwrap(), None, "kept", true);
+
+    // the second open runs the migrations again on the existing schema
+    let reopened = Database::open(&path).unwrap();
+    assert_eq!(get_default_account(&reopened).map(|acc| acc.name), Some("kept".to_string()));
+}
+
+#[test]
+fn analytics_are_cached_per_account_and_bucket() {
+    let db = Database::open_in_memory().unwrap();
+    let analytics = |bucket: &str, computed_at| BucketAnalytics {
+        bucket: bucket.to_string(),
+        object_count: 1,
+        total_size: 10,
+        computed_at,
+        ..BucketAnalytics::default()
+    };
+    save_analytics(&db, 1, &analytics("photos", 100));
+    save_analytics(&db, 1, &analytics("logs", 100));
+    save_analytics(&db, 1, &analytics("photos", 200));
+    save_analytics(&db, 2, &analytics("other", 100));
+
+    let cached = fetch_analytics(&db, 1);
+    assert_eq!(cached, vec![analytics("logs", 100), analytics("photos", 200)]);
+
+    delete_analytics(&db, 1, "logs");
+    assert_eq!(fetch_analytics(&db, 1), vec![analytics("photos", 200)]);
+    assert_eq!(fetch_analytics(&db, 2).len(), 1);
+}
+
+fn indexed(key: &str, size: u64) -> IndexedObject {
+    IndexedObject { key: key.to_string(), size, last_modified: None, e_tag: None, storage_class: None }
+}
+
+fn indexed_keys(db: &Database, prefix: Option<&str>) -> Vec<String> {
+    search_index(db, 1, "photos", prefix, |_| true, usize::MAX).into_iter().map(|object| object.key).collect()
+}
+
+#[test]
+fn recrawls_remove_the_keys_gone_from_the_bucket() {
+    let db = Database::open_in_memory().unwrap();
+    let generation = start_index_generation(&db, 1, "photos");
+    save_indexed_objects(&db, 1, "photos", generation, &[indexed("a.jpg", 1), indexed("b.jpg", 2)]);
+    finish_index_generation(&db, 1, "photos", generation, 100);
+
+    let generation = start_index_generation(&db, 1, "photos");
+    save_indexed_objects(&db, 1, "photos", generation, &[indexed("b.jpg", 5), indexed("c.jpg", 3)]);
+    finish_index_generation(&db, 1, "photos", generation, 200);
+
+    assert_eq!(indexed_keys(&db, None), vec!["b.jpg", "c.jpg"]);
+    let info = get_index_info(&db, 1, "photos").unwrap();
+    assert_eq!((info.last_indexed, info.object_count, info.total_size), (Some(200), 2, 8));
+}
+
+#[test]
+fn interrupted_crawls_keep_the_previous_index() {
+    let db = Database::open_in_memory().unwrap();
+    let generation = start_index_generation(&db, 1, "photos");
+    save_indexed_objects(&db, 1, "photos", generation, &[indexed("a.jpg", 1), indexed("b.jpg", 2)]);
+    finish_index_generation(&db, 1, "photos", generation, 100);
+
+    // the crawl stops after its first page, before finishing the generation
+    let generation = start_index_generation(&db, 1, "photos");
+    save_indexed_objects(&db, 1, "photos", generation, &[indexed("a.jpg", 4)]);
+
+    assert_eq!(indexed_keys(&db, None), vec!["a.jpg", "b.jpg"]);
+    let info = get_index_info(&db, 1, "photos").unwrap();
+    assert_eq!((info.last_indexed, info.object_count, info.total_size), (Some(100), 2, 6));
+}
+
+#[test]
+fn index_search_stops_after_the_keys_with_the_prefix() {
+    let db = Database::open_in_memory().unwrap();
+    let generation = start_index_generation(&db, 1, "photos");
+    let keys = ["2023/a.jpg", "2024/a.jpg", "2024/b.png", "2024/c.jpg", "2024-old/a.jpg", "2025/a.jpg"];
+    let objects: Vec<IndexedObject> = keys.iter().map(|key| indexed(key, 1)).collect();
+    save_indexed_objects(&db, 1, "photos", generation, &objects);
+    save_indexed_objects(&db, 2, "photos", start_index_generation(&db, 2, "photos"), &[indexed("2024/other.jpg", 1)]);
+    finish_index_generation(&db, 1, "photos", generation, 100);
+
+    assert_eq!(indexed_keys(&db, Some("2024/")), vec!["2024/a.jpg", "2024/b.png", "2024/c.jpg"]);
+    assert_eq!(indexed_keys(&db, Some("2024")), vec!["2024-old/a.jpg", "2024/a.jpg", "2024/b.png", "2024/c.jpg"]);
+    assert!(indexed_keys(&db, Some("2026/")).is_empty());
+
+    let jpgs = search_index(&db, 1, "photos", Some("2024/"), |key| key.ends_with(".jpg"), 1);
+    assert_eq!(jpgs, vec![indexed("2024/a.jpg", 1)]);
+}
+
+#[test]
+fn edited_prices_replace_the_built_in_ones() {
+    let db = Database::open_in_memory().unwrap();
+    assert_eq!(fetch_price_table(&db), PriceTable::default());
+
+    let mut edited = PriceTable::default();
+    edited.prices.push(StoragePrice {
+        region: "eu-central-1".to_string(),
+        storage_class: "STANDARD".to_string(),
+        per_gb_month: 0.0245,
+    });
+    save_price_table(&db, &edited);
+    let fetched = fetch_price_table(&db);
+    assert_eq!(fetched.prices.len(), edited.prices.len());
+    assert_eq!(fetched.price(Some("eu-central-1"), "STANDARD"), 0.0245);
+
+    reset_price_table(&db);
+    assert_eq!(fetch_price_table(&db), PriceTable::default());
+}
+
+#[test]
+fn bookmarks_keep_local_and_bucket_locations() {
+    let db = Database::open_in_memory().unwrap();
+    let bucket = Location::parse("s3://photos/2024/").unwrap();
+    let local = Location::parse("/home/user/Pictures").unwrap();
+    add_bookmark(&db, &bucket);
+    add_bookmark(&db, &local);
+    add_bookmark(&db, &bucket);
+
+    let bookmarks = fetch_bookmarks(&db);
+    let locations: Vec<&Location> = bookmarks.iter().map(|bookmark| &bookmark.location).collect();
+    assert_eq!(locations, vec![&local, &bucket]);
+
+    delete_bookmark(&db, bookmarks[0].id);
+    assert_eq!(fetch_bookmarks(&db).len(), 1);
+}
+
+#[test]
+fn header_rules_of_all_buckets_come_first() {
+    let db = Database::open_in_memory().unwrap();
+    let mut bucket_rule = HeaderRule {
+        account_id: 1,
+        bucket: Some("site".to_string()),
+        pattern: "*.html".to_string(),
+        cache_control: Some("no-cache".to_string()),
+        ..Default::default()
+    };
+    let mut account_rule = HeaderRule {
+        account_id: 1,
+        pattern: "*".to_string(),
+        metadata: [("owner".to_string(), "web".to_string())].into(),
+        ..Default::default()
+    };
+    let mut other_account_rule = HeaderRule { account_id: 2, ..account_rule.clone() };
+    save_header_rule(&db, &mut bucket_rule);
+    save_header_rule(&db, &mut account_rule);
+    save_header_rule(&db, &mut other_account_rule);
+
+    assert_eq!(fetch_header_rules(&db, 1), vec![account_rule.clone(), bucket_rule.clone()]);
+
+    bucket_rule.cache_control = Some("max-age=60".to_string());
+    save_header_rule(&db, &mut bucket_rule);
+    delete_header_rule(&db, account_rule.id);
+    assert_eq!(fetch_header_rules(&db, 1), vec![bucket_rule]);
+}
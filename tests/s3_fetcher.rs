@@ -0,0 +1,588 @@
+//! Runs the core library against an s3 server: an in-process mock keeping the buckets in a
+//! temporary directory or, when `DIOS3_TEST_ENDPOINT`, `DIOS3_TEST_ACCESS_KEY` and
+//! `DIOS3_TEST_SECRET_KEY` are set, a real s3 compatible service like a local MinIO.
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use dios3::model::account::Account;
+use dios3::model::bucket::BucketOptions;
+use dios3::model::client_encryption::ClientKey;
+use dios3::model::cross_account::CrossAccountTransfer;
+use dios3::model::encryption::EncryptionSettings;
+use dios3::model::header_rule::HeaderRule;
+use dios3::model::local_selected_item::LocalSelectedItem;
+use dios3::model::text_edit::SaveOutcome;
+use dios3::model::transfer_options::TransferOptions;
+use dios3::model::website::publish_items;
+use dios3::repositories::client_key_repo::save_client_key;
+use dios3::repositories::cross_account_repo::{fetch_transfers, save_transfer};
+use dios3::repositories::database::Database;
+use dios3::repositories::header_rule_repo::save_header_rule;
+use dios3::services::analytics::analyze_bucket;
+use dios3::services::cross_account::transfer_object;
+use dios3::services::s3_data_fetcher::S3DataFetcher;
+use dios3::settings::file_credentials::FileCredential;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder as ConnBuilder;
+use s3s::auth::SimpleAuth;
+use s3s::dto::{
+    DeleteObjectInput, DeleteObjectOutput, HeadObjectInput, HeadObjectOutput, ObjectLockLegalHoldStatus,
+};
+use s3s::{s3_error, S3Request, S3Response, S3Result, S3};
+use s3s::service::S3ServiceBuilder;
+use s3s_fs::FileSystem;
+use tempfile::TempDir;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::unbounded_channel;
+
+const MOCK_ACCESS_KEY: &str = "dios3-test";
+const MOCK_SECRET_KEY: &str = "dios3-test-secret";
+const REGION: &str = "eu-central-1";
+
+/// Server the tests talk to, the mock one lives as long as its directory
+struct TestServer {
+    endpoint: String,
+    access_key: String,
+    secret_key: String,
+    _root: Option<TempDir>,
+}
+
+async fn start_server() -> TestServer {
+    if let (Ok(endpoint), Ok(access_key), Ok(secret_key)) = (
+        std::env::var("DIOS3_TEST_ENDPOINT"),
+        std::env::var("DIOS3_TEST_ACCESS_KEY"),
+        std::env::var("DIOS3_TEST_SECRET_KEY"),
+    ) {
+        return TestServer { endpoint, access_key, secret_key, _root: None };
+    }
+
+    let root = tempfile::tempdir().unwrap();
+    let endpoint = serve(FileSystem::new(root.path()).unwrap()).await;
+    TestServer {
+        endpoint,
+        access_key: MOCK_ACCESS_KEY.to_string(),
+        secret_key: MOCK_SECRET_KEY.to_string(),
+        _root: Some(root),
+    }
+}
+
+/// Serves the mock s3 implementation on a free port, returning its endpoint
+async fn serve(s3: impl S3) -> String {
+    let mut builder = S3ServiceBuilder::new(s3);
+    builder.set_auth(SimpleAuth::from_single(MOCK_ACCESS_KEY, MOCK_SECRET_KEY));
+    let service = builder.build();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let http = ConnBuilder::new(TokioExecutor::new());
+        while let Ok((socket, _)) = listener.accept().await {
+            let connection = http.serve_connection(TokioIo::new(socket), service.clone()).into_owned();
+            tokio::spawn(connection);
+        }
+    });
+    endpoint
+}
+
+/// Bucket holding a single object under legal hold. Like s3 with object lock, deleting the locked
+/// version is refused, while a delete without a version is accepted and only adds a delete marker.
+#[derive(Default)]
+struct LockedObjectMock {
+    delete_requested: Arc<AtomicBool>,
+}
+
+#[async_trait::async_trait]
+impl S3 for LockedObjectMock {
+    async fn head_object(&self, _: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
+        Ok(S3Response::new(HeadObjectOutput {
+            content_length: Some(7),
+            version_id: Some("locked-version".to_string()),
+            object_lock_legal_hold_status: Some(ObjectLockLegalHoldStatus::from_static(ObjectLockLegalHoldStatus::ON)),
+            ..Default::default()
+        }))
+    }
+
+    async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
+        self.delete_requested.store(true, Ordering::SeqCst);
+        if req.input.version_id.is_some() {
+            return Err(s3_error!(AccessDenied, "Access Denied because object protected by object lock."));
+        }
+        Ok(S3Response::new(DeleteObjectOutput { delete_marker: Some(true), ..Default::default() }))
+    }
+}
+
+fn account(server: &TestServer) -> Account {
+    Account {
+        id: 1,
+        name: "test".to_string(),
+        description: String::new(),
+        access_key: server.access_key.clone(),
+        secret_key: server.secret_key.clone(),
+        is_default: true,
+        default_region: REGION.to_string(),
+        encryption: EncryptionSettings::default(),
+        client_key_id: None,
+        endpoint_url: Some(server.endpoint.clone()),
+    }
+}
+
+/// The account points at the server, like a MinIO account would
+fn fetcher(server: &TestServer, account: Account, db: &Database) -> S3DataFetcher {
+    S3DataFetcher::new(Account { endpoint_url: Some(server.endpoint.clone()), ..account }, db.clone())
+}
+
+/// Bucket names are global on a real service, so every test gets its own
+async fn create_test_bucket(fetcher: &S3DataFetcher) -> String {
+    let bucket = format!("dios3-test-{}", rand::random::<u32>());
+    let error = fetcher.create_bucket(bucket.clone(), REGION.to_string(), &BucketOptions::default()).await.unwrap();
+    assert_eq!(error, None);
+    bucket
+}
+
+async fn upload(fetcher: &S3DataFetcher, bucket: &str, key: &str, content: &[u8]) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("upload");
+    fs::write(&path, content).unwrap();
+    let item = LocalSelectedItem::new(
+        key.to_string(),
+        path.to_string_lossy().to_string(),
+        false,
+        bucket.to_string(),
+        key.to_string(),
+        FileCredential::default(),
+        None,
+    );
+    let (upload_tx, _progress) = unbounded_channel();
+    assert!(fetcher.upload_item(item, upload_tx).await.unwrap());
+}
+
+async fn download(fetcher: &S3DataFetcher, bucket: &str, key: &str) -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("download");
+    let (download_tx, _progress) = unbounded_channel();
+    fetcher.download_to_file(bucket, key, &path, None, download_tx).await.unwrap();
+    fs::read(path).unwrap()
+}
+
+/// Removes the objects and the bucket, so nothing is left behind on a real service
+async fn remove_bucket(fetcher: &S3DataFetcher, bucket: &str) {
+    for object in fetcher.list_all_objects(bucket, None).await.unwrap() {
+        fetcher.delete_data(false, Some(bucket.to_string()), object.path, false).await.unwrap();
+    }
+    assert_eq!(fetcher.delete_data(true, None, bucket.to_string(), false).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn creates_lists_and_removes_buckets() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+
+    let bucket = create_test_bucket(&fetcher).await;
+    let buckets = fetcher.list_all_buckets().await.unwrap();
+    assert!(buckets.iter().any(|item| item.name == bucket && item.is_bucket));
+
+    remove_bucket(&fetcher, &bucket).await;
+    let buckets = fetcher.list_all_buckets().await.unwrap();
+    assert!(!buckets.iter().any(|item| item.name == bucket));
+}
+
+#[tokio::test]
+async fn creates_us_east_1_buckets_without_a_location_constraint() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, Account { default_region: "us-east-1".to_string(), ..account(&server) }, &db);
+
+    let bucket = format!("dios3-test-{}", rand::random::<u32>());
+    let error = fetcher.create_bucket(bucket.clone(), "us-east-1".to_string(), &BucketOptions::default()).await.unwrap();
+    assert_eq!(error, None);
+    assert!(fetcher.list_all_buckets().await.unwrap().iter().any(|item| item.name == bucket));
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn tests_the_connection_with_the_credentials_of_the_account() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+
+    let identity = fetcher(&server, account(&server), &db).test_connection().await.unwrap();
+    assert!(identity.ends_with(&format!("buckets at {}", server.endpoint)));
+
+    let mistyped = Account { secret_key: format!("{}-typo", server.secret_key), ..account(&server) };
+    assert!(fetcher(&server, mistyped, &db).test_connection().await.is_err());
+}
+
+#[tokio::test]
+async fn uploads_lists_and_downloads_objects() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+
+    upload(&fetcher, &bucket, "docs/readme.txt", b"hello s3").await;
+    upload(&fetcher, &bucket, "docs/nested/data.csv", b"a,b\n1,2\n").await;
+    upload(&fetcher, &bucket, "top.txt", b"top").await;
+
+    let top_level = fetcher.list_objects(&bucket, None).await.unwrap();
+    let mut names: Vec<_> = top_level.iter().map(|item| (item.path.as_str(), item.is_directory)).collect();
+    names.sort();
+    assert_eq!(names, vec![("docs/", true), ("top.txt", false)]);
+
+    let mut keys: Vec<_> = fetcher
+        .list_all_objects(&bucket, Some("docs/".to_string()))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|item| item.path)
+        .collect();
+    keys.sort();
+    assert_eq!(keys, vec!["docs/nested/data.csv", "docs/readme.txt"]);
+
+    assert_eq!(download(&fetcher, &bucket, "docs/readme.txt").await, b"hello s3");
+    let range = fetcher.get_object_range(&bucket, "docs/readme.txt", 6..=7).await.unwrap();
+    assert_eq!(range.as_ref(), b"s3");
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn published_sites_are_uploaded_with_their_web_headers() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    // the pages are published readable, even when the account encrypts its uploads
+    let key_id = save_client_key(&db, "test key", &ClientKey::generate()).unwrap();
+    let fetcher = fetcher(&server, Account { client_key_id: Some(key_id), ..account(&server) }, &db);
+    let bucket = create_test_bucket(&fetcher).await;
+    let site = tempfile::tempdir().unwrap();
+    fs::write(site.path().join("index.html"), b"<h1>hello</h1>").unwrap();
+    fs::write(site.path().join("style.css"), b"h1 {}").unwrap();
+
+    for item in publish_items(site.path(), &bucket, "", "no-cache", "max-age=60") {
+        let (upload_tx, _progress) = unbounded_channel();
+        assert!(fetcher.upload_item(item, upload_tx).await.unwrap());
+    }
+    let page = fetcher.get_object_details(&bucket, "index.html").await.unwrap();
+    assert_eq!(page.content_type.as_deref(), Some("text/html; charset=utf-8"));
+    assert_eq!(page.cache_control.as_deref(), Some("no-cache"));
+    assert_eq!(page.size, Some(14));
+    let style = fetcher.get_object_details(&bucket, "style.css").await.unwrap();
+    assert_eq!(style.content_type.as_deref(), Some("text/css; charset=utf-8"));
+    assert_eq!(style.cache_control.as_deref(), Some("max-age=60"));
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn uploads_get_the_detected_content_type_and_the_matching_header_rules() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+    let mut rule = HeaderRule {
+        account_id: 1,
+        bucket: Some(bucket.clone()),
+        pattern: "*.js".to_string(),
+        content_type: Some("text/javascript".to_string()),
+        // no Content-Encoding, the mock refuses it next to the aws-chunked encoding of the sdk
+        metadata: [("build".to_string(), "42".to_string())].into(),
+        ..Default::default()
+    };
+    save_header_rule(&db, &mut rule);
+
+    // the local files have no extension, the content type of the logo comes from its first bytes
+    upload(&fetcher, &bucket, "logo", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").await;
+    upload(&fetcher, &bucket, "app.js", b"export {}").await;
+
+    let logo = fetcher.get_object_details(&bucket, "logo").await.unwrap();
+    assert_eq!(logo.content_type.as_deref(), Some("image/png"));
+    let script = fetcher.get_object_details(&bucket, "app.js").await.unwrap();
+    assert_eq!(script.content_type.as_deref(), Some("text/javascript"));
+    assert_eq!(script.metadata.get("build").map(String::as_str), Some("42"));
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn copies_and_deletes_objects() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+
+    upload(&fetcher, &bucket, "original.txt", b"content").await;
+    fetcher.copy_object(&bucket, "original.txt", &bucket, "copy of original.txt", &TransferOptions::default()).await.unwrap();
+    let error = fetcher
+        .delete_data(false, Some(bucket.clone()), "original.txt".to_string(), false)
+        .await
+        .unwrap();
+    assert_eq!(error, None);
+
+    let keys: Vec<_> = fetcher.list_all_objects(&bucket, None).await.unwrap().into_iter().map(|item| item.path).collect();
+    assert_eq!(keys, vec!["copy of original.txt"]);
+    assert_eq!(download(&fetcher, &bucket, "copy of original.txt").await, b"content");
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn deletes_blocked_by_object_lock_are_reported() {
+    let mock = LockedObjectMock::default();
+    let delete_requested = mock.delete_requested.clone();
+    let endpoint = serve(mock).await;
+    let server = TestServer {
+        endpoint,
+        access_key: MOCK_ACCESS_KEY.to_string(),
+        secret_key: MOCK_SECRET_KEY.to_string(),
+        _root: None,
+    };
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+
+    let error = fetcher
+        .delete_data(false, Some("locked-bucket".to_string()), "report.pdf".to_string(), false)
+        .await
+        .unwrap();
+    assert!(error.unwrap().contains("protected by object lock"));
+    // nothing is deleted, neither a delete marker nor the locked version once its retention ends
+    assert!(!delete_requested.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn saving_a_stale_text_object_is_a_conflict() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+    upload(&fetcher, &bucket, "notes.txt", b"first").await;
+
+    let (content, e_tag) = fetcher.get_text_object(&bucket, "notes.txt").await.unwrap();
+    assert_eq!(content, "first");
+    let saved = fetcher.save_text_object(&bucket, "notes.txt", "second".to_string(), e_tag.clone()).await.unwrap();
+    assert!(matches!(saved, SaveOutcome::Saved { .. }));
+
+    // the ETag read before the first save is outdated now
+    let stale = fetcher.save_text_object(&bucket, "notes.txt", "third".to_string(), e_tag).await.unwrap();
+    match stale {
+        SaveOutcome::Conflict { remote_content, .. } => assert_eq!(remote_content, "second"),
+        other => panic!("expected a conflict, got {:?}", other),
+    }
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn analyzes_the_bucket_listing() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+    upload(&fetcher, &bucket, "logs/a.log", &[0; 300]).await;
+    upload(&fetcher, &bucket, "logs/b.log", &[0; 200]).await;
+    upload(&fetcher, &bucket, "photo.jpg", &[0; 100]).await;
+
+    let analytics = analyze_bucket(&fetcher, &bucket, |_| {}).await.unwrap();
+    assert_eq!((analytics.object_count, analytics.total_size), (3, 600));
+    let prefixes: Vec<_> = analytics.by_prefix.iter().map(|group| (group.label.as_str(), group.total_size)).collect();
+    assert_eq!(prefixes, vec![("logs/", 500), ("/", 100)]);
+    assert_eq!(analytics.largest.first().map(|object| object.key.as_str()), Some("logs/a.log"));
+    // everything was just uploaded
+    assert_eq!(analytics.by_age[0].object_count, 3);
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+#[tokio::test]
+async fn aborts_incomplete_multipart_uploads() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let fetcher = fetcher(&server, account(&server), &db);
+    let bucket = create_test_bucket(&fetcher).await;
+
+    let client = fetcher.s3_client().await;
+    let upload = client.create_multipart_upload().bucket(&bucket).key("big.bin").send().await.unwrap();
+    let upload_id = upload.upload_id().unwrap();
+    for part_number in 1..=2 {
+        client
+            .upload_part()
+            .bucket(&bucket)
+            .key("big.bin")
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(vec![0; 1000].into())
+            .send()
+            .await
+            .unwrap();
+    }
+    assert_eq!(fetcher.get_upload_parts(&bucket, "big.bin", upload_id).await.unwrap(), (2, 2000));
+
+    fetcher.abort_multipart_upload(&bucket, "big.bin", upload_id).await.unwrap();
+    // depending on the service the aborted upload is unknown or has no parts left
+    let parts = fetcher.get_upload_parts(&bucket, "big.bin", upload_id).await;
+    assert!(parts.map_or(true, |(count, _)| count == 0));
+    assert!(fetcher.list_all_objects(&bucket, None).await.unwrap().is_empty());
+
+    remove_bucket(&fetcher, &bucket).await;
+}
+
+/// S3 doesn't accept smaller parts, except for the last one
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+#[tokio::test]
+async fn transfers_objects_between_accounts_on_different_servers() {
+    let (source_server, destination_server) = (start_server().await, start_server().await);
+    let db = Database::open_in_memory().unwrap();
+    let source = fetcher(&source_server, account(&source_server), &db);
+    let destination = fetcher(&destination_server, Account { id: 2, ..account(&destination_server) }, &db);
+    let source_bucket = create_test_bucket(&source).await;
+    let destination_bucket = create_test_bucket(&destination).await;
+    upload(&source, &source_bucket, "small.txt", b"small object").await;
+    let big: Vec<u8> = (0..12 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    upload(&source, &source_bucket, "big.bin", &big).await;
+
+    for key in ["small.txt", "big.bin"] {
+        let mut transfer = CrossAccountTransfer::new(1, &source_bucket, key, 2, &destination_bucket, &format!("copy/{}", key));
+        transfer_object(&db, &source, &destination, &mut transfer, MIN_PART_SIZE, |_, _| {}).await.unwrap();
+    }
+    assert_eq!(download(&destination, &destination_bucket, "copy/small.txt").await, b"small object");
+    assert_eq!(download(&destination, &destination_bucket, "copy/big.bin").await, big);
+    // completed transfers aren't kept for resuming
+    assert!(fetch_transfers(&db).is_empty());
+
+    // the web headers of the source are kept
+    source
+        .s3_client()
+        .await
+        .put_object()
+        .bucket(&source_bucket)
+        .key("page.html")
+        .cache_control("max-age=60")
+        .content_disposition("inline")
+        .content_encoding("identity")
+        .body(b"<html></html>".to_vec().into())
+        .send()
+        .await
+        .unwrap();
+    let mut transfer = CrossAccountTransfer::new(1, &source_bucket, "page.html", 2, &destination_bucket, "page.html");
+    transfer_object(&db, &source, &destination, &mut transfer, MIN_PART_SIZE, |_, _| {}).await.unwrap();
+    let head = destination.s3_client().await.head_object().bucket(&destination_bucket).key("page.html").send().await.unwrap();
+    assert_eq!(head.cache_control(), Some("max-age=60"));
+    assert_eq!(head.content_disposition(), Some("inline"));
+    assert_eq!(head.content_encoding(), Some("identity"));
+
+    remove_bucket(&source, &source_bucket).await;
+    remove_bucket(&destination, &destination_bucket).await;
+}
+
+#[tokio::test]
+async fn resumed_transfers_of_replaced_objects_are_forgotten_once_copied() {
+    let (source_server, destination_server) = (start_server().await, start_server().await);
+    let db = Database::open_in_memory().unwrap();
+    let source = fetcher(&source_server, account(&source_server), &db);
+    let destination = fetcher(&destination_server, Account { id: 2, ..account(&destination_server) }, &db);
+    let source_bucket = create_test_bucket(&source).await;
+    let destination_bucket = create_test_bucket(&destination).await;
+    upload(&source, &source_bucket, "data.bin", b"replaced by a small object").await;
+
+    // the transfer started for a big version of the object
+    let client = destination.s3_client().await;
+    let upload = client.create_multipart_upload().bucket(&destination_bucket).key("data.bin").send().await.unwrap();
+    let mut transfer = CrossAccountTransfer::new(1, &source_bucket, "data.bin", 2, &destination_bucket, "data.bin");
+    transfer.size = 12 * 1024 * 1024;
+    transfer.source_e_tag = Some("\"previous\"".to_string());
+    transfer.upload_id = upload.upload_id().map(String::from);
+    save_transfer(&db, &mut transfer);
+
+    let mut saved = fetch_transfers(&db).remove(0);
+    transfer_object(&db, &source, &destination, &mut saved, MIN_PART_SIZE, |_, _| {}).await.unwrap();
+    assert_eq!(download(&destination, &destination_bucket, "data.bin").await, b"replaced by a small object");
+    assert!(fetch_transfers(&db).is_empty());
+
+    remove_bucket(&source, &source_bucket).await;
+    remove_bucket(&destination, &destination_bucket).await;
+}
+
+#[tokio::test]
+async fn interrupted_transfers_continue_after_the_uploaded_parts() {
+    let (source_server, destination_server) = (start_server().await, start_server().await);
+    let db = Database::open_in_memory().unwrap();
+    let source = fetcher(&source_server, account(&source_server), &db);
+    let destination = fetcher(&destination_server, Account { id: 2, ..account(&destination_server) }, &db);
+    let source_bucket = create_test_bucket(&source).await;
+    let destination_bucket = create_test_bucket(&destination).await;
+    let big: Vec<u8> = (0..12 * 1024 * 1024u32).map(|i| (i % 253) as u8).collect();
+    upload(&source, &source_bucket, "big.bin", &big).await;
+    let details = source.get_object_details(&source_bucket, "big.bin").await.unwrap();
+
+    // the first part made it before the transfer was interrupted
+    let client = destination.s3_client().await;
+    let upload = client.create_multipart_upload().bucket(&destination_bucket).key("big.bin").send().await.unwrap();
+    client
+        .upload_part()
+        .bucket(&destination_bucket)
+        .key("big.bin")
+        .upload_id(upload.upload_id().unwrap())
+        .part_number(1)
+        .body(big[..MIN_PART_SIZE as usize].to_vec().into())
+        .send()
+        .await
+        .unwrap();
+    let mut transfer = CrossAccountTransfer::new(1, &source_bucket, "big.bin", 2, &destination_bucket, "big.bin");
+    transfer.size = big.len() as u64;
+    transfer.source_e_tag = details.e_tag.clone();
+    transfer.upload_id = upload.upload_id().map(String::from);
+    save_transfer(&db, &mut transfer);
+
+    let mut saved = fetch_transfers(&db).remove(0);
+    let progress = std::sync::Mutex::new(Vec::new());
+    transfer_object(&db, &source, &destination, &mut saved, MIN_PART_SIZE, |bytes, _| progress.lock().unwrap().push(bytes))
+        .await
+        .unwrap();
+    let progress = progress.into_inner().unwrap();
+    assert_eq!(progress.first(), Some(&MIN_PART_SIZE));
+    assert_eq!(progress.last(), Some(&(big.len() as u64)));
+    assert_eq!(download(&destination, &destination_bucket, "big.bin").await, big);
+    assert!(fetch_transfers(&db).is_empty());
+
+    remove_bucket(&source, &source_bucket).await;
+    remove_bucket(&destination, &destination_bucket).await;
+}
+
+#[tokio::test]
+async fn client_side_encryption_uses_the_given_key_store() {
+    let server = start_server().await;
+    let db = Database::open_in_memory().unwrap();
+    let key_id = save_client_key(&db, "test key", &ClientKey::generate()).unwrap();
+    let encrypting = fetcher(&server, Account { client_key_id: Some(key_id), ..account(&server) }, &db);
+    let bucket = create_test_bucket(&encrypting).await;
+
+    upload(&encrypting, &bucket, "secret.txt", b"only for the key owner").await;
+    // the stored object is encrypted, but any fetcher with the key store can decrypt it
+    let stored = encrypting.get_object_range(&bucket, "secret.txt", 0..=3).await;
+    assert!(stored.is_err());
+    let plain = fetcher(&server, account(&server), &db);
+    assert_eq!(download(&plain, &bucket, "secret.txt").await, b"only for the key owner");
+
+    // bigger files are encrypted part by part into a multipart upload
+    let big: Vec<u8> = (0..17 * 1024 * 1024 + 5).map(|i| (i % 251) as u8).collect();
+    upload(&encrypting, &bucket, "big.bin", &big).await;
+    assert_eq!(download(&plain, &bucket, "big.bin").await, big);
+
+    // sync compares files with the size of the content, objects uploaded without the key keep theirs
+    upload(&plain, &bucket, "plain.txt", b"readable").await;
+    let keys = ["secret.txt", "big.bin", "plain.txt"].map(String::from).to_vec();
+    let sizes = encrypting.plaintext_sizes(&bucket, keys).await.unwrap();
+    assert_eq!(sizes.len(), 2);
+    assert_eq!(sizes["secret.txt"], 22);
+    assert_eq!(sizes["big.bin"], big.len() as u64);
+
+    // without the key the download fails
+    let other_db = Database::open_in_memory().unwrap();
+    let (download_tx, _progress) = unbounded_channel();
+    let dir = tempfile::tempdir().unwrap();
+    let result = fetcher(&server, account(&server), &other_db)
+        .download_to_file(&bucket, "secret.txt", &dir.path().join("secret.txt"), None, download_tx)
+        .await;
+    assert!(result.is_err());
+
+    remove_bucket(&plain, &bucket).await;
+}